@@ -18,6 +18,7 @@ const BANNED_EVENT_NAMES = [
   "connection-state",
   "runtime-diagnostics",
   "connection-error",
+  "delete-message-debug",
 ];
 
 const DISALLOWED_PATTERNS = [
@@ -1,3 +1,104 @@
+use std::{env, fs, path::PathBuf};
+
+/// Commands each webview window may invoke. `"*"` grants every registered command.
+/// The quick window only reads and triages messages, so it never gets settings or
+/// connection-management commands.
+const WINDOW_COMMANDS: &[(&str, &[&str])] = &[
+    ("main", &["*"]),
+    (
+        "quick",
+        &[
+            "bootstrap_state",
+            "subscribe_app_updates",
+            "unsubscribe_app_updates",
+            "open_external_url",
+            "delete_message",
+            "recover_stream",
+            "set_pause",
+            "resume_pause",
+            "fetch_url_preview",
+            "mark_messages_read",
+            "get_event_schema_version",
+            "get_trash",
+            "cancel_operations",
+        ],
+    ),
+];
+
+/// Core (non-app) permissions each window needs on top of its commands.
+const WINDOW_CORE_PERMISSIONS: &[(&str, &[&str])] = &[
+    ("main", &["core:default"]),
+    ("quick", &["core:default", "core:window:allow-hide"]),
+];
+
 fn main() {
-    tauri_build::build();
+    println!("cargo:rerun-if-changed=src/main.rs");
+    let registered = registered_commands();
+
+    for (window, commands) in WINDOW_COMMANDS {
+        for command in *commands {
+            if *command != "*" && !registered.contains(&command.to_string()) {
+                panic!("window `{window}` allows unknown command `{command}`");
+            }
+        }
+    }
+
+    let capabilities_dir =
+        PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set by cargo")).join("capabilities");
+    fs::create_dir_all(&capabilities_dir).expect("failed to create capabilities dir");
+    for (window, commands) in WINDOW_COMMANDS {
+        let allowed: Vec<&str> = if commands.contains(&"*") {
+            registered.iter().map(String::as_str).collect()
+        } else {
+            commands.to_vec()
+        };
+        let mut permissions: Vec<String> = WINDOW_CORE_PERMISSIONS
+            .iter()
+            .filter(|(label, _)| label == window)
+            .flat_map(|(_, core)| core.iter().map(|p| format!("\"{p}\"")))
+            .collect();
+        permissions.extend(
+            allowed
+                .iter()
+                .map(|command| format!("\"allow-{}\"", command.replace('_', "-"))),
+        );
+        let capability = format!(
+            "{{\"identifier\":\"{window}-window\",\"windows\":[\"{window}\"],\"permissions\":[{}]}}",
+            permissions.join(",")
+        );
+        fs::write(capabilities_dir.join(format!("{window}.json")), capability)
+            .expect("failed to write capability file");
+    }
+
+    let commands: &'static [&'static str] = Box::leak(
+        registered
+            .into_iter()
+            .map(|command| &*Box::leak(command.into_boxed_str()))
+            .collect::<Vec<_>>()
+            .into_boxed_slice(),
+    );
+    let pattern: &'static str =
+        Box::leak(format!("{}/*.json", capabilities_dir.display()).into_boxed_str());
+    tauri_build::try_build(
+        tauri_build::Attributes::new()
+            .app_manifest(tauri_build::AppManifest::new().commands(commands))
+            .capabilities_path_pattern(pattern),
+    )
+    .expect("failed to run tauri build");
+}
+
+/// Reads the command names out of the `generate_handler!` list in main.rs so the
+/// capability map cannot drift from what is actually registered.
+fn registered_commands() -> Vec<String> {
+    let source = fs::read_to_string("src/main.rs").expect("failed to read src/main.rs");
+    let start = source
+        .find("generate_handler![")
+        .expect("main.rs registers commands with generate_handler!");
+    let body = &source[start + "generate_handler![".len()..];
+    let end = body.find(']').expect("unterminated generate_handler!");
+    body[..end]
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
 }
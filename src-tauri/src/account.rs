@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{applications::server_context, client_token::decode_response, debug_log};
+
+/// The Gotify user the client token belongs to.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct CurrentUser {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) admin: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct PasswordParams<'a> {
+    pass: &'a str,
+}
+
+pub(crate) async fn get_current_user(app: &AppHandle) -> Result<CurrentUser, String> {
+    let context = server_context(app)?;
+    let request = context
+        .client
+        .get(format!("{}/current/user", context.base_url))
+        .header("X-Gotify-Key", &context.token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to load current user: {error}"))?;
+    decode_response::<CurrentUser>(response, "Current user").await
+}
+
+/// Changes the password of the token's user. Client tokens stay valid, so the
+/// stream keeps running. The password is sent as typed; Gotify accepts any
+/// non-empty one, whitespace included.
+pub(crate) async fn change_password(app: &AppHandle, password: &str) -> Result<(), String> {
+    if password.is_empty() {
+        return Err("New password is required".to_string());
+    }
+    let context = server_context(app)?;
+    let request = context
+        .client
+        .post(format!("{}/current/user/password", context.base_url))
+        .header("X-Gotify-Key", &context.token)
+        .json(&PasswordParams { pass: password });
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to change password: {error}"))?;
+    let status = response.status().as_u16();
+    if !(200..300).contains(&status) {
+        return decode_response::<serde_json::Value>(response, "Change password")
+            .await
+            .map(|_| ());
+    }
+    debug_log("account: password changed");
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    send::MessageSentEvent,
+    settings::{
+        read_settings, save_non_secret_settings, to_settings_response, SettingsResponse,
+        StoredSettings,
+    },
+    AppState, CachedMessage, ACK_DEFAULT_TEMPLATE, MAX_ACK_TEMPLATE_CHARS,
+};
+
+/// "Acknowledge" action on notifications: pushes a short message to a Gotify
+/// application so others watching the same server see the alert was picked up.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub(crate) struct AckSettings {
+    pub(crate) enabled: bool,
+    /// Name of the saved application token the acknowledgement is sent with.
+    pub(crate) app_token_name: String,
+    /// Supports `{title}`, `{app}`, `{id}` and `{priority}` placeholders.
+    pub(crate) message_template: String,
+    /// Only messages at or above this priority offer the action.
+    pub(crate) min_priority: i64,
+}
+
+impl Default for AckSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            app_token_name: String::new(),
+            message_template: ACK_DEFAULT_TEMPLATE.to_string(),
+            min_priority: 0,
+        }
+    }
+}
+
+fn normalize(config: AckSettings) -> Result<AckSettings, String> {
+    let message_template = match config.message_template.trim() {
+        "" => ACK_DEFAULT_TEMPLATE.to_string(),
+        template => template.to_string(),
+    };
+    if message_template.chars().count() > MAX_ACK_TEMPLATE_CHARS {
+        return Err(format!(
+            "Acknowledgement message must be {MAX_ACK_TEMPLATE_CHARS} characters or fewer"
+        ));
+    }
+    Ok(AckSettings {
+        enabled: config.enabled,
+        app_token_name: config.app_token_name.trim().to_string(),
+        message_template,
+        min_priority: config.min_priority.clamp(0, 10),
+    })
+}
+
+pub(crate) fn set_acknowledgement(
+    app: &AppHandle,
+    config: AckSettings,
+) -> Result<SettingsResponse, String> {
+    let config = normalize(config)?;
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        if config.enabled && !settings.app_tokens.contains_key(&config.app_token_name) {
+            return Err("Choose a saved application token to acknowledge with".to_string());
+        }
+        settings.acknowledgement = config;
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!(
+        "acknowledgement enabled={} token={}",
+        settings.acknowledgement.enabled, settings.acknowledgement.app_token_name
+    ));
+    Ok(to_settings_response(settings))
+}
+
+/// Whether a notification for `message` should carry the action. A token
+/// removed after the action was set up turns it off quietly.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn offers_action(settings: &StoredSettings, message: &CachedMessage) -> bool {
+    let ack = &settings.acknowledgement;
+    ack.enabled
+        && settings.app_tokens.contains_key(&ack.app_token_name)
+        && message.priority >= ack.min_priority
+}
+
+fn render(template: &str, message: &CachedMessage) -> String {
+    let title = if message.title.trim().is_empty() {
+        "(no title)"
+    } else {
+        message.title.trim()
+    };
+    template
+        .replace("{title}", title)
+        .replace("{app}", &message.app)
+        .replace("{id}", &message.id.to_string())
+        .replace("{priority}", &message.priority.to_string())
+}
+
+/// Sends the acknowledgement for a cached message.
+pub(crate) async fn acknowledge(
+    app: &AppHandle,
+    message_id: i64,
+) -> Result<MessageSentEvent, String> {
+    let settings = read_settings(app)?;
+    let ack = settings.acknowledgement;
+    if !ack.enabled {
+        return Err("Acknowledgements are turned off".to_string());
+    }
+    let message = app
+        .state::<AppState>()
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?
+        .iter()
+        .find(|message| message.id == message_id)
+        .cloned()
+        .ok_or_else(|| format!("Message {message_id} is no longer cached"))?;
+    // Lets other clients tie the acknowledgement to the original message.
+    let mut extras = Map::new();
+    extras.insert(
+        "gotify-desktop::ack".to_string(),
+        json!({ "messageId": message.id, "appId": message.app_id }),
+    );
+    let sent = crate::send::send_message(
+        app,
+        &ack.app_token_name,
+        Some(format!("Acknowledged: {}", render("{title}", &message))),
+        &render(&ack.message_template, &message),
+        None,
+        Some(extras),
+    )
+    .await?;
+    debug_log(&format!(
+        "acknowledged id={message_id} as message id={}",
+        sent.id
+    ));
+    Ok(sent)
+}
@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    settings::{read_settings, save_non_secret_settings, to_settings_response, SettingsResponse},
+    unix_now_secs, AppState, CachedMessage, MAX_ANDROID_ACTION_PREFIX_CHARS,
+    MAX_ANDROID_ACTION_RULES, PAUSE_FOREVER_SENTINEL,
+};
+
+/// What the desktop does with an `android::action.onReceive.intentUrl`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AndroidAction {
+    /// Opens the intent URL in the browser (http, https and mailto only).
+    OpenUrl,
+    Star,
+    MarkRead,
+    Archive,
+    /// Keeps the message but raises no notification.
+    Silence,
+    /// Stops at this rule so later rules do not apply.
+    Ignore,
+}
+
+/// First matching rule wins. An empty prefix matches every intent URL; no
+/// `app_id` matches every application.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct AndroidActionRule {
+    #[serde(default)]
+    pub(crate) url_prefix: String,
+    #[serde(default)]
+    pub(crate) app_id: Option<i64>,
+    pub(crate) action: AndroidAction,
+}
+
+fn normalize(rules: Vec<AndroidActionRule>) -> Result<Vec<AndroidActionRule>, String> {
+    if rules.len() > MAX_ANDROID_ACTION_RULES {
+        return Err(format!(
+            "At most {MAX_ANDROID_ACTION_RULES} intent rules are supported"
+        ));
+    }
+    rules
+        .into_iter()
+        .map(|rule| {
+            let url_prefix = rule.url_prefix.trim().to_string();
+            if url_prefix.chars().count() > MAX_ANDROID_ACTION_PREFIX_CHARS {
+                return Err(format!(
+                    "Intent URL prefixes must be {MAX_ANDROID_ACTION_PREFIX_CHARS} characters or fewer"
+                ));
+            }
+            Ok(AndroidActionRule {
+                url_prefix,
+                app_id: rule.app_id.filter(|id| *id > 0),
+                action: rule.action,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn set_android_action_rules(
+    app: &AppHandle,
+    rules: Vec<AndroidActionRule>,
+) -> Result<SettingsResponse, String> {
+    let rules = normalize(rules)?;
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.android_action_rules = rules;
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!(
+        "android actions: {} rule(s)",
+        settings.android_action_rules.len()
+    ));
+    Ok(to_settings_response(settings))
+}
+
+/// The rule a freshly received message triggered, with its intent URL.
+#[derive(Debug, Clone)]
+pub(crate) struct MatchedAction {
+    action: AndroidAction,
+    intent_url: String,
+}
+
+pub(crate) fn match_message(app: &AppHandle, message: &CachedMessage) -> Option<MatchedAction> {
+    let intent_url = message.on_receive_intent_url()?;
+    let settings = read_settings(app).ok()?;
+    let rule = settings.android_action_rules.iter().find(|rule| {
+        rule.app_id.is_none_or(|id| id == message.app_id)
+            && intent_url.starts_with(rule.url_prefix.as_str())
+    })?;
+    Some(MatchedAction {
+        action: rule.action,
+        intent_url: intent_url.to_string(),
+    })
+}
+
+impl MatchedAction {
+    /// Local flags the action sets before the message is cached.
+    pub(crate) fn mark(&self, message: &mut CachedMessage) {
+        match self.action {
+            AndroidAction::Star => message.starred = true,
+            AndroidAction::MarkRead => message.read = true,
+            AndroidAction::Archive => message.archived = true,
+            AndroidAction::OpenUrl | AndroidAction::Silence | AndroidAction::Ignore => {}
+        }
+    }
+
+    /// Runs the action once the message is cached; returns whether the
+    /// message may still raise a notification.
+    pub(crate) fn run(&self, app: &AppHandle, message_id: i64) -> bool {
+        debug_log(&format!(
+            "android actions: id={message_id} action={:?}",
+            self.action
+        ));
+        match self.action {
+            AndroidAction::Silence => false,
+            AndroidAction::OpenUrl => {
+                // A paused app should not pop browser windows either.
+                let paused = read_settings(app)
+                    .ok()
+                    .and_then(|settings| settings.pause_until)
+                    .is_some_and(|until| {
+                        until == PAUSE_FOREVER_SENTINEL || unix_now_secs() < until
+                    });
+                if !paused {
+                    let url = self.intent_url.clone();
+                    std::thread::spawn(move || {
+                        if let Err(error) = crate::open_external_url(url) {
+                            debug_log(&format!(
+                                "android actions: id={message_id} open failed: {error}"
+                            ));
+                        }
+                    });
+                }
+                true
+            }
+            _ => true,
+        }
+    }
+}
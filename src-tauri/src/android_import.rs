@@ -0,0 +1,229 @@
+use std::{fs, path::Path};
+
+use rusqlite::{types::Value as SqlValue, Connection, OpenFlags};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::{
+    debug_log,
+    messages::convert_wire_message,
+    settings::{normalize_base_url, read_settings},
+    settings_bundle::resolve_user_path,
+    GotifyMessageWire, ANDROID_BACKUP_MAX_BYTES,
+};
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+const SQLITE_APP_ID_COLUMNS: &[&str] = &["appid", "app_id", "applicationid", "application_id"];
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct AndroidImportSummary {
+    pub(crate) imported: usize,
+    /// Already cached, deleted locally or listed twice in the file.
+    pub(crate) duplicates: usize,
+    /// Entries that are not Gotify messages.
+    pub(crate) skipped: usize,
+}
+
+/// Messages of a backup and the server it names, if any.
+struct BackupEntries {
+    source: Option<String>,
+    entries: Vec<Value>,
+}
+
+/// The Android client keeps messages exactly as `GET /message` returns them, so
+/// an exported history is either that response (`{"messages": [...]}`) or a
+/// bare array of messages. Only the object form can name its server, in a
+/// top-level `url`, `server` or `baseUrl`.
+fn message_entries(root: Value) -> Result<BackupEntries, String> {
+    match root {
+        Value::Array(entries) => Ok(BackupEntries {
+            source: None,
+            entries,
+        }),
+        Value::Object(mut object) => {
+            let source = ["url", "server", "baseUrl"]
+                .iter()
+                .find_map(|key| object.get(*key).and_then(Value::as_str))
+                .map(str::to_string);
+            match object.remove("messages") {
+                Some(Value::Array(entries)) => Ok(BackupEntries { source, entries }),
+                _ => Err("The backup has no \"messages\" list".to_string()),
+            }
+        }
+        _ => Err("The backup is not a list of Gotify messages".to_string()),
+    }
+}
+
+/// Message ids are only unique per server, so every backup must come from the
+/// active one: the server it names, or else the one the user says it is from.
+fn check_source(
+    named: Option<&str>,
+    stated: Option<&str>,
+    active_base_url: &str,
+) -> Result<(), String> {
+    let Some(source) = named.or(stated).filter(|source| !source.trim().is_empty()) else {
+        return Err("The backup does not name its server; say which server it is from".to_string());
+    };
+    let source = normalize_base_url(source)?;
+    if source != active_base_url {
+        return Err(format!(
+            "This backup is from {source}, not the active server {active_base_url}"
+        ));
+    }
+    Ok(())
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Gotify dates are RFC 3339 text; a database may also hold unix seconds or ms.
+fn sqlite_date(value: SqlValue) -> String {
+    match value {
+        SqlValue::Text(text) => text,
+        SqlValue::Integer(stamp) => {
+            let millis = if stamp > 100_000_000_000 {
+                stamp
+            } else {
+                stamp * 1000
+            };
+            chrono::DateTime::from_timestamp_millis(millis)
+                .map(|date| date.to_rfc3339())
+                .unwrap_or_default()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Reads the message table of the Android client's SQLite database. The table
+/// is found by its columns (`id`, `message` and an application id) rather than
+/// its name; other columns are optional.
+fn read_sqlite_messages(path: &Path) -> Result<Vec<Value>, String> {
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|error| format!("Failed to open the database: {error}"))?;
+    let sqlite_error = |error: rusqlite::Error| format!("Failed to read the database: {error}");
+    let tables: Vec<String> = connection
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table'")
+        .and_then(|mut statement| {
+            statement
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()
+        })
+        .map_err(sqlite_error)?;
+
+    for table in tables {
+        let columns: Vec<String> = connection
+            .prepare(&format!("PRAGMA table_info({})", quote_identifier(&table)))
+            .and_then(|mut statement| {
+                statement
+                    .query_map([], |row| row.get(1))?
+                    .collect::<Result<_, _>>()
+            })
+            .map_err(sqlite_error)?;
+        let find = |names: &[&str]| {
+            columns
+                .iter()
+                .find(|column| names.iter().any(|name| column.eq_ignore_ascii_case(name)))
+                .map(|column| quote_identifier(column))
+        };
+        let (Some(id), Some(app_id), Some(message)) = (
+            find(&["id"]),
+            find(SQLITE_APP_ID_COLUMNS),
+            find(&["message"]),
+        ) else {
+            continue;
+        };
+        let optional = |name: &str| find(&[name]).unwrap_or_else(|| "NULL".to_string());
+        let query = format!(
+            "SELECT {id}, {app_id}, {message}, {}, {}, {}, {} FROM {}",
+            optional("title"),
+            optional("priority"),
+            optional("date"),
+            optional("extras"),
+            quote_identifier(&table)
+        );
+        let mut statement = connection.prepare(&query).map_err(sqlite_error)?;
+        let entries = statement
+            .query_map([], |row| {
+                let extras = row
+                    .get::<_, Option<String>>(6)?
+                    .and_then(|extras| serde_json::from_str::<Value>(&extras).ok())
+                    .unwrap_or(Value::Null);
+                Ok(json!({
+                    "id": row.get::<_, i64>(0)?,
+                    "appid": row.get::<_, i64>(1)?,
+                    "message": row.get::<_, String>(2)?,
+                    "title": row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                    "priority": row.get::<_, Option<i64>>(4)?.unwrap_or(0),
+                    "date": sqlite_date(row.get::<_, SqlValue>(5)?),
+                    "extras": extras,
+                }))
+            })
+            .map_err(sqlite_error)?
+            // Rows of the wrong type count as skipped entries.
+            .map(|row| row.unwrap_or(Value::Null))
+            .collect();
+        debug_log(&format!("android import: reading database table {table:?}"));
+        return Ok(entries);
+    }
+    Err("The database has no Gotify message table".to_string())
+}
+
+fn read_backup(path: &Path) -> Result<Value, String> {
+    let metadata = fs::metadata(path).map_err(|error| format!("Failed to read backup: {error}"))?;
+    if metadata.len() > ANDROID_BACKUP_MAX_BYTES {
+        return Err(format!(
+            "Backup is larger than {} MB",
+            ANDROID_BACKUP_MAX_BYTES / (1024 * 1024)
+        ));
+    }
+    let bytes = fs::read(path).map_err(|error| format!("Failed to read backup: {error}"))?;
+    if bytes.starts_with(SQLITE_MAGIC) {
+        return read_sqlite_messages(path).map(Value::Array);
+    }
+    serde_json::from_slice(&bytes).map_err(|error| format!("The backup is not valid JSON: {error}"))
+}
+
+/// Merges messages from an Android client backup (JSON export or the SQLite
+/// database) into the active profile's cache, skipping ids that are already
+/// known. `server_url` says which server a backup that does not name one is from.
+pub(crate) async fn import_android_backup(
+    app: &AppHandle,
+    path: &str,
+    server_url: Option<&str>,
+) -> Result<AndroidImportSummary, String> {
+    let path = resolve_user_path(path)?;
+    let root =
+        crate::work::run_in_background_for("import-android-backup", move || read_backup(&path))
+            .await??;
+    let settings = read_settings(app)?;
+    let active_base_url = normalize_base_url(&settings.base_url)?;
+    let BackupEntries { source, entries } = message_entries(root)?;
+    check_source(source.as_deref(), server_url, &active_base_url)?;
+
+    let total = entries.len();
+    let mut messages = Vec::with_capacity(total);
+    for entry in entries {
+        let Ok(wire) = serde_json::from_value::<GotifyMessageWire>(entry) else {
+            continue;
+        };
+        let mut message = convert_wire_message(app, wire);
+        // History the user has already seen on the phone.
+        message.read = true;
+        message.imported = true;
+        messages.push(message);
+    }
+    let skipped = total - messages.len();
+    let parsed = messages.len();
+    let imported = crate::messages::merge_imported_messages(app, messages)?;
+    debug_log(&format!(
+        "android import: {imported} added, {} duplicate(s), {skipped} skipped",
+        parsed - imported
+    ));
+    Ok(AndroidImportSummary {
+        imported,
+        duplicates: parsed - imported,
+        skipped,
+    })
+}
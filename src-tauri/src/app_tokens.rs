@@ -0,0 +1,97 @@
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    settings::{load_settings, read_settings, save_non_secret_settings, SettingsResponse},
+    AppState, MAX_APP_TOKENS,
+};
+
+fn normalize_app_token_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Application token name is required".to_string());
+    }
+    if trimmed.chars().count() > 64 {
+        return Err("Application token name must be 64 characters or fewer".to_string());
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err("Application token name must not contain control characters".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Stores an application token under `name` for the active server, replacing a
+/// token with the same name. Application tokens can only publish, so sending
+/// never needs the client token.
+pub(crate) fn save_app_token(
+    app: &AppHandle,
+    name: &str,
+    token: &str,
+) -> Result<SettingsResponse, String> {
+    let name = normalize_app_token_name(name)?;
+    let token = token.trim();
+    if token.is_empty() {
+        return Err("Application token is required".to_string());
+    }
+    if token
+        .chars()
+        .any(|ch| ch.is_whitespace() || ch.is_control())
+    {
+        return Err("Application token must not contain spaces".to_string());
+    }
+    {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        if settings.token.as_deref().map(str::trim) == Some(token) {
+            return Err(
+                "This is the client token; create an application in Gotify and use its token"
+                    .to_string(),
+            );
+        }
+        if !settings.app_tokens.contains_key(&name) && settings.app_tokens.len() >= MAX_APP_TOKENS {
+            return Err(format!(
+                "At most {MAX_APP_TOKENS} application tokens can be stored"
+            ));
+        }
+        settings.app_tokens.insert(name.clone(), token.to_string());
+        mirror_into_active_profile(&mut settings);
+        save_non_secret_settings(app, &settings)?;
+    }
+    debug_log(&format!("app tokens: saved {name:?}"));
+    load_settings(app)
+}
+
+pub(crate) fn delete_app_token(app: &AppHandle, name: &str) -> Result<SettingsResponse, String> {
+    let name = normalize_app_token_name(name)?;
+    {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        if settings.app_tokens.remove(&name).is_none() {
+            return Err(format!("No application token named {name:?}"));
+        }
+        mirror_into_active_profile(&mut settings);
+        save_non_secret_settings(app, &settings)?;
+    }
+    debug_log(&format!("app tokens: removed {name:?}"));
+    load_settings(app)
+}
+
+/// Application tokens belong to one server, so they travel with its profile.
+fn mirror_into_active_profile(settings: &mut crate::settings::StoredSettings) {
+    let app_tokens = settings.app_tokens.clone();
+    if let Some(profile) = settings
+        .active_profile
+        .clone()
+        .and_then(|active| settings.profiles.get_mut(&active))
+    {
+        profile.app_tokens = app_tokens;
+    }
+}
@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{
+    client_token::decode_response,
+    debug_log,
+    settings::{load_token, read_settings, server_client_builder},
+    APP_ICON_MAX_BYTES,
+};
+
+/// A Gotify application as the webview sees it. The token is left out; it only
+/// leaves the backend by being saved as an application token.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ApplicationInfo {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    pub(crate) description: String,
+    pub(crate) internal: bool,
+    pub(crate) image: String,
+    pub(crate) default_priority: i64,
+    pub(crate) last_used: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApplicationWire {
+    id: i64,
+    #[serde(default)]
+    token: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    internal: bool,
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    default_priority: i64,
+    #[serde(default)]
+    last_used: Option<String>,
+}
+
+impl From<ApplicationWire> for ApplicationInfo {
+    fn from(wire: ApplicationWire) -> Self {
+        Self {
+            id: wire.id,
+            name: wire.name,
+            description: wire.description,
+            internal: wire.internal,
+            image: wire.image,
+            default_priority: wire.default_priority,
+            last_used: wire.last_used,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApplicationParams<'a> {
+    name: &'a str,
+    description: &'a str,
+    default_priority: i64,
+}
+
+/// Client, active base URL and client token for authenticated REST calls.
+pub(crate) struct ServerContext {
+    pub(crate) client: reqwest::Client,
+    pub(crate) base_url: String,
+    pub(crate) token: String,
+}
+
+pub(crate) fn server_context(app: &AppHandle) -> Result<ServerContext, String> {
+    let settings = read_settings(app)?;
+    let base_url = crate::endpoints::active_base_url(app, &settings)?;
+    let token =
+        load_token()?.ok_or_else(|| "No token found. Save token in settings first.".to_string())?;
+    let client = server_client_builder(app)?
+        .build()
+        .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
+    Ok(ServerContext {
+        client,
+        base_url,
+        token,
+    })
+}
+
+fn validate_params(name: &str, default_priority: Option<i64>) -> Result<(String, i64), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Application name is required".to_string());
+    }
+    Ok((name.to_string(), default_priority.unwrap_or(0).clamp(0, 10)))
+}
+
+pub(crate) async fn list_applications(app: &AppHandle) -> Result<Vec<ApplicationInfo>, String> {
+    fetch_applications(&server_context(app)?).await
+}
+
+async fn fetch_applications(context: &ServerContext) -> Result<Vec<ApplicationInfo>, String> {
+    let request = context
+        .client
+        .get(format!("{}/application", context.base_url))
+        .header("X-Gotify-Key", &context.token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to list applications: {error}"))?;
+    let apps = decode_response::<Vec<ApplicationWire>>(response, "List applications").await?;
+    Ok(apps.into_iter().map(ApplicationInfo::from).collect())
+}
+
+/// Creates an application. With `save_token` its token is stored as an
+/// application token under the application's name, ready for sending.
+pub(crate) async fn create_application(
+    app: &AppHandle,
+    name: &str,
+    description: &str,
+    default_priority: Option<i64>,
+    save_token: bool,
+) -> Result<ApplicationInfo, String> {
+    let (name, default_priority) = validate_params(name, default_priority)?;
+    let context = server_context(app)?;
+    let request = context
+        .client
+        .post(format!("{}/application", context.base_url))
+        .header("X-Gotify-Key", &context.token)
+        .json(&ApplicationParams {
+            name: &name,
+            description: description.trim(),
+            default_priority,
+        });
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to create application: {error}"))?;
+    let created = decode_response::<ApplicationWire>(response, "Create application").await?;
+    debug_log(&format!("applications: created id={}", created.id));
+    if save_token && !created.token.is_empty() {
+        let settings = crate::app_tokens::save_app_token(app, &created.name, &created.token)?;
+        crate::contract::publish_settings_update(app, settings);
+    }
+    refresh_app_meta(app, &context).await;
+    Ok(created.into())
+}
+
+/// Gotify replaces every field on update, so a description or default priority
+/// left unset keeps the application's current value instead of clearing it.
+pub(crate) async fn update_application(
+    app: &AppHandle,
+    app_id: i64,
+    name: &str,
+    description: Option<&str>,
+    default_priority: Option<i64>,
+) -> Result<ApplicationInfo, String> {
+    let context = server_context(app)?;
+    let current = if description.is_none() || default_priority.is_none() {
+        let current = fetch_applications(&context)
+            .await?
+            .into_iter()
+            .find(|application| application.id == app_id)
+            .ok_or_else(|| format!("No application with id {app_id}"))?;
+        Some(current)
+    } else {
+        None
+    };
+    let description = description
+        .or_else(|| current.as_ref().map(|current| current.description.as_str()))
+        .unwrap_or_default();
+    let default_priority =
+        default_priority.or_else(|| current.as_ref().map(|current| current.default_priority));
+    let (name, default_priority) = validate_params(name, default_priority)?;
+    let request = context
+        .client
+        .put(format!("{}/application/{app_id}", context.base_url))
+        .header("X-Gotify-Key", &context.token)
+        .json(&ApplicationParams {
+            name: &name,
+            description: description.trim(),
+            default_priority,
+        });
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to update application: {error}"))?;
+    let updated = decode_response::<ApplicationWire>(response, "Update application").await?;
+    debug_log(&format!("applications: updated id={app_id}"));
+    refresh_app_meta(app, &context).await;
+    Ok(updated.into())
+}
+
+/// Deletes an application; Gotify deletes its messages with it, so they leave
+/// the cache too.
+pub(crate) async fn delete_application(app: &AppHandle, app_id: i64) -> Result<(), String> {
+    let context = server_context(app)?;
+    let request = context
+        .client
+        .delete(format!("{}/application/{app_id}", context.base_url))
+        .header("X-Gotify-Key", &context.token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to delete application: {error}"))?;
+    let status = response.status().as_u16();
+    if !(200..300).contains(&status) {
+        return decode_response::<serde_json::Value>(response, "Delete application")
+            .await
+            .map(|_| ());
+    }
+    debug_log(&format!("applications: deleted id={app_id}"));
+    crate::messages::remove_app_messages_from_cache(app, app_id)?;
+    refresh_app_meta(app, &context).await;
+    Ok(())
+}
+
+/// Image types Gotify accepts; it checks the upload's file extension, so the
+/// type is sniffed from the bytes and the file name is derived from it.
+fn sniff_image_type(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(("png", "image/png"))
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some(("jpg", "image/jpeg"))
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(("gif", "image/gif"))
+    } else {
+        None
+    }
+}
+
+fn multipart_body(boundary: &str, extension: &str, content_type: &str, bytes: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(bytes.len() + 256);
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"image.{extension}\"\r\nContent-Type: {content_type}\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+/// Uploads a PNG, JPEG or GIF as the application image. The size limit is the
+/// one applied when icons are downloaded, so an accepted upload always shows.
+pub(crate) async fn upload_application_image(
+    app: &AppHandle,
+    app_id: i64,
+    file_path: &str,
+) -> Result<ApplicationInfo, String> {
+    let path = crate::settings_bundle::resolve_user_path(file_path)?;
+    let size = std::fs::metadata(&path)
+        .map_err(|error| format!("Cannot read {}: {error}", path.display()))?
+        .len();
+    if size == 0 {
+        return Err("The image file is empty".to_string());
+    }
+    if size > APP_ICON_MAX_BYTES as u64 {
+        return Err(format!(
+            "Image is too large ({size} bytes); the limit is {APP_ICON_MAX_BYTES} bytes"
+        ));
+    }
+    let bytes =
+        std::fs::read(&path).map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+    let (extension, content_type) = sniff_image_type(&bytes)
+        .ok_or_else(|| "Only PNG, JPEG and GIF images can be uploaded".to_string())?;
+
+    let context = server_context(app)?;
+    let boundary = format!("gotify-desktop-{}", crate::unique_time_suffix());
+    let request = context
+        .client
+        .post(format!("{}/application/{app_id}/image", context.base_url))
+        .header("X-Gotify-Key", &context.token)
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(multipart_body(&boundary, extension, content_type, &bytes));
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to upload application image: {error}"))?;
+    let updated = decode_response::<ApplicationWire>(response, "Upload application image").await?;
+    debug_log(&format!(
+        "applications: uploaded image id={app_id} bytes={size}"
+    ));
+    #[cfg(target_os = "macos")]
+    crate::notifications::forget_notification_icon(app, app_id);
+    refresh_app_meta(app, &context).await;
+    Ok(updated.into())
+}
+
+/// Reloads `app_meta` and rewrites the app name and icon stored on cached
+/// messages, so the change shows up without restarting the stream.
+async fn refresh_app_meta(app: &AppHandle, context: &ServerContext) {
+    if let Err(error) =
+        crate::messages::fetch_applications(app, &context.base_url, &context.token).await
+    {
+        debug_log(&format!(
+            "applications: failed to refresh app_meta: {error}"
+        ));
+        return;
+    }
+    if let Err(error) = crate::messages::apply_app_meta_to_cache(app) {
+        debug_log(&format!("applications: failed to relabel cache: {error}"));
+    }
+}
@@ -0,0 +1,109 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    settings::{read_settings, save_non_secret_settings, to_settings_response, SettingsResponse},
+    unix_now_secs, AppState, CachedMessage, DEFAULT_ARCHIVE_PAGE_LIMIT, MAX_ARCHIVE_PAGE_LIMIT,
+    MESSAGE_ARCHIVE_MAX_MESSAGES,
+};
+
+/// A message the server deleted, kept locally. Not to be confused with the
+/// `archived` flag, which pins a message in the live cache.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ArchivedMessage {
+    pub(crate) message: CachedMessage,
+    pub(crate) archived_at: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ArchivedMessagePage {
+    /// Most recently archived first.
+    pub(crate) messages: Vec<ArchivedMessage>,
+    pub(crate) total: usize,
+    /// Pass as `offset` for the next page; `None` on the last one.
+    pub(crate) next_offset: Option<usize>,
+}
+
+pub(crate) fn set_archive_remote_deletions(
+    app: &AppHandle,
+    enabled: bool,
+) -> Result<SettingsResponse, String> {
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.archive_remote_deletions = enabled;
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!("archive of remote deletions set to {enabled}"));
+    Ok(to_settings_response(settings))
+}
+
+/// Called with messages a sync found deleted on the server. Written on the
+/// background worker behind the cache write that dropped them. Returns whether
+/// they were queued for the archive.
+pub(crate) fn archive_remote_deletions(app: &AppHandle, messages: Vec<CachedMessage>) -> bool {
+    if messages.is_empty()
+        || !read_settings(app).is_ok_and(|settings| settings.archive_remote_deletions)
+    {
+        return false;
+    }
+    let cache_path = match crate::messages_file(app) {
+        Ok(path) => path,
+        Err(error) => {
+            debug_log(&format!("archive: {error}"));
+            return false;
+        }
+    };
+    let app = app.clone();
+    crate::work::run_in_background("archive-messages", move || {
+        let app_state = app.state::<AppState>();
+        let Ok(_persist_guard) = app_state.message_persist_lock.lock() else {
+            debug_log("message persist lock poisoned; messages not archived");
+            return;
+        };
+        match crate::message_store::archive_messages(
+            &cache_path,
+            &messages,
+            unix_now_secs(),
+            MESSAGE_ARCHIVE_MAX_MESSAGES,
+        ) {
+            Ok(()) => debug_log(&format!("archive: kept {} message(s)", messages.len())),
+            Err(error) => debug_log(&format!("archive: failed to write: {error}")),
+        }
+    });
+    true
+}
+
+pub(crate) async fn load_archived_messages(
+    app: &AppHandle,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<ArchivedMessagePage, String> {
+    let cache_path = crate::messages_file(app)?;
+    let offset = offset.unwrap_or(0);
+    let limit = limit
+        .unwrap_or(DEFAULT_ARCHIVE_PAGE_LIMIT)
+        .clamp(1, MAX_ARCHIVE_PAGE_LIMIT);
+    let (messages, total) = crate::work::run_in_background_for("load-archive", move || {
+        crate::message_store::load_archived_messages(&cache_path, offset, limit)
+    })
+    .await??;
+    let end = offset.saturating_add(limit);
+    Ok(ArchivedMessagePage {
+        messages: messages
+            .into_iter()
+            .map(|(message, archived_at)| ArchivedMessage {
+                message,
+                archived_at,
+            })
+            .collect(),
+        total,
+        next_offset: (end < total).then_some(end),
+    })
+}
@@ -0,0 +1,224 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::debug_log;
+
+/// Prefix of sealed files; anything else is read as legacy plaintext.
+const SEALED_MAGIC: &[u8] = b"GDENC1\0";
+
+/// Only a key that was actually loaded is kept; a failed Keychain lookup is
+/// retried on the next use.
+static DATA_KEY: OnceLock<[u8; 32]> = OnceLock::new();
+static KEY_LOAD: Mutex<()> = Mutex::new(());
+/// Set once a key is known to exist (loaded, present but unreadable, or a
+/// sealed file was seen). From then on `seal` refuses to fall back to plaintext.
+static KEY_EXPECTED: AtomicBool = AtomicBool::new(false);
+
+fn data_key() -> Option<&'static [u8; 32]> {
+    if let Some(key) = DATA_KEY.get() {
+        return Some(key);
+    }
+    // Serialized so two first uses cannot each create a different Keychain key.
+    let _guard = KEY_LOAD
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(key) = DATA_KEY.get() {
+        return Some(key);
+    }
+    let key = load_or_create_key()?;
+    KEY_EXPECTED.store(true, Ordering::SeqCst);
+    Some(DATA_KEY.get_or_init(|| key))
+}
+
+/// The key lives in the login Keychain, so copying the app's config directory to
+/// another account or machine does not expose cached messages or settings.
+#[cfg(target_os = "macos")]
+fn load_or_create_key() -> Option<[u8; 32]> {
+    use base64::Engine as _;
+
+    let entry = match keyring::Entry::new(
+        crate::AT_REST_KEYCHAIN_SERVICE,
+        crate::AT_REST_KEYCHAIN_ACCOUNT,
+    ) {
+        Ok(entry) => entry,
+        Err(error) => {
+            debug_log(&format!("at-rest: keychain unavailable: {error}"));
+            return None;
+        }
+    };
+    match entry.get_password() {
+        Ok(encoded) => {
+            KEY_EXPECTED.store(true, Ordering::SeqCst);
+            let decoded = base64::engine::general_purpose::STANDARD
+                .decode(encoded.trim())
+                .ok()?;
+            match <[u8; 32]>::try_from(decoded.as_slice()) {
+                Ok(key) => Some(key),
+                Err(_) => {
+                    debug_log("at-rest: keychain key has the wrong length");
+                    None
+                }
+            }
+        }
+        Err(keyring::Error::NoEntry) => {
+            let mut key = [0u8; 32];
+            SystemRandom::new().fill(&mut key).ok()?;
+            let encoded = base64::engine::general_purpose::STANDARD.encode(key);
+            if let Err(error) = entry.set_password(&encoded) {
+                debug_log(&format!(
+                    "at-rest: failed to store key in keychain: {error}"
+                ));
+                return None;
+            }
+            debug_log("at-rest: created data key in keychain");
+            Some(key)
+        }
+        Err(error) => {
+            KEY_EXPECTED.store(true, Ordering::SeqCst);
+            debug_log(&format!(
+                "at-rest: failed to read key from keychain: {error}"
+            ));
+            None
+        }
+    }
+}
+
+/// No Keychain outside macOS; files stay plaintext (0600) as before.
+#[cfg(not(target_os = "macos"))]
+fn load_or_create_key() -> Option<[u8; 32]> {
+    None
+}
+
+/// Looks the key up without creating it when it is missing.
+#[cfg(target_os = "macos")]
+fn key_stored() -> bool {
+    keyring::Entry::new(
+        crate::AT_REST_KEYCHAIN_SERVICE,
+        crate::AT_REST_KEYCHAIN_ACCOUNT,
+    )
+    .and_then(|entry| entry.get_password())
+    .is_ok()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn key_stored() -> bool {
+    false
+}
+
+fn cipher(key: &[u8; 32]) -> Result<LessSafeKey, String> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "Invalid at-rest key".to_string())
+}
+
+/// Whether `seal` encrypts, i.e. the Keychain key is available.
+pub(crate) fn sealing_enabled() -> bool {
+    data_key().is_some()
+}
+
+/// Like `sealing_enabled`, for reporting only: never creates the Keychain key.
+pub(crate) fn key_present() -> bool {
+    DATA_KEY.get().is_some() || key_stored()
+}
+
+/// Encrypts `plaintext` with AES-256-GCM, binding it to `label` so one sealed file
+/// cannot be swapped in for another. Without a key the plaintext is returned as
+/// is, unless a key existed before: then writing would replace sealed data with
+/// plaintext, so it fails instead.
+pub(crate) fn seal(label: &str, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let Some(key) = data_key() else {
+        if KEY_EXPECTED.load(Ordering::SeqCst) {
+            return Err(format!(
+                "Refusing to write {label} unencrypted: the Keychain key is unavailable"
+            ));
+        }
+        return Ok(plaintext.to_vec());
+    };
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce_bytes)
+        .map_err(|_| "Failed to generate nonce".to_string())?;
+    let mut in_out = plaintext.to_vec();
+    cipher(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce_bytes),
+            Aad::from(label.as_bytes()),
+            &mut in_out,
+        )
+        .map_err(|_| format!("Failed to encrypt {label}"))?;
+
+    let mut sealed = Vec::with_capacity(SEALED_MAGIC.len() + NONCE_LEN + in_out.len());
+    sealed.extend_from_slice(SEALED_MAGIC);
+    sealed.extend_from_slice(&nonce_bytes);
+    sealed.extend_from_slice(&in_out);
+    Ok(sealed)
+}
+
+/// Reverses [`seal`]; legacy plaintext passes through unchanged.
+pub(crate) fn open(label: &str, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    let Some(body) = bytes.strip_prefix(SEALED_MAGIC) else {
+        return Ok(bytes);
+    };
+    KEY_EXPECTED.store(true, Ordering::SeqCst);
+    let key = data_key()
+        .ok_or_else(|| format!("{label} is encrypted but the Keychain key is unavailable"))?;
+    if body.len() < NONCE_LEN {
+        return Err(format!("{label} is truncated"));
+    }
+    let (nonce_bytes, ciphertext) = body.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)
+        .map_err(|_| format!("{label} has an invalid nonce"))?;
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = cipher(key)?
+        .open_in_place(nonce, Aad::from(label.as_bytes()), &mut in_out)
+        .map_err(|_| format!("Failed to decrypt {label} (wrong Keychain key or corrupt file)"))?;
+    Ok(plaintext.to_vec())
+}
+
+pub(crate) fn read_to_string(label: &str, path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|error| format!("Failed to read {label}: {error}"))?;
+    String::from_utf8(open(label, bytes)?)
+        .map_err(|error| format!("{label} is not valid UTF-8: {error}"))
+}
+
+/// Startup migration: rewrites a legacy plaintext file sealed, in place.
+pub(crate) fn migrate_plaintext(label: &str, path: &Path) {
+    if data_key().is_none() {
+        return;
+    }
+    let Ok(bytes) = fs::read(path) else {
+        return;
+    };
+    if bytes.starts_with(SEALED_MAGIC) {
+        return;
+    }
+    let sealed = match seal(label, &bytes) {
+        Ok(sealed) => sealed,
+        Err(error) => {
+            debug_log(&format!("at-rest: failed to encrypt {label}: {error}"));
+            return;
+        }
+    };
+    let tmp_path = path.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
+    let result = fs::write(&tmp_path, sealed)
+        .map_err(|error| error.to_string())
+        .and_then(|()| {
+            crate::restrict_file_permissions(&tmp_path);
+            fs::rename(&tmp_path, path).map_err(|error| error.to_string())
+        });
+    match result {
+        Ok(()) => debug_log(&format!("at-rest: encrypted existing {label}")),
+        Err(error) => debug_log(&format!("at-rest: failed to migrate {label}: {error}")),
+    }
+}
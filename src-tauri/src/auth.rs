@@ -0,0 +1,64 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{contract::now_ms, debug_log, AppState, CONNECTION_STATE_AUTH_FAILED};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct AuthRequiredEvent {
+    pub(crate) at_ms: u64,
+    /// `stream` for the websocket handshake, `sync` for `GET /message`.
+    pub(crate) source: String,
+    pub(crate) status: u16,
+    pub(crate) base_url: String,
+}
+
+pub(crate) fn is_auth_failure(status: u16) -> bool {
+    status == 401 || status == 403
+}
+
+/// The server rejected the client token. Retrying with the same token cannot
+/// succeed and only fills the server's auth log, so the stream is stopped and
+/// stays down (wake and network recovery included) until credentials change and
+/// the stream is started again.
+pub(crate) fn report_auth_failure(app: &AppHandle, source: &str, status: u16) {
+    let state = app.state::<AppState>();
+    {
+        let Ok(mut runtime) = state.runtime.lock() else {
+            return;
+        };
+        if runtime.connection_state == CONNECTION_STATE_AUTH_FAILED {
+            return;
+        }
+        runtime.stop_foreground_stream();
+        runtime.should_run = false;
+        runtime.backoff_seconds = 0;
+        runtime.last_error = Some(format!(
+            "Server rejected the client token (HTTP {status}); save a new token to reconnect"
+        ));
+    }
+    debug_log(&format!(
+        "auth failure source={source} status={status}; reconnection paused"
+    ));
+    crate::connection_history::record(
+        app,
+        crate::connection_history::ConnectionEventKind::AuthFailed,
+        Some(format!("{source}: HTTP {status}")),
+    );
+    crate::stream::update_connection_state(app, CONNECTION_STATE_AUTH_FAILED);
+    crate::diagnostics::publish_runtime_snapshot(app);
+
+    let base_url = crate::settings::read_settings(app)
+        .map(|settings| settings.base_url)
+        .unwrap_or_default();
+    crate::contract::emit_event(
+        app,
+        "main",
+        "auth-required",
+        AuthRequiredEvent {
+            at_ms: now_ms(),
+            source: source.to_string(),
+            status,
+            base_url,
+        },
+    );
+}
@@ -0,0 +1,433 @@
+use std::collections::{BTreeMap, HashMap};
+
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use crate::{
+    connection_history::{record_background, ConnectionEventKind},
+    debug_log,
+    diagnostics::publish_runtime_snapshot,
+    messages::fetch_server_messages_after,
+    model::BackgroundStreamStatus,
+    profiles::ServerProfile,
+    settings::{read_settings, split_ipv6_zone},
+    stream::{stream_once, StreamOwner, StreamTarget},
+    truncate_message, unix_now_secs, AppState, GotifyApplicationWire, StreamSlot,
+    CONNECTION_STATE_AUTH_FAILED,
+};
+
+/// Runs a stream for every saved profile that asks for one and is not the
+/// active server, and stops the rest. Called whenever the active profile or a
+/// profile's background setting changes.
+pub(crate) fn sync(app: &AppHandle) {
+    let settings = match read_settings(app) {
+        Ok(settings) => settings,
+        Err(error) => {
+            debug_log(&format!(
+                "background streams: failed to read settings: {error}"
+            ));
+            return;
+        }
+    };
+    let active = crate::stream::stream_key(&settings);
+    let wanted: BTreeMap<String, ServerProfile> = settings
+        .profiles
+        .into_iter()
+        .filter(|(name, profile)| {
+            profile.background_stream
+                && *name != active
+                && !profile.base_url.trim().is_empty()
+                && profile
+                    .token
+                    .as_deref()
+                    .is_some_and(|token| !token.trim().is_empty())
+        })
+        .collect();
+
+    let mut started = Vec::new();
+    {
+        let state = app.state::<AppState>();
+        let Ok(mut runtime) = state.runtime.lock() else {
+            debug_log("background streams: runtime lock poisoned");
+            return;
+        };
+        let stale: Vec<String> = runtime
+            .streams
+            .iter()
+            .filter(|(name, slot)| slot.background.is_some() && !wanted.contains_key(*name))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in stale {
+            if let Some(slot) = runtime.streams.remove(&name) {
+                let _ = slot.stop_tx.send(true);
+                debug_log(&format!("background streams: stopped {name:?}"));
+            }
+        }
+        for (name, profile) in wanted {
+            if runtime.streams.contains_key(&name) {
+                continue;
+            }
+            let (stop_tx, stop_rx) = watch::channel(false);
+            runtime.stream_epoch = runtime.stream_epoch.wrapping_add(1);
+            let epoch = runtime.stream_epoch;
+            runtime.streams.insert(
+                name.clone(),
+                StreamSlot {
+                    stop_tx,
+                    epoch,
+                    background: Some(BackgroundStreamStatus {
+                        profile: name.clone(),
+                        connection_state: "Connecting".to_string(),
+                        last_connected_at: None,
+                        last_message_at: None,
+                        last_error: None,
+                        reconnect_attempts: 0,
+                    }),
+                },
+            );
+            started.push((name, profile, stop_rx, epoch));
+        }
+    }
+    for (name, profile, stop_rx, epoch) in started {
+        debug_log(&format!("background streams: starting {name:?}"));
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            run(app, name, profile, stop_rx, epoch).await;
+        });
+    }
+    status_changed(app);
+}
+
+/// Stops one profile's background stream, if it has one.
+pub(crate) fn stop(app: &AppHandle, name: &str) {
+    let state = app.state::<AppState>();
+    let Ok(mut runtime) = state.runtime.lock() else {
+        return;
+    };
+    if runtime
+        .streams
+        .get(name)
+        .is_some_and(|slot| slot.background.is_some())
+    {
+        if let Some(slot) = runtime.streams.remove(name) {
+            let _ = slot.stop_tx.send(true);
+        }
+    }
+}
+
+/// Stops every background stream; their loops close the sockets on the way out.
+pub(crate) fn stop_all(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(mut runtime) = state.runtime.lock() else {
+        return;
+    };
+    runtime.streams.retain(|_, slot| {
+        if slot.background.is_none() {
+            return true;
+        }
+        let _ = slot.stop_tx.send(true);
+        false
+    });
+}
+
+/// Reconnects every background stream, e.g. after wake when their sockets are
+/// likely dead.
+pub(crate) fn restart(app: &AppHandle) {
+    stop_all(app);
+    sync(app);
+}
+
+fn status_changed(app: &AppHandle) {
+    crate::stream::refresh_tray_status(app);
+    crate::profiles::sync_tray_tooltip(app);
+    publish_runtime_snapshot(app);
+}
+
+/// Applies `update` to the stream's status while `epoch` still owns the slot.
+fn update_status(
+    app: &AppHandle,
+    name: &str,
+    epoch: u64,
+    update: impl FnOnce(&mut BackgroundStreamStatus),
+) {
+    let state = app.state::<AppState>();
+    let updated = state.runtime.lock().ok().is_some_and(|mut runtime| {
+        match runtime
+            .streams
+            .get_mut(name)
+            .filter(|slot| slot.epoch == epoch)
+            .and_then(|slot| slot.background.as_mut())
+        {
+            Some(status) => {
+                update(status);
+                true
+            }
+            None => false,
+        }
+    });
+    if updated {
+        status_changed(app);
+    }
+}
+
+/// A background profile's side of a stream session: its slot, the
+/// application names used for labels and the newest message id seen.
+#[derive(Clone)]
+pub(crate) struct BackgroundOwner {
+    name: String,
+    epoch: u64,
+    app_names: HashMap<i64, String>,
+    last_message_id: Option<i64>,
+}
+
+impl BackgroundOwner {
+    pub(crate) fn record(
+        &self,
+        app: &AppHandle,
+        kind: ConnectionEventKind,
+        detail: Option<String>,
+    ) {
+        record_background(app, &self.name, kind, detail);
+    }
+
+    pub(crate) async fn connected(&mut self, app: &AppHandle, target: &StreamTarget, now: u64) {
+        update_status(app, &self.name, self.epoch, |status| {
+            status.connection_state = "Connected".to_string();
+            status.last_connected_at = Some(now);
+            status.last_error = None;
+        });
+        if self.app_names.is_empty() {
+            match fetch_app_names(app, target).await {
+                Ok(names) => self.app_names = names,
+                Err(error) => debug_log(&format!(
+                    "background stream {:?}: failed to fetch applications: {error}",
+                    self.name
+                )),
+            }
+        }
+    }
+
+    pub(crate) fn deliver(&mut self, app: &AppHandle, wire: crate::GotifyMessageWire) {
+        self.last_message_id = self.last_message_id.max(Some(wire.id));
+        let received_at_ms = crate::contract::now_ms();
+        let app_label = self
+            .app_names
+            .get(&wire.appid)
+            .cloned()
+            .unwrap_or_else(|| format!("app:{}", wire.appid));
+        let message = crate::messages::convert_wire_message_with(app, wire, app_label, None);
+        debug_log(&format!(
+            "background stream {:?}: message id={}",
+            self.name, message.id
+        ));
+        let now = unix_now_secs();
+        update_status(app, &self.name, self.epoch, |status| {
+            status.last_message_at = Some(now);
+        });
+        let app_for_notify = app.clone();
+        let profile = self.name.clone();
+        let notified = message.clone();
+        crate::messages::append_profile_message(app, &self.name, message, move || {
+            crate::notifications::maybe_notify_background_message(
+                &app_for_notify,
+                &notified,
+                Some(received_at_ms),
+                &profile,
+            );
+        });
+    }
+
+    /// Delivers what arrived after the newest message seen, oldest first; the
+    /// profile cache drops the ones it already has. With nothing seen yet there
+    /// is nothing to compare against, so the newest page is not replayed as
+    /// notifications.
+    pub(crate) async fn catch_up(mut self, app: &AppHandle, target: &StreamTarget) {
+        let last_seen = match self.last_message_id {
+            Some(id) => Some(id),
+            None => newest_cached_id(app, &self.name).await,
+        };
+        let Some(last_seen) = last_seen else {
+            return;
+        };
+        let owner = StreamOwner::Background(self.clone());
+        match fetch_server_messages_after(app, &owner, target, last_seen).await {
+            Ok((mut incoming, complete)) => {
+                debug_log(&format!(
+                    "background stream {:?}: catch-up delivered {} message(s) after id={last_seen}{}",
+                    self.name,
+                    incoming.len(),
+                    if complete { "" } else { " (cache limit reached)" }
+                ));
+                incoming.sort_by_key(|wire| wire.id);
+                for wire in incoming {
+                    self.deliver(app, wire);
+                }
+            }
+            Err(error) => debug_log(&format!(
+                "background stream {:?}: reconnect catch-up failed: {error}",
+                self.name
+            )),
+        }
+    }
+}
+
+async fn newest_cached_id(app: &AppHandle, name: &str) -> Option<i64> {
+    let path = crate::profile_messages_file(app, name).ok()?;
+    crate::work::run_in_background_for("background-newest-id", move || {
+        crate::message_store::load_messages(&path)
+            .ok()?
+            .iter()
+            .map(|message| message.id)
+            .max()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+async fn run(
+    app: AppHandle,
+    name: String,
+    profile: ServerProfile,
+    mut stop_rx: watch::Receiver<bool>,
+    epoch: u64,
+) {
+    let (base_url, zone) = split_ipv6_zone(&profile.base_url);
+    let target = StreamTarget {
+        base_url,
+        token: profile.token.unwrap_or_default(),
+        host_pins: crate::settings::parse_host_pins(profile.host_pins),
+        zone,
+        token_in_query: profile.stream_token_in_query,
+    };
+    let mut owner = StreamOwner::Background(BackgroundOwner {
+        name: name.clone(),
+        epoch,
+        app_names: HashMap::new(),
+        last_message_id: None,
+    });
+    let mut backoff_secs = crate::stream::reconnect_backoff(&app).base_secs;
+    // Like the active server, every session after the first catches up on
+    // what the dropped socket missed.
+    let mut reconnecting = false;
+
+    loop {
+        if *stop_rx.borrow() {
+            break;
+        }
+        update_status(&app, &name, epoch, |status| {
+            status.connection_state = "Connecting".to_string();
+        });
+        let attempt_started_at = unix_now_secs();
+        let result = stream_once(&app, &mut owner, &target, &mut stop_rx, reconnecting).await;
+        reconnecting = true;
+        if *stop_rx.borrow() {
+            break;
+        }
+        let error = match result {
+            Ok(()) => "Stream ended unexpectedly".to_string(),
+            Err(error) => {
+                if let Some(status) = error
+                    .status
+                    .filter(|status| crate::auth::is_auth_failure(*status))
+                {
+                    // Same rule as the active server: a rejected token is not retried.
+                    debug_log(&format!(
+                        "background stream {name:?}: token rejected (HTTP {status})"
+                    ));
+                    record_background(
+                        &app,
+                        &name,
+                        ConnectionEventKind::AuthFailed,
+                        Some(format!("HTTP {status}")),
+                    );
+                    update_status(&app, &name, epoch, |stream| {
+                        stream.connection_state = CONNECTION_STATE_AUTH_FAILED.to_string();
+                        stream.last_error =
+                            Some(format!("Server rejected the client token (HTTP {status})"));
+                    });
+                    return;
+                }
+                error.message
+            }
+        };
+        debug_log(&format!("background stream {name:?}: {error}"));
+        record_background(
+            &app,
+            &name,
+            ConnectionEventKind::Disconnected,
+            Some(error.clone()),
+        );
+
+        let policy = crate::stream::reconnect_backoff(&app);
+        let mut connected_at = None;
+        update_status(&app, &name, epoch, |status| {
+            connected_at = status
+                .last_connected_at
+                .filter(|connected_at| *connected_at >= attempt_started_at);
+            status.connection_state = "Backoff".to_string();
+            status.last_error = Some(truncate_message(&error, 300));
+            status.reconnect_attempts = status.reconnect_attempts.saturating_add(1);
+        });
+        if connected_at.is_some_and(|connected_at| {
+            unix_now_secs().saturating_sub(connected_at)
+                >= crate::stream::backoff_reset_after_secs(&app)
+        }) {
+            backoff_secs = policy.base_secs;
+        }
+        record_background(
+            &app,
+            &name,
+            ConnectionEventKind::Backoff,
+            Some(format!("{backoff_secs}s")),
+        );
+
+        let backoff = tokio::time::sleep(std::time::Duration::from_secs(backoff_secs));
+        let state = app.state::<AppState>();
+        tokio::select! {
+            _ = backoff => {
+                backoff_secs = policy.next(backoff_secs);
+            }
+            _ = state.network_wake.notified() => {
+                backoff_secs = policy.base_secs;
+            }
+            _ = stop_rx.changed() => {}
+        }
+    }
+    debug_log(&format!("background stream {name:?}: stopped"));
+}
+
+/// Application names for message labels; icons are only loaded for the
+/// active server.
+async fn fetch_app_names(
+    app: &AppHandle,
+    target: &StreamTarget,
+) -> Result<HashMap<i64, String>, String> {
+    let client = crate::settings::profile_client_builder(
+        app,
+        target.host_pins.clone(),
+        target.zone.clone(),
+    )?
+    .build()
+    .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
+    let request = client
+        .get(format!("{}/application", target.base_url))
+        .header("X-Gotify-Key", &target.token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to fetch applications: {error}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to fetch applications: HTTP {}",
+            response.status().as_u16()
+        ));
+    }
+    let applications = response
+        .json::<Vec<GotifyApplicationWire>>()
+        .await
+        .map_err(|error| format!("Failed to parse applications: {error}"))?;
+    Ok(applications
+        .into_iter()
+        .map(|application| (application.id, application.name))
+        .collect())
+}
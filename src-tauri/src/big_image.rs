@@ -0,0 +1,136 @@
+use std::{fs, time::Duration};
+
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use crate::{
+    debug_log, BIG_IMAGE_CACHE_MAX_FILES, BIG_IMAGE_MAX_BYTES, BIG_IMAGE_REQUEST_TIMEOUT_SECS,
+};
+
+const BIG_IMAGE_FILE_PREFIX: &str = "big-";
+
+/// Local PNG for a message's `client::notification.bigImageUrl`, downloaded
+/// and converted on first use. Runs on the notification thread, so it blocks.
+pub(crate) fn resolve_path(app: &AppHandle, url: &str) -> Option<String> {
+    let icons_dir = crate::notifications::notification_icon_cache_dir(app)?;
+    let digest = Sha256::digest(url.as_bytes());
+    let file_path = icons_dir.join(format!(
+        "{BIG_IMAGE_FILE_PREFIX}{}.png",
+        digest[..12]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>()
+    ));
+    if crate::icns::is_png_file(&file_path) {
+        return Some(file_path.to_string_lossy().to_string());
+    }
+
+    let bytes = match tauri::async_runtime::block_on(download(app, url)) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            debug_log(&format!("big image: {error}"));
+            return None;
+        }
+    };
+    let png = match crate::icns::icon_to_png(&bytes) {
+        Ok(png) => png,
+        Err(error) => {
+            debug_log(&format!("big image: failed converting {url}: {error}"));
+            return None;
+        }
+    };
+    prune(&icons_dir);
+    if let Err(error) = fs::write(&file_path, &png) {
+        debug_log(&format!("big image: failed writing cache file: {error}"));
+        return None;
+    }
+    Some(file_path.to_string_lossy().to_string())
+}
+
+/// Publishers point at their own hosts, so unlike link previews private
+/// addresses are allowed; server headers and tokens are never sent along.
+async fn download(app: &AppHandle, url: &str) -> Result<Vec<u8>, String> {
+    let parsed = reqwest::Url::parse(url.trim())
+        .map_err(|error| format!("invalid bigImageUrl {url:?}: {error}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "unsupported bigImageUrl scheme '{}'",
+            parsed.scheme()
+        ));
+    }
+    let proxy = crate::proxy::configured_proxy(app);
+    let mut response = crate::proxy::apply_to_client(reqwest::Client::builder(), proxy.as_ref())?
+        .timeout(Duration::from_secs(BIG_IMAGE_REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|error| format!("failed to build HTTP client: {error}"))?
+        .get(parsed)
+        .send()
+        .await
+        .map_err(|error| format!("request failed: {error}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "request failed with HTTP {}",
+            response.status().as_u16()
+        ));
+    }
+    if let Some(content_length) = response.content_length() {
+        if content_length > BIG_IMAGE_MAX_BYTES as u64 {
+            return Err(format!(
+                "image too large ({content_length} bytes > {BIG_IMAGE_MAX_BYTES})"
+            ));
+        }
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("image/png")
+        .to_ascii_lowercase();
+    if !content_type.starts_with("image/") {
+        return Err(format!("response is not an image ({content_type})"));
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|error| format!("failed to read image body: {error}"))?
+    {
+        if bytes.len().saturating_add(chunk.len()) > BIG_IMAGE_MAX_BYTES {
+            return Err(format!("image exceeded {BIG_IMAGE_MAX_BYTES} byte limit"));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    if bytes.is_empty() {
+        return Err("image response was empty".to_string());
+    }
+    Ok(bytes)
+}
+
+/// Keeps the newest cached images; app icons in the same directory are left
+/// alone.
+fn prune(icons_dir: &std::path::Path) {
+    let Ok(entries) = fs::read_dir(icons_dir) else {
+        return;
+    };
+    let mut cached: Vec<_> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(BIG_IMAGE_FILE_PREFIX)
+        })
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|meta| meta.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+    if cached.len() < BIG_IMAGE_CACHE_MAX_FILES {
+        return;
+    }
+    cached.sort_unstable_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in cached.into_iter().skip(BIG_IMAGE_CACHE_MAX_FILES - 1) {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    messages::{self, SyncMode},
+    operations::Operation,
+    settings::{load_token, read_settings},
+    AppState, CachedMessage, MESSAGE_ARCHIVE_MAX_BYTES,
+};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct MessageImportSummary {
+    pub(crate) imported: usize,
+    /// Already cached with the same date, deleted locally or listed twice.
+    pub(crate) duplicates: usize,
+    /// Cached under the same id with a different date, so from another server
+    /// or a reset database. The cached message is kept.
+    pub(crate) conflicts: usize,
+    /// Entries that are not exported messages.
+    pub(crate) skipped: usize,
+}
+
+/// Starts deleting `message_ids` one by one (trash rules apply per message) and
+/// returns the operation id at once; progress arrives as `operation-progress`.
+pub(crate) fn start_delete_messages(app: &AppHandle, message_ids: Vec<i64>) -> Result<u64, String> {
+    let mut seen = HashSet::new();
+    let message_ids: Vec<i64> = message_ids
+        .into_iter()
+        .filter(|id| *id > 0 && seen.insert(*id))
+        .collect();
+    if message_ids.is_empty() {
+        return Err("No messages selected".to_string());
+    }
+    let operation = Operation::begin(app, "delete", message_ids.len());
+    let op_id = operation.id();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        run_delete_messages(&app, operation, message_ids).await;
+    });
+    Ok(op_id)
+}
+
+async fn run_delete_messages(app: &AppHandle, mut operation: Operation, message_ids: Vec<i64>) {
+    let mut last_error = None;
+    for message_id in message_ids {
+        if operation.is_cancelled() {
+            break;
+        }
+        match crate::delete_message_by_id(app, message_id, false).await {
+            Ok(()) => operation.advance(true),
+            Err(error) => {
+                last_error = Some(error);
+                operation.advance(false);
+            }
+        }
+    }
+    resync(app).await;
+    operation.finish(last_error);
+}
+
+/// Deletes every message of one application. With the trash on, the cached
+/// messages of the app are trashed one by one (messages beyond the cache limit
+/// stay on the server); otherwise a single `DELETE /application/{id}/message`
+/// removes them all on the server.
+pub(crate) fn start_delete_app_messages(app: &AppHandle, app_id: i64) -> Result<u64, String> {
+    if app_id <= 0 {
+        return Err("Invalid application id".to_string());
+    }
+    if crate::trash::retention_days(app) > 0 {
+        let message_ids: Vec<i64> = app
+            .state::<AppState>()
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?
+            .iter()
+            .filter(|message| message.app_id == app_id)
+            .map(|message| message.id)
+            .collect();
+        if message_ids.is_empty() {
+            return Err("No cached messages for this application".to_string());
+        }
+        let operation = Operation::begin(app, "delete-app", message_ids.len());
+        let op_id = operation.id();
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            run_delete_messages(&app, operation, message_ids).await;
+        });
+        return Ok(op_id);
+    }
+
+    let operation = Operation::begin(app, "delete-app", 1);
+    let op_id = operation.id();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut operation = operation;
+        if operation.is_cancelled() {
+            operation.finish(None);
+            return;
+        }
+        let result = delete_app_messages_on_server(&app, app_id).await;
+        operation.advance(result.is_ok());
+        resync(&app).await;
+        operation.finish(result.err());
+    });
+    Ok(op_id)
+}
+
+async fn delete_app_messages_on_server(app: &AppHandle, app_id: i64) -> Result<(), String> {
+    let settings = read_settings(app)?;
+    let base_url = crate::endpoints::active_base_url(app, &settings)?;
+    let token =
+        load_token()?.ok_or_else(|| "No token found. Save token in settings first.".to_string())?;
+    let client = crate::settings::server_client_builder(app)?
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
+    let request = client
+        .delete(format!("{base_url}/application/{app_id}/message"))
+        .header("X-Gotify-Key", &token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to delete messages of app {app_id}: {error}"))?;
+    let status = response.status().as_u16();
+    debug_log(&format!(
+        "delete_app_messages app_id={app_id} http={status}"
+    ));
+    if !(200..300).contains(&status) {
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "<unable to read response body>".to_string());
+        return Err(format!(
+            "Delete failed (HTTP {status}): {}",
+            crate::truncate_message(&body, 200)
+        ));
+    }
+    messages::remove_app_messages_from_cache(app, app_id)?;
+    Ok(())
+}
+
+/// Writes the cached messages (optionally of one app) to `path` as a JSON array.
+/// A cancelled export leaves no file behind.
+pub(crate) fn start_export_messages(
+    app: &AppHandle,
+    path: &str,
+    app_id: Option<i64>,
+) -> Result<u64, String> {
+    let path = crate::settings_bundle::resolve_user_path(path)?;
+    let selected: Vec<CachedMessage> = app
+        .state::<AppState>()
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?
+        .iter()
+        .filter(|message| app_id.is_none_or(|app_id| message.app_id == app_id))
+        .cloned()
+        .collect();
+    if selected.is_empty() {
+        return Err("No messages to export".to_string());
+    }
+    let mut operation = Operation::begin(app, "export", selected.len());
+    let op_id = operation.id();
+    crate::work::run_in_background("export-messages", move || {
+        let tmp_path = path.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
+        let result = (|| {
+            let mut writer = BufWriter::new(
+                File::create(&tmp_path)
+                    .map_err(|error| format!("Failed to create export: {error}"))?,
+            );
+            writer.write_all(b"[").map_err(|error| error.to_string())?;
+            for (index, message) in selected.iter().enumerate() {
+                if operation.is_cancelled() {
+                    return Ok(false);
+                }
+                if index > 0 {
+                    writer.write_all(b",").map_err(|error| error.to_string())?;
+                }
+                serde_json::to_writer(&mut writer, message).map_err(|error| error.to_string())?;
+                operation.advance(true);
+            }
+            writer.write_all(b"]").map_err(|error| error.to_string())?;
+            writer.flush().map_err(|error| error.to_string())?;
+            drop(writer);
+            crate::restrict_file_permissions(&tmp_path);
+            fs::rename(&tmp_path, &path)
+                .map_err(|error| format!("Failed to move export into place: {error}"))?;
+            Ok(true)
+        })();
+        match result {
+            Ok(true) => {
+                debug_log(&format!("messages exported to {}", path.display()));
+                operation.finish(None);
+            }
+            Ok(false) => {
+                let _ = fs::remove_file(&tmp_path);
+                operation.finish(None);
+            }
+            Err(error) => {
+                let _ = fs::remove_file(&tmp_path);
+                operation.finish(Some(error));
+            }
+        }
+    });
+    Ok(op_id)
+}
+
+async fn resync(app: &AppHandle) {
+    let Ok(Some(token)) = load_token() else {
+        return;
+    };
+    let Ok(base_url) =
+        read_settings(app).and_then(|settings| crate::endpoints::active_base_url(app, &settings))
+    else {
+        return;
+    };
+    if let Err(error) =
+        messages::fetch_recent_messages(app, &base_url, &token, SyncMode::Periodic).await
+    {
+        debug_log(&format!("bulk: refresh after delete failed: {error}"));
+    }
+}
+
+/// Merges an archive written by `export_messages` back into the active cache.
+/// Entries keep their local flags and are marked imported, so they stay after
+/// the server has pruned them. Ids are only unique per server, so a cached id
+/// with a different date is reported as a conflict instead of overwritten.
+pub(crate) async fn import_messages(
+    app: &AppHandle,
+    path: &str,
+) -> Result<MessageImportSummary, String> {
+    let path = crate::settings_bundle::resolve_user_path(path)?;
+    let entries =
+        crate::work::run_in_background_for("import-messages", move || read_archive(&path))
+            .await??;
+    let total = entries.len();
+    let cached_dates: HashMap<i64, String> = app
+        .state::<AppState>()
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?
+        .iter()
+        .map(|message| (message.id, message.date.clone()))
+        .collect();
+
+    let mut parsed = 0usize;
+    let mut conflicts = 0usize;
+    let mut messages = Vec::new();
+    for entry in entries {
+        let Ok(mut message) = serde_json::from_value::<CachedMessage>(entry) else {
+            continue;
+        };
+        if message.id <= 0 {
+            continue;
+        }
+        parsed += 1;
+        match cached_dates.get(&message.id) {
+            Some(date) if *date != message.date => conflicts += 1,
+            Some(_) => {}
+            None => {
+                message.imported = true;
+                message.labels =
+                    crate::labels::normalize_labels(std::mem::take(&mut message.labels))
+                        .unwrap_or_default();
+                messages.push(message);
+            }
+        }
+    }
+    let imported = messages::merge_imported_messages(app, messages)?;
+    let summary = MessageImportSummary {
+        imported,
+        duplicates: parsed - imported - conflicts,
+        conflicts,
+        skipped: total - parsed,
+    };
+    debug_log(&format!("message import: {summary:?}"));
+    Ok(summary)
+}
+
+fn read_archive(path: &Path) -> Result<Vec<Value>, String> {
+    let metadata =
+        fs::metadata(path).map_err(|error| format!("Failed to read archive: {error}"))?;
+    if metadata.len() > MESSAGE_ARCHIVE_MAX_BYTES {
+        return Err(format!(
+            "Archive is larger than {} MB",
+            MESSAGE_ARCHIVE_MAX_BYTES / (1024 * 1024)
+        ));
+    }
+    let bytes = fs::read(path).map_err(|error| format!("Failed to read archive: {error}"))?;
+    match serde_json::from_slice(&bytes) {
+        Ok(Value::Array(entries)) => Ok(entries),
+        Ok(_) => Err("The file is not a message export (expected a JSON list)".to_string()),
+        Err(error) => Err(format!("The archive is not valid JSON: {error}")),
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    settings::{
+        load_settings, load_token, read_settings, save_non_secret_settings, server_client_builder,
+        SettingsResponse,
+    },
+    truncate_message, AppState,
+};
+
+#[derive(Debug, Deserialize)]
+struct GotifyClientWire {
+    id: i64,
+    name: String,
+    token: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ClientParams<'a> {
+    name: &'a str,
+}
+
+/// Gotify has no "regenerate token" endpoint, so rotation creates a sibling client
+/// with the same name, switches to its token, then deletes the old client.
+pub(crate) async fn rotate_client_token(app: &AppHandle) -> Result<SettingsResponse, String> {
+    let settings = read_settings(app)?;
+    let base_url = crate::endpoints::active_base_url(app, &settings)?;
+    let old_token =
+        load_token()?.ok_or_else(|| "No token found. Save token in settings first.".to_string())?;
+    let client = server_client_builder(app)?
+        .build()
+        .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
+
+    let clients = list_clients(&client, &base_url, &old_token).await?;
+    let current = clients
+        .into_iter()
+        .find(|candidate| candidate.token == old_token)
+        .ok_or_else(|| {
+            "Current token does not belong to a Gotify client (application tokens cannot be rotated)"
+                .to_string()
+        })?;
+
+    let request = client
+        .post(format!("{base_url}/client"))
+        .header("X-Gotify-Key", &old_token)
+        .json(&ClientParams {
+            name: &current.name,
+        });
+    // A POST gets a single attempt: a retry could create a second client.
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to create replacement client: {error}"))?;
+    let replacement = decode_response::<GotifyClientWire>(response, "Create client").await?;
+
+    if let Err(error) = list_clients(&client, &base_url, &replacement.token).await {
+        delete_client(&client, &base_url, &old_token, replacement.id).await;
+        return Err(format!("Replacement token failed verification: {error}"));
+    }
+    if let Err(error) = store_token(app, &replacement.token) {
+        delete_client(&client, &base_url, &old_token, replacement.id).await;
+        return Err(error);
+    }
+    debug_log(&format!(
+        "token rotated: client id {} replaced by {}",
+        current.id, replacement.id
+    ));
+
+    delete_client(&client, &base_url, &replacement.token, current.id).await;
+    if let Err(error) = crate::stream::restart_stream(app.clone()) {
+        debug_log(&format!(
+            "failed to restart stream after token rotation: {error}"
+        ));
+    }
+    load_settings(app)
+}
+
+fn store_token(app: &AppHandle, token: &str) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _settings_guard = state
+        .settings_lock
+        .lock()
+        .map_err(|_| "Settings lock poisoned".to_string())?;
+    let mut settings = read_settings(app)?;
+    settings.token = Some(token.to_string());
+    save_non_secret_settings(app, &settings)
+}
+
+async fn list_clients(
+    client: &reqwest::Client,
+    base_url: &str,
+    token: &str,
+) -> Result<Vec<GotifyClientWire>, String> {
+    let request = client
+        .get(format!("{base_url}/client"))
+        .header("X-Gotify-Key", token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to list clients: {error}"))?;
+    decode_response(response, "List clients").await
+}
+
+/// Best effort; a leftover client only shows up as a duplicate in the Gotify UI.
+async fn delete_client(client: &reqwest::Client, base_url: &str, token: &str, client_id: i64) {
+    let request = client
+        .delete(format!("{base_url}/client/{client_id}"))
+        .header("X-Gotify-Key", token);
+    let result = crate::retry::send(request).await;
+    match result {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => debug_log(&format!(
+            "failed to delete client {client_id}: HTTP {}",
+            response.status().as_u16()
+        )),
+        Err(error) => debug_log(&format!("failed to delete client {client_id}: {error}")),
+    }
+}
+
+pub(crate) async fn decode_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    action: &str,
+) -> Result<T, String> {
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "<unable to read response body>".to_string());
+        return Err(format!(
+            "{action} failed with HTTP {status}: {}",
+            truncate_message(&body, 200)
+        ));
+    }
+    response
+        .json::<T>()
+        .await
+        .map_err(|error| format!("{action} returned an unexpected body: {error}"))
+}
@@ -0,0 +1,198 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log, settings::read_settings, unix_now_secs, AppState, COMPACTION_CHECK_INTERVAL_SECS,
+    COMPACTION_CORRUPT_BACKUP_MAX_AGE_SECS, COMPACTION_IDLE_SECS, COMPACTION_INTERVAL_SECS,
+    COMPACTION_TEMP_FILE_MIN_AGE_SECS,
+};
+
+static LAST_COMPACTED_AT: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct CompactionReport {
+    pub(crate) cache_bytes_before: u64,
+    pub(crate) cache_bytes_after: u64,
+    pub(crate) cached_messages: usize,
+    pub(crate) removed_files: Vec<String>,
+    pub(crate) reclaimed_bytes: u64,
+}
+
+/// Syncs the active message store from memory, vacuums it, and clears what
+/// accumulates next to it: temp files from interrupted writes, old corrupt-cache
+/// backups and caches of profiles that no longer exist.
+pub(crate) fn compact_store(app: &AppHandle) -> Result<CompactionReport, String> {
+    let cache_path = crate::messages_file(app)?;
+    let database_path = crate::message_store::database_path(&cache_path);
+    let mut report = CompactionReport {
+        cache_bytes_before: file_len(&database_path),
+        ..CompactionReport::default()
+    };
+
+    let state = app.state::<AppState>();
+    let messages = {
+        let mut messages = state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        crate::messages::evict_to_limit(&mut messages, crate::desired_cache_limit(app));
+        messages.clone()
+    };
+    {
+        let _persist_guard = state
+            .message_persist_lock
+            .lock()
+            .map_err(|_| "Message persist lock poisoned".to_string())?;
+        crate::message_store::save_messages(&cache_path, &messages)?;
+        crate::message_store::vacuum(&cache_path)?;
+    }
+    report.cached_messages = messages.len();
+    report.cache_bytes_after = file_len(&database_path);
+
+    if let Some(config_dir) = cache_path.parent() {
+        remove_stale_files(app, config_dir, &mut report);
+    }
+    LAST_COMPACTED_AT.store(unix_now_secs(), Ordering::SeqCst);
+    debug_log(&format!(
+        "compaction: cache {} -> {} bytes, removed {} file(s), reclaimed {} bytes",
+        report.cache_bytes_before,
+        report.cache_bytes_after,
+        report.removed_files.len(),
+        report.reclaimed_bytes
+    ));
+    Ok(report)
+}
+
+fn file_len(path: &Path) -> u64 {
+    fs::metadata(path).map(|meta| meta.len()).unwrap_or(0)
+}
+
+fn remove_stale_files(app: &AppHandle, config_dir: &Path, report: &mut CompactionReport) {
+    // File stems (`messages-<hash>`), matching both the store and legacy JSON.
+    // Without the profile list every profile's cache would look orphaned.
+    let settings = match read_settings(app) {
+        Ok(settings) => settings,
+        Err(error) => {
+            debug_log(&format!(
+                "compaction: skipped stale file cleanup, settings unreadable: {error}"
+            ));
+            return;
+        }
+    };
+    let profile_caches: HashSet<String> = settings
+        .profiles
+        .keys()
+        .map(|name| {
+            crate::profiles::profile_messages_file_name(name)
+                .trim_end_matches(".json")
+                .to_string()
+        })
+        .collect();
+    let Ok(entries) = fs::read_dir(config_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if !meta.is_file() {
+            continue;
+        }
+        let age_secs = meta
+            .modified()
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .map(|age| age.as_secs())
+            .unwrap_or(0);
+        let stale = if name.contains(".tmp-") {
+            // Younger temp files may belong to a write that is still in progress.
+            age_secs >= COMPACTION_TEMP_FILE_MIN_AGE_SECS
+        } else if name.contains(".corrupt-") {
+            age_secs >= COMPACTION_CORRUPT_BACKUP_MAX_AGE_SECS
+        } else if let Some(stem) = name
+            .strip_suffix(".sqlite3")
+            .or_else(|| name.strip_suffix(".json"))
+            .filter(|stem| stem.starts_with("messages-"))
+        {
+            !profile_caches.contains(stem)
+        } else if let Some(stem) = name
+            .strip_suffix(".json")
+            .filter(|stem| stem.starts_with("trash-"))
+        {
+            !profile_caches.contains(&stem.replacen("trash", "messages", 1))
+        } else if let Some(stem) = name
+            .strip_suffix(".json")
+            .filter(|stem| stem.starts_with("dismissed-"))
+        {
+            !profile_caches.contains(&stem.replacen("dismissed", "messages", 1))
+        } else {
+            false
+        };
+        if !stale {
+            continue;
+        }
+        match fs::remove_file(entry.path()) {
+            Ok(()) => {
+                report.reclaimed_bytes += meta.len();
+                report.removed_files.push(name);
+            }
+            Err(error) => debug_log(&format!("compaction: failed to remove {name}: {error}")),
+        }
+    }
+}
+
+/// Compacts once per `COMPACTION_INTERVAL_SECS`, only after the stream has been
+/// quiet for `COMPACTION_IDLE_SECS` so the rewrite never competes with delivery.
+pub(crate) fn watch_idle_compaction(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(COMPACTION_CHECK_INTERVAL_SECS)).await;
+            let now = unix_now_secs();
+            if now.saturating_sub(LAST_COMPACTED_AT.load(Ordering::SeqCst))
+                < COMPACTION_INTERVAL_SECS
+            {
+                continue;
+            }
+            let last_message_at = app
+                .state::<AppState>()
+                .runtime
+                .lock()
+                .ok()
+                .and_then(|runtime| runtime.last_message_at);
+            if last_message_at.is_some_and(|at| now.saturating_sub(at) < COMPACTION_IDLE_SECS) {
+                continue;
+            }
+            let app_for_job = app.clone();
+            match crate::work::run_in_background_for("compact-store", move || {
+                compact_store(&app_for_job)
+            })
+            .await
+            {
+                Ok(Ok(_)) => {}
+                Ok(Err(error)) | Err(error) => {
+                    debug_log(&format!("compaction: idle run failed: {error}"));
+                }
+            }
+        }
+    });
+}
+
+/// Runs a final compaction on quit, unless one ran recently.
+pub(crate) fn compact_on_quit(app: &AppHandle) {
+    let since_last = unix_now_secs().saturating_sub(LAST_COMPACTED_AT.load(Ordering::SeqCst));
+    if since_last < COMPACTION_CHECK_INTERVAL_SECS {
+        return;
+    }
+    if let Err(error) = compact_store(app) {
+        debug_log(&format!("compaction: quit run failed: {error}"));
+    }
+}
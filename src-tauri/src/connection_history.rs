@@ -0,0 +1,82 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    contract::now_ms, debug_log, truncate_message, AppState, CONNECTION_HISTORY_MAX_EVENTS,
+};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ConnectionEventKind {
+    Connecting,
+    Connected,
+    Polling,
+    Disconnected,
+    Backoff,
+    LivenessTimeout,
+    AuthFailed,
+    NetworkChanged,
+    Woke,
+    WatchdogRecovery,
+    Stopped,
+}
+
+/// One step in a stream's lifecycle. `profile` is set for background streams;
+/// `None` is the active server.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ConnectionEvent {
+    pub(crate) at_ms: u64,
+    pub(crate) kind: ConnectionEventKind,
+    pub(crate) detail: Option<String>,
+    pub(crate) profile: Option<String>,
+}
+
+pub(crate) fn record(app: &AppHandle, kind: ConnectionEventKind, detail: Option<String>) {
+    push(app, kind, detail, None);
+}
+
+pub(crate) fn record_background(
+    app: &AppHandle,
+    profile: &str,
+    kind: ConnectionEventKind,
+    detail: Option<String>,
+) {
+    push(app, kind, detail, Some(profile.to_string()));
+}
+
+fn push(
+    app: &AppHandle,
+    kind: ConnectionEventKind,
+    detail: Option<String>,
+    profile: Option<String>,
+) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let Ok(mut history) = state.connection_history.lock() else {
+        debug_log("connection history lock poisoned");
+        return;
+    };
+    if history.len() >= CONNECTION_HISTORY_MAX_EVENTS {
+        history.pop_front();
+    }
+    history.push_back(ConnectionEvent {
+        at_ms: now_ms(),
+        kind,
+        detail: detail.map(|detail| truncate_message(&detail, 300)),
+        profile,
+    });
+}
+
+/// The newest `limit` events, oldest first.
+pub(crate) fn recent(app: &AppHandle, limit: usize) -> Vec<ConnectionEvent> {
+    let state = app.state::<AppState>();
+    let Ok(history) = state.connection_history.lock() else {
+        return Vec::new();
+    };
+    history
+        .iter()
+        .skip(history.len().saturating_sub(limit))
+        .cloned()
+        .collect()
+}
@@ -1,23 +1,196 @@
 #[cfg(target_os = "macos")]
 pub(crate) const LAUNCH_AGENT_LABEL: &str = "net.gotify.desktop";
+#[cfg(target_os = "macos")]
+pub(crate) const QUIET_HOURS_SOUND_PATH: &str = "/System/Library/Sounds/Glass.aiff";
+#[cfg(target_os = "macos")]
+pub(crate) const AT_REST_KEYCHAIN_SERVICE: &str = "net.gotify.desktop";
+#[cfg(target_os = "macos")]
+pub(crate) const AT_REST_KEYCHAIN_ACCOUNT: &str = "at-rest-key";
+
+/// Tray menu ids for server profiles are this prefix followed by the profile name.
+pub(crate) const TRAY_PROFILE_ITEM_PREFIX: &str = "switch_profile:";
+pub(crate) const MAX_PROFILE_BADGE_CHARS: usize = 8;
+pub(crate) const TRAY_CONTEXT_ITEM_PREFIX: &str = "switch_context:";
+pub(crate) const MAX_CONTEXT_NAME_CHARS: usize = 32;
+pub(crate) const MAX_NOTIFICATION_SOUND_CHARS: usize = 32;
+pub(crate) const MAX_NOTIFICATION_SOUND_BANDS: usize = 11;
+pub(crate) const MAX_CUSTOM_SOUND_BYTES: u64 = 5 * 1024 * 1024;
+/// macOS falls back to the default sound for notification sounds this long or longer.
+pub(crate) const MAX_CUSTOM_SOUND_SECS: f64 = 30.0;
+/// Config-dir list of the sound names this app copied into `~/Library/Sounds`.
+pub(crate) const IMPORTED_SOUNDS_FILE: &str = "imported-sounds.json";
+pub(crate) const MAX_ANDROID_ACTION_RULES: usize = 32;
+pub(crate) const MAX_ANDROID_ACTION_PREFIX_CHARS: usize = 500;
 
 pub(crate) const DEFAULT_CACHE_LIMIT: usize = 100;
+/// AEAD labels for the sealed settings and message cache files.
+pub(crate) const SETTINGS_LABEL: &str = "settings.json";
+pub(crate) const MESSAGES_LABEL: &str = "messages.json";
+/// How long a message store write waits for another connection's lock.
+pub(crate) const MESSAGE_STORE_BUSY_TIMEOUT_MS: u64 = 5_000;
+/// The message store is written at most this often; changes in between coalesce.
+pub(crate) const MESSAGE_PERSIST_DEBOUNCE_MS: u64 = 1_000;
+/// Message rows at least this large are deflated before sealing; smaller ones
+/// would barely shrink.
+pub(crate) const MESSAGE_COMPRESS_MIN_BYTES: usize = 256;
+pub(crate) const TRASH_LABEL: &str = "trash.json";
+pub(crate) const DISMISSED_LABEL: &str = "dismissed.json";
+/// Dismissed ids kept per server; the oldest are dropped past this.
+pub(crate) const DISMISSED_MAX_IDS: usize = 20_000;
 pub(crate) const MAX_API_PAGE_LIMIT: usize = 200;
-pub(crate) const MAX_CACHE_LIMIT: usize = 2000;
+pub(crate) const MAX_CACHE_LIMIT: usize = 10_000;
+/// `search_messages` results when the caller passes no limit, and the most it returns.
+pub(crate) const DEFAULT_SEARCH_LIMIT: usize = 200;
+pub(crate) const MAX_SEARCH_LIMIT: usize = 1_000;
+pub(crate) const DELETE_TOMBSTONE_TTL_SECS: u64 = 120;
+/// Read messages older than this are offered for cleanup.
+pub(crate) const CLEANUP_READ_AGE_DAYS: u64 = 7;
+pub(crate) const CLEANUP_MIN_CANDIDATES: usize = 50;
+/// Below `CLEANUP_MIN_CANDIDATES`, cleanup is still suggested once the cache is this full.
+pub(crate) const CLEANUP_CACHE_FILL_PERCENT: usize = 90;
+pub(crate) const CLEANUP_SUGGEST_INTERVAL_SECS: u64 = 6 * 60 * 60;
+pub(crate) const DEFAULT_BACKOFF_RESET_AFTER_SECS: u64 = 60;
+/// Version of the event and channel payload shapes. Bump on any rename, removal or
+/// meaning change of an existing field; purely additive fields do not need a bump.
+pub(crate) const EVENT_SCHEMA_VERSION: u32 = 1;
+
+pub(crate) const MAX_BACKOFF_RESET_AFTER_SECS: u64 = 3600;
+pub(crate) const DEFAULT_RECONNECT_BACKOFF_BASE_SECS: u64 = 1;
+pub(crate) const DEFAULT_RECONNECT_BACKOFF_MULTIPLIER: f64 = 2.0;
+pub(crate) const DEFAULT_RECONNECT_BACKOFF_MAX_SECS: u64 = 30;
+pub(crate) const MAX_RECONNECT_BACKOFF_SECS: u64 = 3600;
+pub(crate) const MAX_RECONNECT_BACKOFF_MULTIPLIER: f64 = 10.0;
 
-pub(crate) const STREAM_CONNECT_TIMEOUT_SECS: u64 = 10;
+/// Connection state after the server rejected the token; no automatic reconnects.
+pub(crate) const CONNECTION_STATE_AUTH_FAILED: &str = "AuthFailed";
+/// Connection state while new messages arrive by polling instead of the websocket.
+pub(crate) const CONNECTION_STATE_POLLING: &str = "Polling";
+
+pub(crate) const DEFAULT_STREAM_CONNECT_TIMEOUT_SECS: u64 = 10;
+pub(crate) const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 15;
+pub(crate) const MAX_REQUEST_TIMEOUT_SECS: u64 = 300;
+pub(crate) const DEFAULT_REQUEST_RETRY_ATTEMPTS: u32 = 3;
+pub(crate) const DEFAULT_REQUEST_RETRY_BASE_DELAY_MS: u64 = 500;
+pub(crate) const MAX_REQUEST_RETRY_ATTEMPTS: u32 = 10;
+pub(crate) const MAX_REQUEST_RETRY_DELAY_MS: u64 = 10_000;
+pub(crate) const HAPPY_EYEBALLS_DELAY_MS: u64 = 250;
+pub(crate) const DNS_CACHE_TTL_SECS: u64 = 300;
+pub(crate) const DNS_LOOKUP_TIMEOUT_SECS: u64 = 5;
+pub(crate) const PROXY_RESPONSE_MAX_BYTES: usize = 8 * 1024;
+pub(crate) const SYSTEM_PROXY_POLL_SECS: u64 = 15;
 pub(crate) const STREAM_SYNC_INTERVAL_SECS: u64 = 5;
 pub(crate) const STREAM_LIVENESS_CHECK_INTERVAL_SECS: u64 = 15;
 pub(crate) const STREAM_LIVENESS_IDLE_SECS: u64 = 90;
 pub(crate) const STREAM_LIVENESS_PING_GRACE_SECS: u64 = 30;
+/// How often a connected stream pings the server to sample round-trip time.
+pub(crate) const STREAM_RTT_PING_INTERVAL_SECS: u64 = 60;
+pub(crate) const STREAM_POLL_INTERVAL_SECS: u64 = 10;
+/// How long a closing stream waits for the server to answer its Close frame.
+pub(crate) const STREAM_CLOSE_ACK_TIMEOUT_MS: u64 = 1_000;
+/// Upper bound on quitting: streams closing, then queued cache writes landing.
+pub(crate) const QUIT_STREAM_CLOSE_TIMEOUT_MS: u64 = 1_500;
+pub(crate) const QUIT_FLUSH_TIMEOUT_MS: u64 = 3_000;
+/// Stream watchdog: how often it looks, and how long a stream that claims to be
+/// up may go without any event before it is restarted. Well past the liveness
+/// ping, so it only catches a session loop that has stopped running.
+pub(crate) const STREAM_WATCHDOG_INTERVAL_SECS: u64 = 60;
+pub(crate) const STREAM_WATCHDOG_SILENCE_SECS: u64 = 300;
+/// Failed websocket attempts in a row before auto mode falls back to polling.
+pub(crate) const STREAM_AUTO_FALLBACK_FAILURES: u32 = 3;
+/// How long auto mode polls before trying the websocket again.
+pub(crate) const STREAM_POLL_WEBSOCKET_RETRY_SECS: u64 = 300;
+
+pub(crate) const SETTINGS_BUNDLE_PBKDF2_ITERATIONS: u32 = 600_000;
+pub(crate) const SETTINGS_BUNDLE_MIN_PASSPHRASE_CHARS: usize = 8;
+
+pub(crate) const MAX_FAULT_HTTP_DELAY_MS: u64 = 60_000;
+
+pub(crate) const MAX_APP_TOKENS: usize = 20;
+pub(crate) const MAX_SEND_MESSAGE_CHARS: usize = 10_000;
+pub(crate) const SEND_MESSAGE_TIMEOUT_SECS: u64 = 15;
+
+pub(crate) const OPERATION_PROGRESS_MIN_INTERVAL_MS: u64 = 100;
+/// Error returned by a command whose work was cancelled through `cancel_operation(s)`.
+pub(crate) const OPERATION_CANCELLED: &str = "Cancelled";
+pub(crate) const OPERATION_GROUP_SYNC: &str = "sync";
+
+pub(crate) const DEFAULT_TRASH_RETENTION_DAYS: u64 = 7;
+pub(crate) const MAX_TRASH_RETENTION_DAYS: u64 = 90;
+pub(crate) const TRASH_PURGE_INTERVAL_SECS: u64 = 60 * 60;
+pub(crate) const MAX_MESSAGE_RETENTION_DAYS: u64 = 3650;
+pub(crate) const MESSAGE_RETENTION_INTERVAL_SECS: u64 = 60 * 60;
+pub(crate) const MAX_MESSAGE_LABELS: usize = 16;
+pub(crate) const MAX_MESSAGE_LABEL_CHARS: usize = 32;
+pub(crate) const MAX_SNOOZE_MINUTES: u64 = 7 * 24 * 60;
+pub(crate) const SNOOZE_CHECK_INTERVAL_SECS: u64 = 15;
+
+pub(crate) const COMPACTION_CHECK_INTERVAL_SECS: u64 = 15 * 60;
+pub(crate) const COMPACTION_INTERVAL_SECS: u64 = 24 * 60 * 60;
+pub(crate) const COMPACTION_IDLE_SECS: u64 = 10 * 60;
+pub(crate) const COMPACTION_TEMP_FILE_MIN_AGE_SECS: u64 = 60 * 60;
+pub(crate) const COMPACTION_CORRUPT_BACKUP_MAX_AGE_SECS: u64 = 30 * 24 * 60 * 60;
+
+pub(crate) const ENDPOINT_PROBE_INTERVAL_SECS: u64 = 60;
+pub(crate) const ENDPOINT_PROBE_TIMEOUT_SECS: u64 = 3;
+pub(crate) const ENDPOINT_SWITCH_MARGIN_MS: u64 = 50;
+
+pub(crate) const DEFAULT_HEALTH_CHECK_INTERVAL_SECS: u64 = 60;
+pub(crate) const MIN_HEALTH_CHECK_INTERVAL_SECS: u64 = 10;
+pub(crate) const MAX_HEALTH_CHECK_INTERVAL_SECS: u64 = 60 * 60;
+pub(crate) const HEALTH_CHECK_IDLE_POLL_SECS: u64 = 30;
+pub(crate) const HEALTH_CHECK_TIMEOUT_SECS: u64 = 5;
+pub(crate) const SERVER_INFO_TIMEOUT_SECS: u64 = 5;
+
+pub(crate) const HEARTBEAT_INTERVAL_SECS: u64 = 60;
+pub(crate) const HEARTBEAT_REQUEST_TIMEOUT_SECS: u64 = 10;
+
+pub(crate) const CLOCK_SKEW_WARN_SECS: u64 = 120;
+pub(crate) const DELIVERY_METRICS_MAX_SAMPLES: usize = 500;
+/// Handshake and ping round-trip samples kept for the diagnostics min/avg/max.
+pub(crate) const CONNECTION_LATENCY_MAX_SAMPLES: usize = 50;
+/// Connection lifecycle events kept in memory, and how many of the newest ride
+/// along in `RuntimeDiagnostics`.
+pub(crate) const CONNECTION_HISTORY_MAX_EVENTS: usize = 200;
+pub(crate) const CONNECTION_HISTORY_DIAGNOSTICS_EVENTS: usize = 20;
 
 pub(crate) const PREVIEW_REQUEST_TIMEOUT_SECS: u64 = 6;
 pub(crate) const PREVIEW_MAX_REDIRECTS: usize = 5;
 pub(crate) const PREVIEW_MAX_HTML_BYTES: usize = 120_000;
+/// Completes the authentication sheet's "Gotify is trying to …" line.
+pub(crate) const REVEAL_TOKEN_REASON: &str = "show the saved client token";
+pub(crate) const EXPORT_SETTINGS_REASON: &str = "export saved servers and tokens";
+pub(crate) const ACK_ACTION_LABEL: &str = "Acknowledge";
+#[cfg(target_os = "macos")]
+pub(crate) const NOTIFICATION_MARK_READ_LABEL: &str = "Mark Read";
+#[cfg(target_os = "macos")]
+pub(crate) const NOTIFICATION_SNOOZE_LABEL: &str = "Snooze 1 Hour";
+#[cfg(target_os = "macos")]
+pub(crate) const NOTIFICATION_SNOOZE_MINUTES: u64 = 60;
+pub(crate) const ACK_DEFAULT_TEMPLATE: &str = "{title} ({app}, #{id}) was acknowledged.";
+pub(crate) const MAX_ACK_TEMPLATE_CHARS: usize = 500;
 pub(crate) const APP_ICON_MAX_BYTES: usize = 256_000;
+/// Icon downloads in flight at once while refreshing applications.
+pub(crate) const APP_ICON_FETCH_CONCURRENCY: usize = 4;
+pub(crate) const BIG_IMAGE_MAX_BYTES: usize = 4 * 1024 * 1024;
+pub(crate) const BIG_IMAGE_REQUEST_TIMEOUT_SECS: u64 = 10;
+pub(crate) const BIG_IMAGE_CACHE_MAX_FILES: usize = 64;
+pub(crate) const ANDROID_BACKUP_MAX_BYTES: u64 = 64 * 1024 * 1024;
+pub(crate) const MESSAGE_ARCHIVE_MAX_BYTES: u64 = 128 * 1024 * 1024;
+/// Messages kept in a profile's archive of server deletions; the oldest go first.
+pub(crate) const MESSAGE_ARCHIVE_MAX_MESSAGES: usize = 50_000;
+pub(crate) const DEFAULT_ARCHIVE_PAGE_LIMIT: usize = 100;
+pub(crate) const MAX_ARCHIVE_PAGE_LIMIT: usize = 500;
+pub(crate) const PLUGIN_CONFIG_MAX_BYTES: usize = 256 * 1024;
+pub(crate) const ICON_MEMORY_CAP_BYTES: usize = 4 * 1024 * 1024;
+pub(crate) const ICON_URI_SCHEME: &str = "appicon";
+
+pub(crate) const REDACTION_MASK: &str = "[redacted]";
+pub(crate) const MAX_REDACTION_PATTERNS: usize = 50;
+pub(crate) const REDACTION_PATTERN_SIZE_LIMIT: usize = 256 * 1024;
 
 pub(crate) const PAUSE_FOREVER_SENTINEL: u64 = 0;
 pub(crate) const PAUSE_MODE_15M: &str = "15m";
 pub(crate) const PAUSE_MODE_1H: &str = "1h";
 pub(crate) const PAUSE_MODE_CUSTOM: &str = "custom";
 pub(crate) const PAUSE_MODE_FOREVER: &str = "forever";
+pub(crate) const PAUSE_MODE_SCHEDULED: &str = "scheduled";
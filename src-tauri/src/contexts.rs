@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use tauri::{
+    menu::{CheckMenuItem, IsMenuItem, MenuItem, Submenu},
+    AppHandle, Manager,
+};
+
+use crate::{
+    debug_log,
+    settings::{
+        read_settings, save_non_secret_settings, to_settings_response, QuietHoursBand,
+        SettingsResponse, StoredSettings,
+    },
+    AppState, MAX_CONTEXT_NAME_CHARS, MAX_NOTIFICATION_SOUND_BANDS, MAX_NOTIFICATION_SOUND_CHARS,
+    TRAY_CONTEXT_ITEM_PREFIX,
+};
+
+/// Sound value that plays the system default; `NOTIFICATION_SOUND_NONE` turns
+/// sounds off and anything else names a macOS system sound.
+pub(crate) const NOTIFICATION_SOUND_DEFAULT: &str = "default";
+pub(crate) const NOTIFICATION_SOUND_NONE: &str = "none";
+
+/// Sound for messages at or above `min_priority`, up to the next band. Takes the
+/// same values as `notification_sound`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct SoundBand {
+    pub(crate) min_priority: i64,
+    pub(crate) sound: String,
+}
+
+/// A saved alerting posture ("Work", "On-call"): the notification settings it
+/// bundles are copied in and out of `StoredSettings` as a whole.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct NotificationContext {
+    pub(crate) min_priority: i64,
+    pub(crate) quiet_hours_start: Option<u8>,
+    pub(crate) quiet_hours_end: Option<u8>,
+    pub(crate) quiet_hours_bands: Vec<QuietHoursBand>,
+    pub(crate) muted_app_ids: Vec<i64>,
+    pub(crate) notification_sound: String,
+    pub(crate) notification_sound_bands: Vec<SoundBand>,
+}
+
+fn snapshot(settings: &StoredSettings) -> NotificationContext {
+    NotificationContext {
+        min_priority: settings.min_priority,
+        quiet_hours_start: settings.quiet_hours_start,
+        quiet_hours_end: settings.quiet_hours_end,
+        quiet_hours_bands: settings.quiet_hours_bands.clone(),
+        muted_app_ids: settings.muted_app_ids.clone(),
+        notification_sound: settings.notification_sound.clone(),
+        notification_sound_bands: settings.notification_sound_bands.clone(),
+    }
+}
+
+fn apply(settings: &mut StoredSettings, context: NotificationContext) {
+    settings.min_priority = context.min_priority;
+    settings.quiet_hours_start = context.quiet_hours_start;
+    settings.quiet_hours_end = context.quiet_hours_end;
+    settings.quiet_hours_bands = context.quiet_hours_bands;
+    settings.muted_app_ids = context.muted_app_ids;
+    settings.notification_sound = normalize_sound(&context.notification_sound)
+        .unwrap_or_else(|_| NOTIFICATION_SOUND_DEFAULT.to_string());
+    settings.notification_sound_bands =
+        normalize_sound_bands(context.notification_sound_bands).unwrap_or_default();
+}
+
+fn normalize_context_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Context name is required".to_string());
+    }
+    if trimmed.chars().count() > MAX_CONTEXT_NAME_CHARS {
+        return Err(format!(
+            "Context name must be {MAX_CONTEXT_NAME_CHARS} characters or fewer"
+        ));
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err("Context name must not contain control characters".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Empty means the default sound. Names are checked loosely; macOS plays
+/// nothing for a sound it does not know.
+pub(crate) fn normalize_sound(sound: &str) -> Result<String, String> {
+    let trimmed = sound.trim();
+    if trimmed.is_empty() {
+        return Ok(NOTIFICATION_SOUND_DEFAULT.to_string());
+    }
+    if trimmed.chars().count() > MAX_NOTIFICATION_SOUND_CHARS
+        || !trimmed
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '_')
+    {
+        return Err("Sound must be a system sound name like Ping or Glass".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Checks every sound, clamps priorities at 0 and sorts by priority; a
+/// repeated priority keeps the first band.
+pub(crate) fn normalize_sound_bands(bands: Vec<SoundBand>) -> Result<Vec<SoundBand>, String> {
+    if bands.len() > MAX_NOTIFICATION_SOUND_BANDS {
+        return Err(format!(
+            "At most {MAX_NOTIFICATION_SOUND_BANDS} sound bands are allowed"
+        ));
+    }
+    let mut normalized = bands
+        .into_iter()
+        .map(|band| {
+            Ok(SoundBand {
+                min_priority: band.min_priority.max(0),
+                sound: normalize_sound(&band.sound)?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    normalized.sort_by_key(|band| band.min_priority);
+    normalized.dedup_by(|a, b| a.min_priority == b.min_priority);
+    Ok(normalized)
+}
+
+/// The band covering `priority`, or the single `notification_sound` when no band does.
+pub(crate) fn sound_for_priority(settings: &StoredSettings, priority: i64) -> String {
+    settings
+        .notification_sound_bands
+        .iter()
+        .filter(|band| priority >= band.min_priority)
+        .max_by_key(|band| band.min_priority)
+        .map(|band| band.sound.clone())
+        .unwrap_or_else(|| settings.notification_sound.clone())
+}
+
+fn update_settings(
+    app: &AppHandle,
+    change: impl FnOnce(&mut StoredSettings) -> Result<(), String>,
+) -> Result<StoredSettings, String> {
+    let state = app.state::<AppState>();
+    let _settings_guard = state
+        .settings_lock
+        .lock()
+        .map_err(|_| "Settings lock poisoned".to_string())?;
+    let mut settings = read_settings(app)?;
+    change(&mut settings)?;
+    save_non_secret_settings(app, &settings)?;
+    Ok(settings)
+}
+
+/// Saves the current notification settings as `name` and marks it active.
+pub(crate) fn save_context(app: &AppHandle, name: &str) -> Result<SettingsResponse, String> {
+    let name = normalize_context_name(name)?;
+    let settings = update_settings(app, |settings| {
+        settings.contexts.insert(name.clone(), snapshot(settings));
+        settings.active_context = Some(name.clone());
+        Ok(())
+    })?;
+    refresh_tray_contexts(app);
+    debug_log(&format!("contexts: saved {name:?}"));
+    Ok(to_settings_response(settings))
+}
+
+pub(crate) fn switch_context(app: &AppHandle, name: &str) -> Result<SettingsResponse, String> {
+    let name = normalize_context_name(name)?;
+    let settings = update_settings(app, |settings| {
+        let context = settings
+            .contexts
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| format!("No context named {name:?}"))?;
+        apply(settings, context);
+        settings.active_context = Some(name.clone());
+        Ok(())
+    })?;
+    crate::notifications::refresh_quiet_hours_badge(app);
+    refresh_tray_contexts(app);
+    debug_log(&format!("contexts: switched to {name:?}"));
+    Ok(to_settings_response(settings))
+}
+
+/// Removing the active context leaves its settings in place; only the name goes.
+pub(crate) fn delete_context(app: &AppHandle, name: &str) -> Result<SettingsResponse, String> {
+    let name = normalize_context_name(name)?;
+    let settings = update_settings(app, |settings| {
+        if settings.contexts.remove(&name).is_none() {
+            return Err(format!("No context named {name:?}"));
+        }
+        if settings.active_context.as_deref() == Some(name.as_str()) {
+            settings.active_context = None;
+        }
+        Ok(())
+    })?;
+    refresh_tray_contexts(app);
+    Ok(to_settings_response(settings))
+}
+
+pub(crate) fn set_app_muted(
+    app: &AppHandle,
+    app_id: i64,
+    muted: bool,
+) -> Result<SettingsResponse, String> {
+    let settings = update_settings(app, |settings| {
+        settings.muted_app_ids.retain(|id| *id != app_id);
+        if muted {
+            settings.muted_app_ids.push(app_id);
+            settings.muted_app_ids.sort_unstable();
+        }
+        Ok(())
+    })?;
+    debug_log(&format!("notifications: app_id={app_id} muted={muted}"));
+    Ok(to_settings_response(settings))
+}
+
+pub(crate) fn set_notification_sound(
+    app: &AppHandle,
+    sound: &str,
+) -> Result<SettingsResponse, String> {
+    let sound = normalize_sound(sound)?;
+    let settings = update_settings(app, |settings| {
+        settings.notification_sound = sound;
+        Ok(())
+    })?;
+    Ok(to_settings_response(settings))
+}
+
+pub(crate) fn set_notification_sound_bands(
+    app: &AppHandle,
+    bands: Vec<SoundBand>,
+) -> Result<SettingsResponse, String> {
+    let bands = normalize_sound_bands(bands)?;
+    let settings = update_settings(app, |settings| {
+        settings.notification_sound_bands = bands;
+        Ok(())
+    })?;
+    Ok(to_settings_response(settings))
+}
+
+/// "Context" tray submenu with one checkable entry per saved context.
+pub(crate) fn create_tray_contexts_menu(
+    app: &AppHandle,
+) -> Result<Submenu<tauri::Wry>, tauri::Error> {
+    let submenu = Submenu::with_id(app, "contexts", "Context", true)?;
+    fill_tray_contexts_menu(app, &submenu)?;
+    if let Ok(mut menu) = app.state::<AppState>().tray_contexts_menu.lock() {
+        *menu = Some(submenu.clone());
+    }
+    Ok(submenu)
+}
+
+pub(crate) fn refresh_tray_contexts(app: &AppHandle) {
+    let Some(submenu) = app
+        .state::<AppState>()
+        .tray_contexts_menu
+        .lock()
+        .ok()
+        .and_then(|menu| menu.clone())
+    else {
+        return;
+    };
+    let result = submenu.items().and_then(|items| {
+        for item in &items {
+            submenu.remove(item)?;
+        }
+        fill_tray_contexts_menu(app, &submenu)
+    });
+    if let Err(error) = result {
+        debug_log(&format!("contexts: failed to rebuild tray menu: {error}"));
+    }
+}
+
+fn fill_tray_contexts_menu(
+    app: &AppHandle,
+    submenu: &Submenu<tauri::Wry>,
+) -> Result<(), tauri::Error> {
+    let settings = read_settings(app).unwrap_or_default();
+    if settings.contexts.is_empty() {
+        let placeholder = MenuItem::with_id(
+            app,
+            "contexts_empty",
+            "No saved contexts",
+            false,
+            None::<&str>,
+        )?;
+        return submenu.append(&placeholder);
+    }
+    for name in settings.contexts.keys() {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("{TRAY_CONTEXT_ITEM_PREFIX}{name}"),
+            name,
+            true,
+            settings.active_context.as_deref() == Some(name.as_str()),
+            None::<&str>,
+        )?;
+        submenu.append(&item as &dyn IsMenuItem<tauri::Wry>)?;
+    }
+    Ok(())
+}
+
+/// Tray entry point; errors surface through the stream error channel.
+pub(crate) fn switch_from_tray(app: &AppHandle, name: &str) {
+    let result = switch_context(app, name);
+    // Clicking toggles the check mark natively; rebuild so it matches settings.
+    refresh_tray_contexts(app);
+    match result {
+        Ok(settings) => {
+            crate::contract::publish_settings_update(app, settings);
+        }
+        Err(error) => {
+            let _ = crate::contract::publish_stream_error(
+                app,
+                format!("Failed to switch context: {error}"),
+            );
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use serde::Serialize;
 use serde_json::Value;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::{ipc::Channel, AppHandle, Manager};
+use tauri::{ipc::Channel, AppHandle, Emitter, Manager, Runtime};
 
 use crate::{
-    debug_log, AppState, CachedMessage, RevisionKey, RuntimeDiagnostics, SettingsResponse,
+    debug_log, notification_queue::PendingNotification, trash::TrashedMessage, AppState,
+    CachedMessage, RevisionKey, RuntimeDiagnostics, SettingsResponse, EVENT_SCHEMA_VERSION,
 };
 
 #[derive(Debug, Serialize, Clone)]
@@ -32,6 +33,14 @@ pub(crate) struct MessageRemovedData {
     pub(crate) message_id: i64,
 }
 
+/// What a sync changed in the cache: new or edited messages, and ids that left
+/// it (deleted on the server or evicted past the cache limit).
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct MessagesDeltaData {
+    pub(crate) upserted: Vec<CachedMessage>,
+    pub(crate) removed: Vec<i64>,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub(crate) struct StreamErrorData {
     pub(crate) message: String,
@@ -50,21 +59,53 @@ pub(crate) struct BootstrapState {
 #[serde(tag = "type", content = "payload")]
 pub(crate) enum AppUpdate {
     #[serde(rename = "settings.updated")]
-    SettingsUpdated(DomainSnapshot<SettingsResponse>),
+    SettingsUpdated(Box<DomainSnapshot<SettingsResponse>>),
     #[serde(rename = "pause.updated")]
     PauseUpdated(DomainSnapshot<PauseStateData>),
     #[serde(rename = "messages.replace")]
     MessagesReplace(DomainSnapshot<Vec<CachedMessage>>),
     #[serde(rename = "messages.upsert")]
-    MessagesUpsert(DomainSnapshot<CachedMessage>),
+    MessagesUpsert(Box<DomainSnapshot<CachedMessage>>),
     #[serde(rename = "messages.remove")]
     MessagesRemove(DomainSnapshot<MessageRemovedData>),
+    #[serde(rename = "messages.delta")]
+    MessagesDelta(DomainSnapshot<MessagesDeltaData>),
     #[serde(rename = "connection.updated")]
     ConnectionUpdated(DomainSnapshot<ConnectionStateData>),
     #[serde(rename = "runtime.updated")]
-    RuntimeUpdated(DomainSnapshot<RuntimeDiagnostics>),
+    RuntimeUpdated(Box<DomainSnapshot<RuntimeDiagnostics>>),
     #[serde(rename = "stream.error")]
     StreamError(DomainSnapshot<StreamErrorData>),
+    #[serde(rename = "notifications.pending")]
+    PendingNotifications(DomainSnapshot<Vec<PendingNotification>>),
+    #[serde(rename = "trash.updated")]
+    TrashUpdated(DomainSnapshot<Vec<TrashedMessage>>),
+}
+
+/// Envelope for `emit_to` events: the payload's own fields plus `schema`, so
+/// listeners can tell which revision of the event shapes they are reading.
+#[derive(Debug, Serialize, Clone)]
+struct VersionedEvent<T> {
+    schema: u32,
+    #[serde(flatten)]
+    payload: T,
+}
+
+/// Targeted low-frequency event. All `emit_to` traffic goes through here so every
+/// payload carries the schema version.
+pub(crate) fn emit_event<R: Runtime, T: Serialize + Clone>(
+    app: &AppHandle<R>,
+    target: &str,
+    event: &str,
+    payload: T,
+) {
+    let versioned = VersionedEvent {
+        schema: EVENT_SCHEMA_VERSION,
+        payload,
+    };
+    if let Err(error) = app.emit_to(target, event, versioned) {
+        debug_log(&format!("failed to emit {event} to {target}: {error}"));
+    }
 }
 
 pub(crate) fn now_ms() -> u64 {
@@ -136,13 +177,16 @@ pub(crate) fn unregister_app_update_channel(app: &AppHandle, label: &str) -> Res
 }
 
 pub(crate) fn publish_update(app: &AppHandle, update: AppUpdate) {
-    let payload = match serde_json::to_value(&update) {
+    let mut payload = match serde_json::to_value(&update) {
         Ok(payload) => payload,
         Err(error) => {
             debug_log(&format!("failed to serialize app update: {error}"));
             return;
         }
     };
+    if let Value::Object(envelope) = &mut payload {
+        envelope.insert("schema".to_string(), Value::from(EVENT_SCHEMA_VERSION));
+    }
 
     let state = app.state::<AppState>();
     let channels_snapshot = match state.update_channels.lock() {
@@ -186,7 +230,25 @@ pub(crate) fn publish_settings_update(
     settings: SettingsResponse,
 ) -> DomainSnapshot<SettingsResponse> {
     let snapshot = snapshot_with_bump(app, RevisionKey::Settings, settings);
-    publish_update(app, AppUpdate::SettingsUpdated(snapshot.clone()));
+    publish_update(app, AppUpdate::SettingsUpdated(Box::new(snapshot.clone())));
+    snapshot
+}
+
+pub(crate) fn publish_pending_notifications(
+    app: &AppHandle,
+    pending: Vec<PendingNotification>,
+) -> DomainSnapshot<Vec<PendingNotification>> {
+    let snapshot = snapshot_with_bump(app, RevisionKey::PendingNotifications, pending);
+    publish_update(app, AppUpdate::PendingNotifications(snapshot.clone()));
+    snapshot
+}
+
+pub(crate) fn publish_trash_update(
+    app: &AppHandle,
+    trash: Vec<TrashedMessage>,
+) -> DomainSnapshot<Vec<TrashedMessage>> {
+    let snapshot = snapshot_with_bump(app, RevisionKey::Trash, trash);
+    publish_update(app, AppUpdate::TrashUpdated(snapshot.clone()));
     snapshot
 }
 
@@ -213,7 +275,7 @@ pub(crate) fn publish_message_upsert(
     message: CachedMessage,
 ) -> DomainSnapshot<CachedMessage> {
     let snapshot = snapshot_with_bump(app, RevisionKey::Messages, message);
-    publish_update(app, AppUpdate::MessagesUpsert(snapshot.clone()));
+    publish_update(app, AppUpdate::MessagesUpsert(Box::new(snapshot.clone())));
     snapshot
 }
 
@@ -230,6 +292,15 @@ pub(crate) fn publish_message_remove(
     snapshot
 }
 
+pub(crate) fn publish_messages_delta(
+    app: &AppHandle,
+    delta: MessagesDeltaData,
+) -> DomainSnapshot<MessagesDeltaData> {
+    let snapshot = snapshot_with_bump(app, RevisionKey::Messages, delta);
+    publish_update(app, AppUpdate::MessagesDelta(snapshot.clone()));
+    snapshot
+}
+
 pub(crate) fn publish_connection_update(
     app: &AppHandle,
     state: String,
@@ -244,7 +315,7 @@ pub(crate) fn publish_runtime_update(
     runtime: RuntimeDiagnostics,
 ) -> DomainSnapshot<RuntimeDiagnostics> {
     let snapshot = snapshot_with_bump(app, RevisionKey::Runtime, runtime);
-    publish_update(app, AppUpdate::RuntimeUpdated(snapshot.clone()));
+    publish_update(app, AppUpdate::RuntimeUpdated(Box::new(snapshot.clone())));
     snapshot
 }
 
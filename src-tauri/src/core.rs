@@ -1,5 +1,4 @@
 use base64::Engine as _;
-use serde::Serialize;
 #[cfg(debug_assertions)]
 use std::io::Write as _;
 use std::{
@@ -9,19 +8,10 @@ use std::{
     sync::atomic::Ordering,
     time::{SystemTime, UNIX_EPOCH},
 };
-use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri::{AppHandle, Manager, Runtime};
 
 use crate::{FILE_SUFFIX_COUNTER, SETTINGS_FILE};
 
-#[derive(Debug, Serialize, Clone)]
-pub(crate) struct DeleteMessageDebugEvent {
-    pub(crate) at: u64,
-    pub(crate) message_id: i64,
-    pub(crate) phase: String,
-    pub(crate) detail: String,
-    pub(crate) status: Option<u16>,
-}
-
 pub(crate) fn settings_file<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
     let config_dir = app
         .path()
@@ -43,7 +33,26 @@ pub(crate) fn messages_file<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, S
     fs::create_dir_all(&config_dir)
         .map_err(|error| format!("Failed to create config directory: {error}"))?;
 
-    Ok(config_dir.join("messages.json"))
+    // Each saved server profile keeps its own cache next to the default one.
+    let active_profile = app
+        .try_state::<crate::AppState>()
+        .and_then(|state| state.active_profile.lock().ok()?.clone());
+    Ok(match active_profile {
+        Some(name) => config_dir.join(crate::profiles::profile_messages_file_name(&name)),
+        None => config_dir.join("messages.json"),
+    })
+}
+
+/// Cache file of a saved profile, whether or not it is the active one.
+pub(crate) fn profile_messages_file<R: Runtime>(
+    app: &AppHandle<R>,
+    profile: &str,
+) -> Result<PathBuf, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|error| format!("Failed to resolve app config dir: {error}"))?;
+    Ok(config_dir.join(crate::profiles::profile_messages_file_name(profile)))
 }
 
 pub(crate) fn restrict_file_permissions(path: &Path) {
@@ -132,23 +141,6 @@ pub(crate) fn debug_log(message: &str) {
     }
 }
 
-pub(crate) fn emit_delete_debug(
-    app: &AppHandle,
-    message_id: i64,
-    phase: &str,
-    detail: &str,
-    status: Option<u16>,
-) {
-    let event = DeleteMessageDebugEvent {
-        at: unix_now_secs(),
-        message_id,
-        phase: phase.to_string(),
-        detail: truncate_message(detail, 800),
-        status,
-    };
-    let _ = app.emit_to("main", "delete-message-debug", event);
-}
-
 pub(crate) fn get_settings_path() -> Result<&'static PathBuf, String> {
     SETTINGS_FILE
         .get()
@@ -1,7 +1,9 @@
 use serde::Serialize;
 use tauri::{AppHandle, Manager};
 
-use crate::{debug_log, unix_now_secs, AppState};
+use crate::{
+    debug_log, unix_now_secs, AppState, CLOCK_SKEW_WARN_SECS, CONNECTION_HISTORY_DIAGNOSTICS_EVENTS,
+};
 
 #[derive(Debug, Serialize, Clone)]
 pub(crate) struct RuntimeDiagnostics {
@@ -15,6 +17,32 @@ pub(crate) struct RuntimeDiagnostics {
     pub(crate) last_error: Option<String>,
     pub(crate) backoff_seconds: u64,
     pub(crate) reconnect_attempts: u64,
+    pub(crate) clock_skew_seconds: Option<i64>,
+    pub(crate) last_heartbeat_at: Option<u64>,
+    pub(crate) stream_remote_addr: Option<String>,
+    pub(crate) active_endpoint: Option<String>,
+    pub(crate) server_health: Option<String>,
+    pub(crate) database_health: Option<String>,
+    pub(crate) last_health_check_at: Option<u64>,
+    pub(crate) handshake_latency: Option<crate::metrics::RollingLatency>,
+    pub(crate) ping_rtt: Option<crate::metrics::RollingLatency>,
+    pub(crate) background_streams: Vec<crate::model::BackgroundStreamStatus>,
+    pub(crate) watchdog_recoveries: u64,
+    pub(crate) last_watchdog_recovery: Option<String>,
+    pub(crate) last_watchdog_recovery_at: Option<u64>,
+    /// Newest connection events, oldest first; `get_connection_history` has the rest.
+    pub(crate) recent_connection_events: Vec<crate::connection_history::ConnectionEvent>,
+    /// Settings, the message cache and its store are sealed with the Keychain
+    /// key. False when the key is unavailable and files are written in plaintext.
+    pub(crate) encrypted_at_rest: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ClockSkewWarning {
+    pub(crate) skew_seconds: i64,
+    pub(crate) threshold_seconds: u64,
+    pub(crate) source: String,
+    pub(crate) detected_at: u64,
 }
 
 pub(crate) fn snapshot_runtime(app: &AppHandle) -> Result<RuntimeDiagnostics, String> {
@@ -40,6 +68,24 @@ pub(crate) fn snapshot_runtime(app: &AppHandle) -> Result<RuntimeDiagnostics, St
         last_error: runtime.last_error.clone(),
         backoff_seconds: runtime.backoff_seconds,
         reconnect_attempts: runtime.reconnect_attempts,
+        clock_skew_seconds: runtime.clock_skew_seconds,
+        last_heartbeat_at: runtime.last_heartbeat_at,
+        stream_remote_addr: runtime.stream_remote_addr.clone(),
+        active_endpoint: runtime.active_endpoint.clone(),
+        server_health: runtime.server_health.clone(),
+        database_health: runtime.database_health.clone(),
+        last_health_check_at: runtime.last_health_check_at,
+        handshake_latency: runtime.handshake_latency.summary(),
+        ping_rtt: runtime.ping_rtt.summary(),
+        background_streams: runtime.background_streams(),
+        watchdog_recoveries: runtime.watchdog_recoveries,
+        last_watchdog_recovery: runtime.last_watchdog_recovery.clone(),
+        last_watchdog_recovery_at: runtime.last_watchdog_recovery_at,
+        recent_connection_events: crate::connection_history::recent(
+            app,
+            CONNECTION_HISTORY_DIAGNOSTICS_EVENTS,
+        ),
+        encrypted_at_rest: crate::at_rest::key_present(),
     })
 }
 
@@ -62,3 +108,58 @@ pub(crate) fn mark_stream_activity(app: &AppHandle, at: u64, _source: &str) {
     }
     publish_runtime_snapshot(app);
 }
+
+/// Records a server-side timestamp sample and warns once when the local clock
+/// drifts beyond `CLOCK_SKEW_WARN_SECS`. Positive skew means the local clock is ahead.
+pub(crate) fn record_server_time(app: &AppHandle, server_secs: i64, source: &str) {
+    let now = unix_now_secs();
+    let skew = (now as i64).saturating_sub(server_secs);
+    let exceeded = skew.unsigned_abs() > CLOCK_SKEW_WARN_SECS;
+    let mut should_warn = false;
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Ok(mut runtime) = state.runtime.lock() {
+            runtime.clock_skew_seconds = Some(skew);
+            should_warn = exceeded && !runtime.clock_skew_warned;
+            runtime.clock_skew_warned = exceeded;
+        }
+    }
+
+    if !should_warn {
+        return;
+    }
+    debug_log(&format!("clock skew detected skew={skew}s source={source}"));
+    let warning = ClockSkewWarning {
+        skew_seconds: skew,
+        threshold_seconds: CLOCK_SKEW_WARN_SECS,
+        source: source.to_string(),
+        detected_at: now,
+    };
+    crate::contract::emit_event(app, "main", "clock-skew-warning", warning.clone());
+    crate::contract::emit_event(app, "quick", "clock-skew-warning", warning);
+    publish_runtime_snapshot(app);
+}
+
+pub(crate) fn record_server_date_header(
+    app: &AppHandle,
+    headers: &reqwest::header::HeaderMap,
+    source: &str,
+) {
+    let Some(value) = headers
+        .get(reqwest::header::DATE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return;
+    };
+    match chrono::DateTime::parse_from_rfc2822(value) {
+        Ok(server_time) => record_server_time(app, server_time.timestamp(), source),
+        Err(error) => debug_log(&format!(
+            "ignoring unparsable Date header {value:?}: {error}"
+        )),
+    }
+}
+
+pub(crate) fn record_server_message_date(app: &AppHandle, date: &str) {
+    if let Ok(server_time) = chrono::DateTime::parse_from_rfc3339(date) {
+        record_server_time(app, server_time.timestamp(), "stream-message");
+    }
+}
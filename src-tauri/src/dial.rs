@@ -0,0 +1,250 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6},
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use tokio::net::TcpStream;
+
+use crate::{
+    debug_log, settings::IpFamilyPreference, DNS_CACHE_TTL_SECS, DNS_LOOKUP_TIMEOUT_SECS,
+    HAPPY_EYEBALLS_DELAY_MS,
+};
+
+struct CachedLookup {
+    addrs: Vec<IpAddr>,
+    resolved_at: Instant,
+}
+
+static DNS_CACHE: OnceLock<Mutex<HashMap<String, CachedLookup>>> = OnceLock::new();
+
+fn dns_cache() -> &'static Mutex<HashMap<String, CachedLookup>> {
+    DNS_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Resolves `host` for both the stream and REST clients. A pinned address wins;
+/// otherwise a fresh cached lookup is reused, and a failed or slow lookup falls back
+/// to the last known addresses so flaky DNS (captive portals, VPN switches) does not
+/// block reconnecting to a server that is still reachable.
+pub(crate) async fn resolve_host(
+    host: &str,
+    port: u16,
+    pin: Option<IpAddr>,
+) -> Result<Vec<SocketAddr>, String> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+    if let Some((address, zone)) = host.split_once('%') {
+        return scoped_ipv6_addr(address, zone, port).map(|addr| vec![addr]);
+    }
+    if let Some(ip) = pin {
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+    let key = host.to_ascii_lowercase();
+    let with_port = |addrs: &[IpAddr]| {
+        addrs
+            .iter()
+            .map(|ip| SocketAddr::new(*ip, port))
+            .collect::<Vec<_>>()
+    };
+    if let Ok(cache) = dns_cache().lock() {
+        if let Some(cached) = cache.get(&key) {
+            if cached.resolved_at.elapsed() < Duration::from_secs(DNS_CACHE_TTL_SECS) {
+                return Ok(with_port(&cached.addrs));
+            }
+        }
+    }
+
+    let lookup = tokio::time::timeout(
+        Duration::from_secs(DNS_LOOKUP_TIMEOUT_SECS),
+        tokio::net::lookup_host((host, port)),
+    )
+    .await
+    .map_err(|_| format!("timed out after {DNS_LOOKUP_TIMEOUT_SECS} seconds"))
+    .and_then(|result| result.map_err(|error| error.to_string()));
+    match lookup {
+        Ok(resolved) => {
+            let addrs: Vec<IpAddr> = resolved.map(|addr| addr.ip()).collect();
+            if let Ok(mut cache) = dns_cache().lock() {
+                cache.insert(
+                    key,
+                    CachedLookup {
+                        addrs: addrs.clone(),
+                        resolved_at: Instant::now(),
+                    },
+                );
+            }
+            Ok(with_port(&addrs))
+        }
+        Err(error) => {
+            let stale = dns_cache()
+                .lock()
+                .ok()
+                .and_then(|cache| cache.get(&key).map(|cached| with_port(&cached.addrs)));
+            match stale {
+                Some(addrs) if !addrs.is_empty() => {
+                    debug_log(&format!(
+                        "dns lookup for {host} failed ({error}); using cached addresses"
+                    ));
+                    Ok(addrs)
+                }
+                _ => Err(format!("Failed to resolve {host}: {error}")),
+            }
+        }
+    }
+}
+
+/// Link-local literal such as `fe80::1%en0`; the zone is an interface name or index.
+fn scoped_ipv6_addr(address: &str, zone: &str, port: u16) -> Result<SocketAddr, String> {
+    let ip = address
+        .parse::<Ipv6Addr>()
+        .map_err(|_| format!("Invalid IPv6 address {address}"))?;
+    let scope_id = zone
+        .parse::<u32>()
+        .ok()
+        .or_else(|| interface_index(zone))
+        .ok_or_else(|| format!("No network interface named {zone}"))?;
+    Ok(SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope_id)))
+}
+
+fn interface_index(name: &str) -> Option<u32> {
+    extern "C" {
+        fn if_nametoindex(ifname: *const std::ffi::c_char) -> std::ffi::c_uint;
+    }
+    let name = std::ffi::CString::new(name).ok()?;
+    let index = unsafe { if_nametoindex(name.as_ptr()) };
+    (index != 0).then_some(index)
+}
+
+/// `reqwest` resolver backed by [`resolve_host`], so REST calls share the pins and cache.
+pub(crate) struct DialResolver {
+    host_pins: BTreeMap<String, IpAddr>,
+}
+
+impl DialResolver {
+    pub(crate) fn new(host_pins: BTreeMap<String, IpAddr>) -> Arc<Self> {
+        Arc::new(Self { host_pins })
+    }
+}
+
+impl reqwest::dns::Resolve for DialResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        let pin = self.host_pins.get(&host.to_ascii_lowercase()).copied();
+        Box::pin(async move {
+            // Port 0 is replaced by reqwest with the URL's port.
+            let addrs = resolve_host(&host, 0, pin).await?;
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Resolves `host` and connects over TCP. `Auto` races address families
+/// happy-eyeballs style (RFC 8305: IPv6 first, next attempt after a short delay),
+/// so a broken AAAA record costs a few hundred milliseconds instead of a hang.
+pub(crate) async fn connect_tcp(
+    host: &str,
+    port: u16,
+    preference: IpFamilyPreference,
+    pin: Option<IpAddr>,
+) -> Result<(TcpStream, SocketAddr), String> {
+    let resolved = resolve_host(host, port, pin).await?;
+    let ordered = order_addresses(resolved, preference);
+    if ordered.is_empty() {
+        return Err(format!(
+            "No {} address found for {host}",
+            match preference {
+                IpFamilyPreference::Ipv4 => "IPv4",
+                IpFamilyPreference::Ipv6 => "IPv6",
+                IpFamilyPreference::Auto => "usable",
+            }
+        ));
+    }
+
+    let attempt = |addr: SocketAddr| async move {
+        TcpStream::connect(addr)
+            .await
+            .map(|stream| (stream, addr))
+            .map_err(|error| format!("{addr}: {error}"))
+    };
+    let mut queue = ordered.into_iter();
+    let mut in_flight = FuturesUnordered::new();
+    let mut errors = Vec::new();
+    loop {
+        if in_flight.is_empty() {
+            match queue.next() {
+                Some(addr) => in_flight.push(attempt(addr)),
+                None => break,
+            }
+        }
+        tokio::select! {
+            Some(result) = in_flight.next() => match result {
+                Ok(connected) => {
+                    let _ = connected.0.set_nodelay(true);
+                    return Ok(connected);
+                }
+                Err(error) => errors.push(error),
+            },
+            _ = tokio::time::sleep(std::time::Duration::from_millis(HAPPY_EYEBALLS_DELAY_MS)),
+                if queue.len() > 0 =>
+            {
+                if let Some(addr) = queue.next() {
+                    in_flight.push(attempt(addr));
+                }
+            }
+        }
+    }
+    Err(format!(
+        "Failed to connect to {host}: {}",
+        errors.join("; ")
+    ))
+}
+
+fn order_addresses(addrs: Vec<SocketAddr>, preference: IpFamilyPreference) -> Vec<SocketAddr> {
+    let (v6, v4): (Vec<SocketAddr>, Vec<SocketAddr>) =
+        addrs.into_iter().partition(SocketAddr::is_ipv6);
+    match preference {
+        IpFamilyPreference::Ipv4 => v4,
+        IpFamilyPreference::Ipv6 => v6,
+        IpFamilyPreference::Auto => {
+            let mut ordered = Vec::with_capacity(v6.len() + v4.len());
+            let mut v6 = v6.into_iter();
+            let mut v4 = v4.into_iter();
+            loop {
+                match (v6.next(), v4.next()) {
+                    (None, None) => break,
+                    (a, b) => ordered.extend(a.into_iter().chain(b)),
+                }
+            }
+            ordered
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_link_local_addresses_by_index() {
+        let addr = scoped_ipv6_addr("fe80::1", "4", 8080).unwrap();
+        assert_eq!(
+            addr,
+            SocketAddr::V6(SocketAddrV6::new("fe80::1".parse().unwrap(), 8080, 0, 4))
+        );
+        // reqwest passes port 0 when the URL has none and fills it in itself.
+        assert_eq!(scoped_ipv6_addr("fe80::1", "4", 0).unwrap().port(), 0);
+    }
+
+    #[test]
+    fn rejects_bad_addresses_and_unknown_interfaces() {
+        assert!(scoped_ipv6_addr("not-an-ip", "4", 80).is_err());
+        assert!(scoped_ipv6_addr("192.168.1.1", "4", 80).is_err());
+        assert_eq!(
+            scoped_ipv6_addr("fe80::1", "nosuchif9", 80).unwrap_err(),
+            "No network interface named nosuchif9"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{debug_log, AppState, DISMISSED_LABEL, DISMISSED_MAX_IDS};
+
+/// Dismissal file that belongs to the active message cache (`messages.json` ->
+/// `dismissed.json`, `messages-<hash>.json` -> `dismissed-<hash>.json`).
+fn dismissed_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let messages_path = crate::messages_file(app)?;
+    let name = messages_path
+        .file_name()
+        .map(|name| name.to_string_lossy().replacen("messages", "dismissed", 1))
+        .ok_or_else(|| "Message cache path has no file name".to_string())?;
+    Ok(messages_path.with_file_name(name))
+}
+
+pub(crate) fn load_dismissed_from_disk(app: &AppHandle) -> HashSet<i64> {
+    let Ok(path) = dismissed_file(app) else {
+        return HashSet::new();
+    };
+    if !path.exists() {
+        return HashSet::new();
+    }
+    match crate::at_rest::read_to_string(DISMISSED_LABEL, &path).and_then(|content| {
+        serde_json::from_str::<HashSet<i64>>(&content).map_err(|error| error.to_string())
+    }) {
+        Ok(ids) => ids,
+        Err(error) => {
+            debug_log(&format!(
+                "dismiss: failed to load {}: {error}",
+                path.display()
+            ));
+            HashSet::new()
+        }
+    }
+}
+
+/// Swaps in the dismissals of the active profile; used at startup and on profile switch.
+pub(crate) fn replace_dismissed(app: &AppHandle, ids: HashSet<i64>) {
+    if let Ok(mut current) = app.state::<AppState>().dismissed.lock() {
+        *current = ids;
+    }
+}
+
+fn persist_dismissed(app: &AppHandle, ids: Vec<i64>) {
+    let Ok(path) = dismissed_file(app) else {
+        return;
+    };
+    crate::work::run_in_background("persist-dismissed", move || {
+        let result = serde_json::to_string(&ids)
+            .map_err(|error| error.to_string())
+            .and_then(|content| crate::at_rest::seal(DISMISSED_LABEL, content.as_bytes()))
+            .and_then(|sealed| {
+                let tmp_path = path.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
+                fs::write(&tmp_path, sealed).map_err(|error| error.to_string())?;
+                crate::restrict_file_permissions(&tmp_path);
+                fs::rename(&tmp_path, &path).map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            debug_log(&format!(
+                "dismiss: failed to write {}: {error}",
+                path.display()
+            ));
+        }
+    });
+}
+
+/// Ids syncs, the stream and imports must not bring back into the cache.
+pub(crate) fn dismissed_ids(app: &AppHandle) -> HashSet<i64> {
+    app.try_state::<AppState>()
+        .and_then(|state| state.dismissed.lock().ok().map(|ids| ids.clone()))
+        .unwrap_or_default()
+}
+
+/// Hides a message on this Mac only: it leaves the cache and stays out of it,
+/// but `DELETE /message/{id}` is never sent, so other clients keep it. Returns
+/// whether it was cached.
+pub(crate) fn dismiss_message(app: &AppHandle, message_id: i64) -> Result<bool, String> {
+    {
+        let state = app.state::<AppState>();
+        let mut ids = state
+            .dismissed
+            .lock()
+            .map_err(|_| "Dismissal list lock poisoned".to_string())?;
+        ids.insert(message_id);
+        // Ids only grow, so the oldest dismissals are the ones the server is
+        // least likely to still send.
+        if ids.len() > DISMISSED_MAX_IDS {
+            let mut sorted: Vec<i64> = ids.iter().copied().collect();
+            sorted.sort_unstable();
+            for id in &sorted[..sorted.len() - DISMISSED_MAX_IDS] {
+                ids.remove(id);
+            }
+        }
+        // Queued under the lock so writes reach the serial worker in the order
+        // the set changed; otherwise an older snapshot could land last.
+        persist_dismissed(app, ids.iter().copied().collect());
+    }
+
+    crate::notification_queue::cancel_for_messages(app, &[message_id]);
+    crate::notifications::withdraw_notifications(app, &[message_id]);
+    let removed = crate::messages::remove_messages_from_cache(app, &HashSet::from([message_id]))?;
+    debug_log(&format!("dismiss: message {message_id} hidden locally"));
+    Ok(removed > 0)
+}
@@ -0,0 +1,218 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    contract::now_ms,
+    debug_log,
+    settings::{
+        normalize_base_url, read_settings, save_non_secret_settings, split_ipv6_zone,
+        to_settings_response, SettingsResponse, StoredSettings,
+    },
+    AppState, CONNECTION_STATE_AUTH_FAILED, ENDPOINT_PROBE_INTERVAL_SECS,
+    ENDPOINT_PROBE_TIMEOUT_SECS, ENDPOINT_SWITCH_MARGIN_MS,
+};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct EndpointChangedEvent {
+    pub(crate) at_ms: u64,
+    pub(crate) base_url: String,
+    pub(crate) previous: String,
+    /// True when the LAN URL is now in use.
+    pub(crate) lan: bool,
+    pub(crate) latency_ms: u64,
+}
+
+fn lan_base_url(settings: &StoredSettings) -> Option<String> {
+    settings
+        .lan_base_url
+        .as_deref()
+        .and_then(|url| normalize_base_url(url).ok())
+}
+
+/// Base URL for HTTP requests and the stream: the LAN URL while the last probe
+/// preferred it, the configured server URL otherwise.
+/// An IPv6 zone is left out, since URL parsers reject it; see `active_ipv6_zone`.
+pub(crate) fn active_base_url(
+    app: &AppHandle,
+    settings: &StoredSettings,
+) -> Result<String, String> {
+    active_endpoint_url(app, settings).map(|url| split_ipv6_zone(&url).0)
+}
+
+/// Interface zone of the active endpoint when it is a link-local IPv6 host.
+pub(crate) fn active_ipv6_zone<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    let settings = read_settings(app).ok()?;
+    split_ipv6_zone(&active_endpoint_url(app, &settings).ok()?).1
+}
+
+/// Active endpoint as configured, zone included.
+fn active_endpoint_url<R: Runtime>(
+    app: &AppHandle<R>,
+    settings: &StoredSettings,
+) -> Result<String, String> {
+    let primary = normalize_base_url(&settings.base_url)?;
+    let Some(lan) = lan_base_url(settings) else {
+        return Ok(primary);
+    };
+    let active = app.try_state::<AppState>().and_then(|state| {
+        state
+            .runtime
+            .lock()
+            .ok()
+            .and_then(|runtime| runtime.active_endpoint.clone())
+    });
+    Ok(if active.as_deref() == Some(lan.as_str()) {
+        lan
+    } else {
+        primary
+    })
+}
+
+/// An empty URL turns switching off. The LAN URL belongs to the active server,
+/// so it is mirrored into its profile.
+pub(crate) fn set_lan_base_url(
+    app: &AppHandle,
+    url: Option<String>,
+) -> Result<SettingsResponse, String> {
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        let lan_base_url = match url.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(url) => Some(normalize_base_url(url)?),
+        };
+        if lan_base_url.is_some()
+            && lan_base_url.as_deref() == normalize_base_url(&settings.base_url).ok().as_deref()
+        {
+            return Err("The LAN URL must differ from the server URL".to_string());
+        }
+        settings.lan_base_url = lan_base_url.clone();
+        if let Some(profile) = settings
+            .active_profile
+            .clone()
+            .and_then(|active| settings.profiles.get_mut(&active))
+        {
+            profile.lan_base_url = lan_base_url;
+        }
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!(
+        "endpoints: LAN URL {}",
+        if settings.lan_base_url.is_some() {
+            "set"
+        } else {
+            "cleared"
+        }
+    ));
+    let app_for_probe = app.clone();
+    tauri::async_runtime::spawn(async move { probe(&app_for_probe).await });
+    Ok(to_settings_response(settings))
+}
+
+/// Round trip of the unauthenticated `GET /version`, or `None` when unreachable.
+async fn measure(client: &reqwest::Client, base_url: &str) -> Option<u64> {
+    let started = Instant::now();
+    let (base_url, _) = split_ipv6_zone(base_url);
+    match client.get(format!("{base_url}/version")).send().await {
+        Ok(response) if response.status().is_success() => {
+            Some(started.elapsed().as_millis() as u64)
+        }
+        _ => None,
+    }
+}
+
+/// Picks the faster reachable URL. The current one is kept unless it is
+/// unreachable or slower by more than `ENDPOINT_SWITCH_MARGIN_MS`, so similar
+/// latencies do not flip the stream back and forth.
+fn choose(current_is_lan: bool, wan_ms: Option<u64>, lan_ms: Option<u64>) -> Option<(bool, u64)> {
+    let (current_ms, other_ms) = if current_is_lan {
+        (lan_ms, wan_ms)
+    } else {
+        (wan_ms, lan_ms)
+    };
+    match (current_ms, other_ms) {
+        (_, None) => None,
+        (Some(current), Some(other)) if other + ENDPOINT_SWITCH_MARGIN_MS >= current => None,
+        (_, Some(other)) => Some((!current_is_lan, other)),
+    }
+}
+
+pub(crate) async fn probe(app: &AppHandle) {
+    let Ok(settings) = read_settings(app) else {
+        return;
+    };
+    let Ok(primary) = normalize_base_url(&settings.base_url) else {
+        return;
+    };
+    let Some(lan) = lan_base_url(&settings) else {
+        if let Ok(mut runtime) = app.state::<AppState>().runtime.lock() {
+            runtime.active_endpoint = None;
+        }
+        return;
+    };
+    let client = match crate::settings::server_client_builder(app).and_then(|builder| {
+        builder
+            .timeout(Duration::from_secs(ENDPOINT_PROBE_TIMEOUT_SECS))
+            .build()
+            .map_err(|error| error.to_string())
+    }) {
+        Ok(client) => client,
+        Err(error) => {
+            debug_log(&format!("endpoints: failed to build HTTP client: {error}"));
+            return;
+        }
+    };
+    let (wan_ms, lan_ms) = tokio::join!(measure(&client, &primary), measure(&client, &lan));
+    let previous = active_endpoint_url(app, &settings).unwrap_or_else(|_| primary.clone());
+    let current_is_lan = previous == lan;
+    let Some((use_lan, latency_ms)) = choose(current_is_lan, wan_ms, lan_ms) else {
+        return;
+    };
+    let next = if use_lan { lan } else { primary };
+    let restart = {
+        let state = app.state::<AppState>();
+        let Ok(mut runtime) = state.runtime.lock() else {
+            return;
+        };
+        runtime.active_endpoint = Some(next.clone());
+        runtime.should_run && runtime.connection_state != CONNECTION_STATE_AUTH_FAILED
+    };
+    debug_log(&format!(
+        "endpoints: switched to {} (wan={wan_ms:?}ms lan={lan_ms:?}ms)",
+        if use_lan { "LAN" } else { "server URL" }
+    ));
+    crate::contract::emit_event(
+        app,
+        "main",
+        "endpoint-changed",
+        EndpointChangedEvent {
+            at_ms: now_ms(),
+            base_url: next,
+            previous,
+            lan: use_lan,
+            latency_ms,
+        },
+    );
+    if restart {
+        if let Err(error) = crate::stream::restart_stream(app.clone()) {
+            debug_log(&format!("endpoints: stream restart failed: {error}"));
+        }
+    }
+    crate::diagnostics::publish_runtime_snapshot(app);
+}
+
+pub(crate) fn watch_endpoints(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            probe(&app).await;
+            tokio::time::sleep(Duration::from_secs(ENDPOINT_PROBE_INTERVAL_SECS)).await;
+        }
+    });
+}
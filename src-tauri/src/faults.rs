@@ -0,0 +1,128 @@
+use std::{
+    future::Future,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    settings::{read_settings, save_non_secret_settings, to_settings_response, SettingsResponse},
+    AppState, MAX_FAULT_HTTP_DELAY_MS,
+};
+
+/// Network fault injection for exercising reconnect, retry and watchdog paths
+/// during development. Stored in settings but only honoured by debug builds.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub(crate) struct FaultInjection {
+    pub(crate) enabled: bool,
+    /// Close the stream this many seconds after every connect; 0 disables.
+    pub(crate) drop_stream_after_secs: u64,
+    /// Extra latency before every Gotify REST request.
+    pub(crate) http_delay_ms: u64,
+    /// Share (0-100) of Gotify REST requests answered with a synthetic HTTP 500.
+    pub(crate) http_error_percent: u8,
+}
+
+static ACTIVE: OnceLock<Mutex<Option<FaultInjection>>> = OnceLock::new();
+
+pub(crate) fn is_available() -> bool {
+    cfg!(debug_assertions)
+}
+
+/// Mirrors the stored configuration so the hot paths do not read settings.
+pub(crate) fn set_config(config: &FaultInjection) {
+    let active = (is_available() && config.enabled).then(|| config.clone());
+    if let Ok(mut current) = ACTIVE.get_or_init(|| Mutex::new(None)).lock() {
+        if *current != active {
+            debug_log(&format!("fault injection: {active:?}"));
+        }
+        *current = active;
+    }
+}
+
+fn active() -> Option<FaultInjection> {
+    ACTIVE
+        .get()
+        .and_then(|active| active.lock().ok().and_then(|active| active.clone()))
+}
+
+fn normalize(config: FaultInjection) -> FaultInjection {
+    FaultInjection {
+        enabled: config.enabled,
+        drop_stream_after_secs: config.drop_stream_after_secs,
+        http_delay_ms: config.http_delay_ms.min(MAX_FAULT_HTTP_DELAY_MS),
+        http_error_percent: config.http_error_percent.min(100),
+    }
+}
+
+pub(crate) fn set_fault_injection(
+    app: &AppHandle,
+    config: FaultInjection,
+) -> Result<SettingsResponse, String> {
+    if !is_available() {
+        return Err("Fault injection is only available in debug builds".to_string());
+    }
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.fault_injection = normalize(config);
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    Ok(to_settings_response(settings))
+}
+
+fn roll_percent(percent: u8) -> bool {
+    if percent == 0 {
+        return false;
+    }
+    let mut byte = [0u8; 1];
+    if SystemRandom::new().fill(&mut byte).is_err() {
+        return false;
+    }
+    u16::from(byte[0]) * 100 / 256 < u16::from(percent)
+}
+
+/// Sends a Gotify REST request, applying the configured delay and error rate.
+/// Injected errors are real `reqwest::Response`s, so callers take their usual
+/// status handling path.
+pub(crate) async fn send(
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, reqwest::Error> {
+    if let Some(config) = active() {
+        if config.http_delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(config.http_delay_ms)).await;
+        }
+        if roll_percent(config.http_error_percent) {
+            debug_log("fault injection: answering request with HTTP 500");
+            let response = tauri::http::Response::builder()
+                .status(500)
+                .body("injected fault".to_string())
+                .unwrap_or_default();
+            return Ok(reqwest::Response::from(response));
+        }
+    }
+    request.send().await
+}
+
+/// Resolves when the injected stream drop is due; never resolves when off.
+pub(crate) fn stream_drop_timer() -> impl Future<Output = ()> {
+    let after = active()
+        .map(|config| config.drop_stream_after_secs)
+        .filter(|secs| *secs > 0);
+    async move {
+        match after {
+            Some(secs) => tokio::time::sleep(Duration::from_secs(secs)).await,
+            None => std::future::pending().await,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use tauri::{menu::MenuItem, AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    settings::{read_settings, save_non_secret_settings, to_settings_response, SettingsResponse},
+    unix_now_secs, AppState, HEALTH_CHECK_IDLE_POLL_SECS, HEALTH_CHECK_TIMEOUT_SECS,
+    MAX_HEALTH_CHECK_INTERVAL_SECS, MIN_HEALTH_CHECK_INTERVAL_SECS,
+};
+
+/// Body of Gotify's `GET /health`; values are `green`, `orange` or `red`.
+#[derive(Debug, Deserialize)]
+struct HealthWire {
+    #[serde(default)]
+    health: String,
+    #[serde(default)]
+    database: String,
+}
+
+/// Result of one check. `unreachable` covers transport errors and bodies that
+/// are not a health report.
+#[derive(Debug, Clone, PartialEq)]
+struct HealthReport {
+    server: String,
+    database: String,
+}
+
+impl HealthReport {
+    fn unreachable() -> Self {
+        Self {
+            server: "unreachable".to_string(),
+            database: "unknown".to_string(),
+        }
+    }
+}
+
+pub(crate) fn create_tray_item(app: &AppHandle) -> Result<MenuItem<tauri::Wry>, tauri::Error> {
+    let item = MenuItem::with_id(app, "server_health", "Server: Unknown", false, None::<&str>)?;
+    if let Ok(mut tray_item) = app.state::<AppState>().tray_health_item.lock() {
+        *tray_item = Some(item.clone());
+    }
+    Ok(item)
+}
+
+fn tray_label(report: Option<&HealthReport>) -> String {
+    let Some(report) = report else {
+        return "Server: Unknown".to_string();
+    };
+    match (report.server.as_str(), report.database.as_str()) {
+        ("unreachable", _) => "Server: Unreachable".to_string(),
+        ("green", "green") => "Server: Healthy".to_string(),
+        (_, "red") => "Server: Database down".to_string(),
+        (server, database) => format!("Server: Degraded ({server}, database {database})"),
+    }
+}
+
+fn sync_tray_item(app: &AppHandle, report: Option<&HealthReport>) {
+    let Some(item) = app
+        .state::<AppState>()
+        .tray_health_item
+        .lock()
+        .ok()
+        .and_then(|item| item.clone())
+    else {
+        return;
+    };
+    if let Err(error) = item.set_text(tray_label(report)) {
+        debug_log(&format!(
+            "failed to update server health menu item: {error}"
+        ));
+    }
+}
+
+/// 0 turns checks off; anything else is clamped to the supported range.
+pub(crate) fn normalize_interval(secs: u64) -> u64 {
+    if secs == 0 {
+        0
+    } else {
+        secs.clamp(
+            MIN_HEALTH_CHECK_INTERVAL_SECS,
+            MAX_HEALTH_CHECK_INTERVAL_SECS,
+        )
+    }
+}
+
+pub(crate) fn set_health_check_interval(
+    app: &AppHandle,
+    secs: u64,
+) -> Result<SettingsResponse, String> {
+    if secs > 0 {
+        crate::server_info::require(app, crate::server_info::ServerFeature::Health)?;
+    }
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.health_check_interval_secs = normalize_interval(secs);
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    if settings.health_check_interval_secs == 0 {
+        record(app, None);
+    }
+    // A stored permit, so a change made during a check is not lost; the loop
+    // then checks right away and sleeps the new interval.
+    app.state::<AppState>().health_wake.notify_one();
+    Ok(to_settings_response(settings))
+}
+
+async fn check(app: &AppHandle) -> Option<HealthReport> {
+    if !crate::server_info::supports(app, crate::server_info::ServerFeature::Health) {
+        return None;
+    }
+    let settings = read_settings(app).ok()?;
+    let base_url = crate::endpoints::active_base_url(app, &settings).ok()?;
+    let client = crate::settings::server_client_builder(app)
+        .and_then(|builder| {
+            builder
+                .timeout(Duration::from_secs(HEALTH_CHECK_TIMEOUT_SECS))
+                .build()
+                .map_err(|error| error.to_string())
+        })
+        .map_err(|error| debug_log(&format!("health: failed to build HTTP client: {error}")))
+        .ok()?;
+    // An unhealthy server answers 500 with the same body, so the status is not
+    // checked; the body says what is wrong.
+    let report = match client.get(format!("{base_url}/health")).send().await {
+        Ok(response) => match response.json::<HealthWire>().await {
+            Ok(wire) if !wire.health.is_empty() => HealthReport {
+                server: wire.health,
+                database: wire.database,
+            },
+            _ => HealthReport::unreachable(),
+        },
+        Err(error) => {
+            debug_log(&format!("health: request failed: {error}"));
+            HealthReport::unreachable()
+        }
+    };
+    Some(report)
+}
+
+/// Stores the latest report and updates the tray line and diagnostics when it
+/// changed. `None` clears it (checks off or no server).
+fn record(app: &AppHandle, report: Option<HealthReport>) {
+    let changed = {
+        let state = app.state::<AppState>();
+        let Ok(mut runtime) = state.runtime.lock() else {
+            return;
+        };
+        let previous = runtime
+            .server_health
+            .clone()
+            .zip(runtime.database_health.clone())
+            .map(|(server, database)| HealthReport { server, database });
+        runtime.last_health_check_at = report.as_ref().map(|_| unix_now_secs());
+        runtime.server_health = report.as_ref().map(|report| report.server.clone());
+        runtime.database_health = report.as_ref().map(|report| report.database.clone());
+        previous != report
+    };
+    if changed {
+        debug_log(&format!("health: {}", tray_label(report.as_ref())));
+        sync_tray_item(app, report.as_ref());
+    }
+    crate::diagnostics::publish_runtime_snapshot(app);
+}
+
+pub(crate) fn watch_server_health(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        loop {
+            let interval = read_settings(&app)
+                .map(|settings| normalize_interval(settings.health_check_interval_secs))
+                .unwrap_or(0);
+            let sleep_secs = if interval == 0 {
+                HEALTH_CHECK_IDLE_POLL_SECS
+            } else {
+                let report = check(&app).await;
+                record(&app, report);
+                interval
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(sleep_secs)) => {}
+                _ = state.health_wake.notified() => {}
+            }
+        }
+    });
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log, settings::read_settings, truncate_message, unix_now_secs, AppState,
+    HEARTBEAT_REQUEST_TIMEOUT_SECS,
+};
+
+/// Pings the user-configured heartbeat URL (healthchecks.io style) while the
+/// stream is connected, so external monitoring notices when this client goes quiet.
+pub(crate) async fn send_heartbeat(app: &AppHandle) {
+    let Some(url) = read_settings(app)
+        .ok()
+        .and_then(|settings| settings.heartbeat_url)
+    else {
+        return;
+    };
+
+    let ca_cert_path = crate::tls::configured_ca_cert_path(app);
+    let trusted_certificates = crate::tls::configured_trusted_certificates(app);
+    let client =
+        match crate::tls::http_client_builder(ca_cert_path.as_deref(), &trusted_certificates)
+            .and_then(|builder| {
+                crate::proxy::apply_to_client(builder, crate::proxy::configured_proxy(app).as_ref())
+            })
+            .and_then(|builder| {
+                builder
+                    .timeout(Duration::from_secs(HEARTBEAT_REQUEST_TIMEOUT_SECS))
+                    .build()
+                    .map_err(|error| error.to_string())
+            }) {
+            Ok(client) => client,
+            Err(error) => {
+                debug_log(&format!("heartbeat: failed to build HTTP client: {error}"));
+                return;
+            }
+        };
+
+    match client.get(&url).send().await {
+        Ok(response) if response.status().is_success() => {
+            if let Ok(mut runtime) = app.state::<AppState>().runtime.lock() {
+                runtime.last_heartbeat_at = Some(unix_now_secs());
+            }
+        }
+        Ok(response) => {
+            debug_log(&format!(
+                "heartbeat: HTTP {} from {}",
+                response.status().as_u16(),
+                truncate_message(&url, 120)
+            ));
+        }
+        Err(error) => {
+            debug_log(&format!("heartbeat: request failed: {error}"));
+        }
+    }
+}
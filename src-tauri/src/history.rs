@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{
+    applications::server_context,
+    client_token::decode_response,
+    messages::{convert_wire_message, is_tombstoned},
+    server_info::{require, ServerFeature},
+    CachedMessage, GotifyMessageWire, MAX_API_PAGE_LIMIT,
+};
+
+#[derive(Debug, Deserialize)]
+struct PagingWire {
+    #[serde(default)]
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagePageWire {
+    #[serde(default)]
+    messages: Vec<GotifyMessageWire>,
+    #[serde(default)]
+    paging: Option<PagingWire>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct OlderMessagesPage {
+    /// Newest first, like the cache.
+    pub(crate) messages: Vec<CachedMessage>,
+    /// Pass as `before_id` for the next page; `None` once the server has no
+    /// older messages.
+    pub(crate) next_before_id: Option<i64>,
+}
+
+/// One page of messages older than `before_id`, straight from the server. The
+/// cache is left alone, so scrolling far back never evicts live messages.
+pub(crate) async fn load_older_messages(
+    app: &AppHandle,
+    before_id: i64,
+    limit: Option<usize>,
+) -> Result<OlderMessagesPage, String> {
+    require(app, ServerFeature::MessagePaging)?;
+    if before_id <= 1 {
+        return Ok(OlderMessagesPage {
+            messages: Vec::new(),
+            next_before_id: None,
+        });
+    }
+    let limit = limit
+        .unwrap_or(MAX_API_PAGE_LIMIT)
+        .clamp(1, MAX_API_PAGE_LIMIT);
+    let context = server_context(app)?;
+    let request = context
+        .client
+        .get(format!(
+            "{}/message?limit={limit}&since={before_id}",
+            context.base_url
+        ))
+        .header("X-Gotify-Key", &context.token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to load older messages: {error}"))?;
+    let page = decode_response::<MessagePageWire>(response, "Older messages").await?;
+
+    let oldest_id = page.messages.iter().map(|message| message.id).min();
+    let has_more = page
+        .paging
+        .as_ref()
+        .and_then(|paging| paging.next.as_deref())
+        .is_some_and(|next| !next.is_empty());
+    let mut trashed = crate::trash::trashed_ids(app);
+    trashed.extend(crate::dismiss::dismissed_ids(app));
+    let mut messages: Vec<CachedMessage> = page
+        .messages
+        .into_iter()
+        .filter(|message| !is_tombstoned(app, message.id) && !trashed.contains(&message.id))
+        .map(|wire| {
+            let mut message = convert_wire_message(app, wire);
+            // Scrolling back through history is not news.
+            message.read = true;
+            message
+        })
+        .collect();
+    messages.sort_by(crate::cached_message_cmp);
+    Ok(OlderMessagesPage {
+        messages,
+        next_before_id: oldest_id.filter(|_| has_more),
+    })
+}
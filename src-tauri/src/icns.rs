@@ -0,0 +1,193 @@
+use std::{
+    fs,
+    io::{Cursor, Read},
+    path::Path,
+    process::Stdio,
+};
+
+use image::{imageops::FilterType, DynamicImage, ImageFormat, RgbaImage};
+
+use crate::debug_log;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// SVG render size; notification icons are shown far smaller.
+const SVG_RENDER_PIXELS: u32 = 512;
+
+/// Real format of an app icon, by magic bytes. Gotify serves whatever was
+/// uploaded, so neither the file name nor the Content-Type can be trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IconFormat {
+    Png,
+    Gif,
+    Jpeg,
+    WebP,
+    Svg,
+}
+
+pub(crate) fn sniff_icon_format(bytes: &[u8]) -> Option<IconFormat> {
+    if bytes.starts_with(PNG_SIGNATURE) {
+        return Some(IconFormat::Png);
+    }
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return Some(IconFormat::Gif);
+    }
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(IconFormat::Jpeg);
+    }
+    if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        return Some(IconFormat::WebP);
+    }
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_ascii_lowercase();
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    if head.starts_with('<') && head.contains("<svg") {
+        return Some(IconFormat::Svg);
+    }
+    None
+}
+
+/// Converts an app icon to PNG for the notification cache. Animated GIF and WebP
+/// keep only their first frame; SVG is rasterized by Quick Look.
+pub(crate) fn icon_to_png(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let format = match sniff_icon_format(bytes) {
+        Some(IconFormat::Png) => return Ok(bytes.to_vec()),
+        Some(IconFormat::Svg) => return render_svg_to_png(bytes),
+        Some(IconFormat::Gif) => ImageFormat::Gif,
+        Some(IconFormat::Jpeg) => ImageFormat::Jpeg,
+        Some(IconFormat::WebP) => ImageFormat::WebP,
+        None => return Err("Unrecognized icon format".to_string()),
+    };
+    let decoded = image::load_from_memory_with_format(bytes, format)
+        .map_err(|error| format!("Failed to decode {format:?} icon: {error}"))?;
+    let mut png = Vec::new();
+    decoded
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|error| format!("Failed to encode icon as PNG: {error}"))?;
+    Ok(png)
+}
+
+/// Cache entries written before icons were transcoded may hold GIF or SVG data
+/// under a `.png` name; those are rebuilt.
+pub(crate) fn is_png_file(path: &Path) -> bool {
+    let mut signature = [0u8; 8];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut signature))
+        .is_ok()
+        && signature == PNG_SIGNATURE
+}
+
+fn render_svg_to_png(svg: &[u8]) -> Result<Vec<u8>, String> {
+    let work_dir =
+        std::env::temp_dir().join(format!("gotify-svg-icon-{}", crate::unique_time_suffix()));
+    fs::create_dir_all(&work_dir)
+        .map_err(|error| format!("Failed to create SVG work directory: {error}"))?;
+    let svg_path = work_dir.join("icon.svg");
+    let result = fs::write(&svg_path, svg)
+        .map_err(|error| format!("Failed to write SVG icon: {error}"))
+        .and_then(|()| {
+            let status = crate::work::background_command("/usr/bin/qlmanage")
+                .arg("-t")
+                .arg("-s")
+                .arg(SVG_RENDER_PIXELS.to_string())
+                .arg("-o")
+                .arg(&work_dir)
+                .arg(&svg_path)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map_err(|error| format!("Failed to run qlmanage: {error}"))?;
+            if !status.success() {
+                return Err(format!("qlmanage exited with {status}"));
+            }
+            fs::read(work_dir.join("icon.svg.png"))
+                .map_err(|error| format!("qlmanage produced no thumbnail: {error}"))
+        });
+    if let Err(error) = fs::remove_dir_all(&work_dir) {
+        debug_log(&format!("failed to remove SVG work directory: {error}"));
+    }
+    result
+}
+
+/// ICNS element types that carry PNG data, keyed by pixel size. Retina variants
+/// share the bitmap of the size they double (`ic11` is 16pt@2x = 32px).
+const ICNS_ELEMENTS: [(&[u8; 4], u32); 11] = [
+    (b"icp4", 16),
+    (b"ic11", 32),
+    (b"icp5", 32),
+    (b"ic12", 64),
+    (b"icp6", 64),
+    (b"ic07", 128),
+    (b"ic13", 256),
+    (b"ic08", 256),
+    (b"ic14", 512),
+    (b"ic09", 512),
+    (b"ic10", 1024),
+];
+
+/// Builds an `.icns` from a PNG in-process: the source is padded to a square,
+/// resized for each slot and packed into the container. Written via a temp file
+/// so a half-written icon is never picked up.
+pub(crate) fn write_icns_from_png(source_png: &Path, target_icns: &Path) -> Result<(), String> {
+    let source = image::open(source_png)
+        .map_err(|error| format!("Failed to decode {}: {error}", source_png.display()))?;
+    let icns = encode_icns(&square_canvas(source))?;
+
+    let tmp_path = target_icns.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
+    fs::write(&tmp_path, icns).map_err(|error| format!("Failed to write icns: {error}"))?;
+    fs::rename(&tmp_path, target_icns).map_err(|error| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Failed to move icns into place: {error}")
+    })
+}
+
+/// Centers the image on a transparent square so non-square icons keep their
+/// aspect ratio instead of being stretched.
+fn square_canvas(source: DynamicImage) -> RgbaImage {
+    let source = source.into_rgba8();
+    let (width, height) = source.dimensions();
+    if width == height {
+        return source;
+    }
+    let side = width.max(height);
+    let mut canvas = RgbaImage::new(side, side);
+    image::imageops::overlay(
+        &mut canvas,
+        &source,
+        i64::from((side - width) / 2),
+        i64::from((side - height) / 2),
+    );
+    canvas
+}
+
+fn encode_icns(source: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    let mut encoded: Vec<(u32, Vec<u8>)> = Vec::new();
+    for (element_type, pixels) in ICNS_ELEMENTS {
+        let index = match encoded.iter().position(|(size, _)| *size == pixels) {
+            Some(index) => index,
+            None => {
+                let resized = image::imageops::resize(source, pixels, pixels, FilterType::Lanczos3);
+                let mut png = Vec::new();
+                resized
+                    .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                    .map_err(|error| format!("Failed to encode {pixels}px icon: {error}"))?;
+                encoded.push((pixels, png));
+                encoded.len() - 1
+            }
+        };
+        let png = &encoded[index].1;
+        body.extend_from_slice(element_type);
+        body.extend_from_slice(&element_length(png.len())?.to_be_bytes());
+        body.extend_from_slice(png);
+    }
+
+    let mut icns = Vec::with_capacity(body.len() + 8);
+    icns.extend_from_slice(b"icns");
+    icns.extend_from_slice(&element_length(body.len())?.to_be_bytes());
+    icns.extend_from_slice(&body);
+    Ok(icns)
+}
+
+/// Element and file lengths include their own 8-byte header.
+fn element_length(data_len: usize) -> Result<u32, String> {
+    u32::try_from(data_len + 8).map_err(|_| "Icon too large for icns".to_string())
+}
@@ -0,0 +1,280 @@
+use std::{collections::HashMap, fs, mem::size_of, path::PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{
+    http::{header::CONTENT_TYPE, Request, Response, StatusCode},
+    AppHandle, Manager,
+};
+
+use crate::{
+    core::decode_data_url_bytes, debug_log, AppState, ApplicationMeta, CachedMessage,
+    APP_ICON_MAX_BYTES, ICON_MEMORY_CAP_BYTES, ICON_URI_SCHEME,
+};
+
+/// Approximate heap usage of the in-memory caches. Strings are counted by
+/// length, so the numbers are a lower bound, but icons dominate either way.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct MemoryStats {
+    pub(crate) cached_messages: usize,
+    pub(crate) message_cache_bytes: usize,
+    /// Part of `message_cache_bytes` held by inline (data URL) app icons.
+    pub(crate) message_icon_bytes: usize,
+    pub(crate) app_meta_entries: usize,
+    pub(crate) app_meta_bytes: usize,
+    pub(crate) app_meta_icon_bytes: usize,
+    pub(crate) disk_backed_icons: usize,
+    pub(crate) icon_memory_cap_bytes: usize,
+}
+
+fn message_bytes(message: &CachedMessage) -> usize {
+    size_of::<CachedMessage>()
+        + message.title.len()
+        + message.message.len()
+        + message.app.len()
+        + message.app_icon.as_ref().map_or(0, String::len)
+        + message.date.len()
+        + message.language.as_ref().map_or(0, String::len)
+        + json_bytes(&message.extras)
+        + if message.extra.is_empty() {
+            0
+        } else {
+            serde_json::to_vec(&message.extra).map_or(0, |bytes| bytes.len())
+        }
+}
+
+fn json_bytes(value: &serde_json::Value) -> usize {
+    if value.is_null() {
+        0
+    } else {
+        serde_json::to_vec(value).map_or(0, |bytes| bytes.len())
+    }
+}
+
+fn inline_icon_bytes(icon: &str) -> usize {
+    if icon.trim_start().starts_with("data:") {
+        icon.len()
+    } else {
+        0
+    }
+}
+
+fn cached_message_icon_bytes(app: &AppHandle) -> usize {
+    app.state::<AppState>()
+        .messages
+        .lock()
+        .map(|messages| {
+            messages
+                .iter()
+                .filter_map(|message| message.app_icon.as_deref())
+                .map(inline_icon_bytes)
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+pub(crate) fn memory_stats(app: &AppHandle) -> Result<MemoryStats, String> {
+    let state = app.state::<AppState>();
+    let (cached_messages, message_cache_bytes, message_icon_bytes) = {
+        let messages = state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        (
+            messages.len(),
+            messages.iter().map(message_bytes).sum(),
+            messages
+                .iter()
+                .filter_map(|message| message.app_icon.as_deref())
+                .map(inline_icon_bytes)
+                .sum(),
+        )
+    };
+    let meta = state
+        .app_meta
+        .lock()
+        .map_err(|_| "Application map lock poisoned".to_string())?;
+    Ok(MemoryStats {
+        cached_messages,
+        message_cache_bytes,
+        message_icon_bytes,
+        app_meta_entries: meta.len(),
+        app_meta_bytes: meta
+            .values()
+            .map(|entry| {
+                size_of::<(i64, ApplicationMeta)>() + entry.name.len() + entry.icon_url.len()
+            })
+            .sum(),
+        app_meta_icon_bytes: meta
+            .values()
+            .map(|entry| inline_icon_bytes(&entry.icon_url))
+            .sum(),
+        disk_backed_icons: meta
+            .values()
+            .filter(|entry| is_icon_ref(&entry.icon_url))
+            .count(),
+        icon_memory_cap_bytes: ICON_MEMORY_CAP_BYTES,
+    })
+}
+
+/// Swaps inline data URL icons for references served from disk once the icons
+/// held by `next_meta` and the message cache exceed `ICON_MEMORY_CAP_BYTES`.
+/// Every cached message carries its own copy of the icon string, so a few
+/// large icons over a full cache add up quickly. Returns `true` when icons were
+/// moved to disk; the caller then rewrites the cached messages.
+pub(crate) fn enforce_memory_cap(
+    app: &AppHandle,
+    next_meta: &mut HashMap<i64, ApplicationMeta>,
+) -> bool {
+    let meta_icon_bytes: usize = next_meta
+        .values()
+        .map(|entry| inline_icon_bytes(&entry.icon_url))
+        .sum();
+    if meta_icon_bytes == 0 {
+        return false;
+    }
+    let total = meta_icon_bytes + cached_message_icon_bytes(app);
+    if total <= ICON_MEMORY_CAP_BYTES {
+        return false;
+    }
+    let Some(dir) = icon_store_dir(app) else {
+        return false;
+    };
+    let mut moved = 0;
+    for (app_id, entry) in next_meta.iter_mut() {
+        if inline_icon_bytes(&entry.icon_url) == 0 {
+            continue;
+        }
+        match store_icon(&dir, &entry.icon_url) {
+            Ok(reference) => {
+                entry.icon_url = reference;
+                moved += 1;
+            }
+            Err(error) => debug_log(&format!(
+                "icon store: keeping inline icon app_id={app_id}: {error}"
+            )),
+        }
+    }
+    debug_log(&format!(
+        "icon store: {total} bytes of inline icons over cap {ICON_MEMORY_CAP_BYTES}; moved {moved} icon(s) to disk"
+    ));
+    moved > 0
+}
+
+fn icon_store_dir(app: &AppHandle) -> Option<PathBuf> {
+    let base_dir = app
+        .path()
+        .app_cache_dir()
+        .or_else(|_| app.path().app_config_dir())
+        .ok()?;
+    let dir = base_dir.join("app-icons");
+    fs::create_dir_all(&dir).ok()?;
+    Some(dir)
+}
+
+/// Writes the icon under a content hash, so identical icons share a file and
+/// profiles on different servers never collide on app ids.
+fn store_icon(dir: &std::path::Path, data_url: &str) -> Result<String, String> {
+    let content_type = data_url
+        .trim_start()
+        .trim_start_matches("data:")
+        .split([';', ','])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let extension = extension_for(&content_type)
+        .ok_or_else(|| format!("unsupported icon type {content_type}"))?;
+    let bytes = decode_data_url_bytes(data_url, APP_ICON_MAX_BYTES)?;
+    let digest = Sha256::digest(&bytes);
+    let hex: String = digest[..12]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let file_name = format!("{hex}.{extension}");
+    let path = dir.join(&file_name);
+    if !path.exists() {
+        let tmp_path = path.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
+        fs::write(&tmp_path, &bytes).map_err(|error| error.to_string())?;
+        crate::restrict_file_permissions(&tmp_path);
+        fs::rename(&tmp_path, &path).map_err(|error| error.to_string())?;
+    }
+    Ok(icon_ref_url(&file_name))
+}
+
+fn extension_for(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "image/png" => Some("png"),
+        "image/jpeg" | "image/jpg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        "image/svg+xml" => Some("svg"),
+        "image/x-icon" | "image/vnd.microsoft.icon" => Some("ico"),
+        "image/bmp" => Some("bmp"),
+        _ => None,
+    }
+}
+
+fn content_type_for(extension: &str) -> Option<&'static str> {
+    match extension {
+        "png" => Some("image/png"),
+        "jpg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "svg" => Some("image/svg+xml"),
+        "ico" => Some("image/x-icon"),
+        "bmp" => Some("image/bmp"),
+        _ => None,
+    }
+}
+
+// Windows webviews expose custom schemes as http://<scheme>.localhost.
+fn icon_ref_prefix() -> String {
+    if cfg!(windows) {
+        format!("http://{ICON_URI_SCHEME}.localhost/")
+    } else {
+        format!("{ICON_URI_SCHEME}://localhost/")
+    }
+}
+
+fn icon_ref_url(file_name: &str) -> String {
+    format!("{}{file_name}", icon_ref_prefix())
+}
+
+pub(crate) fn is_icon_ref(icon_url: &str) -> bool {
+    icon_url.starts_with(&icon_ref_prefix())
+}
+
+/// Resolves an icon reference to a file in the store. Only `<hex>.<ext>` names
+/// are accepted, so a crafted URL cannot reach outside the directory.
+fn icon_path(app: &AppHandle, file_name: &str) -> Option<(PathBuf, &'static str)> {
+    let (stem, extension) = file_name.split_once('.')?;
+    if stem.is_empty() || !stem.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let content_type = content_type_for(extension)?;
+    Some((icon_store_dir(app)?.join(file_name), content_type))
+}
+
+/// Icon bytes behind a reference, for the notification icon cache.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn read_icon_ref(app: &AppHandle, icon_url: &str) -> Option<Vec<u8>> {
+    let file_name = icon_url.strip_prefix(&icon_ref_prefix())?;
+    let (path, _) = icon_path(app, file_name)?;
+    fs::read(path).ok()
+}
+
+/// Handler for the icon URI scheme registered on the builder.
+pub(crate) fn serve(app: &AppHandle, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let file_name = request.uri().path().trim_start_matches('/');
+    let found = icon_path(app, file_name)
+        .and_then(|(path, content_type)| fs::read(path).ok().map(|bytes| (bytes, content_type)));
+    match found {
+        Some((bytes, content_type)) => Response::builder()
+            .header(CONTENT_TYPE, content_type)
+            .body(bytes),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Vec::new()),
+    }
+    .unwrap_or_else(|_| Response::new(Vec::new()))
+}
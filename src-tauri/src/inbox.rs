@@ -0,0 +1,188 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    contract::now_ms, unix_now_secs, AppState, CachedMessage, CLEANUP_CACHE_FILL_PERCENT,
+    CLEANUP_MIN_CANDIDATES, CLEANUP_READ_AGE_DAYS, CLEANUP_SUGGEST_INTERVAL_SECS,
+};
+
+/// What the last evaluation saw, so advisories fire on transitions rather than on
+/// every cache change.
+#[derive(Debug, Default)]
+pub(crate) struct InboxAdvisoryState {
+    last_unread_count: Option<usize>,
+    last_cleanup_candidates: usize,
+    last_cleanup_suggested_at: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct UnreadCountChangedEvent {
+    pub(crate) at_ms: u64,
+    pub(crate) unread_count: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct InboxZeroEvent {
+    pub(crate) at_ms: u64,
+    pub(crate) cached_messages: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CleanupSuggestedEvent {
+    pub(crate) at_ms: u64,
+    pub(crate) candidate_count: usize,
+    /// Read, unprotected messages older than `older_than_days`, newest first.
+    pub(crate) candidate_ids: Vec<i64>,
+    pub(crate) oldest_candidate_date: Option<String>,
+    pub(crate) older_than_days: u64,
+    pub(crate) cached_messages: usize,
+    pub(crate) cache_limit: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct InboxAnalysis {
+    pub(crate) unread_count: usize,
+    pub(crate) cleanup_candidates: Vec<i64>,
+    pub(crate) oldest_candidate_date: Option<String>,
+}
+
+/// Counts unread messages and collects cleanup candidates. Starred and archived
+/// messages are never candidates; undated ones are skipped rather than guessed.
+pub(crate) fn analyze(messages: &[CachedMessage], now_secs: u64) -> InboxAnalysis {
+    let cutoff = now_secs.saturating_sub(CLEANUP_READ_AGE_DAYS * 24 * 60 * 60);
+    let mut analysis = InboxAnalysis {
+        unread_count: 0,
+        cleanup_candidates: Vec::new(),
+        oldest_candidate_date: None,
+    };
+    for message in messages {
+        if !message.read {
+            if !message.is_snoozed(now_secs) {
+                analysis.unread_count += 1;
+            }
+            continue;
+        }
+        if message.is_protected() {
+            continue;
+        }
+        let Ok(date) = chrono::DateTime::parse_from_rfc3339(&message.date) else {
+            continue;
+        };
+        if u64::try_from(date.timestamp()).is_ok_and(|secs| secs < cutoff) {
+            analysis.cleanup_candidates.push(message.id);
+            analysis.oldest_candidate_date = Some(message.date.clone());
+        }
+    }
+    analysis
+}
+
+/// Emits `inbox-zero` when the unread count drops to zero and `cleanup-suggested`
+/// when enough old read messages pile up. Both are advisory; nothing is deleted.
+pub(crate) fn evaluate(app: &AppHandle, messages: &[CachedMessage]) {
+    let analysis = analyze(messages, unix_now_secs());
+    let cache_limit = crate::desired_cache_limit(app);
+    let candidate_count = analysis.cleanup_candidates.len();
+    let now_secs = unix_now_secs();
+
+    let (previous_unread, reached_zero, suggest_cleanup) = {
+        let state = app.state::<AppState>();
+        let Ok(mut advisory) = state.inbox_advisory.lock() else {
+            return;
+        };
+        let previous_unread = advisory.last_unread_count.replace(analysis.unread_count);
+        let reached_zero =
+            previous_unread.is_some_and(|count| count > 0) && analysis.unread_count == 0;
+
+        let crowded = messages.len() * 100 >= cache_limit * CLEANUP_CACHE_FILL_PERCENT;
+        let worth_it =
+            candidate_count >= CLEANUP_MIN_CANDIDATES || (crowded && candidate_count > 0);
+        let suggest_cleanup = worth_it
+            && candidate_count != advisory.last_cleanup_candidates
+            && now_secs.saturating_sub(advisory.last_cleanup_suggested_at)
+                >= CLEANUP_SUGGEST_INTERVAL_SECS;
+        if suggest_cleanup {
+            advisory.last_cleanup_suggested_at = now_secs;
+        }
+        if suggest_cleanup || candidate_count == 0 {
+            advisory.last_cleanup_candidates = candidate_count;
+        }
+        (previous_unread, reached_zero, suggest_cleanup)
+    };
+
+    if previous_unread != Some(analysis.unread_count) {
+        publish_unread_count(app, analysis.unread_count);
+    }
+
+    if reached_zero {
+        crate::contract::emit_event(
+            app,
+            "main",
+            "inbox-zero",
+            InboxZeroEvent {
+                at_ms: now_ms(),
+                cached_messages: messages.len(),
+            },
+        );
+    }
+    if suggest_cleanup {
+        crate::contract::emit_event(
+            app,
+            "main",
+            "cleanup-suggested",
+            CleanupSuggestedEvent {
+                at_ms: now_ms(),
+                candidate_count,
+                candidate_ids: analysis.cleanup_candidates,
+                oldest_candidate_date: analysis.oldest_candidate_date,
+                older_than_days: CLEANUP_READ_AGE_DAYS,
+                cached_messages: messages.len(),
+                cache_limit,
+            },
+        );
+    }
+}
+
+/// Unread messages in the cache right now, leaving out snoozed ones.
+pub(crate) fn unread_count(app: &AppHandle) -> Result<usize, String> {
+    let state = app.state::<AppState>();
+    let messages = state
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?;
+    let now_secs = unix_now_secs();
+    Ok(messages
+        .iter()
+        .filter(|message| !message.read && !message.is_snoozed(now_secs))
+        .count())
+}
+
+/// Recounts after the cache was replaced without going through `evaluate`
+/// (startup, profile switch), so the badge never shows another server's count.
+pub(crate) fn sync_unread_count(app: &AppHandle) {
+    let Ok(unread_count) = unread_count(app) else {
+        return;
+    };
+    if let Ok(mut advisory) = app.state::<AppState>().inbox_advisory.lock() {
+        advisory.last_unread_count = Some(unread_count);
+    }
+    publish_unread_count(app, unread_count);
+}
+
+/// Emits `unread-count-changed` to both windows and mirrors the count on the
+/// dock badge and tray tooltip.
+fn publish_unread_count(app: &AppHandle, unread_count: usize) {
+    let payload = UnreadCountChangedEvent {
+        at_ms: now_ms(),
+        unread_count,
+    };
+    crate::contract::emit_event(app, "main", "unread-count-changed", payload.clone());
+    crate::contract::emit_event(app, "quick", "unread-count-changed", payload);
+    #[cfg(target_os = "macos")]
+    if let Some(window) = app.get_webview_window("main") {
+        let badge = (unread_count > 0).then(|| i64::try_from(unread_count).unwrap_or(i64::MAX));
+        if let Err(error) = window.set_badge_count(badge) {
+            crate::debug_log(&format!("failed to set dock badge: {error}"));
+        }
+    }
+    crate::profiles::sync_tray_tooltip(app);
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    contract::{self, DomainSnapshot},
+    messages::persist_messages_snapshot,
+    AppState, CachedMessage, RevisionKey, MAX_MESSAGE_LABELS, MAX_MESSAGE_LABEL_CHARS,
+};
+
+#[derive(Debug, Serialize)]
+pub(crate) struct LabelUsage {
+    pub(crate) label: String,
+    pub(crate) count: usize,
+}
+
+/// Trims labels, drops empty ones and case-insensitive duplicates (the first
+/// spelling wins) and sorts them, so equal sets compare equal.
+pub(crate) fn normalize_labels(labels: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for label in labels {
+        let label = label.trim();
+        if label.is_empty() {
+            continue;
+        }
+        if label.chars().count() > MAX_MESSAGE_LABEL_CHARS {
+            return Err(format!(
+                "Label {label:?} is longer than {MAX_MESSAGE_LABEL_CHARS} characters"
+            ));
+        }
+        if normalized
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(label))
+        {
+            continue;
+        }
+        normalized.push(label.to_string());
+    }
+    if normalized.len() > MAX_MESSAGE_LABELS {
+        return Err(format!(
+            "A message can have at most {MAX_MESSAGE_LABELS} labels"
+        ));
+    }
+    normalized.sort_by_key(|label| label.to_lowercase());
+    Ok(normalized)
+}
+
+/// Replaces the local labels of one cached message; an empty list clears them.
+pub(crate) fn set_message_labels(
+    app: &AppHandle,
+    message_id: i64,
+    labels: Vec<String>,
+) -> Result<DomainSnapshot<Vec<CachedMessage>>, String> {
+    let labels = normalize_labels(labels)?;
+    let app_state = app.state::<AppState>();
+    let updated_snapshot = {
+        let mut messages_guard = app_state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        let message = messages_guard
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| format!("Message {message_id} is not cached"))?;
+        if message.labels == labels {
+            let revision = contract::current_revision(app, RevisionKey::Messages);
+            return Ok(contract::snapshot_at_revision(
+                revision,
+                messages_guard.clone(),
+            ));
+        }
+        message.labels = labels;
+        messages_guard.clone()
+    };
+
+    persist_messages_snapshot(app, &updated_snapshot)?;
+    Ok(contract::publish_messages_replace(app, updated_snapshot))
+}
+
+/// Labels in use across the cache with how many messages carry each, sorted
+/// case-insensitively.
+pub(crate) fn list_message_labels(app: &AppHandle) -> Result<Vec<LabelUsage>, String> {
+    let state = app.state::<AppState>();
+    let messages = state
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?;
+    let mut usage: BTreeMap<String, LabelUsage> = BTreeMap::new();
+    for label in messages.iter().flat_map(|message| &message.labels) {
+        usage
+            .entry(label.to_lowercase())
+            .or_insert_with(|| LabelUsage {
+                label: label.clone(),
+                count: 0,
+            })
+            .count += 1;
+    }
+    Ok(usage.into_values().collect())
+}
+
+pub(crate) fn has_any_label(message: &CachedMessage, labels: &[String]) -> bool {
+    labels.is_empty()
+        || message.labels.iter().any(|label| {
+            labels
+                .iter()
+                .any(|wanted| wanted.trim().eq_ignore_ascii_case(label))
+        })
+}
@@ -0,0 +1,14 @@
+/// ISO 639-3 code (`eng`, `deu`, `cmn`) of a message's language, detected by
+/// `whatlang`. Returns `None` when the text is too short or the detection is not
+/// reliable.
+pub(crate) fn detect_language(title: &str, body: &str) -> Option<String> {
+    let text = format!("{title}\n{body}");
+    whatlang::detect(&text)
+        .filter(whatlang::Info::is_reliable)
+        .map(|info| info.lang().code().to_string())
+}
+
+/// Caches written before detection moved to `whatlang` hold two-letter codes.
+pub(crate) fn is_current_code(code: &str) -> bool {
+    code.len() == 3
+}
@@ -0,0 +1,47 @@
+use std::sync::mpsc;
+
+use block2::RcBlock;
+use objc2::{
+    msg_send,
+    rc::Retained,
+    runtime::{AnyClass, AnyObject, Bool},
+};
+use objc2_foundation::{NSError, NSString};
+
+/// `LAPolicyDeviceOwnerAuthentication`: Touch ID or a paired Watch, with the
+/// login password as fallback, so Macs without biometrics can still pass.
+const LA_POLICY_DEVICE_OWNER_AUTHENTICATION: isize = 2;
+
+#[link(name = "LocalAuthentication", kind = "framework")]
+extern "C" {}
+
+/// Asks the user to authenticate with the system sheet and blocks until they
+/// answer. `reason` completes the sheet's "Gotify is trying to …" line.
+pub(crate) fn authenticate(reason: &str) -> Result<(), String> {
+    let class = AnyClass::get(c"LAContext")
+        .ok_or_else(|| "Local authentication is not available".to_string())?;
+    let context: Retained<AnyObject> = unsafe { msg_send![class, new] };
+    let (sender, receiver) = mpsc::channel();
+    let reply = RcBlock::new(move |success: Bool, error: *mut NSError| {
+        let result = if success.as_bool() {
+            Ok(())
+        } else {
+            Err(unsafe { error.as_ref() }
+                .map(|error| error.localizedDescription().to_string())
+                .unwrap_or_else(|| "Authentication failed".to_string()))
+        };
+        let _ = sender.send(result);
+    });
+    let reason = NSString::from_str(reason);
+    let _: () = unsafe {
+        msg_send![
+            &*context,
+            evaluatePolicy: LA_POLICY_DEVICE_OWNER_AUTHENTICATION,
+            localizedReason: &*reason,
+            reply: &*reply
+        ]
+    };
+    receiver
+        .recv()
+        .map_err(|_| "Authentication did not complete".to_string())?
+}
@@ -1,6 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::{
+    collections::BTreeMap,
     fs,
     path::PathBuf,
     process::Command,
@@ -11,33 +12,98 @@ use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{AppHandle, Manager, Webview, WebviewUrl};
 
+mod account;
+mod ack;
+mod android_actions;
+mod android_import;
+mod app_tokens;
+mod applications;
+mod archive;
+mod at_rest;
+mod auth;
+mod background_streams;
+#[cfg(target_os = "macos")]
+mod big_image;
+mod bulk;
+mod client_token;
+mod compaction;
+mod connection_history;
 mod consts;
+mod contexts;
 mod contract;
 mod diagnostics;
+mod dial;
+mod dismiss;
+mod endpoints;
+mod faults;
 use diagnostics::RuntimeDiagnostics;
 mod core;
+mod health;
+mod heartbeat;
+mod history;
+#[cfg(target_os = "macos")]
+mod icns;
+mod icon_store;
+mod inbox;
+mod labels;
+mod language;
+#[cfg(target_os = "macos")]
+mod local_auth;
+mod markdown;
+mod message_store;
 mod messages;
+mod metrics;
 mod model;
+#[cfg(target_os = "macos")]
+mod network_watch;
+mod notification_queue;
 mod notifications;
+mod op_trace;
+mod operations;
 mod pause;
+mod pause_schedule;
+mod plugins;
+#[cfg(target_os = "macos")]
+mod power_watch;
 mod preview;
+mod privacy;
+mod profiles;
+mod proxy;
+mod quit;
+mod redaction;
+mod retention;
+mod retry;
+mod search;
+mod send;
+mod server_info;
 mod settings;
+mod settings_bundle;
+mod snooze;
+mod sounds;
 mod stream;
+mod tls;
+mod trash;
 mod ui_shell;
+#[cfg(target_os = "macos")]
+mod user_notifications;
+mod watchdog;
+mod work;
 pub(crate) use consts::*;
 pub(crate) use core::{
-    debug_log, decode_data_url_bytes, emit_delete_debug, get_settings_path, messages_file,
+    debug_log, decode_data_url_bytes, get_settings_path, messages_file, profile_messages_file,
     redact_ws_url, restrict_file_permissions, settings_file, truncate_message, unique_time_suffix,
     unix_now_secs,
 };
 pub(crate) use model::{
     AppState, ApplicationMeta, CachedMessage, GotifyApplicationWire, GotifyMessageListWire,
-    GotifyMessageWire, RevisionKey, TrayPauseMenuState, UrlPreview,
+    GotifyMessageWire, IconValidators, RevisionKey, StreamSlot, TrayPauseMenuState, UrlPreview,
 };
+use op_trace::TraceOp;
 use settings::{
-    load_settings as load_settings_impl, load_token, normalize_base_url, read_settings,
+    load_settings as load_settings_impl, load_token, normalize_ca_cert_path, read_settings,
     save_settings as save_settings_impl, test_connection as test_connection_impl,
-    PriorityColorMode, PriorityGradient, PriorityThreshold, SettingsResponse,
+    IpFamilyPreference, PauseSchedule, PriorityColorMode, PriorityGradient, PriorityThreshold,
+    QuietHoursBand, SettingsResponse,
 };
 
 /// Resolved at startup; must be set before any `load_settings` / `save_settings` call.
@@ -65,6 +131,22 @@ fn save_settings(
     show_priority_in_notifications: Option<bool>,
     quiet_hours_start: Option<u8>,
     quiet_hours_end: Option<u8>,
+    heartbeat_url: Option<String>,
+    ca_cert_path: Option<String>,
+    quiet_hours_bands: Option<Vec<QuietHoursBand>>,
+    backoff_reset_after_secs: Option<u64>,
+    custom_headers: Option<BTreeMap<String, String>>,
+    pause_schedules: Option<Vec<PauseSchedule>>,
+    ip_family_preference: Option<IpFamilyPreference>,
+    host_pins: Option<BTreeMap<String, String>>,
+    op_trace_enabled: Option<bool>,
+    proxy_url: Option<String>,
+    proxy_username: Option<String>,
+    proxy_password: Option<String>,
+    proxy_bypass: Option<Vec<String>>,
+    privacy_mode: Option<bool>,
+    redaction_patterns: Option<Vec<String>>,
+    trash_retention_days: Option<u64>,
 ) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
     let settings = save_settings_impl(
         &app,
@@ -80,13 +162,58 @@ fn save_settings(
         show_priority_in_notifications,
         quiet_hours_start,
         quiet_hours_end,
+        heartbeat_url,
+        ca_cert_path,
+        quiet_hours_bands,
+        backoff_reset_after_secs,
+        custom_headers,
+        pause_schedules,
+        ip_family_preference,
+        host_pins,
+        op_trace_enabled,
+        proxy_url,
+        proxy_username,
+        proxy_password,
+        proxy_bypass,
+        privacy_mode,
+        redaction_patterns,
+        trash_retention_days,
     )?;
+    privacy::sync_tray_item(&app, settings.privacy_mode);
     Ok(contract::publish_settings_update(&app, settings))
 }
 
 #[tauri::command]
-async fn test_connection(base_url: String, token: Option<String>) -> Result<String, String> {
-    test_connection_impl(base_url, token).await
+async fn test_connection(
+    app: AppHandle,
+    base_url: String,
+    token: Option<String>,
+    ca_cert_path: Option<String>,
+) -> Result<String, String> {
+    let ca_cert_path = match ca_cert_path {
+        Some(path) => normalize_ca_cert_path(&path)?,
+        None => tls::configured_ca_cert_path(&app),
+    };
+    let custom_headers = settings::configured_custom_headers(&app);
+    let host_pins = settings::configured_host_pins(&app);
+    let trusted_certificates = tls::configured_trusted_certificates(&app);
+    let proxy = proxy::configured_proxy(&app);
+    let timeout = Duration::from_secs(settings::configured_request_timeouts(&app).request_secs);
+    let result = test_connection_impl(
+        base_url,
+        token,
+        ca_cert_path,
+        trusted_certificates,
+        custom_headers,
+        host_pins,
+        proxy,
+        timeout,
+    )
+    .await;
+    if result.is_err() {
+        tls::announce_untrusted_certificates(&app);
+    }
+    result
 }
 
 #[tauri::command]
@@ -138,36 +265,87 @@ async fn delete_message(
         return Err("Invalid message id".to_string());
     }
     debug_log(&format!("delete_message requested id={message_id}"));
-    emit_delete_debug(&app, message_id, "start", "delete requested", None);
+    delete_message_by_id(&app, message_id, true).await?;
 
+    let snapshot_messages = app
+        .state::<AppState>()
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?
+        .clone();
+    let revision = contract::current_revision(&app, RevisionKey::Messages);
+    Ok(contract::snapshot_at_revision(revision, snapshot_messages))
+}
+
+/// Deletes one message: into the trash when it is on and the message is cached,
+/// otherwise on the server. `refresh` resyncs afterwards; bulk callers pass
+/// `false` and sync once at the end.
+pub(crate) async fn delete_message_by_id(
+    app: &AppHandle,
+    message_id: i64,
+    refresh: bool,
+) -> Result<(), String> {
+    op_trace::emit(
+        app,
+        TraceOp::Delete,
+        message_id,
+        "start",
+        "delete requested",
+        None,
+    );
+    if trash::retention_days(app) > 0 && trash::move_to_trash(app, message_id)? {
+        return Ok(());
+    }
+    if messages::is_tombstoned(app, message_id) && !messages::is_cached(app, message_id) {
+        op_trace::emit(
+            app,
+            TraceOp::Delete,
+            message_id,
+            "tombstone",
+            "already deleted locally; skipping server request",
+            None,
+        );
+        return Ok(());
+    }
+    messages::add_tombstone(app, message_id);
+    if let Err(error) = delete_message_on_server(app.clone(), message_id, refresh).await {
+        messages::clear_tombstone(app, message_id);
+        return Err(error);
+    }
+    Ok(())
+}
+
+async fn delete_message_on_server(
+    app: AppHandle,
+    message_id: i64,
+    refresh: bool,
+) -> Result<(), String> {
     let settings = read_settings(&app)?;
-    let base_url = normalize_base_url(&settings.base_url)?;
+    let base_url = endpoints::active_base_url(&app, &settings)?;
     let token =
         load_token()?.ok_or_else(|| "No token found. Save token in settings first.".to_string())?;
 
     let endpoint = format!("{base_url}/message/{message_id}");
     let url =
         reqwest::Url::parse(&endpoint).map_err(|error| format!("Invalid delete URL: {error}"))?;
-    emit_delete_debug(
+    op_trace::emit(
         &app,
+        TraceOp::Delete,
         message_id,
         "request",
         &format!("DELETE {} auth=X-Gotify-Key", url),
         None,
     );
 
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(15))
+    let client = settings::server_client_builder(&app)?
         .build()
         .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
-    let response = client
-        .delete(url)
-        .header("X-Gotify-Key", &token)
-        .send()
+    let response = retry::send(client.delete(url).header("X-Gotify-Key", &token))
         .await
         .map_err(|error| {
-            emit_delete_debug(
+            op_trace::emit(
                 &app,
+                TraceOp::Delete,
                 message_id,
                 "network-error",
                 &format!("request failed: {error}"),
@@ -185,8 +363,9 @@ async fn delete_message(
             .text()
             .await
             .unwrap_or_else(|_| "<unable to read response body>".to_string());
-        emit_delete_debug(
+        op_trace::emit(
             &app,
+            TraceOp::Delete,
             message_id,
             "http-error",
             &format!("HTTP {status}: {}", truncate_message(&body, 500)),
@@ -197,8 +376,9 @@ async fn delete_message(
             truncate_message(&body, 200)
         ));
     }
-    emit_delete_debug(
+    op_trace::emit(
         &app,
+        TraceOp::Delete,
         message_id,
         "http-ok",
         &format!("HTTP {status}"),
@@ -206,33 +386,43 @@ async fn delete_message(
     );
 
     messages::remove_message_from_cache(&app, message_id)?;
-    emit_delete_debug(
+    op_trace::emit(
         &app,
+        TraceOp::Delete,
         message_id,
         "cache",
         "removed from local cache",
         Some(status),
     );
+    if !refresh {
+        return Ok(());
+    }
 
     let app_for_sync = app.clone();
     let base_for_sync = base_url.clone();
     tauri::async_runtime::spawn(async move {
         if let Ok(token) = load_token() {
             if let Some(token_value) = token {
-                if let Err(error) =
-                    messages::fetch_recent_messages(&app_for_sync, &base_for_sync, &token_value)
-                        .await
+                if let Err(error) = messages::fetch_recent_messages(
+                    &app_for_sync,
+                    &base_for_sync,
+                    &token_value,
+                    messages::SyncMode::Periodic,
+                )
+                .await
                 {
-                    emit_delete_debug(
+                    op_trace::emit(
                         &app_for_sync,
+                        TraceOp::Delete,
                         message_id,
                         "post-sync-error",
                         &format!("refresh failed: {error}"),
                         None,
                     );
                 } else {
-                    emit_delete_debug(
+                    op_trace::emit(
                         &app_for_sync,
+                        TraceOp::Delete,
                         message_id,
                         "post-sync-ok",
                         "refresh completed",
@@ -242,20 +432,83 @@ async fn delete_message(
             }
         }
     });
-    let snapshot_messages = app
-        .state::<AppState>()
-        .messages
-        .lock()
-        .map_err(|_| "Message cache lock poisoned".to_string())?
-        .clone();
-    let revision = contract::current_revision(&app, RevisionKey::Messages);
-    Ok(contract::snapshot_at_revision(revision, snapshot_messages))
+    Ok(())
+}
+
+#[tauri::command]
+fn mark_messages_read(
+    app: AppHandle,
+    message_ids: Option<Vec<i64>>,
+) -> Result<contract::DomainSnapshot<Vec<CachedMessage>>, String> {
+    messages::mark_messages_read(&app, message_ids)
+}
+
+#[tauri::command]
+fn mark_message_read(
+    app: AppHandle,
+    message_id: i64,
+) -> Result<contract::DomainSnapshot<Vec<CachedMessage>>, String> {
+    messages::mark_messages_read(&app, Some(vec![message_id]))
+}
+
+#[tauri::command]
+fn mark_all_read(app: AppHandle) -> Result<contract::DomainSnapshot<Vec<CachedMessage>>, String> {
+    messages::mark_messages_read(&app, None)
+}
+
+#[tauri::command]
+fn pin_message(
+    app: AppHandle,
+    message_id: i64,
+) -> Result<contract::DomainSnapshot<Vec<CachedMessage>>, String> {
+    messages::set_pinned(&app, message_id, true)
+}
+
+#[tauri::command]
+fn unpin_message(
+    app: AppHandle,
+    message_id: i64,
+) -> Result<contract::DomainSnapshot<Vec<CachedMessage>>, String> {
+    messages::set_pinned(&app, message_id, false)
+}
+
+#[tauri::command]
+fn set_message_labels(
+    app: AppHandle,
+    message_id: i64,
+    labels: Vec<String>,
+) -> Result<contract::DomainSnapshot<Vec<CachedMessage>>, String> {
+    labels::set_message_labels(&app, message_id, labels)
+}
+
+#[tauri::command]
+fn list_message_labels(app: AppHandle) -> Result<Vec<labels::LabelUsage>, String> {
+    labels::list_message_labels(&app)
+}
+
+#[tauri::command]
+fn dismiss_message(app: AppHandle, message_id: i64) -> Result<bool, String> {
+    dismiss::dismiss_message(&app, message_id)
+}
+
+#[tauri::command]
+fn snooze_message(
+    app: AppHandle,
+    message_id: i64,
+    minutes: u64,
+) -> Result<contract::DomainSnapshot<Vec<CachedMessage>>, String> {
+    snooze::snooze_message(&app, message_id, minutes)
+}
+
+#[tauri::command]
+fn get_unread_count(app: AppHandle) -> Result<usize, String> {
+    inbox::unread_count(&app)
 }
 
 #[tauri::command]
 fn bootstrap_state(app: AppHandle) -> Result<contract::BootstrapState, String> {
     let settings = load_settings_impl(&app)?;
-    let pause = pause::get_pause_state_data(&app)?;
+    let pause = pause::PauseController::new(&app).state()?;
     let messages = app
         .state::<AppState>()
         .messages
@@ -330,7 +583,7 @@ fn set_pause(
     app: AppHandle,
     input: pause::SetPauseInput,
 ) -> Result<contract::DomainSnapshot<contract::PauseStateData>, String> {
-    let pause_state = pause::set_pause(app.clone(), input)?;
+    let pause_state = pause::PauseController::new(&app).pause(input)?;
     Ok(contract::snapshot_at_revision(
         contract::current_revision(&app, RevisionKey::Pause),
         pause_state,
@@ -341,7 +594,7 @@ fn set_pause(
 fn resume_pause(
     app: AppHandle,
 ) -> Result<contract::DomainSnapshot<contract::PauseStateData>, String> {
-    let pause_state = pause::resume_pause(app.clone())?;
+    let pause_state = pause::PauseController::new(&app).resume()?;
     Ok(contract::snapshot_at_revision(
         contract::current_revision(&app, RevisionKey::Pause),
         pause_state,
@@ -349,8 +602,622 @@ fn resume_pause(
 }
 
 #[tauri::command]
-async fn fetch_url_preview(url: String) -> Result<UrlPreview, String> {
-    preview::fetch_url_preview(url).await
+async fn reveal_token() -> Result<String, String> {
+    settings::reveal_token().await
+}
+
+#[tauri::command]
+async fn rotate_client_token(
+    app: AppHandle,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = client_token::rotate_client_token(&app).await?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn trust_certificate(
+    app: AppHandle,
+    host: String,
+    fingerprint_sha256: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = tls::trust_certificate(&app, &host, &fingerprint_sha256)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn save_profile(
+    app: AppHandle,
+    name: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = profiles::save_profile(&app, &name)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn delete_profile(
+    app: AppHandle,
+    name: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = profiles::delete_profile(&app, &name)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_profile_badge(
+    app: AppHandle,
+    name: String,
+    badge: Option<String>,
+    color: Option<String>,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = profiles::set_profile_badge(&app, &name, badge, color)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_profile_background_stream(
+    app: AppHandle,
+    name: String,
+    enabled: bool,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = profiles::set_profile_background_stream(&app, &name, enabled)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn save_app_token(
+    app: AppHandle,
+    name: String,
+    token: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = app_tokens::save_app_token(&app, &name, &token)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn delete_app_token(
+    app: AppHandle,
+    name: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = app_tokens::delete_app_token(&app, &name)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+async fn switch_profile(
+    app: AppHandle,
+    name: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = profiles::switch_profile(&app, &name).await?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+async fn compact_store(app: AppHandle) -> Result<compaction::CompactionReport, String> {
+    work::run_in_background_for("compact-store", move || compaction::compact_store(&app)).await?
+}
+
+#[tauri::command]
+async fn export_settings(app: AppHandle, path: String, passphrase: String) -> Result<(), String> {
+    settings::authenticate_owner(EXPORT_SETTINGS_REASON).await?;
+    work::run_in_background_for("export-settings", move || {
+        settings_bundle::export_settings(&app, &path, &passphrase)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn import_settings(
+    app: AppHandle,
+    path: String,
+    passphrase: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = settings_bundle::import_settings(&app, &path, &passphrase).await?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+async fn import_android_backup(
+    app: AppHandle,
+    path: String,
+    server_url: Option<String>,
+) -> Result<android_import::AndroidImportSummary, String> {
+    android_import::import_android_backup(&app, &path, server_url.as_deref()).await
+}
+
+#[tauri::command]
+fn get_event_schema_version() -> u32 {
+    EVENT_SCHEMA_VERSION
+}
+
+/// `enabled: None` flips the current setting.
+#[tauri::command]
+fn set_privacy_mode(
+    app: AppHandle,
+    enabled: Option<bool>,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = privacy::set_privacy_mode(&app, enabled)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+async fn upload_application_image(
+    app: AppHandle,
+    app_id: i64,
+    file_path: String,
+) -> Result<applications::ApplicationInfo, String> {
+    applications::upload_application_image(&app, app_id, &file_path).await
+}
+
+#[tauri::command]
+async fn list_applications(app: AppHandle) -> Result<Vec<applications::ApplicationInfo>, String> {
+    applications::list_applications(&app).await
+}
+
+#[tauri::command]
+async fn create_application(
+    app: AppHandle,
+    name: String,
+    description: Option<String>,
+    default_priority: Option<i64>,
+    save_token: Option<bool>,
+) -> Result<applications::ApplicationInfo, String> {
+    applications::create_application(
+        &app,
+        &name,
+        description.as_deref().unwrap_or(""),
+        default_priority,
+        save_token.unwrap_or(false),
+    )
+    .await
+}
+
+#[tauri::command]
+async fn update_application(
+    app: AppHandle,
+    app_id: i64,
+    name: String,
+    description: Option<String>,
+    default_priority: Option<i64>,
+) -> Result<applications::ApplicationInfo, String> {
+    applications::update_application(
+        &app,
+        app_id,
+        &name,
+        description.as_deref(),
+        default_priority,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn delete_application(app: AppHandle, app_id: i64) -> Result<(), String> {
+    applications::delete_application(&app, app_id).await
+}
+
+#[tauri::command]
+fn save_context(
+    app: AppHandle,
+    name: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = contexts::save_context(&app, &name)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn switch_context(
+    app: AppHandle,
+    name: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = contexts::switch_context(&app, &name)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn delete_context(
+    app: AppHandle,
+    name: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = contexts::delete_context(&app, &name)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_app_muted(
+    app: AppHandle,
+    app_id: i64,
+    muted: bool,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = contexts::set_app_muted(&app, app_id, muted)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_notification_sound(
+    app: AppHandle,
+    sound: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = contexts::set_notification_sound(&app, &sound)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_notification_sound_bands(
+    app: AppHandle,
+    bands: Vec<contexts::SoundBand>,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = contexts::set_notification_sound_bands(&app, bands)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+async fn import_sound(app: AppHandle, path: String) -> Result<String, String> {
+    let manifest = sounds::imported_sounds_file(&app)?;
+    work::run_in_background_for("import-sound", move || {
+        sounds::import_sound(&manifest, &path)
+    })
+    .await?
+}
+
+#[tauri::command]
+fn list_custom_sounds(app: AppHandle) -> Result<Vec<String>, String> {
+    sounds::list_custom_sounds(&sounds::imported_sounds_file(&app)?)
+}
+
+#[tauri::command]
+fn preview_sound(sound: String) -> Result<(), String> {
+    sounds::preview_sound(&sound)
+}
+
+#[tauri::command]
+fn set_connection_mode(
+    app: AppHandle,
+    mode: settings::ConnectionMode,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = stream::set_connection_mode(&app, mode)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_reconnect_backoff(
+    app: AppHandle,
+    backoff: settings::ReconnectBackoff,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = stream::set_reconnect_backoff(&app, backoff)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_request_timeouts(
+    app: AppHandle,
+    timeouts: settings::RequestTimeouts,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = settings::set_request_timeouts(&app, timeouts)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_request_retry(
+    app: AppHandle,
+    policy: retry::RetryPolicy,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = retry::set_request_retry(&app, policy)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_message_retention(
+    app: AppHandle,
+    retention: retention::MessageRetention,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = retention::set_message_retention(&app, retention)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_stream_token_in_query(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = stream::set_stream_token_in_query(&app, enabled)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn reconnect_now(app: AppHandle) -> Result<contract::DomainSnapshot<RuntimeDiagnostics>, String> {
+    stream::reconnect_now(&app);
+    let runtime = stream::get_runtime_diagnostics(app.clone())?;
+    Ok(contract::snapshot_at_revision(
+        contract::current_revision(&app, RevisionKey::Runtime),
+        runtime,
+    ))
+}
+
+#[tauri::command]
+fn set_android_action_rules(
+    app: AppHandle,
+    rules: Vec<android_actions::AndroidActionRule>,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = android_actions::set_android_action_rules(&app, rules)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+async fn load_older_messages(
+    app: AppHandle,
+    before_id: i64,
+    limit: Option<usize>,
+) -> Result<history::OlderMessagesPage, String> {
+    history::load_older_messages(&app, before_id, limit).await
+}
+
+#[tauri::command]
+async fn list_plugins(app: AppHandle) -> Result<Vec<plugins::PluginInfo>, String> {
+    plugins::list_plugins(&app).await
+}
+
+#[tauri::command]
+async fn enable_plugin(app: AppHandle, plugin_id: i64) -> Result<(), String> {
+    plugins::set_plugin_enabled(&app, plugin_id, true).await
+}
+
+#[tauri::command]
+async fn disable_plugin(app: AppHandle, plugin_id: i64) -> Result<(), String> {
+    plugins::set_plugin_enabled(&app, plugin_id, false).await
+}
+
+#[tauri::command]
+async fn get_plugin_display(app: AppHandle, plugin_id: i64) -> Result<String, String> {
+    plugins::get_plugin_display(&app, plugin_id).await
+}
+
+#[tauri::command]
+async fn get_plugin_config(app: AppHandle, plugin_id: i64) -> Result<String, String> {
+    plugins::get_plugin_config(&app, plugin_id).await
+}
+
+#[tauri::command]
+async fn update_plugin_config(
+    app: AppHandle,
+    plugin_id: i64,
+    config: String,
+) -> Result<(), String> {
+    plugins::update_plugin_config(&app, plugin_id, config).await
+}
+
+#[tauri::command]
+async fn get_current_user(app: AppHandle) -> Result<account::CurrentUser, String> {
+    account::get_current_user(&app).await
+}
+
+#[tauri::command]
+async fn change_password(app: AppHandle, password: String) -> Result<(), String> {
+    account::change_password(&app, &password).await
+}
+
+#[tauri::command]
+async fn send_message(
+    app: AppHandle,
+    app_token: String,
+    title: Option<String>,
+    message: String,
+    priority: Option<i64>,
+    extras: Option<serde_json::Map<String, serde_json::Value>>,
+) -> Result<send::MessageSentEvent, String> {
+    send::send_message(&app, &app_token, title, &message, priority, extras).await
+}
+
+#[tauri::command]
+fn set_lan_base_url(
+    app: AppHandle,
+    url: Option<String>,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = endpoints::set_lan_base_url(&app, url)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_health_check_interval(
+    app: AppHandle,
+    secs: u64,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = health::set_health_check_interval(&app, secs)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn set_acknowledgement(
+    app: AppHandle,
+    config: ack::AckSettings,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = ack::set_acknowledgement(&app, config)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+async fn acknowledge_message(
+    app: AppHandle,
+    message_id: i64,
+) -> Result<send::MessageSentEvent, String> {
+    ack::acknowledge(&app, message_id).await
+}
+
+#[tauri::command]
+fn set_fault_injection(
+    app: AppHandle,
+    config: faults::FaultInjection,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = faults::set_fault_injection(&app, config)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn untrust_certificate(
+    app: AppHandle,
+    host: String,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = tls::untrust_certificate(&app, &host)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+fn get_pending_notifications(
+    app: AppHandle,
+) -> Result<contract::DomainSnapshot<Vec<notification_queue::PendingNotification>>, String> {
+    let pending = notification_queue::list(&app)?;
+    Ok(contract::snapshot_at_revision(
+        contract::current_revision(&app, RevisionKey::PendingNotifications),
+        pending,
+    ))
+}
+
+#[tauri::command]
+fn get_trash(
+    app: AppHandle,
+) -> Result<contract::DomainSnapshot<Vec<trash::TrashedMessage>>, String> {
+    let trash = trash::list(&app)?;
+    Ok(contract::snapshot_at_revision(
+        contract::current_revision(&app, RevisionKey::Trash),
+        trash,
+    ))
+}
+
+#[tauri::command]
+fn restore_from_trash(
+    app: AppHandle,
+    message_id: i64,
+) -> Result<contract::DomainSnapshot<Vec<trash::TrashedMessage>>, String> {
+    trash::restore(&app, message_id)
+}
+
+#[tauri::command]
+async fn empty_trash(
+    app: AppHandle,
+) -> Result<contract::DomainSnapshot<Vec<trash::TrashedMessage>>, String> {
+    trash::empty(&app).await
+}
+
+#[tauri::command]
+fn delete_messages(app: AppHandle, message_ids: Vec<i64>) -> Result<u64, String> {
+    bulk::start_delete_messages(&app, message_ids)
+}
+
+#[tauri::command]
+fn delete_app_messages(app: AppHandle, app_id: i64) -> Result<u64, String> {
+    bulk::start_delete_app_messages(&app, app_id)
+}
+
+#[tauri::command]
+fn export_messages(app: AppHandle, path: String, app_id: Option<i64>) -> Result<u64, String> {
+    bulk::start_export_messages(&app, &path, app_id)
+}
+
+#[tauri::command]
+async fn import_messages(
+    app: AppHandle,
+    path: String,
+) -> Result<bulk::MessageImportSummary, String> {
+    bulk::import_messages(&app, &path).await
+}
+
+#[tauri::command]
+fn set_archive_remote_deletions(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<contract::DomainSnapshot<SettingsResponse>, String> {
+    let settings = archive::set_archive_remote_deletions(&app, enabled)?;
+    Ok(contract::publish_settings_update(&app, settings))
+}
+
+#[tauri::command]
+async fn load_archived_messages(
+    app: AppHandle,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<archive::ArchivedMessagePage, String> {
+    archive::load_archived_messages(&app, offset, limit).await
+}
+
+#[tauri::command]
+fn search_messages(
+    app: AppHandle,
+    query: String,
+    app_id: Option<i64>,
+    min_priority: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<CachedMessage>, String> {
+    search::search_messages(&app, &query, app_id, min_priority, limit)
+}
+
+#[tauri::command]
+fn query_messages(
+    app: AppHandle,
+    query: search::MessageQuery,
+) -> Result<search::MessagePage, String> {
+    search::query_messages(&app, &query)
+}
+
+#[tauri::command]
+fn cancel_operation(app: AppHandle, op_id: u64) -> bool {
+    operations::cancel(&app, op_id)
+}
+
+#[tauri::command]
+fn cancel_operations(app: AppHandle, group: String) -> usize {
+    operations::cancel_group(&app, &group)
+}
+
+#[tauri::command]
+fn cancel_pending_notification(
+    app: AppHandle,
+    entry_id: u64,
+) -> Result<contract::DomainSnapshot<Vec<notification_queue::PendingNotification>>, String> {
+    notification_queue::cancel(&app, entry_id)
+}
+
+#[tauri::command]
+fn get_memory_stats(app: AppHandle) -> Result<icon_store::MemoryStats, String> {
+    icon_store::memory_stats(&app)
+}
+
+#[tauri::command]
+async fn get_server_info(app: AppHandle) -> Result<server_info::ServerInfo, String> {
+    server_info::get_server_info(&app).await
+}
+
+#[tauri::command]
+fn get_connection_history(app: AppHandle) -> Vec<connection_history::ConnectionEvent> {
+    connection_history::recent(&app, CONNECTION_HISTORY_MAX_EVENTS)
+}
+
+#[tauri::command]
+async fn get_delivery_metrics(app: AppHandle) -> Result<metrics::DeliveryMetrics, String> {
+    work::run_in_background_for("delivery-metrics", move || metrics::delivery_metrics(&app)).await?
+}
+
+#[tauri::command]
+async fn fetch_url_preview(
+    app: AppHandle,
+    url: String,
+    group: Option<String>,
+) -> Result<UrlPreview, String> {
+    op_trace::emit(&app, TraceOp::Preview, &url, "start", "fetching", None);
+    let proxy = proxy::configured_proxy(&app);
+    let handle = operations::CancelHandle::register(&app, "preview", group.as_deref());
+    let result = handle
+        .run(preview::fetch_url_preview(url.clone(), proxy))
+        .await;
+    match &result {
+        Ok(preview) => op_trace::emit(
+            &app,
+            TraceOp::Preview,
+            &url,
+            "ok",
+            &format!("title={:?}", preview.title),
+            None,
+        ),
+        Err(error) => op_trace::emit(&app, TraceOp::Preview, &url, "error", error, None),
+    }
+    result
 }
 
 fn cached_message_cmp(a: &CachedMessage, b: &CachedMessage) -> std::cmp::Ordering {
@@ -470,8 +1337,94 @@ fn main() {
             restart_stream,
             set_pause,
             resume_pause,
-            fetch_url_preview
+            fetch_url_preview,
+            get_delivery_metrics,
+            get_connection_history,
+            get_pending_notifications,
+            cancel_pending_notification,
+            rotate_client_token,
+            reveal_token,
+            trust_certificate,
+            untrust_certificate,
+            save_profile,
+            delete_profile,
+            switch_profile,
+            mark_messages_read,
+            mark_message_read,
+            mark_all_read,
+            pin_message,
+            unpin_message,
+            snooze_message,
+            dismiss_message,
+            set_message_labels,
+            list_message_labels,
+            get_unread_count,
+            set_privacy_mode,
+            get_event_schema_version,
+            compact_store,
+            export_settings,
+            import_settings,
+            get_trash,
+            restore_from_trash,
+            empty_trash,
+            delete_messages,
+            delete_app_messages,
+            export_messages,
+            import_messages,
+            set_archive_remote_deletions,
+            load_archived_messages,
+            search_messages,
+            query_messages,
+            cancel_operation,
+            cancel_operations,
+            save_app_token,
+            delete_app_token,
+            set_fault_injection,
+            send_message,
+            list_applications,
+            create_application,
+            update_application,
+            delete_application,
+            get_memory_stats,
+            set_acknowledgement,
+            acknowledge_message,
+            upload_application_image,
+            set_lan_base_url,
+            set_health_check_interval,
+            set_profile_badge,
+            set_profile_background_stream,
+            get_server_info,
+            get_current_user,
+            change_password,
+            import_android_backup,
+            list_plugins,
+            enable_plugin,
+            disable_plugin,
+            get_plugin_display,
+            get_plugin_config,
+            update_plugin_config,
+            save_context,
+            switch_context,
+            delete_context,
+            set_app_muted,
+            set_notification_sound,
+            set_notification_sound_bands,
+            import_sound,
+            list_custom_sounds,
+            preview_sound,
+            load_older_messages,
+            set_android_action_rules,
+            set_connection_mode,
+            set_reconnect_backoff,
+            set_request_timeouts,
+            set_request_retry,
+            set_message_retention,
+            set_stream_token_in_query,
+            reconnect_now
         ])
+        .register_uri_scheme_protocol(ICON_URI_SCHEME, |context, request| {
+            icon_store::serve(context.app_handle(), request)
+        })
         .setup(|app| {
             debug_log("setup: starting");
 
@@ -488,11 +1441,22 @@ fn main() {
             let _ = SETTINGS_FILE.set(settings_path.clone());
             // Enforce 0o600 on startup — self-heals after backup restores or copies.
             restrict_file_permissions(&settings_path);
-            if let Ok(messages_path) = messages_file(app.handle()) {
-                restrict_file_permissions(&messages_path);
-            }
+            at_rest::migrate_plaintext(SETTINGS_LABEL, &settings_path);
 
-            let startup_settings = read_settings(app.handle()).unwrap_or_default();
+            let startup_settings = read_settings(app.handle());
+            // Left unset when settings cannot be read, so redaction fails closed.
+            if let Ok(settings) = &startup_settings {
+                redaction::set_patterns(&settings.redaction_patterns);
+            }
+            let startup_settings = startup_settings.unwrap_or_default();
+            profiles::set_active_profile(app.handle(), startup_settings.active_profile.clone());
+            op_trace::set_enabled(startup_settings.op_trace_enabled);
+            faults::set_config(&startup_settings.fault_injection);
+            retry::set_policy(&startup_settings.request_retry);
+            settings::remember_settings_on_disk(
+                app.handle(),
+                serde_json::to_value(&startup_settings).ok(),
+            );
             debug_log(&format!(
                 "setup: loaded settings base_url={:?} has_token={}",
                 startup_settings.base_url,
@@ -506,6 +1470,9 @@ fn main() {
                 debug_log(&format!("failed to configure launch at login: {error}"));
             }
 
+            proxy::load_system_proxy();
+            tauri::async_runtime::spawn(proxy::watch_system_proxy(app.handle().clone()));
+
             let existing_messages = messages::load_messages_from_disk(app.handle())?;
             let app_state = app.state::<AppState>();
             if let Ok(mut messages_guard) = app_state.messages.lock() {
@@ -513,6 +1480,26 @@ fn main() {
             } else {
                 return Err("Message cache lock poisoned".into());
             }
+            if let Ok(mut app_meta) = app_state.app_meta.lock() {
+                *app_meta = messages::load_app_meta_from_disk(app.handle());
+            }
+            trash::replace_trash(app.handle(), trash::load_trash_from_disk(app.handle()));
+            dismiss::replace_dismissed(
+                app.handle(),
+                dismiss::load_dismissed_from_disk(app.handle()),
+            );
+            inbox::sync_unread_count(app.handle());
+            trash::watch_trash_purge(app.handle().clone());
+            retention::watch_message_retention(app.handle().clone());
+            snooze::watch_snoozes(app.handle().clone());
+            endpoints::watch_endpoints(app.handle().clone());
+            health::watch_server_health(app.handle().clone());
+            #[cfg(target_os = "macos")]
+            network_watch::watch_network(app.handle().clone());
+            #[cfg(target_os = "macos")]
+            power_watch::watch_power(app.handle().clone());
+            #[cfg(target_os = "macos")]
+            user_notifications::init(app.handle());
 
             if app.get_webview_window("quick").is_none() {
                 tauri::WebviewWindowBuilder::new(
@@ -547,16 +1534,25 @@ fn main() {
                 true,
                 None::<&str>,
             )?;
+            let profiles_menu = profiles::create_tray_profiles_menu(app.handle())?;
+            let contexts_menu = contexts::create_tray_contexts_menu(app.handle())?;
+            let privacy_item =
+                privacy::create_tray_item(app.handle(), startup_settings.privacy_mode)?;
+            let health_item = health::create_tray_item(app.handle())?;
             let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
             let menu = Menu::with_items(
                 app,
                 &[
                     &pause_items.status_item,
+                    &health_item,
                     &open_item,
+                    &profiles_menu,
+                    &contexts_menu,
                     &pause_items.pause_15m_item,
                     &pause_items.pause_1h_item,
                     &pause_items.pause_forever_item,
                     &pause_items.resume_item,
+                    &privacy_item,
                     &quit_item,
                 ],
             )?;
@@ -586,15 +1582,14 @@ fn main() {
                         ui_shell::show_main_window(app);
                     }
                     "pause_15m" => {
-                        if let Err(error) = pause::set_pause(
-                            app.clone(),
-                            pause::SetPauseInput {
+                        if let Err(error) =
+                            pause::PauseController::new(app).pause(pause::SetPauseInput {
                                 minutes: Some(15),
                                 until: None,
                                 mode: None,
                                 forever: None,
-                            },
-                        ) {
+                            })
+                        {
                             let _ = contract::publish_stream_error(
                                 &app,
                                 format!("Failed to pause notifications: {error}"),
@@ -602,15 +1597,14 @@ fn main() {
                         }
                     }
                     "pause_1h" => {
-                        if let Err(error) = pause::set_pause(
-                            app.clone(),
-                            pause::SetPauseInput {
+                        if let Err(error) =
+                            pause::PauseController::new(app).pause(pause::SetPauseInput {
                                 minutes: Some(60),
                                 until: None,
                                 mode: None,
                                 forever: None,
-                            },
-                        ) {
+                            })
+                        {
                             let _ = contract::publish_stream_error(
                                 &app,
                                 format!("Failed to pause notifications: {error}"),
@@ -618,15 +1612,14 @@ fn main() {
                         }
                     }
                     "pause_forever" => {
-                        if let Err(error) = pause::set_pause(
-                            app.clone(),
-                            pause::SetPauseInput {
+                        if let Err(error) =
+                            pause::PauseController::new(app).pause(pause::SetPauseInput {
                                 minutes: None,
                                 until: None,
                                 mode: None,
                                 forever: Some(true),
-                            },
-                        ) {
+                            })
+                        {
                             let _ = contract::publish_stream_error(
                                 &app,
                                 format!("Failed to pause notifications: {error}"),
@@ -634,18 +1627,31 @@ fn main() {
                         }
                     }
                     "resume_notifications" => {
-                        if let Err(error) = pause::resume_pause(app.clone()) {
+                        if let Err(error) = pause::PauseController::new(app).resume() {
                             let _ = contract::publish_stream_error(
                                 &app,
                                 format!("Failed to resume notifications: {error}"),
                             );
                         }
                     }
-                    "quit" => {
-                        let _ = stream::stop_stream(app.clone());
-                        app.exit(0);
+                    "privacy_mode" => {
+                        if let Err(error) = privacy::set_privacy_mode(app, None)
+                            .map(|settings| contract::publish_settings_update(app, settings))
+                        {
+                            let _ = contract::publish_stream_error(
+                                app,
+                                format!("Failed to change privacy mode: {error}"),
+                            );
+                        }
+                    }
+                    "quit" => quit::quit(app),
+                    other => {
+                        if let Some(name) = other.strip_prefix(TRAY_PROFILE_ITEM_PREFIX) {
+                            profiles::switch_from_tray(app, name.to_string());
+                        } else if let Some(name) = other.strip_prefix(TRAY_CONTEXT_ITEM_PREFIX) {
+                            contexts::switch_from_tray(app, name);
+                        }
                     }
-                    _ => {}
                 });
             if let Some(icon) = ui_shell::tray_icon_for_status("Disconnected")
                 .or_else(|| app.default_window_icon().cloned())
@@ -653,15 +1659,23 @@ fn main() {
                 tray_builder = tray_builder.icon(icon);
             }
             tray_builder.build(app)?;
+            profiles::sync_tray_tooltip(app.handle());
 
             let app_for_pause_refresh = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 loop {
                     tokio::time::sleep(Duration::from_secs(5)).await;
-                    pause::refresh_pause_state_from_settings(&app_for_pause_refresh);
+                    pause_schedule::apply_scheduled_pause(&app_for_pause_refresh);
+                    pause::PauseController::new(&app_for_pause_refresh).refresh();
+                    notifications::refresh_quiet_hours_badge(&app_for_pause_refresh);
+                    notification_queue::release_due(&app_for_pause_refresh);
+                    settings::reload_settings_if_changed(&app_for_pause_refresh);
                 }
             });
 
+            compaction::watch_idle_compaction(app.handle().clone());
+            watchdog::watch_stream(app.handle().clone());
+
             match stream::start_stream(app.handle().clone(), None) {
                 Ok(_) => {}
                 Err(error) => {
@@ -672,6 +1686,7 @@ fn main() {
                     );
                 }
             }
+            background_streams::sync(app.handle());
 
             Ok(())
         })
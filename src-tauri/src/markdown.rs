@@ -0,0 +1,43 @@
+use serde_json::Value;
+
+const MARKDOWN_CONTENT_TYPE: &str = "text/markdown";
+
+/// Whether `extras` ask clients to render the message as markdown
+/// (`client::display.contentType`). Gotify treats anything else as plain text.
+pub(crate) fn declares_markdown(extras: &Value) -> bool {
+    extras
+        .pointer("/client::display/contentType")
+        .and_then(Value::as_str)
+        .is_some_and(|content_type| content_type.trim() == MARKDOWN_CONTENT_TYPE)
+}
+
+/// Markdown flattened for places that only show text, such as notification
+/// banners: markup is dropped, link and image text kept, blocks end lines.
+#[cfg(target_os = "macos")]
+pub(crate) fn to_plain_text(markdown: &str) -> String {
+    use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+    let options =
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS;
+    let mut text = String::new();
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Text(value) | Event::Code(value) => text.push_str(&value),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak | Event::Rule => text.push('\n'),
+            Event::Start(Tag::Item) => text.push_str("• "),
+            Event::TaskListMarker(done) => text.push_str(if done { "[x] " } else { "[ ] " }),
+            Event::End(TagEnd::TableCell) => text.push(' '),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::Item
+                | TagEnd::CodeBlock
+                | TagEnd::TableRow
+                | TagEnd::TableHead,
+            ) if !text.ends_with('\n') => text.push('\n'),
+            _ => {}
+        }
+    }
+    text.trim().to_string()
+}
@@ -0,0 +1,565 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    debug_log, ApplicationMeta, CachedMessage, IconValidators, MESSAGES_LABEL,
+    MESSAGE_COMPRESS_MIN_BYTES, MESSAGE_STORE_BUSY_TIMEOUT_MS,
+};
+
+/// Bump with a migration step in `open` when the schema changes.
+const SCHEMA_VERSION: i64 = 3;
+
+/// `messages.encoding`: how the JSON was packed before sealing.
+const ENCODING_JSON: i64 = 0;
+const ENCODING_DEFLATE: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS messages (
+    id INTEGER PRIMARY KEY,
+    app_id INTEGER NOT NULL,
+    read INTEGER NOT NULL,
+    protected INTEGER NOT NULL,
+    fingerprint INTEGER NOT NULL,
+    body BLOB NOT NULL,
+    encoding INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS messages_by_app ON messages (app_id, id);
+CREATE INDEX IF NOT EXISTS messages_unread ON messages (read, id);
+CREATE TABLE IF NOT EXISTS archived_messages (
+    id INTEGER PRIMARY KEY,
+    archived_at INTEGER NOT NULL,
+    body BLOB NOT NULL,
+    encoding INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS archived_by_time ON archived_messages (archived_at, id);
+CREATE TABLE IF NOT EXISTS applications (
+    app_id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    image TEXT NOT NULL,
+    icon_url TEXT NOT NULL,
+    etag TEXT,
+    last_modified TEXT
+);
+";
+
+/// SQLite database that holds the cache whose legacy JSON file is `cache_path`
+/// (`messages.json` -> `messages.sqlite3`). `messages_file` keeps naming the
+/// JSON file, so trash and profile cleanup derive their names as before.
+pub(crate) fn database_path(cache_path: &Path) -> PathBuf {
+    cache_path.with_extension("sqlite3")
+}
+
+/// Opens the database, creating it on first use. A legacy JSON cache next to
+/// it is imported once and then deleted.
+fn open(cache_path: &Path) -> Result<Connection, String> {
+    let path = database_path(cache_path);
+    let fresh = !path.exists();
+    let mut connection = Connection::open(&path)
+        .map_err(|error| format!("Failed to open message store: {error}"))?;
+    crate::restrict_file_permissions(&path);
+    connection
+        .busy_timeout(Duration::from_millis(MESSAGE_STORE_BUSY_TIMEOUT_MS))
+        .map_err(|error| format!("Failed to configure message store: {error}"))?;
+    let version: i64 = connection
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|error| format!("Failed to read message store version: {error}"))?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "Message store was written by a newer version (schema {version})"
+        ));
+    }
+    connection
+        .execute_batch(SCHEMA)
+        .map_err(|error| format!("Failed to create message store schema: {error}"))?;
+    if version == 1 {
+        connection
+            .execute_batch("ALTER TABLE messages ADD COLUMN encoding INTEGER NOT NULL DEFAULT 0")
+            .map_err(|error| format!("Failed to migrate message store: {error}"))?;
+    }
+    connection
+        .pragma_update(None, "user_version", SCHEMA_VERSION)
+        .map_err(|error| format!("Failed to set message store version: {error}"))?;
+    if fresh && cache_path.exists() {
+        import_legacy_json(&mut connection, cache_path)?;
+    }
+    Ok(connection)
+}
+
+fn import_legacy_json(connection: &mut Connection, cache_path: &Path) -> Result<(), String> {
+    let messages = crate::messages::load_messages_from_path(cache_path)?;
+    write_messages(connection, &messages)?;
+    debug_log(&format!(
+        "message store: imported {} message(s) from {}",
+        messages.len(),
+        cache_path.to_string_lossy()
+    ));
+    if let Err(error) = fs::remove_file(cache_path) {
+        debug_log(&format!(
+            "message store: failed to remove legacy cache file: {error}"
+        ));
+    }
+    Ok(())
+}
+
+/// Identifies a row's content, so a save only rewrites messages that changed.
+/// Whether sealing is on is part of it, so rows written while the Keychain key
+/// was unavailable are sealed on the next save once it is back. So is the
+/// encoding, which rewrites rows from before compression on the next save.
+fn fingerprint(body: &[u8], encoding: i64) -> i64 {
+    let mut hasher = Sha256::new();
+    hasher.update(body);
+    hasher.update([u8::from(crate::at_rest::sealing_enabled())]);
+    hasher.update(encoding.to_le_bytes());
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    i64::from_le_bytes(bytes)
+}
+
+/// A message as stored, before sealing.
+struct EncodedMessage {
+    payload: Vec<u8>,
+    encoding: i64,
+    fingerprint: i64,
+}
+
+fn encode(message: &CachedMessage) -> Result<EncodedMessage, String> {
+    let json = serde_json::to_vec(message)
+        .map_err(|error| format!("Failed to serialize message {}: {error}", message.id))?;
+    // Compress before sealing; sealed bytes do not compress.
+    let (payload, encoding) = if json.len() >= MESSAGE_COMPRESS_MIN_BYTES {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        let compressed = encoder
+            .write_all(&json)
+            .and_then(|_| encoder.finish())
+            .map_err(|error| format!("Failed to compress message {}: {error}", message.id))?;
+        (compressed, ENCODING_DEFLATE)
+    } else {
+        (json.clone(), ENCODING_JSON)
+    };
+    Ok(EncodedMessage {
+        fingerprint: fingerprint(&json, encoding),
+        payload,
+        encoding,
+    })
+}
+
+fn decode(payload: Vec<u8>, encoding: i64) -> Result<CachedMessage, String> {
+    let json = match encoding {
+        ENCODING_JSON => payload,
+        ENCODING_DEFLATE => {
+            let mut json = Vec::new();
+            DeflateDecoder::new(payload.as_slice())
+                .read_to_end(&mut json)
+                .map_err(|error| format!("corrupt compressed body: {error}"))?;
+            json
+        }
+        other => return Err(format!("unknown encoding {other}")),
+    };
+    serde_json::from_slice(&json).map_err(|error| error.to_string())
+}
+
+fn upsert(
+    connection: &Connection,
+    message: &CachedMessage,
+    encoded: &EncodedMessage,
+) -> Result<(), String> {
+    let body = crate::at_rest::seal(MESSAGES_LABEL, &encoded.payload)?;
+    connection
+        .prepare_cached(
+            "INSERT INTO messages (id, app_id, read, protected, fingerprint, body, encoding)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(id) DO UPDATE SET app_id = ?2, read = ?3, protected = ?4,
+                 fingerprint = ?5, body = ?6, encoding = ?7",
+        )
+        .and_then(|mut statement| {
+            statement.execute(params![
+                message.id,
+                message.app_id,
+                message.read,
+                message.is_protected(),
+                encoded.fingerprint,
+                body,
+                encoded.encoding
+            ])
+        })
+        .map(|_| ())
+        .map_err(|error| format!("Failed to write message {}: {error}", message.id))
+}
+
+/// Makes the stored messages match `messages`: changed rows are rewritten,
+/// missing ones deleted, the rest left alone. A missing row that cannot be
+/// decoded was never loaded, not removed, so it is kept.
+fn write_messages(connection: &mut Connection, messages: &[CachedMessage]) -> Result<(), String> {
+    let transaction = connection
+        .transaction()
+        .map_err(|error| format!("Failed to start message store transaction: {error}"))?;
+    let stored: HashMap<i64, i64> = {
+        let mut statement = transaction
+            .prepare("SELECT id, fingerprint FROM messages")
+            .map_err(|error| format!("Failed to read message store: {error}"))?;
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|error| format!("Failed to read message store: {error}"))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|error| format!("Failed to read message store: {error}"))?
+    };
+
+    let mut keep = HashSet::with_capacity(messages.len());
+    let mut written = 0usize;
+    for message in messages {
+        keep.insert(message.id);
+        let encoded = encode(message)?;
+        if stored.get(&message.id) == Some(&encoded.fingerprint) {
+            continue;
+        }
+        upsert(&transaction, message, &encoded)?;
+        written += 1;
+    }
+    let mut removed = 0usize;
+    let mut undecodable = 0usize;
+    for id in stored.keys().filter(|id| !keep.contains(id)) {
+        if !is_decodable(&transaction, *id)? {
+            undecodable += 1;
+            continue;
+        }
+        transaction
+            .execute("DELETE FROM messages WHERE id = ?1", [id])
+            .map_err(|error| format!("Failed to delete message {id}: {error}"))?;
+        removed += 1;
+    }
+    transaction
+        .commit()
+        .map_err(|error| format!("Failed to commit message store: {error}"))?;
+    if written > 0 || removed > 0 {
+        debug_log(&format!(
+            "message store: wrote {written}, removed {removed} of {} message(s)",
+            messages.len()
+        ));
+    }
+    if undecodable > 0 {
+        debug_log(&format!(
+            "message store: kept {undecodable} undecodable message(s)"
+        ));
+    }
+    Ok(())
+}
+
+fn is_decodable(connection: &Connection, id: i64) -> Result<bool, String> {
+    let (body, encoding) = connection
+        .query_row(
+            "SELECT body, encoding FROM messages WHERE id = ?1",
+            [id],
+            |row| Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, i64>(1)?)),
+        )
+        .map_err(|error| format!("Failed to read message {id}: {error}"))?;
+    Ok(crate::at_rest::open(MESSAGES_LABEL, body)
+        .and_then(|payload| decode(payload, encoding))
+        .is_ok())
+}
+
+/// All cached messages, newest first. Rows that cannot be decoded are skipped
+/// and logged rather than failing the whole cache. They stay in the store, so
+/// a Keychain key that comes back makes them readable again, and a sync that
+/// brings the message again overwrites them.
+pub(crate) fn load_messages(cache_path: &Path) -> Result<Vec<CachedMessage>, String> {
+    let connection = open(cache_path)?;
+    let mut statement = connection
+        .prepare("SELECT id, body, encoding FROM messages ORDER BY id DESC")
+        .map_err(|error| format!("Failed to read message store: {error}"))?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Vec<u8>>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })
+        .map_err(|error| format!("Failed to read message store: {error}"))?;
+    let mut messages = Vec::new();
+    for row in rows {
+        let (id, body, encoding) =
+            row.map_err(|error| format!("Failed to read message store: {error}"))?;
+        let decoded = crate::at_rest::open(MESSAGES_LABEL, body)
+            .and_then(|payload| decode(payload, encoding));
+        match decoded {
+            Ok(message) => messages.push(message),
+            Err(error) => debug_log(&format!("message store: skipping message {id}: {error}")),
+        }
+    }
+    Ok(messages)
+}
+
+pub(crate) fn save_messages(cache_path: &Path, messages: &[CachedMessage]) -> Result<(), String> {
+    write_messages(&mut open(cache_path)?, messages)
+}
+
+/// Adds one message unless its id is stored already, then evicts unprotected
+/// messages beyond `cache_limit`. Returns whether it was new.
+pub(crate) fn insert_message(
+    cache_path: &Path,
+    message: &CachedMessage,
+    cache_limit: usize,
+) -> Result<bool, String> {
+    let mut connection = open(cache_path)?;
+    let transaction = connection
+        .transaction()
+        .map_err(|error| format!("Failed to start message store transaction: {error}"))?;
+    let exists = transaction
+        .query_row("SELECT 1 FROM messages WHERE id = ?1", [message.id], |_| {
+            Ok(())
+        })
+        .optional()
+        .map_err(|error| format!("Failed to read message store: {error}"))?
+        .is_some();
+    if exists {
+        return Ok(false);
+    }
+    upsert(&transaction, message, &encode(message)?)?;
+    transaction
+        .execute(
+            "DELETE FROM messages WHERE protected = 0 AND id NOT IN (
+                SELECT id FROM messages WHERE protected = 0 ORDER BY id DESC LIMIT ?1
+            )",
+            [i64::try_from(cache_limit).unwrap_or(i64::MAX)],
+        )
+        .map_err(|error| format!("Failed to evict old messages: {error}"))?;
+    transaction
+        .commit()
+        .map_err(|error| format!("Failed to commit message store: {error}"))?;
+    Ok(true)
+}
+
+/// Keeps copies of messages that left the live cache, newest `max_messages`
+/// only. An id archived again is replaced.
+pub(crate) fn archive_messages(
+    cache_path: &Path,
+    messages: &[CachedMessage],
+    archived_at: u64,
+    max_messages: usize,
+) -> Result<(), String> {
+    let mut connection = open(cache_path)?;
+    let transaction = connection
+        .transaction()
+        .map_err(|error| format!("Failed to start message store transaction: {error}"))?;
+    let archived_at = i64::try_from(archived_at).unwrap_or(i64::MAX);
+    for message in messages {
+        let encoded = encode(message)?;
+        let body = crate::at_rest::seal(MESSAGES_LABEL, &encoded.payload)?;
+        transaction
+            .execute(
+                "INSERT INTO archived_messages (id, archived_at, body, encoding)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(id) DO UPDATE SET archived_at = ?2, body = ?3, encoding = ?4",
+                params![message.id, archived_at, body, encoded.encoding],
+            )
+            .map_err(|error| format!("Failed to archive message {}: {error}", message.id))?;
+    }
+    transaction
+        .execute(
+            "DELETE FROM archived_messages WHERE id NOT IN (
+                SELECT id FROM archived_messages ORDER BY archived_at DESC, id DESC LIMIT ?1
+            )",
+            [i64::try_from(max_messages).unwrap_or(i64::MAX)],
+        )
+        .map_err(|error| format!("Failed to trim the archive: {error}"))?;
+    transaction
+        .commit()
+        .map_err(|error| format!("Failed to commit the archive: {error}"))
+}
+
+/// A page of archived messages with the unix seconds each was archived,
+/// most recently archived first, plus the archive's total size.
+pub(crate) fn load_archived_messages(
+    cache_path: &Path,
+    offset: usize,
+    limit: usize,
+) -> Result<(Vec<(CachedMessage, u64)>, usize), String> {
+    let connection = open(cache_path)?;
+    let total: i64 = connection
+        .query_row("SELECT COUNT(*) FROM archived_messages", [], |row| {
+            row.get(0)
+        })
+        .map_err(|error| format!("Failed to read the archive: {error}"))?;
+    let mut statement = connection
+        .prepare(
+            "SELECT id, archived_at, body, encoding FROM archived_messages
+             ORDER BY archived_at DESC, id DESC LIMIT ?1 OFFSET ?2",
+        )
+        .map_err(|error| format!("Failed to read the archive: {error}"))?;
+    let rows = statement
+        .query_map(
+            [
+                i64::try_from(limit).unwrap_or(i64::MAX),
+                i64::try_from(offset).unwrap_or(i64::MAX),
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            },
+        )
+        .map_err(|error| format!("Failed to read the archive: {error}"))?;
+    let mut messages = Vec::new();
+    for row in rows {
+        let (id, archived_at, body, encoding) =
+            row.map_err(|error| format!("Failed to read the archive: {error}"))?;
+        let decoded = crate::at_rest::open(MESSAGES_LABEL, body)
+            .and_then(|payload| decode(payload, encoding));
+        match decoded {
+            Ok(message) => messages.push((message, u64::try_from(archived_at).unwrap_or(0))),
+            Err(error) => debug_log(&format!("message store: skipping archived {id}: {error}")),
+        }
+    }
+    Ok((messages, usize::try_from(total).unwrap_or(0)))
+}
+
+/// App names and icons as last fetched, so a restart or profile switch shows
+/// them before the server answers and can revalidate icons instead of
+/// downloading them again.
+pub(crate) fn load_applications(
+    cache_path: &Path,
+) -> Result<HashMap<i64, ApplicationMeta>, String> {
+    let connection = open(cache_path)?;
+    let mut statement = connection
+        .prepare("SELECT app_id, name, image, icon_url, etag, last_modified FROM applications")
+        .map_err(|error| format!("Failed to read applications: {error}"))?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                ApplicationMeta {
+                    name: row.get(1)?,
+                    image: row.get(2)?,
+                    icon_url: row.get(3)?,
+                    icon_validators: IconValidators {
+                        etag: row.get(4)?,
+                        last_modified: row.get(5)?,
+                    },
+                },
+            ))
+        })
+        .map_err(|error| format!("Failed to read applications: {error}"))?;
+    rows.collect::<Result<_, _>>()
+        .map_err(|error| format!("Failed to read applications: {error}"))
+}
+
+pub(crate) fn save_applications(
+    cache_path: &Path,
+    applications: &HashMap<i64, ApplicationMeta>,
+) -> Result<(), String> {
+    let mut connection = open(cache_path)?;
+    let transaction = connection
+        .transaction()
+        .map_err(|error| format!("Failed to start message store transaction: {error}"))?;
+    transaction
+        .execute("DELETE FROM applications", [])
+        .map_err(|error| format!("Failed to write applications: {error}"))?;
+    for (app_id, meta) in applications {
+        transaction
+            .execute(
+                "INSERT INTO applications (app_id, name, image, icon_url, etag, last_modified)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    app_id,
+                    meta.name,
+                    meta.image,
+                    meta.icon_url,
+                    meta.icon_validators.etag,
+                    meta.icon_validators.last_modified
+                ],
+            )
+            .map_err(|error| format!("Failed to write application {app_id}: {error}"))?;
+    }
+    transaction
+        .commit()
+        .map_err(|error| format!("Failed to commit applications: {error}"))
+}
+
+/// Rebuilds the database file so space freed by deletes goes back to the disk.
+pub(crate) fn vacuum(cache_path: &Path) -> Result<(), String> {
+    open(cache_path)?
+        .execute_batch("VACUUM")
+        .map_err(|error| format!("Failed to compact message store: {error}"))
+}
+
+/// Deletes a profile's database along with any legacy JSON cache.
+pub(crate) fn remove(cache_path: &Path) {
+    for path in [
+        database_path(cache_path),
+        cache_path.to_path_buf(),
+        PathBuf::from(format!(
+            "{}-journal",
+            database_path(cache_path).to_string_lossy()
+        )),
+    ] {
+        if !path.exists() {
+            continue;
+        }
+        if let Err(error) = fs::remove_file(&path) {
+            debug_log(&format!(
+                "message store: failed to remove {}: {error}",
+                path.to_string_lossy()
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(id: i64) -> CachedMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "app_id": 1,
+            "title": "Backup",
+            "message": "done",
+            "priority": 5,
+            "date": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn corrupt_row_is_skipped_on_load_and_kept_on_save() {
+        let dir = std::env::temp_dir().join(format!("gotify-message-store-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let cache_path = dir.join("messages.json");
+        save_messages(&cache_path, &[cached(1)]).unwrap();
+        open(&cache_path)
+            .unwrap()
+            .execute(
+                "INSERT INTO messages (id, app_id, read, protected, fingerprint, body, encoding)
+                 VALUES (2, 1, 0, 0, 0, ?1, ?2)",
+                params![b"not json".to_vec(), ENCODING_JSON],
+            )
+            .unwrap();
+
+        let loaded = load_messages(&cache_path).unwrap();
+        save_messages(&cache_path, &loaded).unwrap();
+        let corrupt_rows: i64 = open(&cache_path)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM messages WHERE id = 2", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        let ids: Vec<i64> = loaded.iter().map(|message| message.id).collect();
+        assert_eq!(ids, vec![1]);
+        assert_eq!(corrupt_rows, 1);
+    }
+}
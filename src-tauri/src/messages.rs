@@ -1,22 +1,88 @@
-use std::{collections::HashMap, fs, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
 
 use base64::Engine as _;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 
 use crate::{
-    debug_log, messages_file, truncate_message, unix_now_secs, AppState, ApplicationMeta,
-    CachedMessage, GotifyApplicationWire, GotifyMessageListWire, GotifyMessageWire,
-    APP_ICON_MAX_BYTES,
+    contract::DomainSnapshot,
+    debug_log, messages_file,
+    op_trace::{self, TraceOp},
+    truncate_message, unix_now_secs, AppState, ApplicationMeta, CachedMessage,
+    GotifyApplicationWire, GotifyMessageListWire, GotifyMessageWire, IconValidators, RevisionKey,
+    APP_ICON_FETCH_CONCURRENCY, APP_ICON_MAX_BYTES, DELETE_TOMBSTONE_TTL_SECS, MESSAGES_LABEL,
+    MESSAGE_PERSIST_DEBOUNCE_MS,
 };
 
+/// Payload of `message-remotely-deleted`: cached messages a sync found gone
+/// from the server, one event per sync.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct RemotelyDeletedEvent {
+    pub(crate) at_ms: u64,
+    pub(crate) message_ids: Vec<i64>,
+    /// Copies were kept in the local archive.
+    pub(crate) archived: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SyncMode {
+    /// First population of an empty cache; never notifies.
+    Initial,
+    Periodic,
+}
+
 pub(crate) async fn fetch_recent_messages(
     app: &AppHandle,
     base_url: &str,
     token: &str,
+    mode: SyncMode,
+) -> Result<(), String> {
+    let mode_label = match mode {
+        SyncMode::Initial => "initial",
+        SyncMode::Periodic => "periodic",
+    };
+    op_trace::emit(
+        app,
+        TraceOp::Sync,
+        "messages",
+        "start",
+        &format!("mode={mode_label}"),
+        None,
+    );
+    // Registered so stopping the stream (profile switch, import, sleep) drops a
+    // sync still paging through the old server.
+    let handle =
+        crate::operations::CancelHandle::register(app, "sync", Some(crate::OPERATION_GROUP_SYNC));
+    let result = handle
+        .run(fetch_recent_messages_inner(app, base_url, token))
+        .await;
+    match &result {
+        Ok(()) => op_trace::emit(app, TraceOp::Sync, "messages", "ok", "cache replaced", None),
+        Err(error) => op_trace::emit(app, TraceOp::Sync, "messages", "error", error, None),
+    }
+    if mode == SyncMode::Initial {
+        finish_initial_sync(app, result.is_ok());
+    }
+    result
+}
+
+async fn fetch_recent_messages_inner(
+    app: &AppHandle,
+    base_url: &str,
+    token: &str,
 ) -> Result<(), String> {
     let cache_limit = crate::desired_cache_limit(app);
     let mut fresh = Vec::new();
     let mut since: Option<i64> = None;
+    let paging =
+        crate::server_info::supports(app, crate::server_info::ServerFeature::MessagePaging);
 
     while fresh.len() < cache_limit {
         let remaining = cache_limit.saturating_sub(fresh.len());
@@ -30,15 +96,21 @@ pub(crate) async fn fetch_recent_messages(
             endpoint.push_str(&format!("&since={cursor}"));
         }
 
-        let response = reqwest::Client::new()
+        let request = crate::settings::server_client_builder(app)?
+            .build()
+            .map_err(|error| format!("Failed to build HTTP client: {error}"))?
             .get(endpoint)
-            .header("X-Gotify-Key", token)
-            .send()
+            .header("X-Gotify-Key", token);
+        let response = crate::retry::send(request)
             .await
             .map_err(|error| format!("Failed to fetch recent messages: {error}"))?;
 
+        crate::diagnostics::record_server_date_header(app, response.headers(), "message-sync");
         if !response.status().is_success() {
             let status = response.status().as_u16();
+            if crate::auth::is_auth_failure(status) {
+                crate::auth::report_auth_failure(app, "sync", status);
+            }
             let body = response
                 .text()
                 .await
@@ -53,6 +125,17 @@ pub(crate) async fn fetch_recent_messages(
             .json::<GotifyMessageListWire>()
             .await
             .map_err(|error| format!("Failed to decode recent messages: {error}"))?;
+        op_trace::emit(
+            app,
+            TraceOp::Sync,
+            "messages",
+            "page",
+            &format!(
+                "limit={limit} since={since:?} received={}",
+                json.messages.len()
+            ),
+            Some(200),
+        );
 
         if json.messages.is_empty() {
             break;
@@ -81,7 +164,8 @@ pub(crate) async fn fetch_recent_messages(
             break;
         }
 
-        if page_count < limit {
+        // Without paging every request returns the same newest page.
+        if page_count < limit || !paging {
             break;
         }
     }
@@ -89,27 +173,270 @@ pub(crate) async fn fetch_recent_messages(
     fresh.sort_by(crate::cached_message_cmp);
     fresh.dedup_by_key(|message| message.id);
     fresh.sort_by(crate::cached_message_cmp);
-    if fresh.len() > cache_limit {
-        fresh.truncate(cache_limit);
-    }
+    evict_to_limit(&mut fresh, cache_limit);
     replace_message_cache(app, fresh)?;
 
     Ok(())
 }
 
+/// Polling stand-in for the websocket: delivers messages newer than the newest
+/// cached one through `cache_and_emit_message`, oldest first, so they notify
+/// like streamed messages.
+pub(crate) async fn poll_new_messages(
+    app: &AppHandle,
+    base_url: &str,
+    token: &str,
+) -> Result<usize, String> {
+    let newest_cached = newest_cached_id(app)?.unwrap_or(0);
+    let (incoming, _) = fetch_messages_after(app, base_url, token, newest_cached).await?;
+    deliver_new_messages(app, base_url, token, incoming).await
+}
+
+/// After a websocket reconnect, fetches only what arrived after the last message
+/// seen and delivers it like streamed messages. A gap wider than the cache
+/// limit, or nothing seen yet, falls back to a full sync instead.
+pub(crate) async fn catch_up_after_reconnect(
+    app: &AppHandle,
+    target: &crate::stream::StreamTarget,
+) -> Result<(), String> {
+    let (base_url, token) = (target.base_url.as_str(), target.token.as_str());
+    let last_seen = app
+        .state::<AppState>()
+        .runtime
+        .lock()
+        .ok()
+        .and_then(|runtime| runtime.last_message_id);
+    let Some(last_seen) = last_seen.or(newest_cached_id(app)?) else {
+        return fetch_recent_messages(app, base_url, token, SyncMode::Periodic).await;
+    };
+    let (incoming, complete) =
+        fetch_server_messages_after(app, &crate::stream::StreamOwner::Active, target, last_seen)
+            .await?;
+    if !complete {
+        debug_log(&format!(
+            "catch-up: more than {} messages after id={last_seen}; running a full sync",
+            incoming.len()
+        ));
+        return fetch_recent_messages(app, base_url, token, SyncMode::Periodic).await;
+    }
+    let delivered = deliver_new_messages(app, base_url, token, incoming).await?;
+    debug_log(&format!(
+        "catch-up: {delivered} message(s) after id={last_seen}"
+    ));
+    Ok(())
+}
+
+fn newest_cached_id(app: &AppHandle) -> Result<Option<i64>, String> {
+    Ok(app
+        .state::<AppState>()
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?
+        .iter()
+        .map(|message| message.id)
+        .max())
+}
+
+/// Pages back from the newest message until `after_id`, newest first. The flag
+/// is false when the cache limit was hit before reaching it; an `after_id` of 0
+/// stops after the newest page.
+async fn fetch_messages_after(
+    app: &AppHandle,
+    base_url: &str,
+    token: &str,
+    after_id: i64,
+) -> Result<(Vec<GotifyMessageWire>, bool), String> {
+    let target = crate::stream::active_target(app, base_url, token);
+    fetch_server_messages_after(app, &crate::stream::StreamOwner::Active, &target, after_id).await
+}
+
+/// `fetch_messages_after` for any stream's server. Only the active server feeds
+/// clock diagnostics and auth failure handling, and only its version is known,
+/// so a background server is paged until it stops returning older messages.
+pub(crate) async fn fetch_server_messages_after(
+    app: &AppHandle,
+    owner: &crate::stream::StreamOwner,
+    target: &crate::stream::StreamTarget,
+    after_id: i64,
+) -> Result<(Vec<GotifyMessageWire>, bool), String> {
+    let active = owner.is_active();
+    let cache_limit = crate::desired_cache_limit(app);
+    let paging = !active
+        || crate::server_info::supports(app, crate::server_info::ServerFeature::MessagePaging);
+    let client = crate::settings::profile_client_builder(
+        app,
+        target.host_pins.clone(),
+        target.zone.clone(),
+    )?
+    .build()
+    .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
+    let mut incoming = Vec::new();
+    let mut since: Option<i64> = None;
+    loop {
+        let limit = crate::MAX_API_PAGE_LIMIT;
+        let mut endpoint = format!("{}/message?limit={limit}", target.base_url);
+        if let Some(cursor) = since {
+            endpoint.push_str(&format!("&since={cursor}"));
+        }
+        let request = client.get(endpoint).header("X-Gotify-Key", &target.token);
+        let response = crate::retry::send(request)
+            .await
+            .map_err(|error| format!("Failed to poll messages: {error}"))?;
+        if active {
+            crate::diagnostics::record_server_date_header(app, response.headers(), "message-poll");
+        }
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            if active && crate::auth::is_auth_failure(status) {
+                crate::auth::report_auth_failure(app, "poll", status);
+            }
+            return Err(format!("Message poll failed with HTTP {status}"));
+        }
+        let page = response
+            .json::<GotifyMessageListWire>()
+            .await
+            .map_err(|error| format!("Failed to decode polled messages: {error}"))?;
+        let page_len = page.messages.len();
+        let previous = since;
+        let mut reached = false;
+        for wire in page.messages {
+            if wire.id <= after_id {
+                reached = true;
+                break;
+            }
+            // A server that ignores `since` answers with the same page again.
+            if previous.is_some_and(|cursor| wire.id >= cursor) {
+                continue;
+            }
+            since = Some(since.map_or(wire.id, |cursor| cursor.min(wire.id)));
+            incoming.push(wire);
+        }
+        if reached || after_id == 0 || page_len < limit || !paging {
+            // Without paging only the newest page is visible; treat a full page
+            // as a gap that may go further back.
+            let complete = reached || after_id == 0 || page_len < limit;
+            return Ok((incoming, complete));
+        }
+        if incoming.len() >= cache_limit || since == previous {
+            return Ok((incoming, false));
+        }
+    }
+}
+
+async fn deliver_new_messages(
+    app: &AppHandle,
+    base_url: &str,
+    token: &str,
+    mut incoming: Vec<GotifyMessageWire>,
+) -> Result<usize, String> {
+    let count = incoming.len();
+    incoming.sort_by_key(|wire| wire.id);
+    // No clock sample here: these messages may be hours old. The poll's `Date`
+    // header has already been recorded.
+    for wire in incoming {
+        if !has_app_meta(app, wire.appid) {
+            if let Err(error) = fetch_applications(app, base_url, token).await {
+                debug_log(&format!(
+                    "failed to refresh applications for app_id={}: {error}",
+                    wire.appid
+                ));
+            }
+        }
+        let message = convert_wire_message(app, wire);
+        cache_and_emit_message(app, message, true)?;
+    }
+    Ok(count)
+}
+
+pub(crate) fn begin_initial_sync_if_empty(app: &AppHandle) -> SyncMode {
+    let cache_empty = app
+        .state::<AppState>()
+        .messages
+        .lock()
+        .map(|messages| messages.is_empty())
+        .unwrap_or(false);
+    if !cache_empty {
+        return SyncMode::Periodic;
+    }
+    if let Ok(mut runtime) = app.state::<AppState>().runtime.lock() {
+        runtime.initial_sync_pending = true;
+    }
+    debug_log("initial sync mode: cache empty, holding back notifications");
+    SyncMode::Initial
+}
+
+fn finish_initial_sync(app: &AppHandle, succeeded: bool) {
+    let watermark = if succeeded {
+        app.state::<AppState>()
+            .messages
+            .lock()
+            .ok()
+            .and_then(|messages| messages.iter().map(|message| message.id).max())
+    } else {
+        None
+    };
+    if let Ok(mut runtime) = app.state::<AppState>().runtime.lock() {
+        runtime.initial_sync_pending = false;
+        if watermark.is_some() {
+            runtime.notify_watermark_id = watermark;
+        }
+    }
+    debug_log(&format!(
+        "initial sync finished ok={succeeded} watermark={watermark:?}"
+    ));
+}
+
+fn notifications_allowed_for(app: &AppHandle, message_id: i64) -> bool {
+    app.state::<AppState>()
+        .runtime
+        .lock()
+        .map(|runtime| {
+            !runtime.initial_sync_pending
+                && runtime
+                    .notify_watermark_id
+                    .is_none_or(|watermark| message_id > watermark)
+        })
+        .unwrap_or(true)
+}
+
 pub(crate) async fn fetch_applications(
     app: &AppHandle,
     base_url: &str,
     token: &str,
 ) -> Result<(), String> {
+    let previous = app
+        .state::<AppState>()
+        .app_meta
+        .lock()
+        .map(|map| map.clone())
+        .unwrap_or_default();
+    // The etag only stands for what `app_meta` holds; after a reset refetch all.
+    let list_etag = app
+        .state::<AppState>()
+        .application_list_etag
+        .lock()
+        .ok()
+        .and_then(|etag| etag.clone())
+        .filter(|_| !previous.is_empty());
+
     let endpoint = format!("{base_url}/application");
-    let response = reqwest::Client::new()
+    let mut request = crate::settings::server_client_builder(app)?
+        .build()
+        .map_err(|error| format!("Failed to build HTTP client: {error}"))?
         .get(endpoint)
-        .header("X-Gotify-Key", token)
-        .send()
+        .header("X-Gotify-Key", token);
+    if let Some(etag) = &list_etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    let response = crate::retry::send(request)
         .await
         .map_err(|error| format!("Failed to fetch applications: {error}"))?;
+    crate::diagnostics::record_server_date_header(app, response.headers(), "application-sync");
 
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        debug_log("application list not modified");
+        return Ok(());
+    }
     if !response.status().is_success() {
         return Err(format!(
             "Application request failed with HTTP {}",
@@ -117,36 +444,78 @@ pub(crate) async fn fetch_applications(
         ));
     }
 
+    let next_list_etag = header_string(response.headers(), reqwest::header::ETAG);
     let apps = response
         .json::<Vec<GotifyApplicationWire>>()
         .await
         .map_err(|error| format!("Failed to decode applications: {error}"))?;
 
-    let icon_client = reqwest::Client::builder()
+    let icon_client = crate::settings::server_client_builder(app)?
         .timeout(std::time::Duration::from_secs(
             crate::PREVIEW_REQUEST_TIMEOUT_SECS,
         ))
         .build()
         .map_err(|error| format!("Failed to build icon HTTP client: {error}"))?;
 
-    let mut next_map = HashMap::with_capacity(apps.len());
-    for app_item in apps {
-        let icon_url = match resolve_application_image_data_url(
-            &icon_client,
-            base_url,
-            &app_item.image,
-            token,
-        )
-        .await
-        {
-            Ok(icon_url) => icon_url,
+    let icon_client = &icon_client;
+    let previous = &previous;
+    let fetched: Vec<_> = futures_util::stream::iter(apps)
+        .map(|app_item| async move {
+            let cached = previous
+                .get(&app_item.id)
+                .filter(|meta| meta.image == app_item.image && !meta.icon_url.is_empty());
+            let icon = resolve_application_image_data_url(
+                icon_client,
+                base_url,
+                &app_item.image,
+                token,
+                cached.map(|meta| &meta.icon_validators),
+            )
+            .await;
+            (app_item, cached, icon)
+        })
+        .buffer_unordered(APP_ICON_FETCH_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut next_map = HashMap::with_capacity(fetched.len());
+    for (app_item, cached, icon) in fetched {
+        let (icon_url, icon_validators) = match icon {
+            Ok(ApplicationIcon::NotModified) => {
+                op_trace::emit(
+                    app,
+                    TraceOp::IconFetch,
+                    app_item.id,
+                    "ok",
+                    "not modified",
+                    Some(304),
+                );
+                cached
+                    .map(|meta| (meta.icon_url.clone(), meta.icon_validators.clone()))
+                    .unwrap_or_default()
+            }
+            Ok(ApplicationIcon::Downloaded {
+                icon_url,
+                validators,
+            }) => {
+                op_trace::emit(
+                    app,
+                    TraceOp::IconFetch,
+                    app_item.id,
+                    "ok",
+                    &format!("{} bytes as data URL", icon_url.len()),
+                    None,
+                );
+                (icon_url, validators)
+            }
             Err(error) => {
+                op_trace::emit(app, TraceOp::IconFetch, app_item.id, "error", &error, None);
                 debug_log(&format!(
                     "failed to fetch application icon app_id={} name={}: {error}",
                     app_item.id,
                     truncate_message(&app_item.name, 48)
                 ));
-                String::new()
+                (String::new(), IconValidators::default())
             }
         };
         next_map.insert(
@@ -154,19 +523,37 @@ pub(crate) async fn fetch_applications(
             ApplicationMeta {
                 name: app_item.name,
                 icon_url,
+                image: app_item.image,
+                icon_validators,
             },
         );
     }
 
     #[cfg(target_os = "macos")]
-    crate::notifications::warm_notification_icon_cache(app, &next_map);
+    {
+        let app = app.clone();
+        let icons = next_map.clone();
+        crate::work::run_in_background("warm-notification-icons", move || {
+            crate::notifications::warm_notification_icon_cache(&app, &icons);
+        });
+    }
 
-    let state = app.state::<AppState>();
-    let mut map = state
-        .app_meta
-        .lock()
-        .map_err(|_| "Application map lock poisoned".to_string())?;
-    *map = next_map;
+    let moved_to_disk = crate::icon_store::enforce_memory_cap(app, &mut next_map);
+    {
+        let state = app.state::<AppState>();
+        let mut map = state
+            .app_meta
+            .lock()
+            .map_err(|_| "Application map lock poisoned".to_string())?;
+        *map = next_map;
+    }
+    if let Ok(mut etag) = app.state::<AppState>().application_list_etag.lock() {
+        *etag = next_list_etag;
+    }
+    persist_app_meta(app);
+    if moved_to_disk {
+        apply_app_meta_to_cache(app)?;
+    }
     Ok(())
 }
 
@@ -201,6 +588,20 @@ pub(crate) fn has_app_meta(app: &AppHandle, app_id: i64) -> bool {
 
 pub(crate) fn convert_wire_message(app: &AppHandle, message: GotifyMessageWire) -> CachedMessage {
     let (app_label, app_icon) = resolve_app_meta(app, message.appid);
+    convert_wire_message_with(app, message, app_label, app_icon)
+}
+
+/// `convert_wire_message` with the application label supplied by the caller,
+/// for servers whose metadata is not the loaded one.
+pub(crate) fn convert_wire_message_with(
+    app: &AppHandle,
+    mut message: GotifyMessageWire,
+    app_label: String,
+    app_icon: Option<String>,
+) -> CachedMessage {
+    crate::redaction::redact_message(app, message.id, &mut message.title, &mut message.message);
+    let language = crate::language::detect_language(&message.title, &message.message);
+    let markdown = crate::markdown::declares_markdown(&message.extras);
     CachedMessage {
         id: message.id,
         app_id: message.appid,
@@ -210,6 +611,18 @@ pub(crate) fn convert_wire_message(app: &AppHandle, message: GotifyMessageWire)
         app: app_label,
         app_icon,
         date: message.date,
+        language,
+        starred: false,
+        archived: false,
+        pinned: false,
+        snoozed_until: None,
+        labels: Vec::new(),
+        read: false,
+        read_at: None,
+        imported: false,
+        extras: message.extras,
+        markdown,
+        extra: message.extra,
     }
 }
 
@@ -234,27 +647,53 @@ pub(crate) fn cache_and_emit_message(
     message: CachedMessage,
     allow_notification: bool,
 ) -> Result<(), String> {
+    let received_at_ms = crate::contract::now_ms();
+    if is_tombstoned(app, message.id)
+        || crate::trash::trashed_ids(app).contains(&message.id)
+        || crate::dismiss::dismissed_ids(app).contains(&message.id)
+    {
+        debug_log(&format!(
+            "message id={} ignored: deleted locally",
+            message.id
+        ));
+        return Ok(());
+    }
+    let fresh = allow_notification && notifications_allowed_for(app, message.id);
+    let android_action = if fresh {
+        crate::android_actions::match_message(app, &message)
+    } else {
+        None
+    };
     let app_state = app.state::<AppState>();
     let mut messages_guard = app_state
         .messages
         .lock()
         .map_err(|_| "Message cache lock poisoned".to_string())?;
 
+    let mut message = message;
     let mut existed = false;
     if let Some(pos) = messages_guard.iter().position(|m| m.id == message.id) {
         existed = true;
-        messages_guard.remove(pos);
+        let previous = messages_guard.remove(pos);
+        message.starred = previous.starred;
+        message.archived = previous.archived;
+        message.pinned = previous.pinned;
+        message.snoozed_until = previous.snoozed_until;
+        message.labels = previous.labels;
+        message.read = previous.read;
+        message.read_at = previous.read_at;
+    } else if let Some(action) = &android_action {
+        action.mark(&mut message);
     }
 
     messages_guard.insert(0, message.clone());
     let cache_limit = crate::desired_cache_limit(app);
-    if messages_guard.len() > cache_limit {
-        messages_guard.truncate(cache_limit);
-    }
+    evict_to_limit(&mut messages_guard, cache_limit);
 
     let cache_snapshot = messages_guard.clone();
     drop(messages_guard);
     persist_messages_snapshot(app, &cache_snapshot)?;
+    crate::inbox::evaluate(app, &cache_snapshot);
 
     debug_log(&format!(
         "message received id={} title={}",
@@ -271,8 +710,13 @@ pub(crate) fn cache_and_emit_message(
     }
     let _ = crate::contract::publish_message_upsert(app, message.clone());
     crate::diagnostics::publish_runtime_snapshot(app);
-    if allow_notification && !existed {
-        crate::notifications::maybe_notify_message(app, &message);
+    if fresh && !existed {
+        let notify = android_action
+            .as_ref()
+            .is_none_or(|action| action.run(app, message.id));
+        if notify {
+            crate::notifications::maybe_notify_message(app, &message, Some(received_at_ms));
+        }
     }
     Ok(())
 }
@@ -283,38 +727,237 @@ pub(crate) fn replace_message_cache(
 ) -> Result<(), String> {
     let app_state = app.state::<AppState>();
     let cache_limit = crate::desired_cache_limit(app);
+    let mut tombstones = active_tombstones(app);
+    tombstones.extend(crate::trash::trashed_ids(app));
+    tombstones.extend(crate::dismiss::dismissed_ids(app));
     let mut normalized = fresh;
+    if !tombstones.is_empty() {
+        normalized.retain(|message| !tombstones.contains(&message.id));
+    }
     normalized.sort_by(crate::cached_message_cmp);
     normalized.dedup_by_key(|message| message.id);
     normalized.sort_by(crate::cached_message_cmp);
-    if normalized.len() > cache_limit {
-        normalized.truncate(cache_limit);
-    }
+    evict_to_limit(&mut normalized, cache_limit);
 
+    let remotely_deleted;
+    let archived;
+    let delta;
     {
         let mut messages_guard = app_state
             .messages
             .lock()
             .map_err(|_| "Message cache lock poisoned".to_string())?;
-        let changed = messages_guard.len() != normalized.len()
-            || messages_guard.iter().zip(normalized.iter()).any(|(a, b)| {
-                a.id != b.id
-                    || a.date != b.date
-                    || a.priority != b.priority
-                    || a.title != b.title
-                    || a.message != b.message
-            });
-        if !changed {
+        carry_read_state(&messages_guard, &mut normalized);
+        let deleted = detect_remote_deletions(&messages_guard, &normalized, cache_limit);
+        let gone: Vec<CachedMessage> = messages_guard
+            .iter()
+            .filter(|message| deleted.contains(&message.id))
+            .cloned()
+            .collect();
+        carry_protected_messages(&messages_guard, &mut normalized, &deleted);
+        crate::retention::drop_expired(app, &mut normalized);
+        normalized.sort_by(crate::cached_message_cmp);
+        evict_to_limit(&mut normalized, cache_limit);
+        delta = diff_message_cache(&messages_guard, &normalized);
+        if delta.upserted.is_empty() && delta.removed.is_empty() {
             return Ok(());
         }
+        remotely_deleted = deleted;
+        archived = gone;
         *messages_guard = normalized.clone();
     }
 
     persist_messages_snapshot(app, &normalized)?;
-    let _ = crate::contract::publish_messages_replace(app, normalized);
+    let archived = crate::archive::archive_remote_deletions(app, archived);
+    crate::inbox::evaluate(app, &normalized);
+    debug_log(&format!(
+        "sync delta: {} upserted, {} removed",
+        delta.upserted.len(),
+        delta.removed.len()
+    ));
+    let _ = crate::contract::publish_messages_delta(app, delta);
+    if !remotely_deleted.is_empty() {
+        debug_log(&format!(
+            "sync detected {} message(s) deleted on the server",
+            remotely_deleted.len()
+        ));
+        crate::notification_queue::cancel_for_messages(app, &remotely_deleted);
+        crate::notifications::withdraw_notifications(app, &remotely_deleted);
+        crate::contract::emit_event(
+            app,
+            "main",
+            "message-remotely-deleted",
+            RemotelyDeletedEvent {
+                at_ms: crate::contract::now_ms(),
+                message_ids: remotely_deleted,
+                archived,
+            },
+        );
+    }
     Ok(())
 }
 
+/// The one eviction policy for the local cache. Expects newest-first order and keeps
+/// at most `cache_limit` unprotected messages; starred/archived/pinned ones never count
+/// against the limit and are never dropped here.
+pub(crate) fn evict_to_limit(messages: &mut Vec<CachedMessage>, cache_limit: usize) {
+    let mut unprotected_kept = 0usize;
+    messages.retain(|message| {
+        if message.is_protected() {
+            return true;
+        }
+        unprotected_kept += 1;
+        unprotected_kept <= cache_limit
+    });
+}
+
+/// Messages new to the cache or with different server content, and ids that
+/// dropped out. Local flags are carried into `next` before this runs.
+fn diff_message_cache(
+    previous: &[CachedMessage],
+    next: &[CachedMessage],
+) -> crate::contract::MessagesDeltaData {
+    let previous_by_id: HashMap<i64, &CachedMessage> = previous
+        .iter()
+        .map(|message| (message.id, message))
+        .collect();
+    let next_ids: HashSet<i64> = next.iter().map(|message| message.id).collect();
+    let upserted = next
+        .iter()
+        .filter(|message| {
+            previous_by_id.get(&message.id).is_none_or(|old| {
+                old.date != message.date
+                    || old.priority != message.priority
+                    || old.title != message.title
+                    || old.message != message.message
+            })
+        })
+        .cloned()
+        .collect();
+    let removed = previous
+        .iter()
+        .map(|message| message.id)
+        .filter(|id| !next_ids.contains(id))
+        .collect();
+    crate::contract::MessagesDeltaData { upserted, removed }
+}
+
+/// Copies read state and labels onto fresh server copies, which arrive unread
+/// and unlabelled.
+fn carry_read_state(previous: &[CachedMessage], fresh: &mut [CachedMessage]) {
+    let read_at: HashMap<i64, Option<u64>> = previous
+        .iter()
+        .filter(|message| message.read)
+        .map(|message| (message.id, message.read_at))
+        .collect();
+    let labels: HashMap<i64, &Vec<String>> = previous
+        .iter()
+        .filter(|message| !message.labels.is_empty())
+        .map(|message| (message.id, &message.labels))
+        .collect();
+    for message in fresh.iter_mut() {
+        if let Some(read_at) = read_at.get(&message.id) {
+            message.read = true;
+            message.read_at = message.read_at.or(*read_at);
+        }
+        if let Some(labels) = labels.get(&message.id) {
+            message.labels = (*labels).clone();
+        }
+    }
+}
+
+/// Copies local flags onto fresh server copies and keeps protected messages that
+/// only fell out of the synced window (not ones the server deleted).
+fn carry_protected_messages(
+    previous: &[CachedMessage],
+    fresh: &mut Vec<CachedMessage>,
+    remotely_deleted: &[i64],
+) {
+    let mut protected: HashMap<i64, &CachedMessage> = previous
+        .iter()
+        .filter(|message| message.is_protected())
+        .map(|message| (message.id, message))
+        .collect();
+    if protected.is_empty() {
+        return;
+    }
+    for message in fresh.iter_mut() {
+        if let Some(previous) = protected.remove(&message.id) {
+            message.starred = previous.starred;
+            message.archived = previous.archived;
+            message.pinned = previous.pinned;
+            message.snoozed_until = previous.snoozed_until;
+        }
+    }
+    fresh.extend(
+        protected
+            .into_values()
+            .filter(|message| !remotely_deleted.contains(&message.id))
+            .cloned(),
+    );
+}
+
+/// Ids that were cached before but are missing from a fresh server page. Only ids
+/// inside the synced window count; messages that merely rolled past the cache
+/// limit or arrived after the fetch started are not deletions.
+fn detect_remote_deletions(
+    previous: &[CachedMessage],
+    fresh: &[CachedMessage],
+    cache_limit: usize,
+) -> Vec<i64> {
+    let fresh_ids: HashSet<i64> = fresh.iter().map(|message| message.id).collect();
+    let ceiling = fresh_ids.iter().max().copied();
+    let floor = if fresh.len() >= cache_limit {
+        fresh_ids.iter().min().copied()
+    } else {
+        None
+    };
+    previous
+        .iter()
+        .filter(|message| !message.imported)
+        .map(|message| message.id)
+        .filter(|id| !fresh_ids.contains(id))
+        .filter(|id| ceiling.is_none_or(|ceiling| *id <= ceiling))
+        .filter(|id| floor.is_none_or(|floor| *id >= floor))
+        .collect()
+}
+
+/// Adds messages from an imported history. Ids already cached, deleted locally,
+/// dismissed or in the trash are skipped; returns how many were added.
+pub(crate) fn merge_imported_messages(
+    app: &AppHandle,
+    imported: Vec<CachedMessage>,
+) -> Result<usize, String> {
+    let mut skip_ids = active_tombstones(app);
+    skip_ids.extend(crate::trash::trashed_ids(app));
+    skip_ids.extend(crate::dismiss::dismissed_ids(app));
+    let (added, snapshot) = {
+        let app_state = app.state::<AppState>();
+        let mut messages_guard = app_state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        skip_ids.extend(messages_guard.iter().map(|message| message.id));
+        let before = messages_guard.len();
+        for message in imported {
+            if skip_ids.insert(message.id) {
+                messages_guard.push(message);
+            }
+        }
+        if messages_guard.len() == before {
+            return Ok(0);
+        }
+        let added = messages_guard.len() - before;
+        messages_guard.sort_by(crate::cached_message_cmp);
+        evict_to_limit(&mut messages_guard, crate::desired_cache_limit(app));
+        (added, messages_guard.clone())
+    };
+    persist_messages_snapshot(app, &snapshot)?;
+    crate::inbox::evaluate(app, &snapshot);
+    let _ = crate::contract::publish_messages_replace(app, snapshot);
+    Ok(added)
+}
+
 pub(crate) fn remove_message_from_cache(app: &AppHandle, message_id: i64) -> Result<(), String> {
     let app_state = app.state::<AppState>();
     let updated_snapshot;
@@ -328,34 +971,415 @@ pub(crate) fn remove_message_from_cache(app: &AppHandle, message_id: i64) -> Res
     }
 
     persist_messages_snapshot(app, &updated_snapshot)?;
+    crate::inbox::evaluate(app, &updated_snapshot);
     let _ = crate::contract::publish_message_remove(app, message_id);
     Ok(())
 }
 
-fn persist_messages_snapshot(app: &AppHandle, snapshot: &[CachedMessage]) -> Result<(), String> {
+/// Drops the given ids from the cache; returns how many were cached.
+pub(crate) fn remove_messages_from_cache(
+    app: &AppHandle,
+    message_ids: &HashSet<i64>,
+) -> Result<usize, String> {
+    let app_state = app.state::<AppState>();
+    let (removed, updated_snapshot) = {
+        let mut messages_guard = app_state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        let before = messages_guard.len();
+        messages_guard.retain(|cached| !message_ids.contains(&cached.id));
+        (before - messages_guard.len(), messages_guard.clone())
+    };
+    if removed == 0 {
+        return Ok(0);
+    }
+    persist_messages_snapshot(app, &updated_snapshot)?;
+    crate::inbox::evaluate(app, &updated_snapshot);
+    let _ = crate::contract::publish_messages_replace(app, updated_snapshot);
+    Ok(removed)
+}
+
+/// Drops every cached message of one application; returns how many were removed.
+pub(crate) fn remove_app_messages_from_cache(
+    app: &AppHandle,
+    app_id: i64,
+) -> Result<usize, String> {
+    let app_state = app.state::<AppState>();
+    let (removed, updated_snapshot) = {
+        let mut messages_guard = app_state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        let before = messages_guard.len();
+        messages_guard.retain(|cached| cached.app_id != app_id);
+        (before - messages_guard.len(), messages_guard.clone())
+    };
+    if removed == 0 {
+        return Ok(0);
+    }
+    persist_messages_snapshot(app, &updated_snapshot)?;
+    crate::inbox::evaluate(app, &updated_snapshot);
+    let _ = crate::contract::publish_messages_replace(app, updated_snapshot);
+    Ok(removed)
+}
+
+/// Rewrites the app name and icon on cached messages from `app_meta` after an
+/// application was renamed or its image changed.
+pub(crate) fn apply_app_meta_to_cache(app: &AppHandle) -> Result<(), String> {
+    let app_state = app.state::<AppState>();
+    let meta = app_state
+        .app_meta
+        .lock()
+        .map_err(|_| "Application map lock poisoned".to_string())?
+        .clone();
+    let updated_snapshot = {
+        let mut messages_guard = app_state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        let mut changed = false;
+        for message in messages_guard.iter_mut() {
+            let Some(app_meta) = meta.get(&message.app_id) else {
+                continue;
+            };
+            let icon = Some(app_meta.icon_url.clone()).filter(|icon| !icon.trim().is_empty());
+            if message.app != app_meta.name || message.app_icon != icon {
+                message.app = app_meta.name.clone();
+                message.app_icon = icon;
+                changed = true;
+            }
+        }
+        if !changed {
+            return Ok(());
+        }
+        messages_guard.clone()
+    };
+    persist_messages_snapshot(app, &updated_snapshot)?;
+    let _ = crate::contract::publish_messages_replace(app, updated_snapshot);
+    Ok(())
+}
+
+/// Puts a message back into the cache (restore from trash) in date order.
+pub(crate) fn restore_message(app: &AppHandle, message: CachedMessage) -> Result<(), String> {
+    let app_state = app.state::<AppState>();
+    let updated_snapshot = {
+        let mut messages_guard = app_state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        messages_guard.retain(|cached| cached.id != message.id);
+        messages_guard.push(message);
+        messages_guard.sort_by(crate::cached_message_cmp);
+        messages_guard.clone()
+    };
+    persist_messages_snapshot(app, &updated_snapshot)?;
+    crate::inbox::evaluate(app, &updated_snapshot);
+    let _ = crate::contract::publish_messages_replace(app, updated_snapshot);
+    Ok(())
+}
+
+/// Marks the given ids read, or every cached message when `message_ids` is `None`.
+pub(crate) fn mark_messages_read(
+    app: &AppHandle,
+    message_ids: Option<Vec<i64>>,
+) -> Result<DomainSnapshot<Vec<CachedMessage>>, String> {
+    let app_state = app.state::<AppState>();
+    let now = unix_now_secs();
+    let updated_snapshot;
+    {
+        let mut messages_guard = app_state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        let mut changed = false;
+        for message in messages_guard.iter_mut().filter(|message| !message.read) {
+            if message_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&message.id))
+            {
+                message.read = true;
+                message.read_at = Some(now);
+                changed = true;
+            }
+        }
+        if !changed {
+            let revision = crate::contract::current_revision(app, RevisionKey::Messages);
+            return Ok(crate::contract::snapshot_at_revision(
+                revision,
+                messages_guard.clone(),
+            ));
+        }
+        updated_snapshot = messages_guard.clone();
+    }
+
+    persist_messages_snapshot(app, &updated_snapshot)?;
+    crate::inbox::evaluate(app, &updated_snapshot);
+    Ok(crate::contract::publish_messages_replace(
+        app,
+        updated_snapshot,
+    ))
+}
+
+/// Sets the local pinned flag on one cached message. Pinned messages are
+/// protected, so they survive cache truncation and retention pruning.
+pub(crate) fn set_pinned(
+    app: &AppHandle,
+    message_id: i64,
+    pinned: bool,
+) -> Result<DomainSnapshot<Vec<CachedMessage>>, String> {
+    let app_state = app.state::<AppState>();
+    let updated_snapshot;
+    {
+        let mut messages_guard = app_state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        let message = messages_guard
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| format!("Message {message_id} is not cached"))?;
+        if message.pinned == pinned {
+            let revision = crate::contract::current_revision(app, RevisionKey::Messages);
+            return Ok(crate::contract::snapshot_at_revision(
+                revision,
+                messages_guard.clone(),
+            ));
+        }
+        message.pinned = pinned;
+        updated_snapshot = messages_guard.clone();
+    }
+
+    persist_messages_snapshot(app, &updated_snapshot)?;
+    Ok(crate::contract::publish_messages_replace(
+        app,
+        updated_snapshot,
+    ))
+}
+
+/// Marks a message id as deleted so periodic syncs cannot resurrect it
+/// before the server-side delete has propagated.
+pub(crate) fn add_tombstone(app: &AppHandle, message_id: i64) {
+    let state = app.state::<AppState>();
+    if let Ok(mut tombstones) = state.delete_tombstones.lock() {
+        let now = unix_now_secs();
+        tombstones.retain(|_, expires_at| *expires_at > now);
+        tombstones.insert(message_id, now.saturating_add(DELETE_TOMBSTONE_TTL_SECS));
+    };
+}
+
+pub(crate) fn clear_tombstone(app: &AppHandle, message_id: i64) {
+    let state = app.state::<AppState>();
+    if let Ok(mut tombstones) = state.delete_tombstones.lock() {
+        tombstones.remove(&message_id);
+    };
+}
+
+pub(crate) fn is_cached(app: &AppHandle, message_id: i64) -> bool {
+    app.state::<AppState>()
+        .messages
+        .lock()
+        .map(|messages| messages.iter().any(|message| message.id == message_id))
+        .unwrap_or(false)
+}
+
+pub(crate) fn is_tombstoned(app: &AppHandle, message_id: i64) -> bool {
+    active_tombstones(app).contains(&message_id)
+}
+
+fn active_tombstones(app: &AppHandle) -> HashSet<i64> {
+    let Some(state) = app.try_state::<AppState>() else {
+        return HashSet::new();
+    };
+    let Ok(mut tombstones) = state.delete_tombstones.lock() else {
+        return HashSet::new();
+    };
+    let now = unix_now_secs();
+    tombstones.retain(|_, expires_at| *expires_at > now);
+    tombstones.keys().copied().collect()
+}
+
+/// Stores the current app names and icons with the active cache.
+fn persist_app_meta(app: &AppHandle) {
+    let Ok(cache_path) = messages_file(app) else {
+        return;
+    };
+    let Ok(applications) = app
+        .state::<AppState>()
+        .app_meta
+        .lock()
+        .map(|meta| meta.clone())
+    else {
+        return;
+    };
+    crate::work::run_in_background("persist-applications", move || {
+        if let Err(error) = crate::message_store::save_applications(&cache_path, &applications) {
+            debug_log(&format!("failed to persist applications: {error}"));
+        }
+    });
+}
+
+/// When the message store was last written, in unix ms.
+static LAST_MESSAGE_PERSIST_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Queues `snapshot` for the message store. Writes happen on the background
+/// worker at most once per `MESSAGE_PERSIST_DEBOUNCE_MS`; a burst of changes
+/// replaces the pending snapshot and lands as one write.
+///
+/// After a background write failed, the next snapshot is written inline instead,
+/// so its caller gets the error rather than having it only logged.
+pub(crate) fn persist_messages_snapshot(
+    app: &AppHandle,
+    snapshot: &[CachedMessage],
+) -> Result<(), String> {
+    let cache_path = messages_file(app)?;
+    let last_write_failed = app
+        .state::<AppState>()
+        .message_persist_error
+        .lock()
+        .map(|error| error.is_some())
+        .unwrap_or(false);
+    if last_write_failed {
+        if let Ok(mut pending) = app.state::<AppState>().pending_message_persist.lock() {
+            pending.remove(&cache_path);
+        }
+        return save_messages_snapshot(app, &cache_path, snapshot);
+    }
+    let scheduled = app
+        .state::<AppState>()
+        .pending_message_persist
+        .lock()
+        .map_err(|_| "Message persist queue lock poisoned".to_string())?
+        .insert(cache_path.clone(), snapshot.to_vec())
+        .is_some();
+    // A write is already scheduled for this store and will pick up this snapshot.
+    if scheduled {
+        return Ok(());
+    }
+
+    let wait_ms = LAST_MESSAGE_PERSIST_MS
+        .load(Ordering::SeqCst)
+        .saturating_add(MESSAGE_PERSIST_DEBOUNCE_MS)
+        .saturating_sub(crate::contract::now_ms());
+    if wait_ms == 0 {
+        queue_pending_messages_write(app, Some(cache_path));
+        return Ok(());
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+        queue_pending_messages_write(&app, Some(cache_path));
+    });
+    Ok(())
+}
+
+/// Queues every snapshot still waiting out its debounce for writing right away;
+/// used on quit and before a profile switch waits for the worker.
+pub(crate) fn flush_pending_messages(app: &AppHandle) {
+    queue_pending_messages_write(app, None);
+}
+
+/// Writes the pending snapshot of `cache_path`, or of every store for `None`.
+fn queue_pending_messages_write(app: &AppHandle, cache_path: Option<PathBuf>) {
+    let app = app.clone();
+    crate::work::run_in_background("persist-messages", move || {
+        let pending: Vec<(PathBuf, Vec<CachedMessage>)> = app
+            .state::<AppState>()
+            .pending_message_persist
+            .lock()
+            .map(|mut pending| match &cache_path {
+                Some(path) => pending.remove_entry(path).into_iter().collect(),
+                None => pending.drain().collect(),
+            })
+            .unwrap_or_default();
+        for (cache_path, snapshot) in pending {
+            if let Err(error) = save_messages_snapshot(&app, &cache_path, &snapshot) {
+                debug_log(&format!("failed to persist message cache: {error}"));
+            }
+        }
+    });
+}
+
+fn save_messages_snapshot(
+    app: &AppHandle,
+    cache_path: &Path,
+    snapshot: &[CachedMessage],
+) -> Result<(), String> {
     let app_state = app.state::<AppState>();
     let _persist_guard = app_state
         .message_persist_lock
         .lock()
         .map_err(|_| "Message persist lock poisoned".to_string())?;
-    let cache_path = messages_file(app)?;
-    persist_messages_to_path(&cache_path, snapshot)
+    LAST_MESSAGE_PERSIST_MS.store(crate::contract::now_ms(), Ordering::SeqCst);
+    let result = crate::message_store::save_messages(cache_path, snapshot);
+    if let Ok(mut last_error) = app_state.message_persist_error.lock() {
+        *last_error = result.as_ref().err().cloned();
+    }
+    result
 }
 
 pub(crate) fn load_messages_from_disk(app: &AppHandle) -> Result<Vec<CachedMessage>, String> {
-    let path = messages_file(app)?;
+    let mut messages = crate::message_store::load_messages(&messages_file(app)?)?;
+    prepare_loaded_messages(&mut messages);
+    Ok(messages)
+}
+
+/// App names and icons stored with the active cache; empty when there are none.
+pub(crate) fn load_app_meta_from_disk(app: &AppHandle) -> HashMap<i64, ApplicationMeta> {
+    messages_file(app)
+        .and_then(|path| crate::message_store::load_applications(&path))
+        .unwrap_or_else(|error| {
+            debug_log(&format!("failed to load stored applications: {error}"));
+            HashMap::new()
+        })
+}
+
+/// Fills in what older caches did not store.
+fn prepare_loaded_messages(messages: &mut [CachedMessage]) {
+    for message in messages.iter_mut() {
+        message.migrate_extras();
+        if !matches!(&message.language, Some(code) if crate::language::is_current_code(code)) {
+            message.language = crate::language::detect_language(&message.title, &message.message);
+        }
+    }
+}
+
+/// Reads a legacy `messages.json` cache, for the one-time import into the
+/// message store.
+///
+/// Entries are parsed one by one: valid messages are kept and only the ones
+/// that do not parse go to a sealed `.corrupt-*` backup. The whole file is
+/// moved aside only when it is not a readable JSON list at all.
+pub(crate) fn load_messages_from_path(path: &Path) -> Result<Vec<CachedMessage>, String> {
     if !path.exists() {
         return Ok(Vec::new());
     }
 
-    let content = fs::read_to_string(&path)
-        .map_err(|error| format!("Failed to read message cache: {error}"))?;
-    match serde_json::from_str::<Vec<CachedMessage>>(&content) {
-        Ok(messages) => Ok(messages),
+    let bytes = fs::read(path).map_err(|error| format!("Failed to read message cache: {error}"))?;
+    let parsed = crate::at_rest::open(MESSAGES_LABEL, bytes).and_then(|content| {
+        serde_json::from_slice::<Vec<serde_json::Value>>(&content)
+            .map_err(|error| error.to_string())
+    });
+    match parsed {
+        Ok(entries) => {
+            let mut messages = Vec::with_capacity(entries.len());
+            let mut unparseable = Vec::new();
+            for entry in entries {
+                match CachedMessage::deserialize(&entry) {
+                    Ok(message) => messages.push(message),
+                    Err(_) => unparseable.push(entry),
+                }
+            }
+            if !unparseable.is_empty() {
+                quarantine_cache_entries(path, &unparseable);
+            }
+            prepare_loaded_messages(&mut messages);
+            Ok(messages)
+        }
         Err(error) => {
             let backup_path =
                 path.with_extension(format!("corrupt-{}.json", crate::unique_time_suffix()));
-            if let Err(rename_error) = fs::rename(&path, &backup_path) {
+            if let Err(rename_error) = fs::rename(path, &backup_path) {
                 debug_log(&format!(
                     "failed to back up corrupt cache file: {rename_error}"
                 ));
@@ -371,18 +1395,28 @@ pub(crate) fn load_messages_from_disk(app: &AppHandle) -> Result<Vec<CachedMessa
     }
 }
 
-pub(crate) fn persist_messages_to_path(
-    path: &PathBuf,
-    messages: &[CachedMessage],
-) -> Result<(), String> {
-    let content = serde_json::to_string(messages)
-        .map_err(|error| format!("Failed to serialize message cache: {error}"))?;
-    let tmp_path = path.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
-    fs::write(&tmp_path, content)
-        .map_err(|error| format!("Failed to write message cache temp file: {error}"))?;
-    crate::restrict_file_permissions(&tmp_path);
-    fs::rename(&tmp_path, path)
-        .map_err(|error| format!("Failed to atomically replace message cache: {error}"))
+/// Writes cache entries that did not parse next to `path`, sealed like the
+/// cache, so they can be inspected without keeping them in the cache.
+fn quarantine_cache_entries(path: &Path, entries: &[serde_json::Value]) {
+    let backup_path = path.with_extension(format!("corrupt-{}.json", crate::unique_time_suffix()));
+    let result = serde_json::to_vec(entries)
+        .map_err(|error| error.to_string())
+        .and_then(|content| crate::at_rest::seal(MESSAGES_LABEL, &content))
+        .and_then(|sealed| fs::write(&backup_path, sealed).map_err(|error| error.to_string()));
+    match result {
+        Ok(()) => {
+            crate::restrict_file_permissions(&backup_path);
+            debug_log(&format!(
+                "cache: kept valid messages, moved {} unparseable entries to {}",
+                entries.len(),
+                backup_path.to_string_lossy()
+            ));
+        }
+        Err(error) => debug_log(&format!(
+            "cache: failed to back up {} unparseable entries: {error}",
+            entries.len()
+        )),
+    }
 }
 
 pub(crate) fn resolve_application_image_url(
@@ -403,23 +1437,59 @@ pub(crate) fn resolve_application_image_url(
     Ok(base.to_string())
 }
 
+pub(crate) enum ApplicationIcon {
+    /// The server confirmed the cached icon still matches its validators.
+    NotModified,
+    Downloaded {
+        icon_url: String,
+        validators: IconValidators,
+    },
+}
+
+fn header_string(
+    headers: &reqwest::header::HeaderMap,
+    name: reqwest::header::HeaderName,
+) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Downloads an application icon as a data URL. With `cached` validators the
+/// request is conditional and an unchanged icon comes back as `NotModified`.
 pub(crate) async fn resolve_application_image_data_url(
     client: &reqwest::Client,
     base_url: &str,
     image_path: &str,
     token: &str,
-) -> Result<String, String> {
+    cached: Option<&IconValidators>,
+) -> Result<ApplicationIcon, String> {
     let image_url = resolve_application_image_url(base_url, image_path)?;
     if image_url.is_empty() {
-        return Ok(String::new());
+        return Ok(ApplicationIcon::Downloaded {
+            icon_url: String::new(),
+            validators: IconValidators::default(),
+        });
     }
 
-    let response = client
-        .get(&image_url)
-        .header("X-Gotify-Key", token)
-        .send()
+    let mut request = client.get(&image_url).header("X-Gotify-Key", token);
+    let conditional = cached
+        .is_some_and(|validators| validators.etag.is_some() || validators.last_modified.is_some());
+    if let Some(validators) = cached {
+        if let Some(etag) = &validators.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let response = crate::retry::send(request)
         .await
         .map_err(|error| format!("Application icon request failed: {error}"))?;
+    if conditional && response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(ApplicationIcon::NotModified);
+    }
     if !response.status().is_success() {
         return Err(format!(
             "Application icon request failed with HTTP {}",
@@ -447,12 +1517,19 @@ pub(crate) async fn resolve_application_image_data_url(
         ));
     }
 
+    let validators = IconValidators {
+        etag: header_string(response.headers(), reqwest::header::ETAG),
+        last_modified: header_string(response.headers(), reqwest::header::LAST_MODIFIED),
+    };
     let bytes = response
         .bytes()
         .await
         .map_err(|error| format!("Failed to read application icon body: {error}"))?;
     if bytes.is_empty() {
-        return Ok(String::new());
+        return Ok(ApplicationIcon::Downloaded {
+            icon_url: String::new(),
+            validators: IconValidators::default(),
+        });
     }
     if bytes.len() > APP_ICON_MAX_BYTES {
         return Err(format!(
@@ -462,5 +1539,66 @@ pub(crate) async fn resolve_application_image_data_url(
     }
 
     let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
-    Ok(format!("data:{content_type};base64,{encoded}"))
+    Ok(ApplicationIcon::Downloaded {
+        icon_url: format!("data:{content_type};base64,{encoded}"),
+        validators,
+    })
+}
+
+/// Adds a message from a background stream to that profile's message store.
+/// Runs on the serial worker, so it cannot interleave with a profile switch's
+/// flush. `on_new` runs only when the id was not cached yet.
+pub(crate) fn append_profile_message(
+    app: &AppHandle,
+    profile: &str,
+    message: CachedMessage,
+    on_new: impl FnOnce() + Send + 'static,
+) {
+    let Ok(path) = crate::profile_messages_file(app, profile) else {
+        return;
+    };
+    let cache_limit = crate::desired_cache_limit(app);
+    let profile = profile.to_string();
+    crate::work::run_in_background("persist-background-message", move || {
+        match crate::message_store::insert_message(&path, &message, cache_limit) {
+            Ok(true) => on_new(),
+            Ok(false) => {}
+            Err(error) => debug_log(&format!(
+                "background stream {profile:?}: failed to write cache: {error}"
+            )),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(id: i64) -> CachedMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": "Backup",
+            "message": "done",
+            "priority": 5,
+            "date": "2026-01-01T00:00:00Z",
+            "read": false,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn sync_keeps_read_at_of_read_messages() {
+        let mut read = cached(1);
+        read.read = true;
+        read.read_at = Some(1_700_000_000);
+        let previous = vec![read, cached(2)];
+        let mut fresh = vec![cached(1), cached(2)];
+
+        carry_read_state(&previous, &mut fresh);
+
+        assert!(fresh[0].read);
+        assert_eq!(fresh[0].read_at, Some(1_700_000_000));
+        assert!(!fresh[1].read);
+        assert_eq!(fresh[1].read_at, None);
+    }
 }
@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    contract::now_ms, debug_log, AppState, CONNECTION_LATENCY_MAX_SAMPLES,
+    DELIVERY_METRICS_MAX_SAMPLES,
+};
+
+/// One delivered notification, split into the server-to-client leg and the
+/// local pipeline leg so slow deliveries can be attributed.
+#[derive(Debug, Clone)]
+pub(crate) struct DeliverySample {
+    pub(crate) server_to_receive_ms: Option<u64>,
+    pub(crate) receive_to_notify_ms: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct LatencySummary {
+    pub(crate) count: usize,
+    pub(crate) min_ms: u64,
+    pub(crate) p50_ms: u64,
+    pub(crate) p90_ms: u64,
+    pub(crate) p99_ms: u64,
+    pub(crate) max_ms: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct DeliveryMetrics {
+    pub(crate) sample_count: usize,
+    pub(crate) server_to_receive: Option<LatencySummary>,
+    pub(crate) receive_to_notify: Option<LatencySummary>,
+    pub(crate) server_to_notify: Option<LatencySummary>,
+}
+
+pub(crate) fn record_delivery(app: &AppHandle, message_date: &str, received_at_ms: u64) {
+    let delivered_at_ms = now_ms();
+    let server_to_receive_ms = chrono::DateTime::parse_from_rfc3339(message_date)
+        .ok()
+        .and_then(|date| u64::try_from(date.timestamp_millis()).ok())
+        .map(|server_ms| received_at_ms.saturating_sub(server_ms));
+    let sample = DeliverySample {
+        server_to_receive_ms,
+        receive_to_notify_ms: delivered_at_ms.saturating_sub(received_at_ms),
+    };
+
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let Ok(mut samples) = state.delivery_samples.lock() else {
+        debug_log("delivery metrics lock poisoned");
+        return;
+    };
+    if samples.len() >= DELIVERY_METRICS_MAX_SAMPLES {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+pub(crate) fn delivery_metrics(app: &AppHandle) -> Result<DeliveryMetrics, String> {
+    let state = app.state::<AppState>();
+    let samples = state
+        .delivery_samples
+        .lock()
+        .map_err(|_| "Delivery metrics lock poisoned".to_string())?;
+
+    let server_to_receive: Vec<u64> = samples
+        .iter()
+        .filter_map(|sample| sample.server_to_receive_ms)
+        .collect();
+    let receive_to_notify: Vec<u64> = samples
+        .iter()
+        .map(|sample| sample.receive_to_notify_ms)
+        .collect();
+    let server_to_notify: Vec<u64> = samples
+        .iter()
+        .filter_map(|sample| {
+            sample
+                .server_to_receive_ms
+                .map(|network| network.saturating_add(sample.receive_to_notify_ms))
+        })
+        .collect();
+
+    Ok(DeliveryMetrics {
+        sample_count: samples.len(),
+        server_to_receive: summarize(server_to_receive),
+        receive_to_notify: summarize(receive_to_notify),
+        server_to_notify: summarize(server_to_notify),
+    })
+}
+
+fn summarize(mut values: Vec<u64>) -> Option<LatencySummary> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let percentile = |p: usize| values[(values.len() - 1) * p / 100];
+    Some(LatencySummary {
+        count: values.len(),
+        min_ms: values[0],
+        p50_ms: percentile(50),
+        p90_ms: percentile(90),
+        p99_ms: percentile(99),
+        max_ms: values[values.len() - 1],
+    })
+}
+
+/// Last `CONNECTION_LATENCY_MAX_SAMPLES` durations of one stream measurement.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyWindow {
+    samples: VecDeque<u64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct RollingLatency {
+    pub(crate) count: usize,
+    pub(crate) last_ms: u64,
+    pub(crate) min_ms: u64,
+    pub(crate) avg_ms: u64,
+    pub(crate) max_ms: u64,
+}
+
+impl LatencyWindow {
+    pub(crate) fn record(&mut self, elapsed_ms: u64) {
+        if self.samples.len() >= CONNECTION_LATENCY_MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(elapsed_ms);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub(crate) fn summary(&self) -> Option<RollingLatency> {
+        let last_ms = *self.samples.back()?;
+        let count = self.samples.len();
+        Some(RollingLatency {
+            count,
+            last_ms,
+            min_ms: self.samples.iter().copied().min().unwrap_or(last_ms),
+            avg_ms: self.samples.iter().sum::<u64>() / count as u64,
+            max_ms: self.samples.iter().copied().max().unwrap_or(last_ms),
+        })
+    }
+}
@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::{collections::HashMap, sync::Mutex};
+use serde_json::{Map, Value};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+    sync::Mutex,
+};
 use tauri::ipc::Channel;
-use tauri::menu::MenuItem;
-use tokio::sync::watch;
+use tauri::menu::{CheckMenuItem, MenuItem, Submenu};
+use tokio::sync::{watch, Notify};
 
 #[derive(Clone)]
 pub(crate) struct TrayPauseMenuState {
@@ -18,11 +22,49 @@ pub(crate) struct AppState {
     pub(crate) runtime: Mutex<RuntimeState>,
     pub(crate) messages: Mutex<Vec<CachedMessage>>,
     pub(crate) app_meta: Mutex<HashMap<i64, ApplicationMeta>>,
+    /// `ETag` of the last `/application` list, for `If-None-Match`.
+    pub(crate) application_list_etag: Mutex<Option<String>>,
+    /// Recently deleted message ids mapped to their expiry (unix secs).
+    pub(crate) delete_tombstones: Mutex<HashMap<i64, u64>>,
     pub(crate) tray_pause_menu: Mutex<Option<TrayPauseMenuState>>,
     pub(crate) revisions: Mutex<RevisionState>,
     pub(crate) update_channels: Mutex<HashMap<String, Channel<Value>>>,
     pub(crate) settings_lock: Mutex<()>,
     pub(crate) message_persist_lock: Mutex<()>,
+    /// Latest cache snapshot waiting for the background writer, per store; bursts
+    /// coalesce here until that store's debounce ends.
+    pub(crate) pending_message_persist: Mutex<HashMap<PathBuf, Vec<CachedMessage>>>,
+    /// Error of the last message store write, cleared by the next successful one.
+    pub(crate) message_persist_error: Mutex<Option<String>>,
+    pub(crate) delivery_samples: Mutex<VecDeque<crate::metrics::DeliverySample>>,
+    /// Bounded timeline of stream connects, drops and backoffs.
+    pub(crate) connection_history: Mutex<VecDeque<crate::connection_history::ConnectionEvent>>,
+    /// Last settings.json contents this process wrote or loaded, used to spot external edits.
+    pub(crate) settings_on_disk: Mutex<Option<Value>>,
+    pub(crate) pending_notifications: Mutex<Vec<crate::notification_queue::PendingEntry>>,
+    /// Mirrors `active_profile`; selects the message cache file.
+    pub(crate) active_profile: Mutex<Option<String>>,
+    pub(crate) tray_profiles_menu: Mutex<Option<Submenu<tauri::Wry>>>,
+    pub(crate) tray_contexts_menu: Mutex<Option<Submenu<tauri::Wry>>>,
+    pub(crate) inbox_advisory: Mutex<crate::inbox::InboxAdvisoryState>,
+    pub(crate) tray_privacy_item: Mutex<Option<CheckMenuItem<tauri::Wry>>>,
+    pub(crate) tray_health_item: Mutex<Option<MenuItem<tauri::Wry>>>,
+    /// `/version` of the connected server, refreshed on every stream connect.
+    pub(crate) server_info: Mutex<Option<crate::server_info::ServerInfo>>,
+    /// Deleted messages awaiting purge, newest first.
+    pub(crate) trash: Mutex<Vec<crate::trash::TrashedMessage>>,
+    /// Ids hidden on this Mac only with `dismiss_message`; never deleted on the server.
+    pub(crate) dismissed: Mutex<HashSet<i64>>,
+    /// Earliest snooze end (unix secs) per non-active profile that has one;
+    /// `None` until their cache files are scanned again.
+    pub(crate) profile_snoozes: Mutex<Option<HashMap<String, u64>>>,
+    /// Cancellable work in flight (bulk operations, previews, syncs), keyed by operation id.
+    pub(crate) operations: Mutex<HashMap<u64, crate::operations::OperationEntry>>,
+    /// Cuts a stream backoff sleep short: the network came back or the user
+    /// asked to reconnect now.
+    pub(crate) network_wake: Notify,
+    /// Cuts the health check sleep short so a new interval applies at once.
+    pub(crate) health_wake: Notify,
 }
 
 impl AppState {
@@ -31,11 +73,32 @@ impl AppState {
             runtime: Mutex::new(RuntimeState::default()),
             messages: Mutex::new(messages),
             app_meta: Mutex::new(HashMap::new()),
+            application_list_etag: Mutex::new(None),
+            delete_tombstones: Mutex::new(HashMap::new()),
             tray_pause_menu: Mutex::new(None),
             revisions: Mutex::new(RevisionState::default()),
             update_channels: Mutex::new(HashMap::new()),
             settings_lock: Mutex::new(()),
             message_persist_lock: Mutex::new(()),
+            pending_message_persist: Mutex::new(HashMap::new()),
+            message_persist_error: Mutex::new(None),
+            delivery_samples: Mutex::new(VecDeque::new()),
+            connection_history: Mutex::new(VecDeque::new()),
+            settings_on_disk: Mutex::new(None),
+            pending_notifications: Mutex::new(Vec::new()),
+            active_profile: Mutex::new(None),
+            tray_profiles_menu: Mutex::new(None),
+            tray_contexts_menu: Mutex::new(None),
+            inbox_advisory: Mutex::new(crate::inbox::InboxAdvisoryState::default()),
+            tray_privacy_item: Mutex::new(None),
+            tray_health_item: Mutex::new(None),
+            server_info: Mutex::new(None),
+            trash: Mutex::new(Vec::new()),
+            dismissed: Mutex::new(HashSet::new()),
+            profile_snoozes: Mutex::new(None),
+            operations: Mutex::new(HashMap::new()),
+            network_wake: Notify::new(),
+            health_wake: Notify::new(),
         }
     }
 }
@@ -48,6 +111,8 @@ pub(crate) enum RevisionKey {
     Connection,
     Runtime,
     StreamError,
+    PendingNotifications,
+    Trash,
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +123,8 @@ pub(crate) struct RevisionState {
     pub(crate) connection: u64,
     pub(crate) runtime: u64,
     pub(crate) stream_error: u64,
+    pub(crate) pending_notifications: u64,
+    pub(crate) trash: u64,
 }
 
 impl RevisionState {
@@ -69,6 +136,8 @@ impl RevisionState {
             RevisionKey::Connection => self.connection,
             RevisionKey::Runtime => self.runtime,
             RevisionKey::StreamError => self.stream_error,
+            RevisionKey::PendingNotifications => self.pending_notifications,
+            RevisionKey::Trash => self.trash,
         }
     }
 
@@ -80,6 +149,8 @@ impl RevisionState {
             RevisionKey::Connection => &mut self.connection,
             RevisionKey::Runtime => &mut self.runtime,
             RevisionKey::StreamError => &mut self.stream_error,
+            RevisionKey::PendingNotifications => &mut self.pending_notifications,
+            RevisionKey::Trash => &mut self.trash,
         };
         *slot = slot.saturating_add(1);
         *slot
@@ -95,12 +166,35 @@ impl Default for RevisionState {
             connection: 1,
             runtime: 1,
             stream_error: 1,
+            pending_notifications: 1,
+            trash: 1,
         }
     }
 }
 
+/// One running stream loop. The active server's connection state lives on
+/// `RuntimeState` itself; a background profile keeps its own in `background`.
+pub(crate) struct StreamSlot {
+    pub(crate) stop_tx: watch::Sender<bool>,
+    pub(crate) epoch: u64,
+    pub(crate) background: Option<BackgroundStreamStatus>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct BackgroundStreamStatus {
+    pub(crate) profile: String,
+    pub(crate) connection_state: String,
+    pub(crate) last_connected_at: Option<u64>,
+    pub(crate) last_message_at: Option<u64>,
+    pub(crate) last_error: Option<String>,
+    pub(crate) reconnect_attempts: u64,
+}
+
 pub(crate) struct RuntimeState {
-    pub(crate) stop_tx: Option<watch::Sender<bool>>,
+    /// Running stream loops by profile name (`""` when no profile is saved).
+    /// At most one is the foreground stream of the active server.
+    pub(crate) streams: HashMap<String, StreamSlot>,
+    /// Source of `StreamSlot::epoch`, so a finished loop only clears its own slot.
     pub(crate) stream_epoch: u64,
     pub(crate) connection_state: String,
     pub(crate) should_run: bool,
@@ -111,12 +205,37 @@ pub(crate) struct RuntimeState {
     pub(crate) last_error: Option<String>,
     pub(crate) backoff_seconds: u64,
     pub(crate) reconnect_attempts: u64,
+    pub(crate) clock_skew_seconds: Option<i64>,
+    pub(crate) clock_skew_warned: bool,
+    /// True while the first sync into an empty cache is running; notifications are held back.
+    pub(crate) initial_sync_pending: bool,
+    /// Highest message id populated by the initial sync; older ids never notify.
+    pub(crate) notify_watermark_id: Option<i64>,
+    pub(crate) last_heartbeat_at: Option<u64>,
+    /// End of the scheduled pause window already applied, so a manual resume sticks.
+    pub(crate) scheduled_pause_applied_until: Option<u64>,
+    /// Address the stream socket connected to, after family selection.
+    pub(crate) stream_remote_addr: Option<String>,
+    /// Base URL chosen by the LAN/WAN probe; `None` means the configured URL.
+    pub(crate) active_endpoint: Option<String>,
+    /// Latest `GET /health` result: `green`/`orange`/`red`, or `unreachable`.
+    pub(crate) server_health: Option<String>,
+    pub(crate) database_health: Option<String>,
+    pub(crate) last_health_check_at: Option<u64>,
+    /// TCP connect through websocket upgrade, per successful connect.
+    pub(crate) handshake_latency: crate::metrics::LatencyWindow,
+    /// Round trip of the stream's own pings.
+    pub(crate) ping_rtt: crate::metrics::LatencyWindow,
+    /// Times the watchdog restarted a stuck stream, and the latest reason.
+    pub(crate) watchdog_recoveries: u64,
+    pub(crate) last_watchdog_recovery: Option<String>,
+    pub(crate) last_watchdog_recovery_at: Option<u64>,
 }
 
 impl Default for RuntimeState {
     fn default() -> Self {
         Self {
-            stop_tx: None,
+            streams: HashMap::new(),
             stream_epoch: 0,
             connection_state: "Disconnected".to_string(),
             should_run: false,
@@ -127,7 +246,73 @@ impl Default for RuntimeState {
             last_error: None,
             backoff_seconds: 0,
             reconnect_attempts: 0,
+            clock_skew_seconds: None,
+            clock_skew_warned: false,
+            initial_sync_pending: false,
+            notify_watermark_id: None,
+            last_heartbeat_at: None,
+            scheduled_pause_applied_until: None,
+            stream_remote_addr: None,
+            active_endpoint: None,
+            server_health: None,
+            database_health: None,
+            last_health_check_at: None,
+            handshake_latency: crate::metrics::LatencyWindow::default(),
+            ping_rtt: crate::metrics::LatencyWindow::default(),
+            watchdog_recoveries: 0,
+            last_watchdog_recovery: None,
+            last_watchdog_recovery_at: None,
+        }
+    }
+}
+
+impl RuntimeState {
+    pub(crate) fn has_foreground_stream(&self) -> bool {
+        self.streams.values().any(|slot| slot.background.is_none())
+    }
+
+    /// Removes the foreground slot and signals its loop to stop.
+    pub(crate) fn stop_foreground_stream(&mut self) {
+        let key = self
+            .streams
+            .iter()
+            .find(|(_, slot)| slot.background.is_none())
+            .map(|(key, _)| key.clone());
+        if let Some(slot) = key.and_then(|key| self.streams.remove(&key)) {
+            let _ = slot.stop_tx.send(true);
+        }
+    }
+
+    /// Why the foreground stream looks stuck: it should run but has no loop,
+    /// or it claims to be up but has been silent for
+    /// `STREAM_WATCHDOG_SILENCE_SECS`.
+    pub(crate) fn stall_reason(&self, now: u64) -> Option<String> {
+        if !self.should_run || self.connection_state == crate::CONNECTION_STATE_AUTH_FAILED {
+            return None;
         }
+        if !self.has_foreground_stream() {
+            return Some("stream task missing".to_string());
+        }
+        if !matches!(
+            self.connection_state.as_str(),
+            "Connected" | crate::CONNECTION_STATE_POLLING
+        ) {
+            return None;
+        }
+        let last_seen = self.last_stream_event_at.max(self.last_connected_at)?;
+        let silent_for = now.saturating_sub(last_seen);
+        (silent_for >= crate::STREAM_WATCHDOG_SILENCE_SECS)
+            .then(|| format!("{} but silent for {silent_for}s", self.connection_state))
+    }
+
+    pub(crate) fn background_streams(&self) -> Vec<BackgroundStreamStatus> {
+        let mut statuses: Vec<BackgroundStreamStatus> = self
+            .streams
+            .values()
+            .filter_map(|slot| slot.background.clone())
+            .collect();
+        statuses.sort_by(|a, b| a.profile.cmp(&b.profile));
+        statuses
     }
 }
 
@@ -153,6 +338,106 @@ pub(crate) struct CachedMessage {
     #[serde(default)]
     pub(crate) app_icon: Option<String>,
     pub(crate) date: String,
+    #[serde(default)]
+    pub(crate) language: Option<String>,
+    /// Local-only flags; protected messages are never evicted from the cache.
+    #[serde(default)]
+    pub(crate) starred: bool,
+    #[serde(default)]
+    pub(crate) archived: bool,
+    /// Pinned by the user from the inbox so it stays at hand.
+    #[serde(default)]
+    pub(crate) pinned: bool,
+    /// Unix seconds when a snooze ends. Until then the message is left out of
+    /// unread counts; `snooze::wake_due` clears it and notifies again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) snoozed_until: Option<u64>,
+    /// User-assigned triage labels ("handled", "follow-up"), normalized by
+    /// `labels::normalize_labels`. Local only; they go out with exports.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) labels: Vec<String>,
+    /// Local read state. Messages cached before it was tracked count as read.
+    #[serde(default = "read_by_default")]
+    pub(crate) read: bool,
+    /// Unix seconds when it was marked read here. `None` while unread, and for
+    /// messages that arrived read (initial sync, imports, older caches).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) read_at: Option<u64>,
+    /// Merged from another client's history. Kept when the server no longer
+    /// has it and never evicted, like starred, archived and pinned messages.
+    #[serde(default)]
+    pub(crate) imported: bool,
+    /// Gotify `extras` (`client::display`, `client::notification`, ...) as sent;
+    /// null when the message has none.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub(crate) extras: Value,
+    /// Derived from `extras`: the sender marked the body as markdown, so the
+    /// inbox renders it and notifications show it flattened to plain text.
+    #[serde(default)]
+    pub(crate) markdown: bool,
+    /// Server fields this client does not model (whatever a newer Gotify
+    /// adds), kept as received. Nested rather than flattened so a
+    /// future server field can never collide with the local flags above.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub(crate) extra: Map<String, Value>,
+}
+
+fn read_by_default() -> bool {
+    true
+}
+
+impl CachedMessage {
+    pub(crate) fn is_protected(&self) -> bool {
+        self.starred
+            || self.archived
+            || self.pinned
+            || self.imported
+            || self.snoozed_until.is_some()
+    }
+
+    pub(crate) fn is_snoozed(&self, now_secs: u64) -> bool {
+        self.snoozed_until.is_some_and(|until| until > now_secs)
+    }
+
+    /// `client::notification.click.url`: where a click on the notification
+    /// should take the user instead of the inbox.
+    pub(crate) fn click_url(&self) -> Option<&str> {
+        self.extras
+            .pointer("/client::notification/click/url")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+    }
+
+    /// `android::action.onReceive.intentUrl`: what the Android app opens when
+    /// the message arrives.
+    pub(crate) fn on_receive_intent_url(&self) -> Option<&str> {
+        self.extras
+            .pointer("/android::action/onReceive/intentUrl")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+    }
+
+    /// `client::notification.bigImageUrl`: an image to show in the banner.
+    pub(crate) fn big_image_url(&self) -> Option<&str> {
+        self.extras
+            .pointer("/client::notification/bigImageUrl")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+    }
+
+    /// Caches written before `extras` had its own field kept it in `extra`,
+    /// and older ones never derived `markdown` from it.
+    pub(crate) fn migrate_extras(&mut self) {
+        if self.extras.is_null() {
+            if let Some(extras) = self.extra.remove("extras") {
+                self.extras = extras;
+            }
+        }
+        self.markdown = crate::markdown::declares_markdown(&self.extras);
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -166,6 +451,10 @@ pub(crate) struct GotifyMessageWire {
     pub(crate) priority: i64,
     #[serde(default)]
     pub(crate) date: String,
+    #[serde(default)]
+    pub(crate) extras: Value,
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -186,4 +475,15 @@ pub(crate) struct GotifyApplicationWire {
 pub(crate) struct ApplicationMeta {
     pub(crate) name: String,
     pub(crate) icon_url: String,
+    /// Server image path the icon came from; Gotify gives a new upload a new path.
+    pub(crate) image: String,
+    pub(crate) icon_validators: IconValidators,
+}
+
+/// Cache validators from the last icon download, sent back as `If-None-Match`
+/// / `If-Modified-Since` so an unchanged icon is not downloaded again.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IconValidators {
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
 }
@@ -0,0 +1,170 @@
+use core_foundation::{
+    array::CFArray,
+    base::{CFType, TCFType, ToVoid},
+    dictionary::CFDictionary,
+    propertylist::CFPropertyList,
+    runloop::{kCFRunLoopCommonModes, CFRunLoop},
+    string::CFString,
+};
+use system_configuration::dynamic_store::{
+    SCDynamicStore, SCDynamicStoreBuilder, SCDynamicStoreCallBackContext,
+};
+use tauri::AppHandle;
+
+use crate::debug_log;
+
+/// Global IPv4/IPv6 state; `PrimaryInterface` in it is the default route and
+/// `Router` its gateway.
+const GLOBAL_IPV4_KEY: &str = "State:/Network/Global/IPv4";
+const GLOBAL_IPV6_KEY: &str = "State:/Network/Global/IPv6";
+/// Per-interface state holding its `Addresses`.
+const INTERFACE_IPV4_PATTERN: &str = "State:/Network/Interface/[^/]+/IPv4";
+const INTERFACE_IPV6_PATTERN: &str = "State:/Network/Interface/[^/]+/IPv6";
+
+/// What identifies the current route. Joining another Wi-Fi network or getting
+/// a new DHCP lease keeps the interface (`en0`) but changes the router or the
+/// addresses, and the old socket is just as dead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RouteState {
+    primary_interface: Option<String>,
+    ipv4_router: Option<String>,
+    ipv6_router: Option<String>,
+    /// Addresses of the primary interface, both families, sorted.
+    addresses: Vec<String>,
+}
+
+struct WatchContext {
+    app: AppHandle,
+    route: RouteState,
+}
+
+/// Subscribes to default-route changes in the SystemConfiguration dynamic
+/// store. The store delivers callbacks on a run loop, so it gets its own thread.
+pub(crate) fn watch_network(app: AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("network-watch".to_string())
+        .spawn(move || run(app));
+    if let Err(error) = spawned {
+        debug_log(&format!("network watch: failed to start: {error}"));
+    }
+}
+
+fn run(app: AppHandle) {
+    let Some(probe) = SCDynamicStoreBuilder::new("gotify-desktop-network-probe").build() else {
+        debug_log("network watch: dynamic store unavailable");
+        return;
+    };
+    let context = SCDynamicStoreCallBackContext {
+        callout: on_change,
+        info: WatchContext {
+            app,
+            route: route_state(&probe),
+        },
+    };
+    let Some(store) = SCDynamicStoreBuilder::new("gotify-desktop-network")
+        .callback_context(context)
+        .build()
+    else {
+        debug_log("network watch: dynamic store unavailable");
+        return;
+    };
+    let keys = CFArray::from_CFTypes(&[
+        CFString::from_static_string(GLOBAL_IPV4_KEY),
+        CFString::from_static_string(GLOBAL_IPV6_KEY),
+    ]);
+    let patterns = CFArray::from_CFTypes(&[
+        CFString::from_static_string(INTERFACE_IPV4_PATTERN),
+        CFString::from_static_string(INTERFACE_IPV6_PATTERN),
+    ]);
+    if !store.set_notification_keys(&keys, &patterns) {
+        debug_log("network watch: failed to register for route changes");
+        return;
+    }
+    let Some(source) = store.create_run_loop_source() else {
+        debug_log("network watch: failed to create run loop source");
+        return;
+    };
+    CFRunLoop::get_current().add_source(&source, unsafe { kCFRunLoopCommonModes });
+    CFRunLoop::run_current();
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn on_change(store: SCDynamicStore, _changed_keys: CFArray<CFString>, context: &mut WatchContext) {
+    let route = route_state(&store);
+    if route == context.route {
+        return;
+    }
+    debug_log(&format!(
+        "network watch: route {:?} -> {:?}",
+        context.route, route
+    ));
+    context.route = route;
+    // Losing the route is left to the stream's own error handling; only a new
+    // or returning route is worth reconnecting for.
+    if context.route.primary_interface.is_some() {
+        crate::stream::network_changed(&context.app);
+    }
+}
+
+fn dictionary(store: &SCDynamicStore, key: &str) -> Option<CFDictionary> {
+    store
+        .get(CFString::new(key))
+        .and_then(CFPropertyList::downcast_into::<CFDictionary>)
+}
+
+fn string_value(state: &CFDictionary, name: &'static str) -> Option<String> {
+    state
+        .find(CFString::from_static_string(name).to_void())
+        .map(|value| unsafe { CFType::wrap_under_get_rule(*value) })
+        .and_then(CFType::downcast_into::<CFString>)
+        .map(|value| value.to_string())
+}
+
+fn string_list(state: &CFDictionary, name: &'static str) -> Vec<String> {
+    state
+        .find(CFString::from_static_string(name).to_void())
+        .map(|value| unsafe { CFType::wrap_under_get_rule(*value) })
+        .and_then(CFType::downcast_into::<CFArray>)
+        .map(|list| {
+            list.iter()
+                .map(|item| unsafe { CFType::wrap_under_get_rule(*item) })
+                .filter_map(CFType::downcast_into::<CFString>)
+                .map(|item| item.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Default route, IPv4 first, with its routers and addresses; the interface
+/// is `None` when offline.
+fn route_state(store: &SCDynamicStore) -> RouteState {
+    let ipv4 = dictionary(store, GLOBAL_IPV4_KEY);
+    let ipv6 = dictionary(store, GLOBAL_IPV6_KEY);
+    let primary_interface = [&ipv4, &ipv6]
+        .into_iter()
+        .flatten()
+        .find_map(|state| string_value(state, "PrimaryInterface"));
+    let mut addresses: Vec<String> = primary_interface
+        .iter()
+        .flat_map(|interface| {
+            ["IPv4", "IPv6"].into_iter().filter_map(move |family| {
+                dictionary(
+                    store,
+                    &format!("State:/Network/Interface/{interface}/{family}"),
+                )
+            })
+        })
+        .flat_map(|state| string_list(&state, "Addresses"))
+        .collect();
+    addresses.sort();
+    RouteState {
+        primary_interface,
+        ipv4_router: ipv4
+            .as_ref()
+            .and_then(|state| string_value(state, "Router")),
+        ipv6_router: ipv6
+            .as_ref()
+            .and_then(|state| string_value(state, "Router")),
+        addresses,
+    }
+}
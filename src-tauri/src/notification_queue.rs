@@ -0,0 +1,173 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    contract::{self, DomainSnapshot},
+    debug_log,
+    notifications::{is_quiet_hours, maybe_notify_message},
+    settings::read_settings,
+    unix_now_secs, AppState, CachedMessage,
+};
+
+static NEXT_ENTRY_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PendingReason {
+    /// Held during quiet hours and delivered once they end.
+    QuietHours,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct PendingNotification {
+    pub(crate) entry_id: u64,
+    pub(crate) message_id: i64,
+    pub(crate) title: String,
+    pub(crate) app: String,
+    pub(crate) priority: i64,
+    pub(crate) reason: PendingReason,
+    pub(crate) queued_at: u64,
+    /// Unix secs when the entry is due; `None` when it waits on a condition instead.
+    pub(crate) deliver_at: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PendingEntry {
+    info: PendingNotification,
+    message: CachedMessage,
+    /// `None` for a re-notification; see `maybe_notify_message`.
+    received_at_ms: Option<u64>,
+}
+
+pub(crate) fn enqueue(
+    app: &AppHandle,
+    message: &CachedMessage,
+    received_at_ms: Option<u64>,
+    reason: PendingReason,
+    deliver_at: Option<u64>,
+) {
+    let entry = PendingEntry {
+        info: PendingNotification {
+            entry_id: NEXT_ENTRY_ID.fetch_add(1, Ordering::SeqCst),
+            message_id: message.id,
+            title: message.title.clone(),
+            app: message.app.clone(),
+            priority: message.priority,
+            reason,
+            queued_at: unix_now_secs(),
+            deliver_at,
+        },
+        message: message.clone(),
+        received_at_ms,
+    };
+    let state = app.state::<AppState>();
+    let Ok(mut pending) = state.pending_notifications.lock() else {
+        return;
+    };
+    if pending
+        .iter()
+        .any(|queued| queued.info.message_id == message.id && queued.info.reason == reason)
+    {
+        return;
+    }
+    debug_log(&format!(
+        "notify queued id={} reason={reason:?}",
+        message.id
+    ));
+    pending.push(entry);
+    let snapshot = pending.iter().map(|entry| entry.info.clone()).collect();
+    drop(pending);
+    contract::publish_pending_notifications(app, snapshot);
+}
+
+pub(crate) fn list(app: &AppHandle) -> Result<Vec<PendingNotification>, String> {
+    let state = app.state::<AppState>();
+    let pending = state
+        .pending_notifications
+        .lock()
+        .map_err(|_| "Pending notification lock poisoned".to_string())?;
+    Ok(pending.iter().map(|entry| entry.info.clone()).collect())
+}
+
+pub(crate) fn cancel(
+    app: &AppHandle,
+    entry_id: u64,
+) -> Result<DomainSnapshot<Vec<PendingNotification>>, String> {
+    let state = app.state::<AppState>();
+    let mut pending = state
+        .pending_notifications
+        .lock()
+        .map_err(|_| "Pending notification lock poisoned".to_string())?;
+    let before = pending.len();
+    pending.retain(|entry| entry.info.entry_id != entry_id);
+    if pending.len() == before {
+        return Err(format!("No pending notification with id {entry_id}"));
+    }
+    let snapshot = pending.iter().map(|entry| entry.info.clone()).collect();
+    drop(pending);
+    Ok(contract::publish_pending_notifications(app, snapshot))
+}
+
+/// Drops held notifications for messages that no longer exist, so quiet hours
+/// ending does not announce something already deleted. Returns how many.
+pub(crate) fn cancel_for_messages(app: &AppHandle, message_ids: &[i64]) -> usize {
+    let state = app.state::<AppState>();
+    let Ok(mut pending) = state.pending_notifications.lock() else {
+        return 0;
+    };
+    let before = pending.len();
+    pending.retain(|entry| !message_ids.contains(&entry.info.message_id));
+    let cancelled = before - pending.len();
+    if cancelled == 0 {
+        return 0;
+    }
+    let snapshot = pending.iter().map(|entry| entry.info.clone()).collect();
+    drop(pending);
+    debug_log(&format!(
+        "notify queue: dropped {cancelled} entry(ies) for deleted messages"
+    ));
+    contract::publish_pending_notifications(app, snapshot);
+    cancelled
+}
+
+/// Scheduler tick: hands entries whose hold has ended back to the notify pipeline.
+pub(crate) fn release_due(app: &AppHandle) {
+    let quiet_hours = read_settings(app)
+        .map(|settings| is_quiet_hours(settings.quiet_hours_start, settings.quiet_hours_end))
+        .unwrap_or(false);
+    let now = unix_now_secs();
+    let is_due = |entry: &PendingEntry| {
+        let released = match entry.info.reason {
+            PendingReason::QuietHours => !quiet_hours,
+        };
+        released && entry.info.deliver_at.is_none_or(|at| at <= now)
+    };
+    let state = app.state::<AppState>();
+    let (due, snapshot) = {
+        let Ok(mut pending) = state.pending_notifications.lock() else {
+            return;
+        };
+        if pending.is_empty() {
+            return;
+        }
+        let (due, remaining): (Vec<PendingEntry>, Vec<PendingEntry>) =
+            std::mem::take(&mut *pending).into_iter().partition(is_due);
+        *pending = remaining;
+        if due.is_empty() {
+            return;
+        }
+        let snapshot: Vec<PendingNotification> =
+            pending.iter().map(|entry| entry.info.clone()).collect();
+        (due, snapshot)
+    };
+
+    contract::publish_pending_notifications(app, snapshot);
+    for entry in due {
+        if !crate::messages::is_cached(app, entry.message.id) {
+            continue;
+        }
+        maybe_notify_message(app, &entry.message, entry.received_at_ms);
+    }
+}
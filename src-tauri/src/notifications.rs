@@ -2,26 +2,57 @@ use std::{
     collections::HashMap,
     fs,
     path::{Path, PathBuf},
-    process::{Command, Stdio},
-    sync::atomic::{AtomicUsize, Ordering},
+    process::Stdio,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
     thread,
 };
 
 use chrono::Timelike;
 #[cfg(target_os = "macos")]
-use mac_notification_sys::{Notification, NotificationResponse};
-use tauri::{AppHandle, Emitter, Manager};
+use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
 
 use crate::{
-    debug_log, decode_data_url_bytes, settings::read_settings, truncate_message, ui_shell,
-    unix_now_secs, AppState, ApplicationMeta, CachedMessage, APP_ICON_MAX_BYTES,
-    PAUSE_FOREVER_SENTINEL,
+    debug_log, decode_data_url_bytes,
+    settings::{read_settings, QuietHoursBand, QuietHoursDelivery},
+    truncate_message, ui_shell, unix_now_secs, AppState, ApplicationMeta, CachedMessage,
+    APP_ICON_MAX_BYTES, PAUSE_FOREVER_SENTINEL,
 };
 
 #[cfg(target_os = "macos")]
 static IN_FLIGHT_NOTIFICATION_TASKS: AtomicUsize = AtomicUsize::new(0);
+/// Messages delivered silently during the current quiet-hours window.
+static QUIET_HOURS_BADGE_COUNT: AtomicU64 = AtomicU64::new(0);
 
-pub(crate) fn maybe_notify_message(app: &AppHandle, message: &CachedMessage) {
+/// `received_at_ms` is when the message arrived from the server, or `None` for
+/// a re-notification (a snooze ending), which is left out of delivery metrics.
+pub(crate) fn maybe_notify_message(
+    app: &AppHandle,
+    message: &CachedMessage,
+    received_at_ms: Option<u64>,
+) {
+    notify_message(app, message, received_at_ms, None);
+}
+
+/// A message from a background profile's stream. The banner carries that
+/// profile's badge; there is no in-app toast, since the inbox shows the active
+/// server, and app mutes (ids of the active server) do not apply.
+pub(crate) fn maybe_notify_background_message(
+    app: &AppHandle,
+    message: &CachedMessage,
+    received_at_ms: Option<u64>,
+    profile: &str,
+) {
+    notify_message(app, message, received_at_ms, Some(profile));
+}
+
+fn notify_message(
+    app: &AppHandle,
+    message: &CachedMessage,
+    received_at_ms: Option<u64>,
+    background_profile: Option<&str>,
+) {
     let settings = match read_settings(app) {
         Ok(settings) => settings,
         Err(error) => {
@@ -36,6 +67,14 @@ pub(crate) fn maybe_notify_message(app: &AppHandle, message: &CachedMessage) {
         }
     }
 
+    if background_profile.is_none() && settings.muted_app_ids.contains(&message.app_id) {
+        debug_log(&format!(
+            "notify skipped id={} reason=muted app_id={}",
+            message.id, message.app_id
+        ));
+        return;
+    }
+
     if message.priority < settings.min_priority {
         debug_log(&format!(
             "notify skipped id={} reason=priority threshold={} current={}",
@@ -44,10 +83,25 @@ pub(crate) fn maybe_notify_message(app: &AppHandle, message: &CachedMessage) {
         return;
     }
     if is_quiet_hours(settings.quiet_hours_start, settings.quiet_hours_end) {
+        let delivery = quiet_hours_delivery_for(&settings.quiet_hours_bands, message.priority);
         debug_log(&format!(
-            "notify skipped id={} reason=quiet-hours",
+            "notify quiet-hours id={} delivery={delivery:?}",
             message.id
         ));
+        match delivery {
+            // The queue replays into the active server's inbox, so a background
+            // server's message is dropped as before.
+            QuietHoursDelivery::Suppress if background_profile.is_some() => {}
+            QuietHoursDelivery::Suppress => crate::notification_queue::enqueue(
+                app,
+                message,
+                received_at_ms,
+                crate::notification_queue::PendingReason::QuietHours,
+                None,
+            ),
+            QuietHoursDelivery::Silent => bump_quiet_hours_badge(app),
+            QuietHoursDelivery::SoundOnly => play_quiet_hours_sound(),
+        }
         return;
     }
 
@@ -55,14 +109,99 @@ pub(crate) fn maybe_notify_message(app: &AppHandle, message: &CachedMessage) {
         "notify dispatch id={} app_id={} priority={}",
         message.id, message.app_id, message.priority
     ));
-    let _ = app.emit_to("main", "notification-message", message);
-    let _ = app.emit_to("quick", "notification-message", message);
+    if background_profile.is_none() {
+        crate::contract::emit_event(app, "main", "notification-message", message);
+        crate::contract::emit_event(app, "quick", "notification-message", message);
+    }
     #[cfg(target_os = "macos")]
     send_macos_notification(
         app.clone(),
         message.clone(),
-        settings.show_priority_in_notifications,
+        NotificationOptions {
+            show_priority: settings.show_priority_in_notifications,
+            privacy_mode: settings.privacy_mode,
+            badge: match background_profile {
+                Some(profile) => settings
+                    .profiles
+                    .get(profile)
+                    .and_then(|profile| profile.badge.clone()),
+                None => crate::profiles::active_badge(&settings),
+            },
+            sound: crate::contexts::sound_for_priority(&settings, message.priority),
+            // Message ids of a background server mean nothing to the active cache.
+            actions: background_profile.is_none(),
+            ack_action: background_profile.is_none()
+                && crate::ack::offers_action(&settings, message),
+        },
+        received_at_ms,
     );
+    #[cfg(not(target_os = "macos"))]
+    record_delivery(app, message, received_at_ms);
+}
+
+fn record_delivery(app: &AppHandle, message: &CachedMessage, received_at_ms: Option<u64>) {
+    if let Some(received_at_ms) = received_at_ms {
+        crate::metrics::record_delivery(app, &message.date, received_at_ms);
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct NotificationsWithdrawn {
+    pub(crate) message_ids: Vec<i64>,
+}
+
+/// Takes notifications of messages that no longer exist out of Notification
+/// Center and tells the webviews to drop their in-app state. Banners posted
+/// through mac-notification-sys (unbundled dev runs) cannot be removed and
+/// expire on their own.
+pub(crate) fn withdraw_notifications(app: &AppHandle, message_ids: &[i64]) {
+    #[cfg(target_os = "macos")]
+    crate::user_notifications::remove(message_ids);
+    let payload = NotificationsWithdrawn {
+        message_ids: message_ids.to_vec(),
+    };
+    crate::contract::emit_event(app, "main", "notification-withdrawn", payload.clone());
+    crate::contract::emit_event(app, "quick", "notification-withdrawn", payload);
+}
+
+fn quiet_hours_delivery_for(bands: &[QuietHoursBand], priority: i64) -> QuietHoursDelivery {
+    bands
+        .iter()
+        .filter(|band| priority >= band.min_priority)
+        .max_by_key(|band| band.min_priority)
+        .map(|band| band.delivery)
+        .unwrap_or(QuietHoursDelivery::Suppress)
+}
+
+fn bump_quiet_hours_badge(app: &AppHandle) {
+    let count = QUIET_HOURS_BADGE_COUNT.fetch_add(1, Ordering::SeqCst) + 1;
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        let _ = tray.set_title(Some(count.to_string()));
+    }
+}
+
+/// Clears the silent-delivery badge once quiet hours are over.
+pub(crate) fn refresh_quiet_hours_badge(app: &AppHandle) {
+    if QUIET_HOURS_BADGE_COUNT.load(Ordering::SeqCst) == 0 {
+        return;
+    }
+    let in_quiet_hours = read_settings(app)
+        .map(|settings| is_quiet_hours(settings.quiet_hours_start, settings.quiet_hours_end))
+        .unwrap_or(false);
+    if in_quiet_hours {
+        return;
+    }
+    QUIET_HOURS_BADGE_COUNT.store(0, Ordering::SeqCst);
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        let _ = tray.set_title(None::<&str>);
+    }
+}
+
+fn play_quiet_hours_sound() {
+    #[cfg(target_os = "macos")]
+    if let Err(error) = crate::sounds::play(Path::new(crate::QUIET_HOURS_SOUND_PATH)) {
+        debug_log(&format!("failed to play quiet-hours sound: {error}"));
+    }
 }
 
 pub(crate) fn is_quiet_hours(start: Option<u8>, end: Option<u8>) -> bool {
@@ -83,11 +222,119 @@ pub(crate) fn is_quiet_hours(start: Option<u8>, end: Option<u8>) -> bool {
     }
 }
 
+/// How a native notification is presented, resolved from settings at dispatch.
+#[cfg(target_os = "macos")]
+pub(crate) struct NotificationOptions {
+    pub(crate) show_priority: bool,
+    pub(crate) privacy_mode: bool,
+    pub(crate) badge: Option<String>,
+    /// `default`, `none` or a system sound name.
+    pub(crate) sound: String,
+    /// Mark Read and Snooze buttons.
+    pub(crate) actions: bool,
+    pub(crate) ack_action: bool,
+}
+
+/// What a banner shows, after privacy mode and the profile badge.
+#[cfg(target_os = "macos")]
+pub(crate) struct NotificationContent {
+    pub(crate) title: String,
+    pub(crate) subtitle: String,
+    pub(crate) body: String,
+    pub(crate) sound: String,
+    pub(crate) image_path: Option<String>,
+}
+
+#[cfg(target_os = "macos")]
+fn notification_content(
+    app: &AppHandle,
+    message: &CachedMessage,
+    options: &NotificationOptions,
+) -> NotificationContent {
+    let app_name = message.app.trim();
+    let title = match (app_name.is_empty(), options.show_priority) {
+        (true, true) => format!("Priority {}", message.priority),
+        (true, false) => "Gotify".to_string(),
+        (false, true) => format!("{} · Priority {}", app_name, message.priority),
+        (false, false) => app_name.to_string(),
+    };
+    let subtitle = if message.title.trim().is_empty() {
+        "Gotify message".to_string()
+    } else {
+        message.title.clone()
+    };
+    let body = if message.markdown {
+        truncate_message(&crate::markdown::to_plain_text(&message.message), 220)
+    } else {
+        truncate_message(&message.message, 220)
+    };
+
+    // Privacy mode keeps message content off screen shares and recordings.
+    let (title, subtitle, body) = if options.privacy_mode {
+        let sender = if app_name.is_empty() {
+            "Gotify"
+        } else {
+            app_name
+        };
+        (
+            "Gotify".to_string(),
+            String::new(),
+            format!("New message from {sender}"),
+        )
+    } else {
+        (title, subtitle, body)
+    };
+    // The profile badge is not message content, so it stays in privacy mode.
+    let title = match options.badge.as_deref() {
+        Some(badge) => format!("{badge} {title}"),
+        None => title,
+    };
+
+    let image_path = if options.privacy_mode {
+        None
+    } else {
+        message
+            .big_image_url()
+            .and_then(|url| crate::big_image::resolve_path(app, url))
+            .or_else(|| resolve_notification_content_image_path(app, message))
+    };
+    NotificationContent {
+        title,
+        subtitle,
+        body,
+        sound: options.sound.clone(),
+        image_path,
+    }
+}
+
+/// A click on a message notification: opens its click URL, or the main window.
+#[cfg(target_os = "macos")]
+pub(crate) fn open_notified_message(app: &AppHandle, message: &CachedMessage) {
+    // The URL goes through the same scheme check as links opened from the
+    // inbox; if it is refused the window opens instead.
+    let opened_url = message.click_url().is_some_and(|url| {
+        crate::open_external_url(url.to_string())
+            .map_err(|error| {
+                debug_log(&format!(
+                    "notify click url id={} failed: {error}",
+                    message.id
+                ))
+            })
+            .is_ok()
+    });
+    if !opened_url {
+        ui_shell::show_main_window(app);
+    }
+    crate::contract::emit_event(app, "main", "notification-clicked", message);
+    crate::contract::emit_event(app, "quick", "notification-clicked", message);
+}
+
 #[cfg(target_os = "macos")]
 pub(crate) fn send_macos_notification(
     app: AppHandle,
     message: CachedMessage,
-    show_priority_in_notifications: bool,
+    options: NotificationOptions,
+    received_at_ms: Option<u64>,
 ) {
     thread::spawn(move || {
         let message_id = message.id;
@@ -98,59 +345,18 @@ pub(crate) fn send_macos_notification(
             message_id, in_flight
         ));
 
-        ensure_macos_notification_application();
-        let app_name = message.app.trim();
-        let title = match (app_name.is_empty(), show_priority_in_notifications) {
-            (true, true) => format!("Priority {}", message.priority),
-            (true, false) => "Gotify".to_string(),
-            (false, true) => format!("{} · Priority {}", app_name, message.priority),
-            (false, false) => app_name.to_string(),
-        };
-        let subtitle = if message.title.trim().is_empty() {
-            "Gotify message".to_string()
+        let content = notification_content(&app, &message, &options);
+        if crate::user_notifications::is_available() {
+            crate::user_notifications::deliver(
+                message_id,
+                message.app_id,
+                &content,
+                options.actions,
+                options.ack_action,
+            );
+            record_delivery(&app, &message, received_at_ms);
         } else {
-            message.title.clone()
-        };
-        let body = truncate_message(&message.message, 220);
-
-        let mut notification = Notification::new();
-        notification
-            .title(&title)
-            .subtitle(&subtitle)
-            .message(&body)
-            .default_sound()
-            // mac-notification-sys waits in an internal run-loop while waiting
-            // for interactions, which can leave background threads alive for a
-            // long time and cause high CPU. Use fire-and-forget delivery.
-            .wait_for_click(false)
-            .asynchronous(true);
-
-        let sender_icon_path = resolve_default_notification_app_icon_path(&app);
-        if let Some(sender_icon_path) = sender_icon_path.as_deref() {
-            notification.app_icon(sender_icon_path);
-        }
-
-        let content_image_path = resolve_notification_content_image_path(&app, &message);
-        if let Some(content_image_path) = content_image_path.as_deref() {
-            notification.content_image(content_image_path);
-        }
-
-        match notification.send() {
-            Ok(NotificationResponse::Click) | Ok(NotificationResponse::ActionButton(_)) => {
-                debug_log(&format!("mac notify click id={message_id}"));
-                ui_shell::show_main_window(&app);
-                let _ = app.emit_to("main", "notification-clicked", message.clone());
-                let _ = app.emit_to("quick", "notification-clicked", message.clone());
-            }
-            Ok(response) => {
-                debug_log(&format!(
-                    "mac notify delivered id={} response={response:?}",
-                    message_id
-                ));
-            }
-            Err(error) => {
-                debug_log(&format!("failed to show macOS notification: {error}"));
-            }
+            send_legacy_notification(&app, &message, content, options.ack_action, received_at_ms);
         }
 
         let elapsed_ms = started_at.elapsed().as_millis();
@@ -164,6 +370,91 @@ pub(crate) fn send_macos_notification(
     });
 }
 
+/// mac-notification-sys delivery, for runs outside an app bundle where
+/// `UNUserNotificationCenter` is unavailable. It offers one button only, so
+/// Mark Read and Snooze are missing, and the ack action waits on this thread.
+#[cfg(target_os = "macos")]
+fn send_legacy_notification(
+    app: &AppHandle,
+    message: &CachedMessage,
+    content: NotificationContent,
+    ack_action: bool,
+    received_at_ms: Option<u64>,
+) {
+    let message_id = message.id;
+    ensure_macos_notification_application();
+    let mut notification = Notification::new();
+    notification.title(&content.title);
+    if !content.subtitle.is_empty() {
+        notification.subtitle(&content.subtitle);
+    }
+    match content.sound.as_str() {
+        crate::contexts::NOTIFICATION_SOUND_NONE => {}
+        crate::contexts::NOTIFICATION_SOUND_DEFAULT => {
+            notification.default_sound();
+        }
+        name => {
+            notification.sound(name);
+        }
+    }
+    notification
+        .message(&content.body)
+        // mac-notification-sys waits in an internal run-loop while waiting
+        // for interactions, which can leave background threads alive for a
+        // long time and cause high CPU. Use fire-and-forget delivery.
+        .wait_for_click(false)
+        .asynchronous(true);
+    if ack_action {
+        // The action needs the response, so only these notifications keep
+        // a thread waiting until the banner is answered or dismissed.
+        notification
+            .main_button(MainButton::SingleAction(crate::ACK_ACTION_LABEL))
+            .wait_for_click(true)
+            .asynchronous(false);
+        record_delivery(app, message, received_at_ms);
+    }
+
+    let sender_icon_path = resolve_default_notification_app_icon_path(app);
+    if let Some(sender_icon_path) = sender_icon_path.as_deref() {
+        notification.app_icon(sender_icon_path);
+    }
+    if let Some(image_path) = content.image_path.as_deref() {
+        notification.content_image(image_path);
+    }
+
+    let record_delivery = || {
+        if !ack_action {
+            record_delivery(app, message, received_at_ms);
+        }
+    };
+    match notification.send() {
+        Ok(NotificationResponse::ActionButton(action)) if action == crate::ACK_ACTION_LABEL => {
+            debug_log(&format!("mac notify acknowledge id={message_id}"));
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(error) = crate::ack::acknowledge(&app, message_id).await {
+                    debug_log(&format!("acknowledge id={message_id} failed: {error}"));
+                }
+            });
+        }
+        Ok(NotificationResponse::Click) | Ok(NotificationResponse::ActionButton(_)) => {
+            record_delivery();
+            debug_log(&format!("mac notify click id={message_id}"));
+            open_notified_message(app, message);
+        }
+        Ok(response) => {
+            record_delivery();
+            debug_log(&format!(
+                "mac notify delivered id={} response={response:?}",
+                message_id
+            ));
+        }
+        Err(error) => {
+            debug_log(&format!("failed to show macOS notification: {error}"));
+        }
+    }
+}
+
 #[cfg(target_os = "macos")]
 pub(crate) fn ensure_macos_notification_application() {
     static INIT_NOTIFICATION_APP: std::sync::Once = std::sync::Once::new();
@@ -271,12 +562,12 @@ pub(crate) fn cache_remote_notification_icon_png(
 ) -> Option<String> {
     let icons_dir = notification_icon_cache_dir(app)?;
     let file_path = icons_dir.join(format!("app-{app_id}.png"));
-    if file_path.exists() {
+    if crate::icns::is_png_file(&file_path) {
         return Some(file_path.to_string_lossy().to_string());
     }
 
-    if icon_url.trim_start().starts_with("data:") {
-        let bytes = match decode_data_url_bytes(icon_url, APP_ICON_MAX_BYTES) {
+    let bytes = if icon_url.trim_start().starts_with("data:") {
+        match decode_data_url_bytes(icon_url, APP_ICON_MAX_BYTES) {
             Ok(bytes) => bytes,
             Err(error) => {
                 debug_log(&format!(
@@ -284,18 +575,36 @@ pub(crate) fn cache_remote_notification_icon_png(
                 ));
                 return None;
             }
-        };
-        if bytes.is_empty() {
-            return None;
         }
-        if let Err(error) = fs::write(&file_path, &bytes) {
-            debug_log(&format!("failed writing app icon cache file: {error}"));
+    } else {
+        crate::icon_store::read_icon_ref(app, icon_url)?
+    };
+    if bytes.is_empty() {
+        return None;
+    }
+    let png = match crate::icns::icon_to_png(&bytes) {
+        Ok(png) => png,
+        Err(error) => {
+            debug_log(&format!(
+                "failed converting app icon for app_id={app_id}: {error}"
+            ));
             return None;
         }
-        return Some(file_path.to_string_lossy().to_string());
+    };
+    if let Err(error) = fs::write(&file_path, &png) {
+        debug_log(&format!("failed writing app icon cache file: {error}"));
+        return None;
     }
+    Some(file_path.to_string_lossy().to_string())
+}
 
-    None
+/// Drops the converted notification icon of an app, so the next warm-up or
+/// notification converts its current image.
+#[cfg(target_os = "macos")]
+pub(crate) fn forget_notification_icon(app: &AppHandle, app_id: i64) {
+    if let Some(icons_dir) = notification_icon_cache_dir(app) {
+        let _ = fs::remove_file(icons_dir.join(format!("app-{app_id}.png")));
+    }
 }
 
 #[cfg(target_os = "macos")]
@@ -317,7 +626,18 @@ pub(crate) fn ensure_icns_from_png(source_png: &Path, target_icns: &Path) -> boo
     if target_icns.exists() {
         return true;
     }
+    match crate::icns::write_icns_from_png(source_png, target_icns) {
+        Ok(()) => return true,
+        Err(error) => debug_log(&format!(
+            "in-process icns conversion failed, falling back to sips/iconutil: {error}"
+        )),
+    }
+    ensure_icns_with_system_tools(source_png, target_icns)
+}
 
+/// Fallback for sources the image decoder rejects; `sips` reads more formats.
+#[cfg(target_os = "macos")]
+fn ensure_icns_with_system_tools(source_png: &Path, target_icns: &Path) -> bool {
     let iconset_dir = target_icns.with_extension("iconset");
     if fs::create_dir_all(&iconset_dir).is_err() {
         return false;
@@ -338,7 +658,7 @@ pub(crate) fn ensure_icns_from_png(source_png: &Path, target_icns: &Path) -> boo
 
     for (file_name, pixels_h, pixels_w) in sizes {
         let out = iconset_dir.join(file_name);
-        let status = Command::new("sips")
+        let status = crate::work::background_command("sips")
             .arg("-z")
             .arg(pixels_h.to_string())
             .arg(pixels_w.to_string())
@@ -354,7 +674,7 @@ pub(crate) fn ensure_icns_from_png(source_png: &Path, target_icns: &Path) -> boo
         }
     }
 
-    let status = Command::new("iconutil")
+    let status = crate::work::background_command("iconutil")
         .arg("-c")
         .arg("icns")
         .arg(&iconset_dir)
@@ -383,21 +703,30 @@ pub(crate) fn warm_notification_icon_cache(
             continue;
         };
         let file_path = icons_dir.join(format!("app-{app_id}.png"));
-        if file_path.exists() {
+        if crate::icns::is_png_file(&file_path) {
             continue;
         }
-        match decode_data_url_bytes(&meta.icon_url, APP_ICON_MAX_BYTES) {
-            Ok(bytes) if !bytes.is_empty() => {
-                if let Err(error) = fs::write(&file_path, &bytes) {
+        let bytes = match decode_data_url_bytes(&meta.icon_url, APP_ICON_MAX_BYTES) {
+            Ok(bytes) if !bytes.is_empty() => bytes,
+            Ok(_) => continue,
+            Err(error) => {
+                debug_log(&format!(
+                    "warm_notification_icon_cache: failed decoding icon app_id={app_id}: {error}"
+                ));
+                continue;
+            }
+        };
+        match crate::icns::icon_to_png(&bytes) {
+            Ok(png) => {
+                if let Err(error) = fs::write(&file_path, &png) {
                     debug_log(&format!(
                         "warm_notification_icon_cache: failed writing icon app_id={app_id}: {error}"
                     ));
                 }
             }
-            Ok(_) => {}
             Err(error) => {
                 debug_log(&format!(
-                    "warm_notification_icon_cache: failed decoding icon app_id={app_id}: {error}"
+                    "warm_notification_icon_cache: failed converting icon app_id={app_id}: {error}"
                 ));
             }
         }
@@ -0,0 +1,64 @@
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::{contract::now_ms, truncate_message};
+
+static OP_TRACE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TraceOp {
+    Delete,
+    Sync,
+    IconFetch,
+    Preview,
+    StreamConnect,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct OpTraceEvent {
+    pub(crate) at_ms: u64,
+    pub(crate) op: TraceOp,
+    /// What the operation acts on: a message id, app id, URL or host.
+    pub(crate) subject: String,
+    pub(crate) phase: String,
+    pub(crate) detail: String,
+    pub(crate) status: Option<u16>,
+}
+
+/// Mirrors the `op_trace_enabled` setting so tracing stays a cheap no-op when off.
+pub(crate) fn set_enabled(enabled: bool) {
+    OP_TRACE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn is_enabled() -> bool {
+    OP_TRACE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Emits one `op-trace` step to the main window when tracing is enabled.
+pub(crate) fn emit(
+    app: &AppHandle,
+    op: TraceOp,
+    subject: impl Display,
+    phase: &str,
+    detail: &str,
+    status: Option<u16>,
+) {
+    if !is_enabled() {
+        return;
+    }
+    let event = OpTraceEvent {
+        at_ms: now_ms(),
+        op,
+        subject: subject.to_string(),
+        phase: phase.to_string(),
+        detail: truncate_message(detail, 800),
+        status,
+    };
+    crate::contract::emit_event(app, "main", "op-trace", event);
+}
@@ -0,0 +1,227 @@
+use std::{
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use crate::{debug_log, AppState, OPERATION_CANCELLED, OPERATION_PROGRESS_MIN_INTERVAL_MS};
+
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+
+pub(crate) struct OperationEntry {
+    kind: &'static str,
+    group: Option<String>,
+    cancel_tx: watch::Sender<bool>,
+}
+
+/// Registration of one cancellable piece of work (a bulk operation, a preview
+/// fetch, a sync). It stays in the registry until dropped.
+pub(crate) struct CancelHandle {
+    app: AppHandle,
+    id: u64,
+    cancel_rx: watch::Receiver<bool>,
+}
+
+impl CancelHandle {
+    /// `group` lets the frontend cancel related work together, e.g. every
+    /// preview fetch when the window is hidden.
+    pub(crate) fn register(app: &AppHandle, kind: &'static str, group: Option<&str>) -> Self {
+        let id = NEXT_OPERATION_ID.fetch_add(1, Ordering::Relaxed);
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        if let Ok(mut operations) = app.state::<AppState>().operations.lock() {
+            operations.insert(
+                id,
+                OperationEntry {
+                    kind,
+                    group: group.map(str::to_string),
+                    cancel_tx,
+                },
+            );
+        }
+        Self {
+            app: app.clone(),
+            id,
+            cancel_rx,
+        }
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        *self.cancel_rx.borrow()
+    }
+
+    /// Drives `future` until it completes or the handle is cancelled. On cancel
+    /// the future is dropped, which closes its sockets, and `OPERATION_CANCELLED`
+    /// is returned.
+    pub(crate) async fn run<T>(
+        &self,
+        future: impl Future<Output = Result<T, String>>,
+    ) -> Result<T, String> {
+        let mut cancel_rx = self.cancel_rx.clone();
+        tokio::select! {
+            result = future => result,
+            Ok(_) = cancel_rx.wait_for(|cancelled| *cancelled) => Err(OPERATION_CANCELLED.to_string()),
+        }
+    }
+}
+
+impl Drop for CancelHandle {
+    fn drop(&mut self) {
+        if let Ok(mut operations) = self.app.state::<AppState>().operations.lock() {
+            operations.remove(&self.id);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct OperationProgressEvent {
+    pub(crate) op_id: u64,
+    /// `delete`, `delete-app` or `export`.
+    pub(crate) kind: &'static str,
+    /// Items processed so far, failed ones included.
+    pub(crate) done: usize,
+    pub(crate) total: usize,
+    pub(crate) failed: usize,
+    pub(crate) finished: bool,
+    pub(crate) cancelled: bool,
+    pub(crate) error: Option<String>,
+}
+
+/// A running bulk operation that reports progress. The worker polls
+/// `is_cancelled` between items.
+pub(crate) struct Operation {
+    handle: CancelHandle,
+    kind: &'static str,
+    total: usize,
+    done: usize,
+    failed: usize,
+    last_emit: Option<Instant>,
+}
+
+impl Operation {
+    pub(crate) fn begin(app: &AppHandle, kind: &'static str, total: usize) -> Self {
+        let handle = CancelHandle::register(app, kind, None);
+        debug_log(&format!(
+            "operation {} ({kind}) started, {total} item(s)",
+            handle.id()
+        ));
+        let mut operation = Self {
+            handle,
+            kind,
+            total,
+            done: 0,
+            failed: 0,
+            last_emit: None,
+        };
+        operation.emit(false, None);
+        operation
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.handle.id()
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.handle.is_cancelled()
+    }
+
+    /// Records one processed item. Progress events are throttled; the last item
+    /// always emits.
+    pub(crate) fn advance(&mut self, ok: bool) {
+        self.done += 1;
+        if !ok {
+            self.failed += 1;
+        }
+        let due = self.last_emit.is_none_or(|at| {
+            at.elapsed().as_millis() >= u128::from(OPERATION_PROGRESS_MIN_INTERVAL_MS)
+        });
+        if due || self.done >= self.total {
+            self.emit(false, None);
+        }
+    }
+
+    pub(crate) fn finish(mut self, error: Option<String>) {
+        debug_log(&format!(
+            "operation {} ({}) finished: {}/{} done, {} failed{}",
+            self.id(),
+            self.kind,
+            self.done,
+            self.total,
+            self.failed,
+            if self.is_cancelled() {
+                ", cancelled"
+            } else {
+                ""
+            }
+        ));
+        self.emit(true, error);
+    }
+
+    fn emit(&mut self, finished: bool, error: Option<String>) {
+        self.last_emit = Some(Instant::now());
+        crate::contract::emit_event(
+            &self.handle.app,
+            "main",
+            "operation-progress",
+            OperationProgressEvent {
+                op_id: self.id(),
+                kind: self.kind,
+                done: self.done,
+                total: self.total,
+                failed: self.failed,
+                finished,
+                cancelled: self.is_cancelled(),
+                error,
+            },
+        );
+    }
+}
+
+/// Asks a running operation to stop. Bulk operations stop after the item in
+/// flight; fetches are dropped at once. Returns `false` when no such operation
+/// is running (it may already have finished).
+pub(crate) fn cancel(app: &AppHandle, op_id: u64) -> bool {
+    let state = app.state::<AppState>();
+    let Ok(operations) = state.operations.lock() else {
+        return false;
+    };
+    match operations.get(&op_id) {
+        Some(entry) => {
+            entry.cancel_tx.send_replace(true);
+            debug_log(&format!(
+                "operation {op_id} ({}) cancel requested",
+                entry.kind
+            ));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Cancels every running operation registered under `group`; returns how many.
+pub(crate) fn cancel_group(app: &AppHandle, group: &str) -> usize {
+    let state = app.state::<AppState>();
+    let Ok(operations) = state.operations.lock() else {
+        return 0;
+    };
+    let mut cancelled = 0;
+    for entry in operations.values() {
+        if entry.group.as_deref() == Some(group) && !*entry.cancel_tx.borrow() {
+            entry.cancel_tx.send_replace(true);
+            cancelled += 1;
+        }
+    }
+    if cancelled > 0 {
+        debug_log(&format!(
+            "cancelled {cancelled} operation(s) in group {group}"
+        ));
+    }
+    cancelled
+}
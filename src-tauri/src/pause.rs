@@ -1,3 +1,4 @@
+use chrono::Local;
 use serde::Deserialize;
 use tauri::menu::MenuItem;
 use tauri::{AppHandle, Manager};
@@ -73,94 +74,114 @@ pub(crate) fn install_pause_menu_state(
     apply_pause_state_to_tray(app, pause_until, pause_mode);
 }
 
-pub(crate) fn set_pause(app: AppHandle, input: SetPauseInput) -> Result<PauseStateData, String> {
-    if input.forever.unwrap_or(false) {
-        return set_notification_pause_until(
-            &app,
-            Some(PAUSE_FOREVER_SENTINEL),
-            Some(PAUSE_MODE_FOREVER),
-        );
+/// The single entry point for pause changes. Commands, the tray menu, the
+/// scheduler, expiry and external settings edits all go through it, so the
+/// persisted state, the tray menu and the `pause.updated` channel update never
+/// diverge.
+pub(crate) struct PauseController<'a> {
+    app: &'a AppHandle,
+}
+
+impl<'a> PauseController<'a> {
+    pub(crate) fn new(app: &'a AppHandle) -> Self {
+        Self { app }
     }
 
-    if let Some(until) = input.until {
-        if until == PAUSE_FOREVER_SENTINEL {
-            return set_notification_pause_until(
-                &app,
-                Some(PAUSE_FOREVER_SENTINEL),
-                Some(PAUSE_MODE_FOREVER),
-            );
-        }
-        if until <= unix_now_secs() {
-            return Err("Pause 'until' must be in the future".to_string());
-        }
-        let mode = input.mode.unwrap_or_else(|| PAUSE_MODE_CUSTOM.to_string());
-        return set_notification_pause_until(&app, Some(until), Some(&mode));
+    pub(crate) fn state(&self) -> Result<PauseStateData, String> {
+        let settings = read_settings(self.app)?;
+        Ok(pause_state_from_fields(
+            settings.pause_until,
+            settings.pause_mode.as_deref(),
+        ))
     }
 
-    if let Some(minutes) = input.minutes {
-        if minutes == 0 {
-            return Err("Pause duration must be greater than 0 minutes".to_string());
+    pub(crate) fn pause(&self, input: SetPauseInput) -> Result<PauseStateData, String> {
+        if input.forever.unwrap_or(false) {
+            return self.set_until(Some(PAUSE_FOREVER_SENTINEL), Some(PAUSE_MODE_FOREVER));
         }
-        let until = unix_now_secs().saturating_add(minutes.saturating_mul(60));
-        let mode = match minutes {
-            15 => PAUSE_MODE_15M,
-            60 => PAUSE_MODE_1H,
-            _ => PAUSE_MODE_CUSTOM,
-        };
-        return set_notification_pause_until(&app, Some(until), Some(mode));
-    }
 
-    Err("Invalid pause input. Provide minutes, until, or forever=true".to_string())
-}
+        if let Some(until) = input.until {
+            if until == PAUSE_FOREVER_SENTINEL {
+                return self.set_until(Some(PAUSE_FOREVER_SENTINEL), Some(PAUSE_MODE_FOREVER));
+            }
+            if until <= unix_now_secs() {
+                return Err("Pause 'until' must be in the future".to_string());
+            }
+            let mode = input.mode.unwrap_or_else(|| PAUSE_MODE_CUSTOM.to_string());
+            return self.set_until(Some(until), Some(&mode));
+        }
 
-pub(crate) fn resume_pause(app: AppHandle) -> Result<PauseStateData, String> {
-    set_notification_pause_until(&app, None, None)
-}
+        if let Some(minutes) = input.minutes {
+            if minutes == 0 {
+                return Err("Pause duration must be greater than 0 minutes".to_string());
+            }
+            let until = unix_now_secs().saturating_add(minutes.saturating_mul(60));
+            let mode = match minutes {
+                15 => PAUSE_MODE_15M,
+                60 => PAUSE_MODE_1H,
+                _ => PAUSE_MODE_CUSTOM,
+            };
+            return self.set_until(Some(until), Some(mode));
+        }
 
-pub(crate) fn get_pause_state_data(app: &AppHandle) -> Result<PauseStateData, String> {
-    let settings = read_settings(app)?;
-    Ok(pause_state_from_fields(
-        settings.pause_until,
-        settings.pause_mode.as_deref(),
-    ))
-}
+        Err("Invalid pause input. Provide minutes, until, or forever=true".to_string())
+    }
 
-pub(crate) fn set_notification_pause_until(
-    app: &AppHandle,
-    pause_until: Option<u64>,
-    pause_mode: Option<&str>,
-) -> Result<PauseStateData, String> {
-    let app_state = app.state::<AppState>();
-    let _settings_guard = app_state
-        .settings_lock
-        .lock()
-        .map_err(|_| "Settings lock poisoned".to_string())?;
-    let mut settings = read_settings(app)?;
-    settings.pause_until = pause_until;
-    settings.pause_mode = pause_mode.map(|mode| mode.to_string());
-    save_non_secret_settings(app, &settings)?;
+    pub(crate) fn resume(&self) -> Result<PauseStateData, String> {
+        self.set_until(None, None)
+    }
 
-    let pause_state = pause_state_from_fields(settings.pause_until, settings.pause_mode.as_deref());
-    apply_pause_state_to_tray(app, settings.pause_until, settings.pause_mode.as_deref());
-    let _ = crate::contract::publish_pause_update(app, pause_state.clone());
+    /// Persists a new pause state, then updates the tray and publishes it.
+    pub(crate) fn set_until(
+        &self,
+        pause_until: Option<u64>,
+        pause_mode: Option<&str>,
+    ) -> Result<PauseStateData, String> {
+        let app_state = self.app.state::<AppState>();
+        let _settings_guard = app_state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(self.app)?;
+        settings.pause_until = pause_until;
+        settings.pause_mode = pause_mode.map(|mode| mode.to_string());
+        save_non_secret_settings(self.app, &settings)?;
+        Ok(self.announce(settings.pause_until, settings.pause_mode.as_deref()))
+    }
 
-    Ok(pause_state)
-}
+    /// Re-announces the persisted state, e.g. after settings.json was edited on disk.
+    pub(crate) fn publish_current(&self) -> Result<PauseStateData, String> {
+        let settings = read_settings(self.app)?;
+        Ok(self.announce(settings.pause_until, settings.pause_mode.as_deref()))
+    }
 
-pub(crate) fn refresh_pause_state_from_settings(app: &AppHandle) {
-    let settings = match read_settings(app) {
-        Ok(settings) => settings,
-        Err(_) => return,
-    };
+    /// Periodic tick: ends expired pauses and keeps the tray countdown current.
+    pub(crate) fn refresh(&self) {
+        let settings = match read_settings(self.app) {
+            Ok(settings) => settings,
+            Err(_) => return,
+        };
 
-    if let Some(until) = settings.pause_until {
-        if until != PAUSE_FOREVER_SENTINEL && unix_now_secs() >= until {
-            let _ = set_notification_pause_until(app, None, None);
-            return;
+        if let Some(until) = settings.pause_until {
+            if until != PAUSE_FOREVER_SENTINEL && unix_now_secs() >= until {
+                let _ = self.resume();
+                return;
+            }
         }
+
+        apply_pause_state_to_tray(
+            self.app,
+            settings.pause_until,
+            settings.pause_mode.as_deref(),
+        );
     }
 
-    apply_pause_state_to_tray(app, settings.pause_until, settings.pause_mode.as_deref());
+    fn announce(&self, pause_until: Option<u64>, pause_mode: Option<&str>) -> PauseStateData {
+        let pause_state = pause_state_from_fields(pause_until, pause_mode);
+        apply_pause_state_to_tray(self.app, pause_until, pause_mode);
+        let _ = crate::contract::publish_pause_update(self.app, pause_state.clone());
+        pause_state
+    }
 }
 
 fn pause_state_from_fields(pause_until: Option<u64>, pause_mode: Option<&str>) -> PauseStateData {
@@ -242,6 +263,17 @@ fn apply_pause_state_to_tray(app: &AppHandle, pause_until: Option<u64>, pause_mo
     let pause_1h_active = pause_active && pause_mode == Some(PAUSE_MODE_1H);
     let pause_forever_active = pause_active && pause_mode == Some(PAUSE_MODE_FOREVER);
 
+    let next_change = read_settings(app).ok().and_then(|settings| {
+        crate::pause_schedule::next_scheduled_change(&settings.pause_schedules, Local::now())
+    });
+    let status_label = match next_change {
+        Some(change) => format!(
+            "{status_label} · {} {}",
+            if change.pausing { "pauses" } else { "resumes" },
+            change.at.format("%a %H:%M")
+        ),
+        None => status_label,
+    };
     let _ = handles.status_item.set_text(&status_label);
     let _ = handles.resume_item.set_enabled(pause_active);
     let _ = handles.pause_15m_item.set_text(if pause_15m_active {
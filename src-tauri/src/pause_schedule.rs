@@ -0,0 +1,122 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log, pause::PauseController, settings::read_settings, settings::PauseSchedule,
+    unix_now_secs, AppState, PAUSE_MODE_SCHEDULED,
+};
+
+/// A scheduled change of pause state: a window starting (`pausing`) or ending.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ScheduledChange {
+    pub(crate) at: DateTime<Local>,
+    pub(crate) pausing: bool,
+}
+
+/// Scheduler tick: starts a scheduled pause when a window opens. The pause expires
+/// on its own at the window end. A window the user resumed out of is not re-applied.
+pub(crate) fn apply_scheduled_pause(app: &AppHandle) {
+    let Ok(settings) = read_settings(app) else {
+        return;
+    };
+    let Some(window_end) = active_window_end(&settings.pause_schedules, Local::now()) else {
+        return;
+    };
+    let window_end = window_end.timestamp().max(0) as u64;
+
+    let state = app.state::<AppState>();
+    {
+        let Ok(mut runtime) = state.runtime.lock() else {
+            return;
+        };
+        if runtime.scheduled_pause_applied_until == Some(window_end) {
+            return;
+        }
+        if is_paused(settings.pause_until) {
+            return;
+        }
+        runtime.scheduled_pause_applied_until = Some(window_end);
+    }
+
+    debug_log(&format!("scheduled pause started until={window_end}"));
+    if let Err(error) =
+        PauseController::new(app).set_until(Some(window_end), Some(PAUSE_MODE_SCHEDULED))
+    {
+        debug_log(&format!("failed to apply scheduled pause: {error}"));
+    }
+}
+
+fn is_paused(pause_until: Option<u64>) -> bool {
+    match pause_until {
+        Some(crate::PAUSE_FOREVER_SENTINEL) => true,
+        Some(until) => until > unix_now_secs(),
+        None => false,
+    }
+}
+
+/// Occurrences of every schedule that could overlap `now` or start within a week.
+fn windows_around(
+    schedules: &[PauseSchedule],
+    now: DateTime<Local>,
+) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+    let today = now.date_naive();
+    let mut windows = Vec::new();
+    for offset in -1..=7 {
+        let Some(day) = today.checked_add_signed(Duration::days(offset)) else {
+            continue;
+        };
+        let weekday = day.weekday().num_days_from_monday() as u8;
+        for schedule in schedules.iter().filter(|s| s.days.contains(&weekday)) {
+            let end_day = if schedule.end_minute <= schedule.start_minute {
+                day.succ_opt().unwrap_or(day)
+            } else {
+                day
+            };
+            if let (Some(start), Some(end)) = (
+                local_at(day, schedule.start_minute),
+                local_at(end_day, schedule.end_minute),
+            ) {
+                windows.push((start, end));
+            }
+        }
+    }
+    windows
+}
+
+fn local_at(day: NaiveDate, minute_of_day: u16) -> Option<DateTime<Local>> {
+    let naive = day.and_hms_opt(
+        u32::from(minute_of_day / 60),
+        u32::from(minute_of_day % 60),
+        0,
+    )?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+pub(crate) fn active_window_end(
+    schedules: &[PauseSchedule],
+    now: DateTime<Local>,
+) -> Option<DateTime<Local>> {
+    windows_around(schedules, now)
+        .into_iter()
+        .filter(|(start, end)| *start <= now && now < *end)
+        .map(|(_, end)| end)
+        .max()
+}
+
+pub(crate) fn next_scheduled_change(
+    schedules: &[PauseSchedule],
+    now: DateTime<Local>,
+) -> Option<ScheduledChange> {
+    if let Some(end) = active_window_end(schedules, now) {
+        return Some(ScheduledChange {
+            at: end,
+            pausing: false,
+        });
+    }
+    windows_around(schedules, now)
+        .into_iter()
+        .map(|(start, _)| start)
+        .filter(|start| *start > now)
+        .min()
+        .map(|at| ScheduledChange { at, pausing: true })
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{
+    applications::{server_context, ServerContext},
+    client_token::decode_response,
+    debug_log,
+    server_info::{require, ServerFeature},
+    PLUGIN_CONFIG_MAX_BYTES,
+};
+
+/// A server plugin as the webview sees it. The plugin token (used in its
+/// custom routes) is left out.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct PluginInfo {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    pub(crate) module_path: String,
+    pub(crate) enabled: bool,
+    pub(crate) author: Option<String>,
+    pub(crate) website: Option<String>,
+    pub(crate) license: Option<String>,
+    /// `webhooker`, `displayer`, `configurer`, `messenger`, `storager`.
+    pub(crate) capabilities: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginWire {
+    id: i64,
+    name: String,
+    #[serde(default)]
+    module_path: String,
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    website: Option<String>,
+    #[serde(default)]
+    license: Option<String>,
+    #[serde(default)]
+    capabilities: Vec<String>,
+}
+
+impl From<PluginWire> for PluginInfo {
+    fn from(wire: PluginWire) -> Self {
+        Self {
+            id: wire.id,
+            name: wire.name,
+            module_path: wire.module_path,
+            enabled: wire.enabled,
+            author: wire.author,
+            website: wire.website,
+            license: wire.license,
+            capabilities: wire.capabilities,
+        }
+    }
+}
+
+fn plugin_context(app: &AppHandle) -> Result<ServerContext, String> {
+    require(app, ServerFeature::Plugins)?;
+    server_context(app)
+}
+
+/// Gotify answers plugin actions with an empty body, so only the status counts.
+async fn expect_success(response: reqwest::Response, action: &str) -> Result<(), String> {
+    if response.status().is_success() {
+        return Ok(());
+    }
+    decode_response::<serde_json::Value>(response, action)
+        .await
+        .map(|_| ())
+}
+
+async fn read_text(response: reqwest::Response, action: &str) -> Result<String, String> {
+    if !response.status().is_success() {
+        return decode_response::<serde_json::Value>(response, action)
+            .await
+            .map(|_| String::new());
+    }
+    response
+        .text()
+        .await
+        .map_err(|error| format!("{action} returned an unreadable body: {error}"))
+}
+
+pub(crate) async fn list_plugins(app: &AppHandle) -> Result<Vec<PluginInfo>, String> {
+    let context = plugin_context(app)?;
+    let request = context
+        .client
+        .get(format!("{}/plugin", context.base_url))
+        .header("X-Gotify-Key", &context.token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to list plugins: {error}"))?;
+    let plugins = decode_response::<Vec<PluginWire>>(response, "List plugins").await?;
+    Ok(plugins.into_iter().map(PluginInfo::from).collect())
+}
+
+pub(crate) async fn set_plugin_enabled(
+    app: &AppHandle,
+    plugin_id: i64,
+    enabled: bool,
+) -> Result<(), String> {
+    let context = plugin_context(app)?;
+    let action = if enabled { "enable" } else { "disable" };
+    let request = context
+        .client
+        .post(format!("{}/plugin/{plugin_id}/{action}", context.base_url))
+        .header("X-Gotify-Key", &context.token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to {action} plugin: {error}"))?;
+    expect_success(response, &format!("{action} plugin")).await?;
+    debug_log(&format!("plugins: {action}d id={plugin_id}"));
+    Ok(())
+}
+
+/// Markdown the plugin renders for the current user (`displayer` capability).
+pub(crate) async fn get_plugin_display(app: &AppHandle, plugin_id: i64) -> Result<String, String> {
+    let context = plugin_context(app)?;
+    let request = context
+        .client
+        .get(format!("{}/plugin/{plugin_id}/display", context.base_url))
+        .header("X-Gotify-Key", &context.token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to load plugin display: {error}"))?;
+    // The display is sent as a JSON string.
+    let body = read_text(response, "Plugin display").await?;
+    Ok(serde_json::from_str::<String>(&body).unwrap_or(body))
+}
+
+/// YAML configuration of a plugin (`configurer` capability), passed through as
+/// text so comments and key order survive an edit.
+pub(crate) async fn get_plugin_config(app: &AppHandle, plugin_id: i64) -> Result<String, String> {
+    let context = plugin_context(app)?;
+    let request = context
+        .client
+        .get(format!("{}/plugin/{plugin_id}/config", context.base_url))
+        .header("X-Gotify-Key", &context.token);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to load plugin config: {error}"))?;
+    read_text(response, "Plugin config").await
+}
+
+pub(crate) async fn update_plugin_config(
+    app: &AppHandle,
+    plugin_id: i64,
+    config: String,
+) -> Result<(), String> {
+    if config.len() > PLUGIN_CONFIG_MAX_BYTES {
+        return Err(format!(
+            "Plugin config must be {} KB or smaller",
+            PLUGIN_CONFIG_MAX_BYTES / 1024
+        ));
+    }
+    let context = plugin_context(app)?;
+    let request = context
+        .client
+        .post(format!("{}/plugin/{plugin_id}/config", context.base_url))
+        .header("X-Gotify-Key", &context.token)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-yaml")
+        .body(config);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to save plugin config: {error}"))?;
+    expect_success(response, "Save plugin config").await?;
+    debug_log(&format!("plugins: config saved id={plugin_id}"));
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::{
+    ffi::c_void,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use core_foundation::{
+    base::TCFType,
+    runloop::{kCFRunLoopCommonModes, CFRunLoop, CFRunLoopSource, CFRunLoopSourceRef},
+};
+use tauri::AppHandle;
+
+use crate::debug_log;
+
+type IoConnect = u32;
+type IoObject = u32;
+type IoNotificationPortRef = *mut c_void;
+type IoServiceInterestCallback = extern "C" fn(*mut c_void, IoObject, u32, *mut c_void);
+
+/// `iokit_common_msg` values from IOKit/IOMessage.h.
+const IO_MESSAGE_CAN_SYSTEM_SLEEP: u32 = 0xe000_0270;
+const IO_MESSAGE_SYSTEM_WILL_SLEEP: u32 = 0xe000_0280;
+const IO_MESSAGE_SYSTEM_HAS_POWERED_ON: u32 = 0xe000_0300;
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IORegisterForSystemPower(
+        refcon: *mut c_void,
+        port: *mut IoNotificationPortRef,
+        callback: IoServiceInterestCallback,
+        notifier: *mut IoObject,
+    ) -> IoConnect;
+    fn IONotificationPortGetRunLoopSource(port: IoNotificationPortRef) -> CFRunLoopSourceRef;
+    fn IOAllowPowerChange(kernel_port: IoConnect, notification_id: isize) -> i32;
+}
+
+struct PowerContext {
+    app: AppHandle,
+    root_port: AtomicU32,
+}
+
+/// Subscribes to system power events. IOKit delivers them on a run loop, so
+/// this gets its own thread like the network watch.
+pub(crate) fn watch_power(app: AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("power-watch".to_string())
+        .spawn(move || run(app));
+    if let Err(error) = spawned {
+        debug_log(&format!("power watch: failed to start: {error}"));
+    }
+}
+
+fn run(app: AppHandle) {
+    // Lives as long as the run loop below, which is the life of the app.
+    let context = Box::into_raw(Box::new(PowerContext {
+        app,
+        root_port: AtomicU32::new(0),
+    }));
+    let mut port: IoNotificationPortRef = std::ptr::null_mut();
+    let mut notifier: IoObject = 0;
+    let root_port = unsafe {
+        IORegisterForSystemPower(context.cast(), &mut port, on_power_event, &mut notifier)
+    };
+    if root_port == 0 || port.is_null() {
+        debug_log("power watch: failed to register for power events");
+        return;
+    }
+    unsafe { &*context }
+        .root_port
+        .store(root_port, Ordering::SeqCst);
+    let source =
+        unsafe { CFRunLoopSource::wrap_under_get_rule(IONotificationPortGetRunLoopSource(port)) };
+    CFRunLoop::get_current().add_source(&source, unsafe { kCFRunLoopCommonModes });
+    CFRunLoop::run_current();
+}
+
+extern "C" fn on_power_event(
+    refcon: *mut c_void,
+    _service: IoObject,
+    message_type: u32,
+    argument: *mut c_void,
+) {
+    let context = unsafe { &*refcon.cast::<PowerContext>() };
+    match message_type {
+        // Sleep waits for every registered client to answer; never hold it up.
+        IO_MESSAGE_CAN_SYSTEM_SLEEP | IO_MESSAGE_SYSTEM_WILL_SLEEP => unsafe {
+            IOAllowPowerChange(context.root_port.load(Ordering::SeqCst), argument as isize);
+        },
+        IO_MESSAGE_SYSTEM_HAS_POWERED_ON => {
+            debug_log("power watch: system woke");
+            crate::stream::system_woke(&context.app);
+        }
+        _ => {}
+    }
+}
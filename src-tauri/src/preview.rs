@@ -7,12 +7,15 @@ use crate::{
     UrlPreview, PREVIEW_MAX_HTML_BYTES, PREVIEW_MAX_REDIRECTS, PREVIEW_REQUEST_TIMEOUT_SECS,
 };
 
-pub(crate) async fn fetch_url_preview(url: String) -> Result<UrlPreview, String> {
+pub(crate) async fn fetch_url_preview(
+    url: String,
+    proxy: Option<crate::proxy::ProxyConfig>,
+) -> Result<UrlPreview, String> {
     let mut current_url =
         reqwest::Url::parse(url.trim()).map_err(|error| format!("Invalid preview URL: {error}"))?;
     enforce_preview_target_policy(&current_url).await?;
 
-    let client = reqwest::Client::builder()
+    let client = crate::proxy::apply_to_client(reqwest::Client::builder(), proxy.as_ref())?
         .timeout(Duration::from_secs(PREVIEW_REQUEST_TIMEOUT_SECS))
         .redirect(reqwest::redirect::Policy::none())
         .build()
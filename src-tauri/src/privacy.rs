@@ -0,0 +1,62 @@
+use tauri::{menu::CheckMenuItem, AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    settings::{read_settings, save_non_secret_settings, to_settings_response, SettingsResponse},
+    AppState,
+};
+
+pub(crate) fn create_tray_item(
+    app: &AppHandle,
+    enabled: bool,
+) -> Result<CheckMenuItem<tauri::Wry>, tauri::Error> {
+    let item = CheckMenuItem::with_id(
+        app,
+        "privacy_mode",
+        "Privacy Mode",
+        true,
+        enabled,
+        None::<&str>,
+    )?;
+    if let Ok(mut tray_item) = app.state::<AppState>().tray_privacy_item.lock() {
+        *tray_item = Some(item.clone());
+    }
+    Ok(item)
+}
+
+/// Keeps the tray check mark in line with the setting, whichever side changed it.
+pub(crate) fn sync_tray_item(app: &AppHandle, enabled: bool) {
+    let Some(item) = app
+        .state::<AppState>()
+        .tray_privacy_item
+        .lock()
+        .ok()
+        .and_then(|item| item.clone())
+    else {
+        return;
+    };
+    if let Err(error) = item.set_checked(enabled) {
+        debug_log(&format!("failed to update privacy mode menu item: {error}"));
+    }
+}
+
+/// Sets privacy mode, or flips it when `enabled` is `None` (tray and shortcuts).
+pub(crate) fn set_privacy_mode(
+    app: &AppHandle,
+    enabled: Option<bool>,
+) -> Result<SettingsResponse, String> {
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.privacy_mode = enabled.unwrap_or(!settings.privacy_mode);
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!("privacy mode set to {}", settings.privacy_mode));
+    sync_tray_item(app, settings.privacy_mode);
+    Ok(to_settings_response(settings))
+}
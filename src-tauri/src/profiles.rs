@@ -0,0 +1,529 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{
+    menu::{CheckMenuItem, IsMenuItem, MenuItem, Submenu},
+    AppHandle, Manager, Runtime,
+};
+
+use crate::{
+    debug_log,
+    settings::{
+        load_settings, read_settings, save_non_secret_settings, SettingsResponse, StoredSettings,
+    },
+    AppState, MAX_PROFILE_BADGE_CHARS, TRAY_PROFILE_ITEM_PREFIX,
+};
+
+/// A saved server: its URL and the client and application tokens issued by it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct ServerProfile {
+    pub(crate) base_url: String,
+    pub(crate) token: Option<String>,
+    pub(crate) app_tokens: BTreeMap<String, String>,
+    pub(crate) lan_base_url: Option<String>,
+    /// Hostname -> IP address overrides, for split-horizon DNS where this
+    /// server's LAN address differs from what public DNS returns.
+    pub(crate) host_pins: BTreeMap<String, String>,
+    /// Authenticate this server's stream with `?token=` rather than the header.
+    pub(crate) stream_token_in_query: bool,
+    /// Short emoji or text put in front of notification titles and the tray
+    /// tooltip, so alerts from different servers are told apart at a glance.
+    pub(crate) badge: Option<String>,
+    /// `#rrggbb` accent for the profile in the app.
+    pub(crate) color: Option<String>,
+    /// Keep a stream open to this server while another profile is active, so
+    /// its alerts still arrive.
+    pub(crate) background_stream: bool,
+}
+
+/// Profile as shown to the webview; the token is reduced to `has_token`.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ProfileSummary {
+    pub(crate) name: String,
+    pub(crate) base_url: String,
+    pub(crate) has_token: bool,
+    pub(crate) badge: Option<String>,
+    pub(crate) color: Option<String>,
+    pub(crate) background_stream: bool,
+}
+
+pub(crate) fn profile_summaries(stored: &StoredSettings) -> Vec<ProfileSummary> {
+    stored
+        .profiles
+        .iter()
+        .map(|(name, profile)| ProfileSummary {
+            name: name.clone(),
+            base_url: profile.base_url.clone(),
+            has_token: profile
+                .token
+                .as_deref()
+                .is_some_and(|token| !token.trim().is_empty()),
+            badge: profile.badge.clone(),
+            color: profile.color.clone(),
+            background_stream: profile.background_stream,
+        })
+        .collect()
+}
+
+fn normalize_profile_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Profile name is required".to_string());
+    }
+    if trimmed.chars().count() > 64 {
+        return Err("Profile name must be 64 characters or fewer".to_string());
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err("Profile name must not contain control characters".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+fn normalize_badge(badge: Option<String>) -> Result<Option<String>, String> {
+    let Some(badge) = badge.map(|badge| badge.trim().to_string()) else {
+        return Ok(None);
+    };
+    if badge.is_empty() {
+        return Ok(None);
+    }
+    // Counted in chars, so flag and family emoji built from several code
+    // points still fit.
+    if badge.chars().count() > MAX_PROFILE_BADGE_CHARS {
+        return Err(format!(
+            "Badge must be {MAX_PROFILE_BADGE_CHARS} characters or fewer"
+        ));
+    }
+    if badge.chars().any(char::is_control) {
+        return Err("Badge must not contain control characters".to_string());
+    }
+    Ok(Some(badge))
+}
+
+fn normalize_color(color: Option<String>) -> Result<Option<String>, String> {
+    let Some(color) = color.map(|color| color.trim().to_ascii_lowercase()) else {
+        return Ok(None);
+    };
+    if color.is_empty() {
+        return Ok(None);
+    }
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err("Color must look like #1e90ff".to_string());
+    }
+    Ok(Some(color))
+}
+
+/// Badge of the active profile, if it has one.
+pub(crate) fn active_badge(settings: &StoredSettings) -> Option<String> {
+    settings
+        .active_profile
+        .as_ref()
+        .and_then(|active| settings.profiles.get(active))
+        .and_then(|profile| profile.badge.clone())
+}
+
+/// Message cache file name for a profile. Hashed so profile names never need
+/// escaping and do not show up in the config directory listing.
+pub(crate) fn profile_messages_file_name(name: &str) -> String {
+    let digest = Sha256::digest(name.as_bytes());
+    let hex: String = digest[..6]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("messages-{hex}.json")
+}
+
+/// Mirrors `active_profile` into app state so `messages_file` can resolve the
+/// cache path without re-reading settings.
+pub(crate) fn set_active_profile<R: Runtime>(app: &AppHandle<R>, name: Option<String>) {
+    if let Ok(mut active) = app.state::<AppState>().active_profile.lock() {
+        *active = name;
+    }
+}
+
+/// Saves the current server URL and token as `name` and makes it the active profile.
+/// The in-memory message cache moves with it.
+pub(crate) fn save_profile(app: &AppHandle, name: &str) -> Result<SettingsResponse, String> {
+    let name = normalize_profile_name(name)?;
+    {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        if settings.base_url.trim().is_empty() {
+            return Err("Configure a server before saving it as a profile".to_string());
+        }
+        // Saving over an existing profile keeps its badge and color.
+        let existing = settings.profiles.get(&name).cloned().unwrap_or_default();
+        settings.profiles.insert(
+            name.clone(),
+            ServerProfile {
+                base_url: settings.base_url.clone(),
+                token: settings.token.clone(),
+                app_tokens: settings.app_tokens.clone(),
+                lan_base_url: settings.lan_base_url.clone(),
+                host_pins: settings.host_pins.clone(),
+                stream_token_in_query: settings.stream_token_in_query,
+                ..existing
+            },
+        );
+        settings.active_profile = Some(name.clone());
+        save_non_secret_settings(app, &settings)?;
+    }
+    set_active_profile(app, Some(name.clone()));
+    persist_current_messages(app);
+    refresh_tray_profiles(app);
+    debug_log(&format!("profiles: saved and activated {name:?}"));
+    load_settings(app)
+}
+
+pub(crate) fn set_profile_badge(
+    app: &AppHandle,
+    name: &str,
+    badge: Option<String>,
+    color: Option<String>,
+) -> Result<SettingsResponse, String> {
+    let name = normalize_profile_name(name)?;
+    let badge = normalize_badge(badge)?;
+    let color = normalize_color(color)?;
+    {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        let profile = settings
+            .profiles
+            .get_mut(&name)
+            .ok_or_else(|| format!("No saved server named {name:?}"))?;
+        profile.badge = badge;
+        profile.color = color;
+        save_non_secret_settings(app, &settings)?;
+    }
+    refresh_tray_profiles(app);
+    load_settings(app)
+}
+
+pub(crate) fn set_profile_background_stream(
+    app: &AppHandle,
+    name: &str,
+    enabled: bool,
+) -> Result<SettingsResponse, String> {
+    let name = normalize_profile_name(name)?;
+    {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        let profile = settings
+            .profiles
+            .get_mut(&name)
+            .ok_or_else(|| format!("No saved server named {name:?}"))?;
+        profile.background_stream = enabled;
+        save_non_secret_settings(app, &settings)?;
+    }
+    debug_log(&format!(
+        "profiles: background stream for {name:?} {}",
+        if enabled { "enabled" } else { "disabled" }
+    ));
+    crate::background_streams::sync(app);
+    load_settings(app)
+}
+
+pub(crate) fn delete_profile(app: &AppHandle, name: &str) -> Result<SettingsResponse, String> {
+    let name = normalize_profile_name(name)?;
+    {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        if settings.active_profile.as_deref() == Some(name.as_str()) {
+            return Err("Switch to another profile before deleting this one".to_string());
+        }
+        if settings.profiles.remove(&name).is_none() {
+            return Err(format!("No saved server named {name:?}"));
+        }
+        save_non_secret_settings(app, &settings)?;
+    }
+    crate::background_streams::sync(app);
+    if let Ok(path) = crate::messages_file(app) {
+        crate::message_store::remove(&path.with_file_name(profile_messages_file_name(&name)));
+    }
+    refresh_tray_profiles(app);
+    load_settings(app)
+}
+
+/// Stops the stream, swaps the server URL and token for the saved profile, loads
+/// that profile's message cache and reconnects.
+pub(crate) async fn switch_profile(
+    app: &AppHandle,
+    name: &str,
+) -> Result<SettingsResponse, String> {
+    let name = normalize_profile_name(name)?;
+    let current = read_settings(app)?;
+    if current.active_profile.as_deref() == Some(name.as_str()) {
+        return Ok(crate::settings::to_settings_response(current));
+    }
+    if !current.profiles.contains_key(&name) {
+        return Err(format!("No saved server named {name:?}"));
+    }
+
+    let _ = crate::stream::stop_stream(app.clone());
+    // The incoming profile's background stream appends to the cache file that is
+    // about to be loaded; it becomes the foreground stream instead.
+    crate::background_streams::stop(app, &name);
+    // The worker is serial, so this waits until queued cache writes, and the
+    // debounced snapshot flushed here, have landed in the outgoing profile's file.
+    crate::messages::flush_pending_messages(app);
+    crate::work::run_in_background_for("profile-switch-flush", || ()).await?;
+
+    {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        let target = settings
+            .profiles
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| format!("No saved server named {name:?}"))?;
+        if let Some(outgoing) = settings
+            .active_profile
+            .clone()
+            .and_then(|active| settings.profiles.get_mut(&active))
+        {
+            outgoing.base_url = settings.base_url.clone();
+            outgoing.token = settings.token.clone();
+            outgoing.app_tokens = settings.app_tokens.clone();
+            outgoing.lan_base_url = settings.lan_base_url.clone();
+            outgoing.host_pins = settings.host_pins.clone();
+            outgoing.stream_token_in_query = settings.stream_token_in_query;
+        }
+        settings.base_url = target.base_url;
+        settings.token = target.token;
+        settings.app_tokens = target.app_tokens;
+        settings.lan_base_url = target.lan_base_url;
+        settings.host_pins = target.host_pins;
+        settings.stream_token_in_query = target.stream_token_in_query;
+        settings.active_profile = Some(name.clone());
+        save_non_secret_settings(app, &settings)?;
+    }
+    set_active_profile(app, Some(name.clone()));
+    load_profile_state(app)?;
+    crate::snooze::rescan_profiles(app);
+    refresh_tray_profiles(app);
+    debug_log(&format!("profiles: switched to {name:?}"));
+
+    if let Err(error) = crate::stream::start_stream(app.clone(), None) {
+        let _ = crate::contract::publish_stream_error(
+            app,
+            format!("Failed to connect to {name}: {error}"),
+        );
+    }
+    crate::background_streams::sync(app);
+    load_settings(app)
+}
+
+/// Replaces everything cached from the previous server: messages, app metadata,
+/// tombstones and the ids used to decide what is new.
+pub(crate) fn load_profile_state(app: &AppHandle) -> Result<(), String> {
+    let messages = crate::messages::load_messages_from_disk(app)?;
+    let state = app.state::<AppState>();
+    {
+        let mut cache = state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        *cache = messages.clone();
+    }
+    crate::trash::replace_trash(app, crate::trash::load_trash_from_disk(app));
+    crate::dismiss::replace_dismissed(app, crate::dismiss::load_dismissed_from_disk(app));
+    // The stored copy lets icons be revalidated instead of downloaded again.
+    if let Ok(mut app_meta) = state.app_meta.lock() {
+        *app_meta = crate::messages::load_app_meta_from_disk(app);
+    }
+    if let Ok(mut etag) = state.application_list_etag.lock() {
+        *etag = None;
+    }
+    if let Ok(mut tombstones) = state.delete_tombstones.lock() {
+        tombstones.clear();
+    }
+    crate::server_info::clear(app);
+    if let Ok(mut advisory) = state.inbox_advisory.lock() {
+        *advisory = crate::inbox::InboxAdvisoryState::default();
+    }
+    crate::inbox::sync_unread_count(app);
+    if let Ok(mut runtime) = state.runtime.lock() {
+        runtime.last_message_at = None;
+        runtime.last_message_id = None;
+        runtime.last_error = None;
+        runtime.notify_watermark_id = None;
+        runtime.clock_skew_seconds = None;
+        runtime.clock_skew_warned = false;
+        runtime.stream_remote_addr = None;
+        runtime.active_endpoint = None;
+        runtime.server_health = None;
+        runtime.database_health = None;
+        runtime.last_health_check_at = None;
+        runtime.handshake_latency.clear();
+        runtime.ping_rtt.clear();
+    }
+    let _ = crate::contract::publish_messages_replace(app, messages);
+    crate::diagnostics::publish_runtime_snapshot(app);
+    Ok(())
+}
+
+/// Writes the in-memory cache to the newly active profile's store, through the
+/// same debounced, lock-guarded path as every other cache write.
+fn persist_current_messages(app: &AppHandle) {
+    let Ok(messages) = app
+        .state::<AppState>()
+        .messages
+        .lock()
+        .map(|messages| messages.clone())
+    else {
+        return;
+    };
+    if let Err(error) = crate::messages::persist_messages_snapshot(app, &messages) {
+        debug_log(&format!("profiles: failed to write message cache: {error}"));
+    }
+}
+
+/// "Servers" tray submenu with one checkable entry per saved profile.
+pub(crate) fn create_tray_profiles_menu(
+    app: &AppHandle,
+) -> Result<Submenu<tauri::Wry>, tauri::Error> {
+    let submenu = Submenu::with_id(app, "profiles", "Servers", true)?;
+    fill_tray_profiles_menu(app, &submenu)?;
+    if let Ok(mut menu) = app.state::<AppState>().tray_profiles_menu.lock() {
+        *menu = Some(submenu.clone());
+    }
+    Ok(submenu)
+}
+
+pub(crate) fn refresh_tray_profiles(app: &AppHandle) {
+    let Some(submenu) = app
+        .state::<AppState>()
+        .tray_profiles_menu
+        .lock()
+        .ok()
+        .and_then(|menu| menu.clone())
+    else {
+        return;
+    };
+    let result = submenu.items().and_then(|items| {
+        for item in &items {
+            submenu.remove(item)?;
+        }
+        fill_tray_profiles_menu(app, &submenu)
+    });
+    if let Err(error) = result {
+        debug_log(&format!("profiles: failed to rebuild tray menu: {error}"));
+    }
+    sync_tray_tooltip(app);
+}
+
+/// Names the active profile, with its badge, in the tray tooltip, followed by
+/// any background stream that is not connected.
+pub(crate) fn sync_tray_tooltip(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id("main-tray") else {
+        return;
+    };
+    let settings = read_settings(app).unwrap_or_default();
+    let mut tooltip = match (settings.active_profile.as_deref(), active_badge(&settings)) {
+        (Some(name), Some(badge)) => format!("Gotify · {badge} {name}"),
+        (Some(name), None) => format!("Gotify · {name}"),
+        (None, _) => "Gotify".to_string(),
+    };
+    let unread = crate::inbox::unread_count(app).unwrap_or(0);
+    if unread > 0 {
+        tooltip.push_str(&format!(" · {unread} unread"));
+    }
+    let background = app
+        .state::<AppState>()
+        .runtime
+        .lock()
+        .map(|runtime| runtime.background_streams())
+        .unwrap_or_default();
+    for stream in background
+        .iter()
+        .filter(|stream| stream.connection_state != "Connected")
+    {
+        tooltip.push_str(&format!(
+            "\n{}: {}",
+            stream.profile, stream.connection_state
+        ));
+    }
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+fn fill_tray_profiles_menu(
+    app: &AppHandle,
+    submenu: &Submenu<tauri::Wry>,
+) -> Result<(), tauri::Error> {
+    let settings = read_settings(app).unwrap_or_default();
+    if settings.profiles.is_empty() {
+        let placeholder = MenuItem::with_id(
+            app,
+            "profiles_empty",
+            "No saved servers",
+            false,
+            None::<&str>,
+        )?;
+        return submenu.append(&placeholder);
+    }
+    for (name, profile) in &settings.profiles {
+        let host = reqwest::Url::parse(&profile.base_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| profile.base_url.clone());
+        let label = match profile.badge.as_deref() {
+            Some(badge) => format!("{badge} {name} ({host})"),
+            None => format!("{name} ({host})"),
+        };
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("{TRAY_PROFILE_ITEM_PREFIX}{name}"),
+            label,
+            true,
+            settings.active_profile.as_deref() == Some(name.as_str()),
+            None::<&str>,
+        )?;
+        submenu.append(&item as &dyn IsMenuItem<tauri::Wry>)?;
+    }
+    Ok(())
+}
+
+/// Tray entry point; errors surface through the stream error channel.
+pub(crate) fn switch_from_tray(app: &AppHandle, name: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = switch_profile(&app, &name).await;
+        // Clicking toggles the check mark natively; rebuild so it matches settings.
+        refresh_tray_profiles(&app);
+        match result {
+            Ok(settings) => {
+                crate::contract::publish_settings_update(&app, settings);
+            }
+            Err(error) => {
+                let _ = crate::contract::publish_stream_error(
+                    &app,
+                    format!("Failed to switch server: {error}"),
+                );
+            }
+        }
+    });
+}
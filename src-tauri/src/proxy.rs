@@ -0,0 +1,486 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Mutex,
+    time::Duration,
+};
+
+use base64::Engine as _;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    debug_log, model::AppState, settings::read_settings, settings::IpFamilyPreference,
+    PROXY_RESPONSE_MAX_BYTES, SYSTEM_PROXY_POLL_SECS,
+};
+
+/// Outbound proxy for REST calls and the stream.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProxyConfig {
+    url: reqwest::Url,
+    username: Option<String>,
+    password: Option<String>,
+    bypass: Vec<String>,
+    /// macOS "Exclude simple hostnames": single-label hosts go direct.
+    bypass_simple_hostnames: bool,
+    /// Came from the system network settings rather than ours.
+    from_system: bool,
+}
+
+/// Last proxy read from the system network settings.
+static SYSTEM_PROXY: Mutex<Option<ProxyConfig>> = Mutex::new(None);
+
+/// An empty input clears the proxy. Credentials go in the separate fields, not the URL.
+pub(crate) fn normalize_proxy_url(input: &str) -> Result<Option<String>, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    let url =
+        reqwest::Url::parse(trimmed).map_err(|error| format!("Invalid proxy URL: {error}"))?;
+    if !matches!(url.scheme(), "http" | "socks5" | "socks5h") {
+        return Err("Proxy URL must start with http://, socks5:// or socks5h://".to_string());
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err("Proxy URL must include a host".to_string());
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err("Put proxy credentials in the username and password fields".to_string());
+    }
+    Ok(Some(url.as_str().trim_end_matches('/').to_string()))
+}
+
+/// Bypass entries follow `NO_PROXY` rules: `*`, an IP, a CIDR range, or a domain
+/// (with or without a leading dot) that also matches its subdomains.
+pub(crate) fn normalize_proxy_bypass(entries: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = entries
+        .into_iter()
+        .map(|entry| entry.trim().to_ascii_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+/// An explicit `proxy_url` wins; otherwise the system proxy is used, with our
+/// bypass entries added to the system exception list (so `*` forces direct).
+pub(crate) fn configured_proxy<R: Runtime>(app: &AppHandle<R>) -> Option<ProxyConfig> {
+    let settings = read_settings(app).ok()?;
+    let Some(proxy_url) = settings.proxy_url.as_deref() else {
+        let mut proxy = system_proxy()?;
+        proxy.bypass.extend(settings.proxy_bypass);
+        return Some(proxy);
+    };
+    let url = reqwest::Url::parse(proxy_url).ok()?;
+    Some(ProxyConfig {
+        url,
+        username: settings.proxy_username.filter(|value| !value.is_empty()),
+        password: settings.proxy_password.filter(|value| !value.is_empty()),
+        bypass: settings.proxy_bypass,
+        bypass_simple_hostnames: false,
+        from_system: false,
+    })
+}
+
+fn system_proxy() -> Option<ProxyConfig> {
+    SYSTEM_PROXY.lock().ok()?.clone()
+}
+
+/// Reads the system proxy once, before the first connection is made.
+pub(crate) fn load_system_proxy() {
+    let proxy = read_system_proxy();
+    if let Some(proxy) = &proxy {
+        debug_log(&format!("system proxy: {}", proxy.display()));
+    }
+    if let Ok(mut current) = SYSTEM_PROXY.lock() {
+        *current = proxy;
+    }
+}
+
+/// Polls the system proxy so switching networks or editing the proxy in System
+/// Settings takes effect without a restart. The stream reconnects on a change
+/// unless an explicit proxy is configured.
+pub(crate) async fn watch_system_proxy(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(SYSTEM_PROXY_POLL_SECS)).await;
+        let Ok(proxy) = crate::work::run_in_background_for("system-proxy", read_system_proxy).await
+        else {
+            continue;
+        };
+        let changed = match SYSTEM_PROXY.lock() {
+            Ok(mut current) if *current != proxy => {
+                *current = proxy.clone();
+                true
+            }
+            _ => false,
+        };
+        if !changed {
+            continue;
+        }
+        debug_log(&format!(
+            "system proxy changed: {}",
+            proxy
+                .as_ref()
+                .map_or("none".to_string(), ProxyConfig::display)
+        ));
+        let overridden = read_settings(&app).is_ok_and(|settings| settings.proxy_url.is_some());
+        let should_run = app
+            .state::<AppState>()
+            .runtime
+            .lock()
+            .is_ok_and(|runtime| runtime.should_run);
+        if !overridden && should_run {
+            if let Err(error) = crate::stream::restart_stream(app.clone()) {
+                debug_log(&format!(
+                    "failed to restart stream after system proxy change: {error}"
+                ));
+            }
+        }
+    }
+}
+
+/// Adds the configured proxy to a client builder; without one, reqwest's own
+/// environment-variable handling is left untouched. Bypass decisions use the same
+/// matcher as the stream so both paths agree.
+pub(crate) fn apply_to_client(
+    builder: reqwest::ClientBuilder,
+    proxy: Option<&ProxyConfig>,
+) -> Result<reqwest::ClientBuilder, String> {
+    let Some(proxy) = proxy else {
+        return Ok(builder);
+    };
+    let matcher = proxy.clone();
+    let mut reqwest_proxy = reqwest::Proxy::custom(move |target| {
+        let host = target
+            .host_str()?
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        (!matcher.bypasses(host)).then(|| matcher.url.clone())
+    });
+    if let Some(username) = &proxy.username {
+        reqwest_proxy = reqwest_proxy.basic_auth(username, proxy.password.as_deref().unwrap_or(""));
+    }
+    Ok(builder.proxy(reqwest_proxy))
+}
+
+impl ProxyConfig {
+    pub(crate) fn bypasses(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        if self.bypass_simple_hostnames && !host.contains('.') && !host.contains(':') {
+            return true;
+        }
+        self.bypass
+            .iter()
+            .any(|entry| bypass_entry_matches(entry, &host))
+    }
+
+    pub(crate) fn display(&self) -> String {
+        let url = self.url.as_str().trim_end_matches('/');
+        if self.from_system {
+            format!("{url} from system settings")
+        } else {
+            url.to_string()
+        }
+    }
+
+    /// Opens a TCP tunnel to `host:port` through the proxy. Returns the stream and
+    /// the proxy's address.
+    pub(crate) async fn connect(
+        &self,
+        host: &str,
+        port: u16,
+        preference: IpFamilyPreference,
+    ) -> Result<(TcpStream, SocketAddr), String> {
+        let proxy_host = self
+            .url
+            .host_str()
+            .ok_or_else(|| "Proxy URL has no host".to_string())?
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let proxy_port = self.url.port_or_known_default().unwrap_or(1080);
+        let (mut stream, proxy_addr) =
+            crate::dial::connect_tcp(proxy_host, proxy_port, preference, None)
+                .await
+                .map_err(|error| format!("Proxy unreachable: {error}"))?;
+        match self.url.scheme() {
+            "http" => self.http_connect(&mut stream, host, port).await?,
+            scheme => {
+                self.socks5_connect(&mut stream, host, port, scheme == "socks5h")
+                    .await?
+            }
+        }
+        Ok((stream, proxy_addr))
+    }
+
+    async fn http_connect(
+        &self,
+        stream: &mut TcpStream,
+        host: &str,
+        port: u16,
+    ) -> Result<(), String> {
+        let authority = match host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(ip)) => format!("[{ip}]:{port}"),
+            _ => format!("{host}:{port}"),
+        };
+        let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+        if let Some(username) = &self.username {
+            let credentials = base64::engine::general_purpose::STANDARD.encode(format!(
+                "{username}:{}",
+                self.password.as_deref().unwrap_or("")
+            ));
+            request.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+        }
+        request.push_str("\r\n");
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .map_err(|error| format!("Proxy CONNECT failed: {error}"))?;
+
+        // Read byte by byte so nothing after the header block is consumed.
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            if response.len() >= PROXY_RESPONSE_MAX_BYTES {
+                return Err("Proxy CONNECT response too large".to_string());
+            }
+            let byte = stream
+                .read_u8()
+                .await
+                .map_err(|error| format!("Proxy closed the connection: {error}"))?;
+            response.push(byte);
+        }
+        let status_line = String::from_utf8_lossy(&response)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+        if status != "200" {
+            return Err(format!("Proxy refused CONNECT: {}", status_line.trim()));
+        }
+        Ok(())
+    }
+
+    async fn socks5_connect(
+        &self,
+        stream: &mut TcpStream,
+        host: &str,
+        port: u16,
+        remote_dns: bool,
+    ) -> Result<(), String> {
+        let io_error = |error: std::io::Error| format!("SOCKS5 handshake failed: {error}");
+        let greeting: &[u8] = if self.username.is_some() {
+            &[5, 2, 0x00, 0x02]
+        } else {
+            &[5, 1, 0x00]
+        };
+        stream.write_all(greeting).await.map_err(io_error)?;
+        let mut choice = [0u8; 2];
+        stream.read_exact(&mut choice).await.map_err(io_error)?;
+        match choice {
+            [5, 0x00] => {}
+            [5, 0x02] => {
+                let username = self.username.as_deref().unwrap_or("");
+                let password = self.password.as_deref().unwrap_or("");
+                if username.len() > 255 || password.len() > 255 {
+                    return Err("SOCKS5 credentials are too long".to_string());
+                }
+                let mut auth = vec![1, username.len() as u8];
+                auth.extend_from_slice(username.as_bytes());
+                auth.push(password.len() as u8);
+                auth.extend_from_slice(password.as_bytes());
+                stream.write_all(&auth).await.map_err(io_error)?;
+                let mut status = [0u8; 2];
+                stream.read_exact(&mut status).await.map_err(io_error)?;
+                if status[1] != 0 {
+                    return Err("SOCKS5 proxy rejected the credentials".to_string());
+                }
+            }
+            _ => return Err("SOCKS5 proxy offered no acceptable authentication".to_string()),
+        }
+
+        let target_ip = match host.parse::<IpAddr>() {
+            Ok(ip) => Some(ip),
+            Err(_) if remote_dns => None,
+            Err(_) => crate::dial::resolve_host(host, port, None)
+                .await?
+                .first()
+                .map(SocketAddr::ip),
+        };
+        let mut request = vec![5, 1, 0];
+        match target_ip {
+            Some(IpAddr::V4(ip)) => {
+                request.push(1);
+                request.extend_from_slice(&ip.octets());
+            }
+            Some(IpAddr::V6(ip)) => {
+                request.push(4);
+                request.extend_from_slice(&ip.octets());
+            }
+            None => {
+                if host.len() > 255 {
+                    return Err("Host name too long for SOCKS5".to_string());
+                }
+                request.push(3);
+                request.push(host.len() as u8);
+                request.extend_from_slice(host.as_bytes());
+            }
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request).await.map_err(io_error)?;
+
+        let mut reply = [0u8; 4];
+        stream.read_exact(&mut reply).await.map_err(io_error)?;
+        if reply[1] != 0 {
+            return Err(format!(
+                "SOCKS5 connect failed: {}",
+                socks5_reply_text(reply[1])
+            ));
+        }
+        let bound_len = match reply[3] {
+            1 => 4,
+            4 => 16,
+            3 => usize::from(stream.read_u8().await.map_err(io_error)?),
+            other => return Err(format!("SOCKS5 reply has unknown address type {other}")),
+        };
+        let mut bound = vec![0u8; bound_len + 2];
+        stream.read_exact(&mut bound).await.map_err(io_error)?;
+        Ok(())
+    }
+}
+
+/// Parses `scutil --proxy`. HTTPS is preferred since the server is normally
+/// reached over TLS; both HTTP entries are used as CONNECT proxies. PAC files
+/// and proxy credentials stored in the Keychain are not supported.
+#[cfg(target_os = "macos")]
+fn read_system_proxy() -> Option<ProxyConfig> {
+    let output = std::process::Command::new("/usr/sbin/scutil")
+        .arg("--proxy")
+        .output()
+        .map_err(|error| debug_log(&format!("failed to run scutil --proxy: {error}")))
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_scutil_proxy(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_system_proxy() -> Option<ProxyConfig> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn parse_scutil_proxy(output: &str) -> Option<ProxyConfig> {
+    let mut values = std::collections::HashMap::new();
+    let mut exceptions = Vec::new();
+    let mut in_exceptions = false;
+    // Only top-level keys count; `__SCOPED__` holds per-interface copies.
+    let mut depth = 0usize;
+    for line in output.lines() {
+        let line = line.trim();
+        if line == "}" {
+            depth = depth.saturating_sub(1);
+            in_exceptions &= depth > 1;
+            continue;
+        }
+        let opens_block = line.ends_with('{');
+        match (depth, line.split_once(" : ")) {
+            (1, Some(("ExceptionsList", _))) => in_exceptions = true,
+            (1, Some((key, value))) if !opens_block => {
+                values.insert(key, value);
+            }
+            (2, Some((_, entry))) if in_exceptions => {
+                exceptions.push(system_exception_to_bypass(entry));
+            }
+            _ => {}
+        }
+        if opens_block {
+            depth += 1;
+        }
+    }
+
+    let enabled = |prefix: &str| values.get(format!("{prefix}Enable").as_str()) == Some(&"1");
+    let endpoint = |prefix: &str| {
+        let host = values.get(format!("{prefix}Proxy").as_str())?;
+        let port = values.get(format!("{prefix}Port").as_str())?;
+        Some(format!("{host}:{port}"))
+    };
+    let url = [("HTTPS", "http"), ("HTTP", "http"), ("SOCKS", "socks5h")]
+        .into_iter()
+        .filter(|(prefix, _)| enabled(prefix))
+        .find_map(|(prefix, scheme)| {
+            reqwest::Url::parse(&format!("{scheme}://{}", endpoint(prefix)?)).ok()
+        })?;
+    Some(ProxyConfig {
+        url,
+        username: None,
+        password: None,
+        bypass: normalize_proxy_bypass(exceptions),
+        bypass_simple_hostnames: values.get("ExcludeSimpleHostnames") == Some(&"1"),
+        from_system: true,
+    })
+}
+
+/// System exceptions use `*.example.com` wildcards and short CIDRs like
+/// `169.254/16`; rewrite them into the bypass syntax used above.
+#[cfg(target_os = "macos")]
+fn system_exception_to_bypass(entry: &str) -> String {
+    let entry = entry.trim().trim_start_matches('*');
+    let Some((network, prefix)) = entry.split_once('/') else {
+        return entry.to_string();
+    };
+    if network.contains(':') || network.parse::<IpAddr>().is_ok() {
+        return entry.to_string();
+    }
+    let mut octets: Vec<&str> = network.split('.').collect();
+    octets.resize(4, "0");
+    format!("{}/{prefix}", octets.join("."))
+}
+
+fn socks5_reply_text(code: u8) -> &'static str {
+    match code {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+fn bypass_entry_matches(entry: &str, host: &str) -> bool {
+    if entry == "*" {
+        return true;
+    }
+    if let Some((network, prefix)) = entry.split_once('/') {
+        let (Ok(network), Ok(prefix), Ok(ip)) = (
+            network.parse::<IpAddr>(),
+            prefix.parse::<u32>(),
+            host.parse::<IpAddr>(),
+        ) else {
+            return false;
+        };
+        return match (network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) if prefix <= 32 => {
+                let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) if prefix <= 128 => {
+                let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        };
+    }
+    if let Ok(ip) = entry.parse::<IpAddr>() {
+        return host.parse::<IpAddr>() == Ok(ip);
+    }
+    let domain = entry.trim_start_matches('.');
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
@@ -0,0 +1,48 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use tauri::AppHandle;
+
+use crate::{debug_log, QUIT_FLUSH_TIMEOUT_MS, QUIT_STREAM_CLOSE_TIMEOUT_MS};
+
+static QUITTING: AtomicBool = AtomicBool::new(false);
+
+/// Tray "Quit": closes every stream with a Close frame and waits briefly for
+/// the server's answer, lets queued cache writes land, then exits. Each wait is
+/// bounded so an unreachable server cannot hold the app open.
+pub(crate) fn quit(app: &AppHandle) {
+    if QUITTING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = crate::stream::stop_stream(app.clone());
+        crate::background_streams::stop_all(&app);
+
+        let deadline = Instant::now() + Duration::from_millis(QUIT_STREAM_CLOSE_TIMEOUT_MS);
+        while crate::stream::open_sockets() > 0 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        let open = crate::stream::open_sockets();
+        if open > 0 {
+            debug_log(&format!(
+                "quit: {open} stream(s) still closing; exiting anyway"
+            ));
+        }
+
+        // The worker is serial, so this returns once everything queued before it,
+        // including message cache persistence, has run.
+        crate::messages::flush_pending_messages(&app);
+        let flush = crate::work::run_in_background_for("quit-flush", || ());
+        match tokio::time::timeout(Duration::from_millis(QUIT_FLUSH_TIMEOUT_MS), flush).await {
+            Ok(Ok(())) => {}
+            Ok(Err(error)) => debug_log(&format!("quit: flush failed: {error}")),
+            Err(_) => debug_log("quit: pending cache writes did not finish in time"),
+        }
+
+        crate::compaction::compact_on_quit(&app);
+        app.exit(0);
+    });
+}
@@ -0,0 +1,113 @@
+use std::sync::{Arc, RwLock};
+
+use regex::{Regex, RegexBuilder};
+use tauri::{AppHandle, Runtime};
+
+use crate::{
+    debug_log, settings::read_settings, MAX_REDACTION_PATTERNS, REDACTION_MASK,
+    REDACTION_PATTERN_SIZE_LIMIT,
+};
+
+/// Compiled patterns of the current settings, so messages do not read the
+/// settings file. `None` until settings were loaded, or while they hold a
+/// pattern that does not compile.
+static ACTIVE: RwLock<Option<Arc<Vec<Regex>>>> = RwLock::new(None);
+
+fn compile_pattern(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .size_limit(REDACTION_PATTERN_SIZE_LIMIT)
+        .build()
+        .map_err(|error| format!("Invalid redaction pattern {pattern:?}: {error}"))
+}
+
+/// Trims, drops blanks and duplicates, and rejects patterns that do not compile
+/// so a typo is reported on save instead of silently letting secrets through.
+pub(crate) fn normalize_redaction_patterns(patterns: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for pattern in patterns {
+        let pattern = pattern.trim().to_string();
+        if pattern.is_empty() || normalized.contains(&pattern) {
+            continue;
+        }
+        compile_pattern(&pattern)?;
+        normalized.push(pattern);
+    }
+    if normalized.len() > MAX_REDACTION_PATTERNS {
+        return Err(format!(
+            "At most {MAX_REDACTION_PATTERNS} redaction patterns are supported"
+        ));
+    }
+    Ok(normalized)
+}
+
+/// Mirrors the stored patterns; called wherever settings are loaded or saved.
+/// A hand-edited settings file can hold a pattern that does not compile, which
+/// leaves redaction failing closed until it is fixed.
+pub(crate) fn set_patterns(patterns: &[String]) {
+    let compiled = match patterns
+        .iter()
+        .map(|pattern| compile_pattern(pattern))
+        .collect::<Result<Vec<Regex>, String>>()
+    {
+        Ok(compiled) => Some(Arc::new(compiled)),
+        Err(error) => {
+            debug_log(&format!("redaction: {error}; masking message content"));
+            None
+        }
+    };
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = compiled;
+    }
+}
+
+fn active_patterns<R: Runtime>(app: &AppHandle<R>) -> Option<Arc<Vec<Regex>>> {
+    if let Some(patterns) = ACTIVE.read().ok().and_then(|active| active.clone()) {
+        return Some(patterns);
+    }
+    match read_settings(app) {
+        Ok(settings) => set_patterns(&settings.redaction_patterns),
+        Err(error) => debug_log(&format!("redaction: cannot read patterns: {error}")),
+    }
+    ACTIVE.read().ok().and_then(|active| active.clone())
+}
+
+fn redact_text(patterns: &[Regex], text: &mut String) -> bool {
+    let mut changed = false;
+    for pattern in patterns {
+        if let std::borrow::Cow::Owned(replaced) = pattern.replace_all(text, REDACTION_MASK) {
+            *text = replaced;
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Masks every match of the user's redaction patterns in the title and body.
+/// Runs when a message is first converted, so neither the notification nor the
+/// on-disk cache ever sees the original text. When the patterns cannot be read
+/// the whole title and body are masked instead of passed through.
+pub(crate) fn redact_message<R: Runtime>(
+    app: &AppHandle<R>,
+    message_id: i64,
+    title: &mut String,
+    body: &mut String,
+) {
+    let Some(patterns) = active_patterns(app) else {
+        debug_log(&format!(
+            "redaction: patterns unavailable, masked message id={message_id}"
+        ));
+        *title = REDACTION_MASK.to_string();
+        *body = REDACTION_MASK.to_string();
+        return;
+    };
+    if patterns.is_empty() {
+        return;
+    }
+    let title_changed = redact_text(&patterns, title);
+    let body_changed = redact_text(&patterns, body);
+    if title_changed || body_changed {
+        debug_log(&format!(
+            "redaction: masked content in message id={message_id}"
+        ));
+    }
+}
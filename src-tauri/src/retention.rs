@@ -0,0 +1,180 @@
+use std::{collections::HashSet, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    op_trace::{self, TraceOp},
+    settings::{
+        load_token, read_settings, save_non_secret_settings, to_settings_response, SettingsResponse,
+    },
+    unix_now_secs, AppState, CachedMessage, MAX_MESSAGE_RETENTION_DAYS,
+    MESSAGE_RETENTION_INTERVAL_SECS,
+};
+
+/// Age limit for cached messages. `days` 0 keeps them until the cache limit
+/// evicts them. Starred, archived and imported messages are never pruned.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default)]
+pub(crate) struct MessageRetention {
+    pub(crate) days: u64,
+    /// Also delete pruned messages on the server, for every client.
+    pub(crate) delete_on_server: bool,
+}
+
+impl MessageRetention {
+    pub(crate) fn validate(self) -> Result<Self, String> {
+        if self.days > MAX_MESSAGE_RETENTION_DAYS {
+            return Err(format!(
+                "Retention must be between 0 and {MAX_MESSAGE_RETENTION_DAYS} days"
+            ));
+        }
+        Ok(self)
+    }
+
+    /// Unix seconds before which messages expire; `None` while retention is off.
+    fn cutoff(self, now_secs: u64) -> Option<i64> {
+        if self.days == 0 {
+            return None;
+        }
+        let cutoff = now_secs.saturating_sub(self.days.saturating_mul(24 * 60 * 60));
+        Some(i64::try_from(cutoff).unwrap_or(i64::MAX))
+    }
+}
+
+/// Undated messages are kept rather than guessed at.
+fn is_expired(message: &CachedMessage, cutoff: i64) -> bool {
+    !message.is_protected()
+        && chrono::DateTime::parse_from_rfc3339(&message.date)
+            .is_ok_and(|date| date.timestamp() < cutoff)
+}
+
+/// Removes expired messages from a synced list, so a full sync does not bring
+/// back what pruning took out of the cache. Local flags must already be carried
+/// over, or a starred message would look unprotected.
+pub(crate) fn drop_expired(app: &AppHandle, messages: &mut Vec<CachedMessage>) {
+    let retention = read_settings(app)
+        .map(|settings| settings.message_retention)
+        .unwrap_or_default();
+    if let Some(cutoff) = retention.cutoff(unix_now_secs()) {
+        messages.retain(|message| !is_expired(message, cutoff));
+    }
+}
+
+pub(crate) fn set_message_retention(
+    app: &AppHandle,
+    retention: MessageRetention,
+) -> Result<SettingsResponse, String> {
+    let retention = retention.validate()?;
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.message_retention = retention;
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!("message retention set to {retention:?}"));
+    if retention.days > 0 {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(error) = prune(&app).await {
+                debug_log(&format!("retention: prune failed: {error}"));
+            }
+        });
+    }
+    Ok(to_settings_response(settings))
+}
+
+/// Drops expired messages from the cache and, when configured, deletes them on
+/// the server. Returns how many left the cache.
+async fn prune(app: &AppHandle) -> Result<usize, String> {
+    let retention = read_settings(app)?.message_retention;
+    let Some(cutoff) = retention.cutoff(unix_now_secs()) else {
+        return Ok(0);
+    };
+    let expired: HashSet<i64> = app
+        .state::<AppState>()
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?
+        .iter()
+        .filter(|message| is_expired(message, cutoff))
+        .map(|message| message.id)
+        .collect();
+    if expired.is_empty() {
+        return Ok(0);
+    }
+    let removed = crate::messages::remove_messages_from_cache(app, &expired)?;
+    debug_log(&format!(
+        "retention: pruned {removed} message(s) older than {} days",
+        retention.days
+    ));
+    if retention.delete_on_server {
+        delete_on_server(app, &expired).await?;
+    }
+    Ok(removed)
+}
+
+/// Best effort: a failed delete is logged, not retried. The message stays on
+/// the server, and `drop_expired` keeps it out of the cache.
+async fn delete_on_server(app: &AppHandle, message_ids: &HashSet<i64>) -> Result<(), String> {
+    let settings = read_settings(app)?;
+    let base_url = crate::endpoints::active_base_url(app, &settings)?;
+    let token =
+        load_token()?.ok_or_else(|| "No token found. Save token in settings first.".to_string())?;
+    let client = crate::settings::server_client_builder(app)?
+        .build()
+        .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
+
+    let mut deleted = 0usize;
+    let mut last_error = None;
+    for &message_id in message_ids {
+        let request = client
+            .delete(format!("{base_url}/message/{message_id}"))
+            .header("X-Gotify-Key", &token);
+        let result = crate::retry::send(request)
+            .await
+            .map_err(|error| error.to_string())
+            .map(|response| response.status().as_u16());
+        match result {
+            // 404: already gone, e.g. deleted from another client.
+            Ok(status) if (200..300).contains(&status) || status == 404 => {
+                op_trace::emit(
+                    app,
+                    TraceOp::Delete,
+                    message_id,
+                    "retention",
+                    "expired message deleted on server",
+                    Some(status),
+                );
+                deleted += 1;
+            }
+            Ok(status) => last_error = Some(format!("HTTP {status}")),
+            Err(error) => last_error = Some(error),
+        }
+    }
+    debug_log(&format!(
+        "retention: deleted {deleted} expired message(s) on the server{}",
+        last_error
+            .as_ref()
+            .map(|error| format!(", last error: {error}"))
+            .unwrap_or_default()
+    ));
+    Ok(())
+}
+
+pub(crate) fn watch_message_retention(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(MESSAGE_RETENTION_INTERVAL_SECS)).await;
+            if let Err(error) = prune(&app).await {
+                debug_log(&format!("retention: scheduled prune failed: {error}"));
+            }
+        }
+    });
+}
@@ -0,0 +1,147 @@
+use std::{
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    settings::{read_settings, save_non_secret_settings, to_settings_response, SettingsResponse},
+    AppState, DEFAULT_REQUEST_RETRY_ATTEMPTS, DEFAULT_REQUEST_RETRY_BASE_DELAY_MS,
+    MAX_REQUEST_RETRY_ATTEMPTS, MAX_REQUEST_RETRY_DELAY_MS,
+};
+
+/// Retries for Gotify REST requests that fail with a network error or a 5xx.
+/// `attempts` includes the first try; the wait starts at `base_delay_ms` and
+/// doubles after each failure, with jitter.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub(crate) struct RetryPolicy {
+    pub(crate) attempts: u32,
+    pub(crate) base_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_REQUEST_RETRY_ATTEMPTS,
+            base_delay_ms: DEFAULT_REQUEST_RETRY_BASE_DELAY_MS,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub(crate) fn validate(self) -> Result<Self, String> {
+        if !(1..=MAX_REQUEST_RETRY_ATTEMPTS).contains(&self.attempts) {
+            return Err(format!(
+                "Attempts must be between 1 and {MAX_REQUEST_RETRY_ATTEMPTS}"
+            ));
+        }
+        if !(1..=MAX_REQUEST_RETRY_DELAY_MS).contains(&self.base_delay_ms) {
+            return Err(format!(
+                "Retry delay must be between 1 and {MAX_REQUEST_RETRY_DELAY_MS} ms"
+            ));
+        }
+        Ok(self)
+    }
+}
+
+static ACTIVE: OnceLock<Mutex<RetryPolicy>> = OnceLock::new();
+
+/// Mirrors the stored policy so every request does not read settings. An
+/// out-of-range stored policy falls back to the default.
+pub(crate) fn set_policy(policy: &RetryPolicy) {
+    let policy = policy.validate().unwrap_or_default();
+    if let Ok(mut current) = ACTIVE
+        .get_or_init(|| Mutex::new(RetryPolicy::default()))
+        .lock()
+    {
+        *current = policy;
+    }
+}
+
+fn policy() -> RetryPolicy {
+    ACTIVE
+        .get()
+        .and_then(|active| active.lock().ok().map(|active| *active))
+        .unwrap_or_default()
+}
+
+pub(crate) fn set_request_retry(
+    app: &AppHandle,
+    policy: RetryPolicy,
+) -> Result<SettingsResponse, String> {
+    let policy = policy.validate()?;
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.request_retry = policy;
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!("request retry set to {policy:?}"));
+    Ok(to_settings_response(settings))
+}
+
+/// Only requests that are safe to repeat are retried, so a timed-out
+/// `POST /message` is never sent twice.
+fn is_idempotent(request: &reqwest::RequestBuilder) -> bool {
+    request
+        .try_clone()
+        .and_then(|request| request.build().ok())
+        .is_some_and(|request| request.method().is_idempotent())
+}
+
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_request()
+}
+
+/// Half the delay plus a random share of the other half, so clients that
+/// failed together do not retry in lockstep.
+fn jittered(delay_ms: u64) -> u64 {
+    let half = delay_ms / 2;
+    let mut bytes = [0u8; 8];
+    if SystemRandom::new().fill(&mut bytes).is_err() {
+        return delay_ms;
+    }
+    half + u64::from_le_bytes(bytes) % (delay_ms - half + 1)
+}
+
+/// Sends a Gotify REST request through fault injection, retrying network
+/// errors and 5xx responses under the configured policy. The last attempt's
+/// outcome is returned as is, so callers keep their usual error handling.
+pub(crate) async fn send(
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let policy = policy();
+    if policy.attempts <= 1 || !is_idempotent(&request) {
+        return crate::faults::send(request).await;
+    }
+    let mut delay_ms = policy.base_delay_ms;
+    for attempt in 1..policy.attempts {
+        let Some(retry) = request.try_clone() else {
+            break;
+        };
+        let failure = match crate::faults::send(retry).await {
+            Ok(response) if !response.status().is_server_error() => return Ok(response),
+            Ok(response) => format!("HTTP {}", response.status().as_u16()),
+            Err(error) if !is_transient(&error) => return Err(error),
+            Err(error) => error.to_string(),
+        };
+        let wait_ms = jittered(delay_ms);
+        debug_log(&format!(
+            "rest retry: attempt {attempt}/{} failed ({failure}); retrying in {wait_ms}ms",
+            policy.attempts
+        ));
+        tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+        delay_ms = delay_ms.saturating_mul(2).min(MAX_REQUEST_RETRY_DELAY_MS);
+    }
+    crate::faults::send(request).await
+}
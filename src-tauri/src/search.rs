@@ -0,0 +1,137 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{AppState, CachedMessage, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT};
+
+/// Filters for `query_messages`. Every field is optional and they combine with
+/// AND; an empty query pages through the whole cache.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub(crate) struct MessageQuery {
+    /// Empty matches every app.
+    pub(crate) app_ids: Vec<i64>,
+    pub(crate) min_priority: Option<i64>,
+    pub(crate) max_priority: Option<i64>,
+    /// Inclusive RFC 3339 bounds. A message whose date does not parse never
+    /// matches a date bound.
+    pub(crate) since: Option<String>,
+    pub(crate) until: Option<String>,
+    pub(crate) read: Option<bool>,
+    /// Matches messages carrying any of these labels, case-insensitively.
+    /// Empty matches every message.
+    pub(crate) labels: Vec<String>,
+    pub(crate) offset: usize,
+    pub(crate) limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct MessagePage {
+    /// Newest first, like the cache.
+    pub(crate) messages: Vec<CachedMessage>,
+    /// Matches across all pages.
+    pub(crate) total: usize,
+    /// Pass as `offset` for the next page; `None` on the last one.
+    pub(crate) next_offset: Option<usize>,
+}
+
+/// Searches the in-memory cache rather than the store: rows there are sealed, and
+/// an on-disk index would hold the plaintext the sealing exists to hide.
+///
+/// Every whitespace-separated term must appear, case-insensitively, in the title
+/// or body. Results are newest first. An empty query matches everything the
+/// filters allow.
+pub(crate) fn search_messages(
+    app: &AppHandle,
+    query: &str,
+    app_id: Option<i64>,
+    min_priority: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<CachedMessage>, String> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let state = app.state::<AppState>();
+    let messages = state
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?;
+    let mut hits: Vec<&CachedMessage> = messages
+        .iter()
+        .filter(|message| app_id.is_none_or(|app_id| message.app_id == app_id))
+        .filter(|message| min_priority.is_none_or(|min| message.priority >= min))
+        .filter(|message| matches_terms(message, &terms))
+        .collect();
+    hits.sort_by_key(|message| std::cmp::Reverse(message.id));
+    hits.truncate(limit);
+    Ok(hits.into_iter().cloned().collect())
+}
+
+fn matches_terms(message: &CachedMessage, terms: &[String]) -> bool {
+    if terms.is_empty() {
+        return true;
+    }
+    let title = message.title.to_lowercase();
+    let body = message.message.to_lowercase();
+    terms
+        .iter()
+        .all(|term| title.contains(term.as_str()) || body.contains(term.as_str()))
+}
+
+/// One page of cached messages matching `query`. Offsets index the current
+/// cache, so a message arriving between calls shifts later pages by one.
+pub(crate) fn query_messages(app: &AppHandle, query: &MessageQuery) -> Result<MessagePage, String> {
+    let since = query.since.as_deref().map(parse_bound).transpose()?;
+    let until = query.until.as_deref().map(parse_bound).transpose()?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let state = app.state::<AppState>();
+    let messages = state
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?;
+    let mut matches: Vec<&CachedMessage> = messages
+        .iter()
+        .filter(|message| query.app_ids.is_empty() || query.app_ids.contains(&message.app_id))
+        .filter(|message| query.min_priority.is_none_or(|min| message.priority >= min))
+        .filter(|message| query.max_priority.is_none_or(|max| message.priority <= max))
+        .filter(|message| query.read.is_none_or(|read| message.read == read))
+        .filter(|message| within_dates(message, since, until))
+        .filter(|message| crate::labels::has_any_label(message, &query.labels))
+        .collect();
+    matches.sort_by(|a, b| crate::cached_message_cmp(a, b));
+    let total = matches.len();
+    let end = query.offset.saturating_add(limit).min(total);
+    let page = matches
+        .get(query.offset.min(total)..end)
+        .unwrap_or_default()
+        .iter()
+        .map(|message| (*message).clone())
+        .collect();
+    Ok(MessagePage {
+        messages: page,
+        total,
+        next_offset: (end < total).then_some(end),
+    })
+}
+
+fn parse_bound(value: &str) -> Result<DateTime<FixedOffset>, String> {
+    DateTime::parse_from_rfc3339(value).map_err(|error| format!("Invalid date {value:?}: {error}"))
+}
+
+fn within_dates(
+    message: &CachedMessage,
+    since: Option<DateTime<FixedOffset>>,
+    until: Option<DateTime<FixedOffset>>,
+) -> bool {
+    if since.is_none() && until.is_none() {
+        return true;
+    }
+    let Ok(date) = DateTime::parse_from_rfc3339(&message.date) else {
+        return false;
+    };
+    since.is_none_or(|since| date >= since) && until.is_none_or(|until| date <= until)
+}
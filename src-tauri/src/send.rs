@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::AppHandle;
+
+use crate::{
+    contract::now_ms, debug_log, settings::read_settings, truncate_message, GotifyMessageWire,
+    MAX_SEND_MESSAGE_CHARS, SEND_MESSAGE_TIMEOUT_SECS,
+};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct MessageSentEvent {
+    pub(crate) at_ms: u64,
+    pub(crate) id: i64,
+    pub(crate) app_id: i64,
+    pub(crate) title: String,
+    pub(crate) priority: i64,
+    /// Name of the saved application token used, if any.
+    pub(crate) token_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SendMessageBody {
+    title: String,
+    message: String,
+    priority: i64,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    extras: Map<String, Value>,
+}
+
+/// Body of Gotify's error responses.
+#[derive(Debug, Deserialize)]
+struct GotifyErrorBody {
+    #[serde(rename = "errorDescription", default)]
+    error_description: String,
+}
+
+/// `app_token` is the name of a saved application token or a raw token. A saved
+/// name wins, so the webview never needs to hold the secret.
+fn resolve_app_token(
+    settings: &crate::settings::StoredSettings,
+    app_token: &str,
+) -> Result<(String, Option<String>), String> {
+    let key = app_token.trim();
+    if key.is_empty() {
+        return Err("Choose an application token to send with".to_string());
+    }
+    if let Some(token) = settings.app_tokens.get(key) {
+        return Ok((token.clone(), Some(key.to_string())));
+    }
+    if settings.token.as_deref().map(str::trim) == Some(key) {
+        return Err("The client token cannot send messages; use an application token".to_string());
+    }
+    Ok((key.to_string(), None))
+}
+
+fn map_send_error(status: u16, body: &str) -> String {
+    let description = serde_json::from_str::<GotifyErrorBody>(body)
+        .map(|error| error.error_description)
+        .unwrap_or_default();
+    let detail = if description.is_empty() {
+        truncate_message(body, 200)
+    } else {
+        description
+    };
+    match status {
+        400 => format!("Gotify rejected the message: {detail}"),
+        401 | 403 => "The application token was rejected by the server".to_string(),
+        _ => format!("Sending failed (HTTP {status}): {detail}"),
+    }
+}
+
+/// Publishes a message with `POST /message` and emits `message-sent`; the event
+/// payload is also the command result.
+pub(crate) async fn send_message(
+    app: &AppHandle,
+    app_token: &str,
+    title: Option<String>,
+    message: &str,
+    priority: Option<i64>,
+    extras: Option<Map<String, Value>>,
+) -> Result<MessageSentEvent, String> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Err("Message text is required".to_string());
+    }
+    if message.chars().count() > MAX_SEND_MESSAGE_CHARS {
+        return Err(format!(
+            "Message must be {MAX_SEND_MESSAGE_CHARS} characters or fewer"
+        ));
+    }
+    let settings = read_settings(app)?;
+    let base_url = crate::endpoints::active_base_url(app, &settings)?;
+    let (token, token_name) = resolve_app_token(&settings, app_token)?;
+    let body = SendMessageBody {
+        title: title.unwrap_or_default().trim().to_string(),
+        message: message.to_string(),
+        priority: priority.unwrap_or(0).clamp(0, 10),
+        extras: extras.unwrap_or_default(),
+    };
+
+    let client = crate::settings::server_client_builder(app)?
+        .timeout(Duration::from_secs(SEND_MESSAGE_TIMEOUT_SECS))
+        .build()
+        .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
+    let request = client
+        .post(format!("{base_url}/message"))
+        .header("X-Gotify-Key", &token)
+        .json(&body);
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to send message: {error}"))?;
+    let status = response.status().as_u16();
+    debug_log(&format!(
+        "send_message token={} http={status}",
+        token_name.as_deref().unwrap_or("<raw>")
+    ));
+    if !response.status().is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(map_send_error(status, &text));
+    }
+    let sent = response
+        .json::<GotifyMessageWire>()
+        .await
+        .map_err(|error| format!("Message sent, but the response was unexpected: {error}"))?;
+
+    let event = MessageSentEvent {
+        at_ms: now_ms(),
+        id: sent.id,
+        app_id: sent.appid,
+        title: sent.title,
+        priority: sent.priority,
+        token_name,
+    };
+    crate::contract::emit_event(app, "main", "message-sent", event.clone());
+    Ok(event)
+}
@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log, settings::read_settings, unix_now_secs, AppState, SERVER_INFO_TIMEOUT_SECS,
+};
+
+/// Body of the unauthenticated `GET /version`.
+#[derive(Debug, Deserialize)]
+struct VersionWire {
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    commit: String,
+    #[serde(default, rename = "buildDate")]
+    build_date: String,
+}
+
+/// Server API features that older Gotify releases lack. Anything this client
+/// calls that is not listed here is available on every supported server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ServerFeature {
+    /// `limit`/`since` on `GET /message`.
+    MessagePaging,
+    /// `GET /health`.
+    Health,
+    /// `/plugin` endpoints.
+    Plugins,
+}
+
+impl ServerFeature {
+    const ALL: [ServerFeature; 3] = [
+        ServerFeature::MessagePaging,
+        ServerFeature::Health,
+        ServerFeature::Plugins,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            ServerFeature::MessagePaging => "message_paging",
+            ServerFeature::Health => "health",
+            ServerFeature::Plugins => "plugins",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ServerFeature::MessagePaging => "Message paging",
+            ServerFeature::Health => "Health checks",
+            ServerFeature::Plugins => "Plugins",
+        }
+    }
+
+    /// The Gotify release that introduced the feature.
+    fn min_version(self) -> (u64, u64, u64) {
+        match self {
+            ServerFeature::MessagePaging => (1, 2, 0),
+            ServerFeature::Plugins => (2, 0, 0),
+            ServerFeature::Health => (2, 0, 6),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ServerInfo {
+    pub(crate) base_url: String,
+    pub(crate) version: String,
+    pub(crate) commit: String,
+    pub(crate) build_date: String,
+    /// Names of the `ServerFeature`s the version supports.
+    pub(crate) features: Vec<&'static str>,
+    pub(crate) fetched_at: u64,
+}
+
+/// `2.9.0`, `v2.9.0` and `2.9.0-rc1` all parse; development builds that do not
+/// report a version return `None`.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// Unknown versions are assumed to support everything, so a missing or odd
+/// `/version` response never blocks a feature.
+fn version_supports(version: &str, feature: ServerFeature) -> bool {
+    parse_version(version).is_none_or(|parsed| parsed >= feature.min_version())
+}
+
+pub(crate) fn cached(app: &AppHandle) -> Option<ServerInfo> {
+    app.state::<AppState>()
+        .server_info
+        .lock()
+        .ok()
+        .and_then(|info| info.clone())
+}
+
+pub(crate) fn clear(app: &AppHandle) {
+    if let Ok(mut info) = app.state::<AppState>().server_info.lock() {
+        *info = None;
+    }
+}
+
+/// Whether the connected server supports `feature`; true until its version is
+/// known.
+pub(crate) fn supports(app: &AppHandle, feature: ServerFeature) -> bool {
+    cached(app).is_none_or(|info| version_supports(&info.version, feature))
+}
+
+/// Error for commands that cannot work on the connected server.
+pub(crate) fn require(app: &AppHandle, feature: ServerFeature) -> Result<(), String> {
+    match cached(app) {
+        Some(info) if !version_supports(&info.version, feature) => {
+            let (major, minor, patch) = feature.min_version();
+            Err(format!(
+                "{} needs Gotify {major}.{minor}.{patch} or newer; this server runs {}",
+                feature.label(),
+                info.version
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Fetches `/version` from the active endpoint and caches the result.
+pub(crate) async fn refresh(app: &AppHandle) -> Result<ServerInfo, String> {
+    let settings = read_settings(app)?;
+    let base_url = crate::endpoints::active_base_url(app, &settings)?;
+    let request = crate::settings::server_client_builder(app)?
+        .timeout(Duration::from_secs(SERVER_INFO_TIMEOUT_SECS))
+        .build()
+        .map_err(|error| format!("Failed to build HTTP client: {error}"))?
+        .get(format!("{base_url}/version"));
+    let response = crate::retry::send(request)
+        .await
+        .map_err(|error| format!("Failed to fetch server version: {error}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Server version request failed with HTTP {}",
+            response.status().as_u16()
+        ));
+    }
+    let wire = response
+        .json::<VersionWire>()
+        .await
+        .map_err(|error| format!("Failed to decode server version: {error}"))?;
+    let info = ServerInfo {
+        base_url,
+        features: ServerFeature::ALL
+            .into_iter()
+            .filter(|feature| version_supports(&wire.version, *feature))
+            .map(ServerFeature::name)
+            .collect(),
+        version: wire.version,
+        commit: wire.commit,
+        build_date: wire.build_date,
+        fetched_at: unix_now_secs(),
+    };
+    let previous = cached(app).map(|previous| previous.version);
+    if previous.as_deref() != Some(info.version.as_str()) {
+        debug_log(&format!(
+            "server info: Gotify {} features={:?}",
+            info.version, info.features
+        ));
+    }
+    if let Ok(mut cached) = app.state::<AppState>().server_info.lock() {
+        *cached = Some(info.clone());
+    }
+    Ok(info)
+}
+
+/// Cached info, fetched first when nothing is cached yet.
+pub(crate) async fn get_server_info(app: &AppHandle) -> Result<ServerInfo, String> {
+    match cached(app) {
+        Some(info) => Ok(info),
+        None => refresh(app).await,
+    }
+}
+
+/// Called once the stream is up, so the version follows server upgrades and
+/// profile switches.
+pub(crate) fn refresh_in_background(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(error) = refresh(&app).await {
+            debug_log(&format!("server info: {error}"));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_release_and_prerelease_versions() {
+        assert_eq!(parse_version("2.9.0"), Some((2, 9, 0)));
+        assert_eq!(parse_version("v2.0.6"), Some((2, 0, 6)));
+        assert_eq!(parse_version("2.1.0-rc1"), Some((2, 1, 0)));
+        assert_eq!(parse_version("2.4"), Some((2, 4, 0)));
+        assert_eq!(parse_version("unknown"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn compares_against_the_introducing_release() {
+        assert!(!version_supports("2.0.5", ServerFeature::Health));
+        assert!(version_supports("2.0.6", ServerFeature::Health));
+        assert!(version_supports("2.10.0", ServerFeature::Health));
+        assert!(!version_supports("1.9.9", ServerFeature::Plugins));
+        assert!(version_supports("2.0.0", ServerFeature::Plugins));
+        assert!(!version_supports("1.1.0", ServerFeature::MessagePaging));
+        assert!(version_supports("1.2.0", ServerFeature::MessagePaging));
+    }
+
+    #[test]
+    fn unknown_versions_support_everything() {
+        for feature in ServerFeature::ALL {
+            assert!(version_supports("dev", feature));
+        }
+    }
+}
@@ -1,10 +1,17 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
-use std::{fs, time::Duration};
+use std::{collections::BTreeMap, fs, net::IpAddr, time::Duration};
 use tauri::{AppHandle, Manager, Runtime};
 
 use crate::{
     apply_launch_at_login, debug_log, get_settings_path, normalize_cache_limit,
-    restrict_file_permissions, settings_file, truncate_message, DEFAULT_CACHE_LIMIT,
+    restrict_file_permissions, settings_file, truncate_message, DEFAULT_BACKOFF_RESET_AFTER_SECS,
+    DEFAULT_CACHE_LIMIT, DEFAULT_HEALTH_CHECK_INTERVAL_SECS, DEFAULT_RECONNECT_BACKOFF_BASE_SECS,
+    DEFAULT_RECONNECT_BACKOFF_MAX_SECS, DEFAULT_RECONNECT_BACKOFF_MULTIPLIER,
+    DEFAULT_REQUEST_TIMEOUT_SECS, DEFAULT_STREAM_CONNECT_TIMEOUT_SECS,
+    DEFAULT_TRASH_RETENTION_DAYS, MAX_BACKOFF_RESET_AFTER_SECS, MAX_NOTIFICATION_SOUND_BANDS,
+    MAX_RECONNECT_BACKOFF_MULTIPLIER, MAX_RECONNECT_BACKOFF_SECS, MAX_REQUEST_TIMEOUT_SECS,
+    MAX_TRASH_RETENTION_DAYS, REVEAL_TOKEN_REASON, SETTINGS_LABEL,
 };
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -28,6 +35,136 @@ pub(crate) struct PriorityGradient {
     pub(crate) end_color: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum QuietHoursDelivery {
+    /// Holds the notification in the pending queue and delivers it once quiet
+    /// hours end.
+    #[serde(alias = "defer")]
+    Suppress,
+    /// No sound and no banner; only the tray badge count is bumped.
+    Silent,
+    /// Plays the alert sound without showing a banner.
+    SoundOnly,
+}
+
+/// Delivery used during quiet hours for messages at or above `min_priority`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct QuietHoursBand {
+    pub(crate) min_priority: i64,
+    pub(crate) delivery: QuietHoursDelivery,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum IpFamilyPreference {
+    /// Race IPv6 and IPv4 (happy eyeballs).
+    #[default]
+    Auto,
+    Ipv4,
+    Ipv6,
+}
+
+/// How new messages reach the app. `Auto` uses the websocket and polls while
+/// it keeps failing, e.g. behind proxies that kill websocket upgrades.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ConnectionMode {
+    #[default]
+    Websocket,
+    Polling,
+    Auto,
+}
+
+/// Delays between reconnect attempts: `base_secs` after the first failure,
+/// multiplied by `multiplier` after each further one, never above `max_secs`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub(crate) struct ReconnectBackoff {
+    pub(crate) base_secs: u64,
+    pub(crate) multiplier: f64,
+    pub(crate) max_secs: u64,
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self {
+            base_secs: DEFAULT_RECONNECT_BACKOFF_BASE_SECS,
+            multiplier: DEFAULT_RECONNECT_BACKOFF_MULTIPLIER,
+            max_secs: DEFAULT_RECONNECT_BACKOFF_MAX_SECS,
+        }
+    }
+}
+
+impl ReconnectBackoff {
+    pub(crate) fn validate(self) -> Result<Self, String> {
+        if !(1..=MAX_RECONNECT_BACKOFF_SECS).contains(&self.base_secs) {
+            return Err(format!(
+                "Initial delay must be between 1 and {MAX_RECONNECT_BACKOFF_SECS} seconds"
+            ));
+        }
+        if !(self.base_secs..=MAX_RECONNECT_BACKOFF_SECS).contains(&self.max_secs) {
+            return Err(format!(
+                "Maximum delay must be between the initial delay and {MAX_RECONNECT_BACKOFF_SECS} seconds"
+            ));
+        }
+        if !(1.0..=MAX_RECONNECT_BACKOFF_MULTIPLIER).contains(&self.multiplier) {
+            return Err(format!(
+                "Multiplier must be between 1 and {MAX_RECONNECT_BACKOFF_MULTIPLIER}"
+            ));
+        }
+        Ok(self)
+    }
+
+    /// Delay after another failure, given the one just waited out.
+    pub(crate) fn next(self, current_secs: u64) -> u64 {
+        ((current_secs as f64 * self.multiplier).ceil() as u64).clamp(self.base_secs, self.max_secs)
+    }
+}
+
+/// Timeouts for calls to the Gotify server: `request_secs` per REST request,
+/// `stream_connect_secs` to open the websocket.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub(crate) struct RequestTimeouts {
+    pub(crate) request_secs: u64,
+    pub(crate) stream_connect_secs: u64,
+}
+
+impl Default for RequestTimeouts {
+    fn default() -> Self {
+        Self {
+            request_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            stream_connect_secs: DEFAULT_STREAM_CONNECT_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl RequestTimeouts {
+    pub(crate) fn validate(self) -> Result<Self, String> {
+        if !(1..=MAX_REQUEST_TIMEOUT_SECS).contains(&self.request_secs) {
+            return Err(format!(
+                "Request timeout must be between 1 and {MAX_REQUEST_TIMEOUT_SECS} seconds"
+            ));
+        }
+        if !(1..=MAX_REQUEST_TIMEOUT_SECS).contains(&self.stream_connect_secs) {
+            return Err(format!(
+                "Stream connect timeout must be between 1 and {MAX_REQUEST_TIMEOUT_SECS} seconds"
+            ));
+        }
+        Ok(self)
+    }
+}
+
+/// Recurring pause window in local time. `days` are 0 (Monday) to 6 (Sunday); an
+/// end at or before the start runs past midnight.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct PauseSchedule {
+    pub(crate) days: Vec<u8>,
+    pub(crate) start_minute: u16,
+    pub(crate) end_minute: u16,
+}
+
 fn default_priority_color_mode() -> PriorityColorMode {
     PriorityColorMode::Gradient
 }
@@ -92,6 +229,30 @@ fn normalize_priority_gradient(
     gradient
 }
 
+fn normalize_pause_schedules(schedules: Vec<PauseSchedule>) -> Vec<PauseSchedule> {
+    schedules
+        .into_iter()
+        .filter_map(|mut schedule| {
+            schedule.days.retain(|day| *day <= 6);
+            schedule.days.sort_unstable();
+            schedule.days.dedup();
+            schedule.start_minute %= 1440;
+            schedule.end_minute %= 1440;
+            (!schedule.days.is_empty() && schedule.start_minute != schedule.end_minute)
+                .then_some(schedule)
+        })
+        .collect()
+}
+
+fn normalize_quiet_hours_bands(mut bands: Vec<QuietHoursBand>) -> Vec<QuietHoursBand> {
+    for band in &mut bands {
+        band.min_priority = band.min_priority.max(0);
+    }
+    bands.sort_by_key(|band| band.min_priority);
+    bands.dedup_by(|a, b| a.min_priority == b.min_priority);
+    bands
+}
+
 fn is_hex_color(value: &str) -> bool {
     let chars: Vec<char> = value.chars().collect();
     if chars.len() != 7 || chars[0] != '#' {
@@ -120,6 +281,67 @@ pub(crate) struct StoredSettings {
     pub(crate) pause_mode: Option<String>,
     pub(crate) quiet_hours_start: Option<u8>,
     pub(crate) quiet_hours_end: Option<u8>,
+    pub(crate) heartbeat_url: Option<String>,
+    pub(crate) ca_cert_path: Option<String>,
+    pub(crate) quiet_hours_bands: Vec<QuietHoursBand>,
+    pub(crate) backoff_reset_after_secs: u64,
+    /// Extra headers for reverse-proxy auth (e.g. Cloudflare Access); values are secrets.
+    pub(crate) custom_headers: BTreeMap<String, String>,
+    pub(crate) pause_schedules: Vec<PauseSchedule>,
+    pub(crate) ip_family_preference: IpFamilyPreference,
+    /// Hostname -> IP address, bypassing DNS for the Gotify server. Mirrored
+    /// into the active profile.
+    pub(crate) host_pins: BTreeMap<String, String>,
+    /// Send the token as `?token=` on the stream upgrade instead of the
+    /// `X-Gotify-Key` header, for proxies that strip it. Mirrored into the
+    /// active profile.
+    pub(crate) stream_token_in_query: bool,
+    /// Hostname -> SHA-256 fingerprint of a certificate the user trusted on first use.
+    pub(crate) trusted_certificates: BTreeMap<String, String>,
+    /// Emit `op-trace` events for sync, icon, preview, delete and stream connect steps.
+    pub(crate) op_trace_enabled: bool,
+    /// `http://`, `socks5://` or `socks5h://` proxy for REST calls and the stream.
+    pub(crate) proxy_url: Option<String>,
+    pub(crate) proxy_username: Option<String>,
+    pub(crate) proxy_password: Option<String>,
+    pub(crate) proxy_bypass: Vec<String>,
+    /// Saved servers by name; the active one mirrors `base_url` and `token`.
+    pub(crate) profiles: BTreeMap<String, crate::profiles::ServerProfile>,
+    pub(crate) active_profile: Option<String>,
+    /// Notifications say only "New message from <app>", without title or body.
+    pub(crate) privacy_mode: bool,
+    pub(crate) redaction_patterns: Vec<String>,
+    /// Days deleted messages stay in the trash before the server delete; 0 deletes at once.
+    pub(crate) trash_retention_days: u64,
+    /// Application tokens for sending, by name. They belong to the active server
+    /// and are mirrored into its profile like `token`.
+    pub(crate) app_tokens: BTreeMap<String, String>,
+    /// Second URL of the same server (usually its LAN address); the faster
+    /// reachable one is used. Mirrored into the active profile.
+    pub(crate) lan_base_url: Option<String>,
+    /// Seconds between `GET /health` checks; 0 turns them off.
+    pub(crate) health_check_interval_secs: u64,
+    pub(crate) acknowledgement: crate::ack::AckSettings,
+    pub(crate) fault_injection: crate::faults::FaultInjection,
+    /// Gotify application ids whose messages never raise a notification.
+    pub(crate) muted_app_ids: Vec<i64>,
+    /// `default`, `none` or a macOS system sound name.
+    pub(crate) notification_sound: String,
+    /// Per-priority sounds; priorities below the lowest band use `notification_sound`.
+    pub(crate) notification_sound_bands: Vec<crate::contexts::SoundBand>,
+    /// Saved notification settings ("Work", "On-call"), by name.
+    pub(crate) contexts: BTreeMap<String, crate::contexts::NotificationContext>,
+    /// Context last switched to or saved; settings may have been edited since.
+    pub(crate) active_context: Option<String>,
+    /// What to do with `android::action.onReceive` intents; first match wins.
+    pub(crate) android_action_rules: Vec<crate::android_actions::AndroidActionRule>,
+    pub(crate) connection_mode: ConnectionMode,
+    pub(crate) reconnect_backoff: ReconnectBackoff,
+    pub(crate) request_timeouts: RequestTimeouts,
+    pub(crate) request_retry: crate::retry::RetryPolicy,
+    pub(crate) message_retention: crate::retention::MessageRetention,
+    /// Keep messages deleted on the server in the profile's local archive.
+    pub(crate) archive_remote_deletions: bool,
 }
 
 impl Default for StoredSettings {
@@ -139,6 +361,43 @@ impl Default for StoredSettings {
             pause_mode: None,
             quiet_hours_start: None,
             quiet_hours_end: None,
+            heartbeat_url: None,
+            ca_cert_path: None,
+            quiet_hours_bands: Vec::new(),
+            backoff_reset_after_secs: DEFAULT_BACKOFF_RESET_AFTER_SECS,
+            custom_headers: BTreeMap::new(),
+            pause_schedules: Vec::new(),
+            ip_family_preference: IpFamilyPreference::Auto,
+            host_pins: BTreeMap::new(),
+            stream_token_in_query: false,
+            trusted_certificates: BTreeMap::new(),
+            op_trace_enabled: false,
+            proxy_url: None,
+            proxy_username: None,
+            proxy_password: None,
+            proxy_bypass: Vec::new(),
+            profiles: BTreeMap::new(),
+            active_profile: None,
+            privacy_mode: false,
+            redaction_patterns: Vec::new(),
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            app_tokens: BTreeMap::new(),
+            lan_base_url: None,
+            health_check_interval_secs: DEFAULT_HEALTH_CHECK_INTERVAL_SECS,
+            acknowledgement: crate::ack::AckSettings::default(),
+            fault_injection: crate::faults::FaultInjection::default(),
+            muted_app_ids: Vec::new(),
+            notification_sound: crate::contexts::NOTIFICATION_SOUND_DEFAULT.to_string(),
+            notification_sound_bands: Vec::new(),
+            contexts: BTreeMap::new(),
+            active_context: None,
+            android_action_rules: Vec::new(),
+            connection_mode: ConnectionMode::Websocket,
+            reconnect_backoff: ReconnectBackoff::default(),
+            request_timeouts: RequestTimeouts::default(),
+            request_retry: crate::retry::RetryPolicy::default(),
+            message_retention: crate::retention::MessageRetention::default(),
+            archive_remote_deletions: false,
         }
     }
 }
@@ -159,9 +418,53 @@ pub(crate) struct SettingsResponse {
     pub(crate) pause_mode: Option<String>,
     pub(crate) quiet_hours_start: Option<u8>,
     pub(crate) quiet_hours_end: Option<u8>,
+    pub(crate) heartbeat_url: Option<String>,
+    pub(crate) ca_cert_path: Option<String>,
+    pub(crate) quiet_hours_bands: Vec<QuietHoursBand>,
+    pub(crate) backoff_reset_after_secs: u64,
+    /// Header names only, like `has_token`, so secrets never reach the webview.
+    pub(crate) custom_header_names: Vec<String>,
+    pub(crate) pause_schedules: Vec<PauseSchedule>,
+    pub(crate) ip_family_preference: IpFamilyPreference,
+    pub(crate) host_pins: BTreeMap<String, String>,
+    pub(crate) stream_token_in_query: bool,
+    pub(crate) trusted_certificates: BTreeMap<String, String>,
+    pub(crate) op_trace_enabled: bool,
+    pub(crate) proxy_url: Option<String>,
+    pub(crate) proxy_username: Option<String>,
+    pub(crate) has_proxy_password: bool,
+    pub(crate) proxy_bypass: Vec<String>,
+    pub(crate) profiles: Vec<crate::profiles::ProfileSummary>,
+    pub(crate) active_profile: Option<String>,
+    pub(crate) privacy_mode: bool,
+    pub(crate) redaction_patterns: Vec<String>,
+    pub(crate) trash_retention_days: u64,
+    /// Names only; the tokens stay in the backend.
+    pub(crate) app_token_names: Vec<String>,
+    pub(crate) lan_base_url: Option<String>,
+    pub(crate) health_check_interval_secs: u64,
+    pub(crate) acknowledgement: crate::ack::AckSettings,
+    pub(crate) fault_injection: crate::faults::FaultInjection,
+    /// Debug builds only; the frontend hides the fault injection controls otherwise.
+    pub(crate) fault_injection_available: bool,
+    pub(crate) muted_app_ids: Vec<i64>,
+    pub(crate) notification_sound: String,
+    pub(crate) notification_sound_bands: Vec<crate::contexts::SoundBand>,
+    /// `MAX_NOTIFICATION_SOUND_BANDS`, so the form stops adding bands where the
+    /// backend would reject them.
+    pub(crate) max_notification_sound_bands: usize,
+    pub(crate) contexts: BTreeMap<String, crate::contexts::NotificationContext>,
+    pub(crate) active_context: Option<String>,
+    pub(crate) android_action_rules: Vec<crate::android_actions::AndroidActionRule>,
+    pub(crate) connection_mode: ConnectionMode,
+    pub(crate) reconnect_backoff: ReconnectBackoff,
+    pub(crate) request_timeouts: RequestTimeouts,
+    pub(crate) request_retry: crate::retry::RetryPolicy,
+    pub(crate) message_retention: crate::retention::MessageRetention,
+    pub(crate) archive_remote_deletions: bool,
 }
 
-fn to_settings_response(stored: StoredSettings) -> SettingsResponse {
+pub(crate) fn to_settings_response(stored: StoredSettings) -> SettingsResponse {
     let priority_thresholds =
         normalize_priority_thresholds(Some(stored.priority_thresholds.clone()), &[]);
     let default_gradient = default_priority_gradient();
@@ -172,6 +475,8 @@ fn to_settings_response(stored: StoredSettings) -> SettingsResponse {
         .as_deref()
         .map_or(false, |t| !t.trim().is_empty());
 
+    let profiles = crate::profiles::profile_summaries(&stored);
+
     SettingsResponse {
         base_url: stored.base_url,
         has_token,
@@ -187,6 +492,52 @@ fn to_settings_response(stored: StoredSettings) -> SettingsResponse {
         pause_mode: stored.pause_mode,
         quiet_hours_start: stored.quiet_hours_start,
         quiet_hours_end: stored.quiet_hours_end,
+        heartbeat_url: stored.heartbeat_url,
+        ca_cert_path: stored.ca_cert_path,
+        quiet_hours_bands: normalize_quiet_hours_bands(stored.quiet_hours_bands),
+        backoff_reset_after_secs: stored
+            .backoff_reset_after_secs
+            .min(MAX_BACKOFF_RESET_AFTER_SECS),
+        custom_header_names: stored.custom_headers.keys().cloned().collect(),
+        app_token_names: stored.app_tokens.keys().cloned().collect(),
+        lan_base_url: stored.lan_base_url,
+        health_check_interval_secs: crate::health::normalize_interval(
+            stored.health_check_interval_secs,
+        ),
+        acknowledgement: stored.acknowledgement,
+        fault_injection: stored.fault_injection,
+        fault_injection_available: crate::faults::is_available(),
+        muted_app_ids: stored.muted_app_ids,
+        notification_sound: stored.notification_sound,
+        notification_sound_bands: stored.notification_sound_bands,
+        max_notification_sound_bands: MAX_NOTIFICATION_SOUND_BANDS,
+        contexts: stored.contexts,
+        active_context: stored.active_context,
+        android_action_rules: stored.android_action_rules,
+        connection_mode: stored.connection_mode,
+        reconnect_backoff: stored.reconnect_backoff,
+        request_timeouts: stored.request_timeouts,
+        request_retry: stored.request_retry,
+        message_retention: stored.message_retention,
+        archive_remote_deletions: stored.archive_remote_deletions,
+        pause_schedules: normalize_pause_schedules(stored.pause_schedules),
+        ip_family_preference: stored.ip_family_preference,
+        host_pins: stored.host_pins,
+        stream_token_in_query: stored.stream_token_in_query,
+        trusted_certificates: stored.trusted_certificates,
+        op_trace_enabled: stored.op_trace_enabled,
+        proxy_url: stored.proxy_url,
+        proxy_username: stored.proxy_username,
+        has_proxy_password: stored
+            .proxy_password
+            .as_deref()
+            .is_some_and(|password| !password.is_empty()),
+        proxy_bypass: stored.proxy_bypass,
+        profiles,
+        active_profile: stored.active_profile,
+        privacy_mode: stored.privacy_mode,
+        redaction_patterns: stored.redaction_patterns,
+        trash_retention_days: stored.trash_retention_days,
     }
 }
 
@@ -209,6 +560,22 @@ pub(crate) fn save_settings<R: Runtime>(
     show_priority_in_notifications: Option<bool>,
     quiet_hours_start: Option<u8>,
     quiet_hours_end: Option<u8>,
+    heartbeat_url: Option<String>,
+    ca_cert_path: Option<String>,
+    quiet_hours_bands: Option<Vec<QuietHoursBand>>,
+    backoff_reset_after_secs: Option<u64>,
+    custom_headers: Option<BTreeMap<String, String>>,
+    pause_schedules: Option<Vec<PauseSchedule>>,
+    ip_family_preference: Option<IpFamilyPreference>,
+    host_pins: Option<BTreeMap<String, String>>,
+    op_trace_enabled: Option<bool>,
+    proxy_url: Option<String>,
+    proxy_username: Option<String>,
+    proxy_password: Option<String>,
+    proxy_bypass: Option<Vec<String>>,
+    privacy_mode: Option<bool>,
+    redaction_patterns: Option<Vec<String>>,
+    trash_retention_days: Option<u64>,
 ) -> Result<SettingsResponse, String> {
     debug_log(&format!(
         "save_settings called: base_url={base_url:?} token_len={} min_priority={min_priority:?} cache_limit={cache_limit:?}",
@@ -231,6 +598,30 @@ pub(crate) fn save_settings<R: Runtime>(
     let current_gradient =
         normalize_priority_gradient(Some(current.priority_gradient.clone()), &default_gradient);
     let next_gradient = normalize_priority_gradient(priority_gradient, &current_gradient);
+    let next_heartbeat_url = match heartbeat_url {
+        Some(raw) => normalize_heartbeat_url(&raw)?,
+        None => current.heartbeat_url.clone(),
+    };
+    let next_proxy_url = match proxy_url {
+        Some(url) => crate::proxy::normalize_proxy_url(&url)?,
+        None => current.proxy_url.clone(),
+    };
+    let next_redaction_patterns = match redaction_patterns {
+        Some(patterns) => crate::redaction::normalize_redaction_patterns(patterns)?,
+        None => current.redaction_patterns.clone(),
+    };
+    let next_host_pins = match host_pins {
+        Some(pins) => normalize_host_pins(pins)?,
+        None => current.host_pins.clone(),
+    };
+    let next_custom_headers = match custom_headers {
+        Some(headers) => normalize_custom_headers(headers)?,
+        None => current.custom_headers.clone(),
+    };
+    let next_ca_cert_path = match ca_cert_path {
+        Some(raw) => normalize_ca_cert_path(&raw)?,
+        None => current.ca_cert_path.clone(),
+    };
 
     let new_token = if token.trim().is_empty() {
         debug_log("save_settings: no new token provided, keeping existing");
@@ -252,6 +643,17 @@ pub(crate) fn save_settings<R: Runtime>(
         Some(token.trim().to_string())
     };
 
+    let mut next_profiles = current.profiles.clone();
+    if let Some(profile) = current
+        .active_profile
+        .as_ref()
+        .and_then(|name| next_profiles.get_mut(name))
+    {
+        profile.base_url = normalized_url.clone();
+        profile.token = new_token.clone();
+        profile.host_pins = next_host_pins.clone();
+    }
+
     let next_settings = StoredSettings {
         base_url: normalized_url.clone(),
         token: new_token,
@@ -268,6 +670,60 @@ pub(crate) fn save_settings<R: Runtime>(
         pause_mode: current.pause_mode,
         quiet_hours_start: quiet_start.map(|h| h % 24),
         quiet_hours_end: quiet_end.map(|h| h % 24),
+        heartbeat_url: next_heartbeat_url,
+        ca_cert_path: next_ca_cert_path,
+        quiet_hours_bands: normalize_quiet_hours_bands(
+            quiet_hours_bands.unwrap_or_else(|| current.quiet_hours_bands.clone()),
+        ),
+        backoff_reset_after_secs: backoff_reset_after_secs
+            .unwrap_or(current.backoff_reset_after_secs)
+            .min(MAX_BACKOFF_RESET_AFTER_SECS),
+        custom_headers: next_custom_headers,
+        pause_schedules: normalize_pause_schedules(
+            pause_schedules.unwrap_or_else(|| current.pause_schedules.clone()),
+        ),
+        ip_family_preference: ip_family_preference.unwrap_or(current.ip_family_preference),
+        host_pins: next_host_pins,
+        stream_token_in_query: current.stream_token_in_query,
+        trusted_certificates: current.trusted_certificates.clone(),
+        op_trace_enabled: op_trace_enabled.unwrap_or(current.op_trace_enabled),
+        proxy_url: next_proxy_url,
+        proxy_username: match proxy_username {
+            Some(username) => Some(username.trim().to_string()).filter(|value| !value.is_empty()),
+            None => current.proxy_username.clone(),
+        },
+        // `None` keeps the stored password, an empty string clears it.
+        proxy_password: match proxy_password {
+            Some(password) => Some(password).filter(|value| !value.is_empty()),
+            None => current.proxy_password.clone(),
+        },
+        proxy_bypass: crate::proxy::normalize_proxy_bypass(
+            proxy_bypass.unwrap_or_else(|| current.proxy_bypass.clone()),
+        ),
+        profiles: next_profiles,
+        active_profile: current.active_profile.clone(),
+        privacy_mode: privacy_mode.unwrap_or(current.privacy_mode),
+        redaction_patterns: next_redaction_patterns,
+        trash_retention_days: trash_retention_days
+            .unwrap_or(current.trash_retention_days)
+            .min(MAX_TRASH_RETENTION_DAYS),
+        app_tokens: current.app_tokens.clone(),
+        lan_base_url: current.lan_base_url.clone(),
+        health_check_interval_secs: current.health_check_interval_secs,
+        acknowledgement: current.acknowledgement.clone(),
+        fault_injection: current.fault_injection.clone(),
+        muted_app_ids: current.muted_app_ids.clone(),
+        notification_sound: current.notification_sound.clone(),
+        notification_sound_bands: current.notification_sound_bands.clone(),
+        contexts: current.contexts.clone(),
+        active_context: current.active_context.clone(),
+        android_action_rules: current.android_action_rules.clone(),
+        connection_mode: current.connection_mode,
+        reconnect_backoff: current.reconnect_backoff,
+        request_timeouts: current.request_timeouts,
+        request_retry: current.request_retry,
+        message_retention: current.message_retention,
+        archive_remote_deletions: current.archive_remote_deletions,
     };
 
     save_non_secret_settings(app, &next_settings)?;
@@ -282,9 +738,16 @@ pub(crate) fn save_settings<R: Runtime>(
     Ok(to_settings_response(next_settings))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn test_connection(
     base_url: String,
     token: Option<String>,
+    ca_cert_path: Option<String>,
+    trusted_certificates: BTreeMap<String, String>,
+    custom_headers: HeaderMap,
+    host_pins: BTreeMap<String, IpAddr>,
+    proxy: Option<crate::proxy::ProxyConfig>,
+    timeout: Duration,
 ) -> Result<String, String> {
     debug_log(&format!(
         "test_connection: base_url={base_url:?} token_provided={}",
@@ -309,10 +772,22 @@ pub(crate) async fn test_connection(
         }
     };
 
-    let endpoint = format!("{normalized_url}/application");
+    let (request_url, zone) = split_ipv6_zone(&normalized_url);
+    let endpoint = format!("{request_url}/application");
     debug_log(&format!("test_connection: GET {endpoint}"));
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(15))
+    let ca_cert_count = match ca_cert_path.as_deref() {
+        Some(path) => crate::tls::load_ca_certificates(path)?.len(),
+        None => 0,
+    };
+    let mut client_builder =
+        crate::tls::http_client_builder(ca_cert_path.as_deref(), &trusted_certificates)?;
+    if let Some(zone) = &zone {
+        client_builder = client_builder.interface(zone);
+    }
+    let client = crate::proxy::apply_to_client(client_builder, proxy.as_ref())?
+        .default_headers(custom_headers)
+        .dns_resolver(crate::dial::DialResolver::new(host_pins))
+        .timeout(timeout)
         .build()
         .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
     let response = client
@@ -320,11 +795,21 @@ pub(crate) async fn test_connection(
         .header("X-Gotify-Key", &token_to_use)
         .send()
         .await
-        .map_err(|error| format!("Connection request failed: {error}"))?;
+        .map_err(|error| match (ca_cert_count, ca_cert_path.as_deref()) {
+            (0, _) | (_, None) => format!("Connection request failed: {error}"),
+            (count, Some(path)) => format!(
+                "Connection request failed with custom CA {path} ({count} certificate(s) loaded): {error}"
+            ),
+        })?;
 
     let status = response.status().as_u16();
     debug_log(&format!("test_connection: HTTP {status}"));
     if response.status().is_success() {
+        if ca_cert_count > 0 {
+            return Ok(format!(
+                "Connection successful (custom CA loaded: {ca_cert_count} certificate(s))"
+            ));
+        }
         return Ok("Connection successful".to_string());
     }
 
@@ -345,8 +830,7 @@ pub(crate) fn read_settings<R: Runtime>(app: &AppHandle<R>) -> Result<StoredSett
         return Ok(StoredSettings::default());
     }
 
-    let content =
-        fs::read_to_string(path).map_err(|error| format!("Failed to read settings: {error}"))?;
+    let content = crate::at_rest::read_to_string(SETTINGS_LABEL, &path)?;
     serde_json::from_str::<StoredSettings>(&content)
         .map_err(|error| format!("Failed to parse settings: {error}"))
 }
@@ -358,16 +842,152 @@ pub(crate) fn save_non_secret_settings<R: Runtime>(
     let path = settings_file(app)?;
     let content = serde_json::to_string_pretty(settings)
         .map_err(|error| format!("Failed to serialize settings: {error}"))?;
+    let sealed = crate::at_rest::seal(SETTINGS_LABEL, content.as_bytes())?;
     let tmp_path = path.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
-    fs::write(&tmp_path, content)
+    fs::write(&tmp_path, sealed)
         .map_err(|error| format!("Failed to write settings temp file: {error}"))?;
     restrict_file_permissions(&tmp_path);
     fs::rename(&tmp_path, &path)
         .map_err(|error| format!("Failed to atomically replace settings: {error}"))?;
     restrict_file_permissions(&path);
+    crate::op_trace::set_enabled(settings.op_trace_enabled);
+    crate::faults::set_config(&settings.fault_injection);
+    crate::retry::set_policy(&settings.request_retry);
+    crate::redaction::set_patterns(&settings.redaction_patterns);
+    remember_settings_on_disk(app, serde_json::to_value(settings).ok());
     Ok(())
 }
 
+pub(crate) fn remember_settings_on_disk<R: Runtime>(
+    app: &AppHandle<R>,
+    settings: Option<serde_json::Value>,
+) {
+    let Some(state) = app.try_state::<crate::AppState>() else {
+        return;
+    };
+    if let Ok(mut on_disk) = state.settings_on_disk.lock() {
+        *on_disk = settings;
+    };
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct SettingsReloaded {
+    pub(crate) changed_fields: Vec<String>,
+}
+
+/// Picks up hand edits or restored backups of settings.json without a restart.
+pub(crate) fn reload_settings_if_changed(app: &AppHandle) {
+    let state = app.state::<crate::AppState>();
+    let changed_fields = {
+        let Ok(_settings_guard) = state.settings_lock.lock() else {
+            return;
+        };
+        let Ok(path) = settings_file(app) else {
+            return;
+        };
+        let Ok(content) = crate::at_rest::read_to_string(SETTINGS_LABEL, &path) else {
+            return;
+        };
+        let fresh = match serde_json::from_str::<StoredSettings>(&content)
+            .map_err(|error| error.to_string())
+            .and_then(|settings| serde_json::to_value(settings).map_err(|error| error.to_string()))
+        {
+            Ok(value) => value,
+            Err(error) => {
+                debug_log(&format!(
+                    "settings reload skipped, file is not valid: {error}"
+                ));
+                return;
+            }
+        };
+        let Ok(mut on_disk) = state.settings_on_disk.lock() else {
+            return;
+        };
+        let Some(previous) = on_disk.replace(fresh.clone()) else {
+            return;
+        };
+        changed_settings_fields(&previous, &fresh)
+    };
+    if changed_fields.is_empty() {
+        return;
+    }
+
+    debug_log(&format!(
+        "settings.json changed externally: {}",
+        changed_fields.join(",")
+    ));
+    if let Ok(settings) = load_settings(app) {
+        crate::op_trace::set_enabled(settings.op_trace_enabled);
+        crate::faults::set_config(&settings.fault_injection);
+        crate::retry::set_policy(&settings.request_retry);
+        crate::redaction::set_patterns(&settings.redaction_patterns);
+        crate::contract::publish_settings_update(app, settings);
+    }
+    if changed_fields.iter().any(|field| {
+        matches!(
+            field.as_str(),
+            "base_url"
+                | "token"
+                | "ca_cert_path"
+                | "custom_headers"
+                | "host_pins"
+                | "stream_token_in_query"
+                | "trusted_certificates"
+                | "proxy_url"
+                | "proxy_username"
+                | "proxy_password"
+                | "proxy_bypass"
+        )
+    }) {
+        if let Err(error) = crate::stream::restart_stream(app.clone()) {
+            debug_log(&format!(
+                "failed to restart stream after settings reload: {error}"
+            ));
+        }
+    }
+    if changed_fields
+        .iter()
+        .any(|field| field == "pause_until" || field == "pause_mode")
+    {
+        let _ = crate::pause::PauseController::new(app).publish_current();
+    }
+    if changed_fields.iter().any(|field| field == "privacy_mode") {
+        let privacy_mode = read_settings(app)
+            .map(|settings| settings.privacy_mode)
+            .unwrap_or(false);
+        crate::privacy::sync_tray_item(app, privacy_mode);
+    }
+    #[cfg(target_os = "macos")]
+    if changed_fields
+        .iter()
+        .any(|field| field == "launch_at_login")
+    {
+        let launch_at_login = read_settings(app)
+            .map(|settings| settings.launch_at_login)
+            .unwrap_or(false);
+        if let Err(error) = apply_launch_at_login(launch_at_login) {
+            debug_log(&format!("failed to apply launch-at-login change: {error}"));
+        }
+    }
+
+    let payload = SettingsReloaded { changed_fields };
+    crate::contract::emit_event(app, "main", "settings-reloaded", payload.clone());
+    crate::contract::emit_event(app, "quick", "settings-reloaded", payload);
+}
+
+fn changed_settings_fields(previous: &serde_json::Value, fresh: &serde_json::Value) -> Vec<String> {
+    let (Some(previous), Some(fresh)) = (previous.as_object(), fresh.as_object()) else {
+        return Vec::new();
+    };
+    let mut changed: Vec<String> = fresh
+        .iter()
+        .filter(|(key, value)| previous.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    changed.sort();
+    changed
+}
+
 pub(crate) fn load_token() -> Result<Option<String>, String> {
     let path = get_settings_path()?;
     debug_log(&format!("load_token: reading settings from {path:?}"));
@@ -375,8 +995,7 @@ pub(crate) fn load_token() -> Result<Option<String>, String> {
         debug_log("load_token: settings file not found - no token");
         return Ok(None);
     }
-    let raw = fs::read_to_string(path)
-        .map_err(|error| format!("Failed to read settings for token: {error}"))?;
+    let raw = crate::at_rest::read_to_string(SETTINGS_LABEL, path)?;
     let settings: StoredSettings = serde_json::from_str(&raw).unwrap_or_default();
     match settings.token.filter(|t| !t.trim().is_empty()) {
         Some(t) => {
@@ -390,21 +1009,295 @@ pub(crate) fn load_token() -> Result<Option<String>, String> {
     }
 }
 
+/// The stored client token, for showing it in settings again. Every call asks
+/// for Touch ID or the login password first.
+pub(crate) async fn reveal_token() -> Result<String, String> {
+    authenticate_owner(REVEAL_TOKEN_REASON).await?;
+    let token =
+        load_token()?.ok_or_else(|| "No token found. Save token in settings first.".to_string())?;
+    debug_log("reveal_token: token shown after local authentication");
+    Ok(token)
+}
+
+/// Shows the system authentication sheet (Touch ID or the login password) and
+/// waits for it off the async runtime. Required before any command hands out
+/// a stored secret.
+pub(crate) async fn authenticate_owner(reason: &'static str) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || authenticate_owner_blocking(reason))
+        .await
+        .map_err(|error| format!("Failed to join authentication task: {error}"))?
+}
+
+#[cfg(target_os = "macos")]
+fn authenticate_owner_blocking(reason: &str) -> Result<(), String> {
+    crate::local_auth::authenticate(reason)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn authenticate_owner_blocking(_reason: &str) -> Result<(), String> {
+    Err("Local authentication needs macOS".to_string())
+}
+
+/// Bracketed IPv6 hosts are accepted, including link-local ones scoped to an
+/// interface (`http://[fe80::1%en0]:8080`); the zone is stored in its RFC 6874
+/// form, `%25en0`.
 pub(crate) fn normalize_base_url(input: &str) -> Result<String, String> {
     let trimmed = input.trim().trim_end_matches('/');
     if trimmed.is_empty() {
         return Err("Server URL is required".to_string());
     }
 
-    let url =
-        reqwest::Url::parse(trimmed).map_err(|error| format!("Invalid server URL: {error}"))?;
+    let (without_zone, zone) = split_ipv6_zone(trimmed);
+    let url = reqwest::Url::parse(&without_zone).map_err(|error| {
+        if is_unbracketed_ipv6(&without_zone) {
+            "IPv6 addresses must be in brackets, like http://[fd00::1]:8080".to_string()
+        } else {
+            format!("Invalid server URL: {error}")
+        }
+    })?;
 
     let scheme = url.scheme();
     if scheme != "http" && scheme != "https" {
         return Err("Server URL must start with http:// or https://".to_string());
     }
 
-    Ok(trimmed.to_string())
+    let Some(zone) = zone else {
+        return Ok(trimmed.to_string());
+    };
+    if zone.is_empty()
+        || !zone
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        return Err(format!(
+            "Invalid IPv6 zone {zone:?}; use an interface name like en0"
+        ));
+    }
+    let range = ipv6_zone_range(trimmed).unwrap_or(0..0);
+    Ok(format!(
+        "{}%25{zone}{}",
+        &trimmed[..range.start],
+        &trimmed[range.end..]
+    ))
+}
+
+/// Byte range of the `%zone` inside a bracketed IPv6 host, `%` included.
+fn ipv6_zone_range(url: &str) -> Option<std::ops::Range<usize>> {
+    let authority_start = url.find("://")? + 3;
+    let authority_end = url[authority_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |index| authority_start + index);
+    let authority = &url[authority_start..authority_end];
+    let open = authority.find('[')?;
+    let close = open + authority[open..].find(']')?;
+    let percent = open + authority[open..close].find('%')?;
+    Some(authority_start + percent..authority_start + close)
+}
+
+/// Splits the interface zone off a base URL: `http://[fe80::1%25en0]:8080`
+/// gives `http://[fe80::1]:8080` and `en0`. URL parsers reject zones, so
+/// requests use the URL without it and the zone is applied when connecting.
+pub(crate) fn split_ipv6_zone(url: &str) -> (String, Option<String>) {
+    let Some(range) = ipv6_zone_range(url) else {
+        return (url.to_string(), None);
+    };
+    let raw = &url[range.start + 1..range.end];
+    let zone = raw
+        .strip_prefix("25")
+        .filter(|zone| !zone.is_empty())
+        .unwrap_or(raw);
+    (
+        format!("{}{}", &url[..range.start], &url[range.end..]),
+        Some(zone.to_string()),
+    )
+}
+
+fn is_unbracketed_ipv6(url: &str) -> bool {
+    let authority = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    !authority.contains('[') && authority.matches(':').count() >= 2
+}
+
+/// An empty input clears the heartbeat URL.
+pub(crate) fn normalize_heartbeat_url(input: &str) -> Result<Option<String>, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+
+    let url =
+        reqwest::Url::parse(trimmed).map_err(|error| format!("Invalid heartbeat URL: {error}"))?;
+    match url.scheme() {
+        "http" | "https" => Ok(Some(url.to_string())),
+        _ => Err("Heartbeat URL must start with http:// or https://".to_string()),
+    }
+}
+
+const RESERVED_HEADERS: &[&str] = &[
+    "x-gotify-key",
+    "host",
+    "connection",
+    "upgrade",
+    "content-length",
+];
+
+/// Empty names are dropped; reserved or malformed headers are rejected.
+pub(crate) fn normalize_custom_headers(
+    headers: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut normalized = BTreeMap::new();
+    for (name, value) in headers {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        let lowered = name.to_ascii_lowercase();
+        if RESERVED_HEADERS.contains(&lowered.as_str()) || lowered.starts_with("sec-websocket-") {
+            return Err(format!("Header {name} cannot be overridden"));
+        }
+        HeaderName::from_bytes(name.as_bytes())
+            .map_err(|error| format!("Invalid header name {name}: {error}"))?;
+        HeaderValue::from_str(value.trim())
+            .map_err(|error| format!("Invalid value for header {name}: {error}"))?;
+        normalized.insert(name.to_string(), value.trim().to_string());
+    }
+    Ok(normalized)
+}
+
+pub(crate) fn custom_header_map(headers: &BTreeMap<String, String>) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(mut value)) => {
+                value.set_sensitive(true);
+                map.insert(name, value);
+            }
+            _ => debug_log(&format!("ignoring invalid custom header {name}")),
+        }
+    }
+    map
+}
+
+pub(crate) fn configured_custom_headers<R: Runtime>(app: &AppHandle<R>) -> HeaderMap {
+    read_settings(app)
+        .map(|settings| custom_header_map(&settings.custom_headers))
+        .unwrap_or_default()
+}
+
+/// HTTP client builder for Gotify server requests: custom CA plus proxy headers,
+/// with the configured request timeout. Callers with a tighter budget (probes,
+/// previews) set their own.
+pub(crate) fn server_client_builder<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<reqwest::ClientBuilder, String> {
+    profile_client_builder(
+        app,
+        configured_host_pins(app),
+        crate::endpoints::active_ipv6_zone(app),
+    )
+}
+
+/// `server_client_builder` for a server other than the active one, with its
+/// own DNS overrides and IPv6 zone.
+pub(crate) fn profile_client_builder<R: Runtime>(
+    app: &AppHandle<R>,
+    host_pins: BTreeMap<String, IpAddr>,
+    zone: Option<String>,
+) -> Result<reqwest::ClientBuilder, String> {
+    let ca_cert_path = crate::tls::configured_ca_cert_path(app);
+    let trusted_certificates = crate::tls::configured_trusted_certificates(app);
+    let mut builder =
+        crate::tls::http_client_builder(ca_cert_path.as_deref(), &trusted_certificates)?;
+    // A link-local server is only reachable through the interface its zone names.
+    if let Some(zone) = zone {
+        builder = builder.interface(&zone);
+    }
+    Ok(
+        crate::proxy::apply_to_client(builder, crate::proxy::configured_proxy(app).as_ref())?
+            .default_headers(configured_custom_headers(app))
+            .dns_resolver(crate::dial::DialResolver::new(host_pins))
+            .timeout(Duration::from_secs(
+                configured_request_timeouts(app).request_secs,
+            )),
+    )
+}
+
+/// Stored timeouts, or the defaults when the settings file holds some out of range.
+pub(crate) fn configured_request_timeouts<R: Runtime>(app: &AppHandle<R>) -> RequestTimeouts {
+    read_settings(app)
+        .ok()
+        .and_then(|settings| settings.request_timeouts.validate().ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn set_request_timeouts(
+    app: &AppHandle,
+    timeouts: RequestTimeouts,
+) -> Result<SettingsResponse, String> {
+    let timeouts = timeouts.validate()?;
+    let settings = {
+        let state = app.state::<crate::AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.request_timeouts = timeouts;
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!("request timeouts set to {timeouts:?}"));
+    Ok(to_settings_response(settings))
+}
+
+/// Hosts are matched case-insensitively; an empty address removes the pin.
+pub(crate) fn normalize_host_pins(
+    pins: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut normalized = BTreeMap::new();
+    for (host, address) in pins {
+        let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+        let address = address.trim();
+        if host.is_empty() || address.is_empty() {
+            continue;
+        }
+        let ip = address
+            .parse::<IpAddr>()
+            .map_err(|_| format!("Invalid IP address for {host}: {address}"))?;
+        normalized.insert(host, ip.to_string());
+    }
+    Ok(normalized)
+}
+
+pub(crate) fn configured_host_pins<R: Runtime>(app: &AppHandle<R>) -> BTreeMap<String, IpAddr> {
+    parse_host_pins(
+        read_settings(app)
+            .map(|settings| settings.host_pins)
+            .unwrap_or_default(),
+    )
+}
+
+pub(crate) fn parse_host_pins(pins: BTreeMap<String, String>) -> BTreeMap<String, IpAddr> {
+    pins.into_iter()
+        .filter_map(|(host, address)| Some((host, address.parse().ok()?)))
+        .collect()
+}
+
+/// An empty input clears the CA path; anything else must load as PEM.
+pub(crate) fn normalize_ca_cert_path(input: &str) -> Result<Option<String>, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    crate::tls::load_ca_certificates(trimmed)?;
+    Ok(Some(trimmed.to_string()))
 }
 
 pub(crate) fn build_stream_ws_url(base_url: &str) -> Result<String, String> {
@@ -430,3 +1323,68 @@ pub(crate) fn build_stream_ws_url(base_url: &str) -> Result<String, String> {
     ws_url.set_path(&path);
     Ok(ws_url.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_zone_inside_the_brackets() {
+        let url = "http://[fe80::1%25en0]:8080/gotify";
+        let range = ipv6_zone_range(url).unwrap();
+        assert_eq!(&url[range], "%25en0");
+        assert_eq!(ipv6_zone_range("http://[fe80::1]:8080"), None);
+        assert_eq!(ipv6_zone_range("http://host:8080/?q=%25"), None);
+        assert_eq!(ipv6_zone_range("not a url"), None);
+    }
+
+    #[test]
+    fn splits_encoded_and_raw_zones() {
+        assert_eq!(
+            split_ipv6_zone("http://[fe80::1%25en0]:8080/gotify"),
+            (
+                "http://[fe80::1]:8080/gotify".to_string(),
+                Some("en0".to_string())
+            )
+        );
+        assert_eq!(
+            split_ipv6_zone("http://[fe80::1%en0]:8080"),
+            ("http://[fe80::1]:8080".to_string(), Some("en0".to_string()))
+        );
+        assert_eq!(
+            split_ipv6_zone("https://[fe80::1%25en0]"),
+            ("https://[fe80::1]".to_string(), Some("en0".to_string()))
+        );
+        assert_eq!(
+            split_ipv6_zone("http://[fd00::1]:8080"),
+            ("http://[fd00::1]:8080".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn detects_unbracketed_ipv6_hosts() {
+        assert!(is_unbracketed_ipv6("http://fd00::1"));
+        assert!(is_unbracketed_ipv6("http://fe80::1%en0:8080/gotify"));
+        assert!(!is_unbracketed_ipv6("http://[fd00::1]:8080"));
+        assert!(!is_unbracketed_ipv6("http://gotify.local:8080/a:b"));
+    }
+
+    #[test]
+    fn normalizes_zones_to_their_encoded_form() {
+        assert_eq!(
+            normalize_base_url("http://[fe80::1%en0]:8080/").unwrap(),
+            "http://[fe80::1%25en0]:8080"
+        );
+        assert_eq!(
+            normalize_base_url("http://[fe80::1%25en0]").unwrap(),
+            "http://[fe80::1%25en0]"
+        );
+        assert!(normalize_base_url("http://[fe80::1%]:8080").is_err());
+        assert!(normalize_base_url("http://[fe80::1%en/0]:8080").is_err());
+        assert!(normalize_base_url("http://[fe80::1%25en 0]:8080").is_err());
+        assert_eq!(
+            normalize_base_url("http://fd00::1:8080").unwrap_err(),
+            "IPv6 addresses must be in brackets, like http://[fd00::1]:8080"
+        );
+    }
+}
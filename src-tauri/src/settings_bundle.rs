@@ -0,0 +1,227 @@
+use std::{fs, num::NonZeroU32, path::PathBuf};
+
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    debug_log,
+    settings::{read_settings, save_non_secret_settings, SettingsResponse, StoredSettings},
+    AppState, SETTINGS_BUNDLE_MIN_PASSPHRASE_CHARS, SETTINGS_BUNDLE_PBKDF2_ITERATIONS,
+};
+
+/// File layout: magic, salt, PBKDF2 iteration count (u32 BE), nonce, then the
+/// AES-256-GCM ciphertext of the JSON payload. The magic is the AAD.
+const BUNDLE_MAGIC: &[u8] = b"GDEXPORT1\0";
+const SALT_LEN: usize = 16;
+const BUNDLE_FORMAT: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BundlePayload {
+    format: u32,
+    exported_at: u64,
+    app_version: String,
+    settings: StoredSettings,
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    iterations: NonZeroU32,
+) -> Result<LessSafeKey, String> {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    UnboundKey::new(&AES_256_GCM, &key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "Failed to derive export key".to_string())
+}
+
+/// Resolves a user-typed path, expanding a leading `~/`.
+pub(crate) fn resolve_user_path(raw: &str) -> Result<PathBuf, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err("A file path is required".to_string());
+    }
+    match trimmed.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(rest))
+            .ok_or_else(|| "Cannot resolve ~ without HOME".to_string()),
+        None => Ok(PathBuf::from(trimmed)),
+    }
+}
+
+/// Writes every stored setting, including server profiles, tokens, proxy
+/// credentials and notification rules, encrypted with a key derived from
+/// `passphrase`. Pause state is left out; it only makes sense on this machine.
+pub(crate) fn export_settings(app: &AppHandle, path: &str, passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < SETTINGS_BUNDLE_MIN_PASSPHRASE_CHARS {
+        return Err(format!(
+            "Passphrase must be at least {SETTINGS_BUNDLE_MIN_PASSPHRASE_CHARS} characters"
+        ));
+    }
+    let path = resolve_user_path(path)?;
+    let mut settings = read_settings(app)?;
+    settings.pause_until = None;
+    settings.pause_mode = None;
+    let payload = BundlePayload {
+        format: BUNDLE_FORMAT,
+        exported_at: crate::unix_now_secs(),
+        app_version: app.package_info().version.to_string(),
+        settings,
+    };
+    let mut in_out = serde_json::to_vec(&payload)
+        .map_err(|error| format!("Failed to serialize settings: {error}"))?;
+
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|()| rng.fill(&mut nonce_bytes))
+        .map_err(|_| "Failed to generate randomness".to_string())?;
+    let iterations = NonZeroU32::new(SETTINGS_BUNDLE_PBKDF2_ITERATIONS)
+        .ok_or_else(|| "Invalid iteration count".to_string())?;
+    derive_key(passphrase, &salt, iterations)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce_bytes),
+            Aad::from(BUNDLE_MAGIC),
+            &mut in_out,
+        )
+        .map_err(|_| "Failed to encrypt settings".to_string())?;
+
+    let mut bundle =
+        Vec::with_capacity(BUNDLE_MAGIC.len() + SALT_LEN + 4 + NONCE_LEN + in_out.len());
+    bundle.extend_from_slice(BUNDLE_MAGIC);
+    bundle.extend_from_slice(&salt);
+    bundle.extend_from_slice(&SETTINGS_BUNDLE_PBKDF2_ITERATIONS.to_be_bytes());
+    bundle.extend_from_slice(&nonce_bytes);
+    bundle.extend_from_slice(&in_out);
+
+    let tmp_path = path.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
+    fs::write(&tmp_path, bundle).map_err(|error| format!("Failed to write export: {error}"))?;
+    crate::restrict_file_permissions(&tmp_path);
+    fs::rename(&tmp_path, &path).map_err(|error| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Failed to move export into place: {error}")
+    })?;
+    debug_log(&format!("settings exported to {}", path.display()));
+    Ok(())
+}
+
+fn open_bundle(bytes: &[u8], passphrase: &str) -> Result<BundlePayload, String> {
+    let body = bytes
+        .strip_prefix(BUNDLE_MAGIC)
+        .ok_or_else(|| "Not a Gotify Desktop settings export".to_string())?;
+    if body.len() < SALT_LEN + 4 + NONCE_LEN {
+        return Err("Settings export is truncated".to_string());
+    }
+    let (salt, rest) = body.split_at(SALT_LEN);
+    let (iterations, rest) = rest.split_at(4);
+    let (nonce_bytes, ciphertext) = rest.split_at(NONCE_LEN);
+    let iterations =
+        u32::from_be_bytes([iterations[0], iterations[1], iterations[2], iterations[3]]);
+    // Bounded so a crafted file cannot stall the worker for minutes.
+    let iterations = NonZeroU32::new(iterations)
+        .filter(|count| count.get() <= SETTINGS_BUNDLE_PBKDF2_ITERATIONS * 4)
+        .ok_or_else(|| "Settings export has an invalid key derivation setting".to_string())?;
+    let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)
+        .map_err(|_| "Settings export has an invalid nonce".to_string())?;
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = derive_key(passphrase, salt, iterations)?
+        .open_in_place(nonce, Aad::from(BUNDLE_MAGIC), &mut in_out)
+        .map_err(|_| "Wrong passphrase or corrupt settings export".to_string())?;
+    let payload: BundlePayload = serde_json::from_slice(plaintext)
+        .map_err(|error| format!("Failed to parse settings export: {error}"))?;
+    if payload.format > BUNDLE_FORMAT {
+        return Err(format!(
+            "Settings export was made by a newer version ({})",
+            payload.app_version
+        ));
+    }
+    Ok(payload)
+}
+
+/// Replaces all settings with the exported ones and reconnects, the same way a
+/// profile switch does. A CA certificate path that does not exist here is dropped.
+pub(crate) async fn import_settings(
+    app: &AppHandle,
+    path: &str,
+    passphrase: &str,
+) -> Result<SettingsResponse, String> {
+    let path = resolve_user_path(path)?;
+    let passphrase = passphrase.to_string();
+    let payload = crate::work::run_in_background_for("import-settings", move || {
+        let bytes =
+            fs::read(&path).map_err(|error| format!("Failed to read settings export: {error}"))?;
+        open_bundle(&bytes, &passphrase)
+    })
+    .await??;
+
+    let mut imported = payload.settings;
+    imported.base_url = crate::settings::normalize_base_url(&imported.base_url).unwrap_or_default();
+    if let Some(ca_cert_path) = imported.ca_cert_path.take() {
+        match crate::settings::normalize_ca_cert_path(&ca_cert_path) {
+            Ok(valid) => imported.ca_cert_path = valid,
+            Err(error) => debug_log(&format!("import: dropping CA certificate path: {error}")),
+        }
+    }
+    imported.redaction_patterns =
+        crate::redaction::normalize_redaction_patterns(imported.redaction_patterns)?;
+    if imported
+        .active_profile
+        .as_ref()
+        .is_some_and(|name| !imported.profiles.contains_key(name))
+    {
+        imported.active_profile = None;
+    }
+
+    let _ = crate::stream::stop_stream(app.clone());
+    if let Some(name) = imported.active_profile.as_deref() {
+        crate::background_streams::stop(app, name);
+    }
+    crate::messages::flush_pending_messages(app);
+    crate::work::run_in_background_for("import-settings-flush", || ()).await?;
+    {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let current = read_settings(app)?;
+        imported.pause_until = current.pause_until;
+        imported.pause_mode = current.pause_mode;
+        save_non_secret_settings(app, &imported)?;
+    }
+    debug_log(&format!(
+        "settings imported (exported by {} at {})",
+        payload.app_version, payload.exported_at
+    ));
+
+    crate::profiles::set_active_profile(app, imported.active_profile.clone());
+    crate::profiles::load_profile_state(app)?;
+    crate::profiles::refresh_tray_profiles(app);
+    crate::privacy::sync_tray_item(app, imported.privacy_mode);
+    #[cfg(target_os = "macos")]
+    if let Err(error) = crate::apply_launch_at_login(imported.launch_at_login) {
+        debug_log(&format!("import: failed to apply launch at login: {error}"));
+    }
+    if !imported.base_url.is_empty() {
+        if let Err(error) = crate::stream::start_stream(app.clone(), None) {
+            let _ = crate::contract::publish_stream_error(
+                app,
+                format!("Failed to connect after import: {error}"),
+            );
+        }
+    }
+    crate::background_streams::sync(app);
+    crate::settings::load_settings(app)
+}
@@ -0,0 +1,208 @@
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    contract::{self, DomainSnapshot},
+    debug_log,
+    messages::persist_messages_snapshot,
+    notifications::{maybe_notify_background_message, maybe_notify_message},
+    settings::read_settings,
+    unix_now_secs, AppState, CachedMessage, MAX_SNOOZE_MINUTES, SNOOZE_CHECK_INTERVAL_SECS,
+};
+
+/// Hides a cached message from unread counts for `minutes`. When the snooze
+/// ends it comes back unread and is notified again. A quiet-hours notification
+/// still held for it is dropped, so it does not fire while snoozed.
+pub(crate) fn snooze_message(
+    app: &AppHandle,
+    message_id: i64,
+    minutes: u64,
+) -> Result<DomainSnapshot<Vec<CachedMessage>>, String> {
+    if minutes == 0 || minutes > MAX_SNOOZE_MINUTES {
+        return Err(format!(
+            "Snooze must be between 1 and {MAX_SNOOZE_MINUTES} minutes"
+        ));
+    }
+    let until = unix_now_secs().saturating_add(minutes * 60);
+    let app_state = app.state::<AppState>();
+    let updated_snapshot = {
+        let mut messages_guard = app_state
+            .messages
+            .lock()
+            .map_err(|_| "Message cache lock poisoned".to_string())?;
+        let message = messages_guard
+            .iter_mut()
+            .find(|message| message.id == message_id)
+            .ok_or_else(|| format!("Message {message_id} is not cached"))?;
+        message.snoozed_until = Some(until);
+        messages_guard.clone()
+    };
+
+    crate::notification_queue::cancel_for_messages(app, &[message_id]);
+    persist_messages_snapshot(app, &updated_snapshot)?;
+    crate::inbox::evaluate(app, &updated_snapshot);
+    debug_log(&format!("snooze: message {message_id} until {until}"));
+    Ok(contract::publish_messages_replace(app, updated_snapshot))
+}
+
+/// Scheduler tick: ends the active server's snoozes that are due, marks those
+/// messages unread and hands them back to the notify pipeline. Snoozes that ran out while the app
+/// was closed end on the first tick after startup.
+pub(crate) fn wake_due(app: &AppHandle) {
+    let now = unix_now_secs();
+    let app_state = app.state::<AppState>();
+    let (woken, updated_snapshot) = {
+        let Ok(mut messages_guard) = app_state.messages.lock() else {
+            return;
+        };
+        let mut woken = Vec::new();
+        for message in messages_guard.iter_mut() {
+            if message.snoozed_until.is_none_or(|until| until > now) {
+                continue;
+            }
+            message.snoozed_until = None;
+            message.read = false;
+            message.read_at = None;
+            woken.push(message.clone());
+        }
+        if woken.is_empty() {
+            return;
+        }
+        (woken, messages_guard.clone())
+    };
+
+    if let Err(error) = persist_messages_snapshot(app, &updated_snapshot) {
+        debug_log(&format!(
+            "snooze: failed to persist woken messages: {error}"
+        ));
+    }
+    crate::inbox::evaluate(app, &updated_snapshot);
+    contract::publish_messages_replace(app, updated_snapshot);
+    // A re-notification, not a delivery: the message arrived long ago.
+    for message in &woken {
+        debug_log(&format!("snooze: message {} is due", message.id));
+        maybe_notify_message(app, message, None);
+    }
+}
+
+/// Makes the next tick scan the other profiles' caches again, e.g. after a
+/// switch moved a profile with snoozed messages out of the foreground.
+pub(crate) fn rescan_profiles(app: &AppHandle) {
+    if let Ok(mut snoozes) = app.state::<AppState>().profile_snoozes.lock() {
+        *snoozes = None;
+    }
+}
+
+/// `wake_due` for saved servers other than the active one: their snoozes end
+/// in their cache files and notify with the server's badge. Files are only
+/// opened for a scan after a switch or startup and when a snooze is due.
+async fn wake_due_in_profiles(app: &AppHandle) {
+    let Ok(settings) = read_settings(app) else {
+        return;
+    };
+    let profiles: HashMap<String, PathBuf> = settings
+        .profiles
+        .keys()
+        .filter(|name| settings.active_profile.as_ref() != Some(*name))
+        .filter_map(|name| {
+            crate::profile_messages_file(app, name)
+                .ok()
+                .map(|path| (name.clone(), path))
+        })
+        .collect();
+    let now = unix_now_secs();
+    let known = app
+        .state::<AppState>()
+        .profile_snoozes
+        .lock()
+        .ok()
+        .and_then(|snoozes| snoozes.clone());
+    let due: HashMap<String, PathBuf> = match known {
+        Some(snoozes) => profiles
+            .into_iter()
+            .filter(|(name, _)| snoozes.get(name).is_some_and(|until| *until <= now))
+            .collect(),
+        None => profiles,
+    };
+    if due.is_empty() {
+        return;
+    }
+
+    let names: Vec<String> = due.keys().cloned().collect();
+    let result = crate::work::run_in_background_for("wake-profile-snoozes", move || {
+        due.into_iter()
+            .map(|(name, path)| {
+                let woken = wake_profile_file(&path, now).unwrap_or_else(|error| {
+                    debug_log(&format!("snooze: failed to wake {name:?}: {error}"));
+                    (Vec::new(), None)
+                });
+                (name, woken)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await;
+    let Ok(results) = result else {
+        return;
+    };
+    if let Ok(mut snoozes) = app.state::<AppState>().profile_snoozes.lock() {
+        let snoozes = snoozes.get_or_insert_with(HashMap::new);
+        for name in &names {
+            snoozes.remove(name);
+        }
+        for (name, (_, next)) in &results {
+            if let Some(next) = next {
+                snoozes.insert(name.clone(), *next);
+            }
+        }
+    }
+    for (name, (woken, _)) in results {
+        for message in &woken {
+            debug_log(&format!(
+                "snooze: message {} of {name:?} is due",
+                message.id
+            ));
+            maybe_notify_background_message(app, message, None, &name);
+        }
+    }
+}
+
+/// Ends the due snoozes in one profile's cache file. Returns the woken
+/// messages and the next snooze end still in the file.
+fn wake_profile_file(
+    path: &std::path::Path,
+    now: u64,
+) -> Result<(Vec<CachedMessage>, Option<u64>), String> {
+    if !crate::message_store::database_path(path).exists() && !path.exists() {
+        return Ok((Vec::new(), None));
+    }
+    let mut messages = crate::message_store::load_messages(path)?;
+    let mut woken = Vec::new();
+    for message in &mut messages {
+        if message.snoozed_until.is_none_or(|until| until > now) {
+            continue;
+        }
+        message.snoozed_until = None;
+        message.read = false;
+        message.read_at = None;
+        woken.push(message.clone());
+    }
+    if !woken.is_empty() {
+        crate::message_store::save_messages(path, &messages)?;
+    }
+    let next = messages
+        .iter()
+        .filter_map(|message| message.snoozed_until)
+        .min();
+    Ok((woken, next))
+}
+
+pub(crate) fn watch_snoozes(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(SNOOZE_CHECK_INTERVAL_SECS)).await;
+            wake_due(&app);
+            wake_due_in_profiles(&app).await;
+        }
+    });
+}
@@ -0,0 +1,233 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    contexts::{normalize_sound, NOTIFICATION_SOUND_DEFAULT, NOTIFICATION_SOUND_NONE},
+    debug_log, IMPORTED_SOUNDS_FILE, MAX_CUSTOM_SOUND_BYTES, MAX_CUSTOM_SOUND_SECS,
+    QUIET_HOURS_SOUND_PATH,
+};
+
+const SYSTEM_SOUNDS_DIR: &str = "/System/Library/Sounds";
+/// Extensions Notification Center plays by name, in lookup order.
+const SOUND_EXTENSIONS: [&str; 3] = ["aiff", "aif", "caf"];
+
+/// `~/Library/Sounds`: macOS looks a notification sound name up here after the
+/// app bundle, so a copied file can be referenced like a system sound.
+fn user_sounds_dir() -> Result<PathBuf, String> {
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join("Library").join("Sounds"))
+        .ok_or_else(|| "Cannot find the sounds folder without HOME".to_string())
+}
+
+/// Names of the sounds this app copied into `~/Library/Sounds`. The folder is
+/// shared with the user and other apps, so only these are listed, replaced or
+/// removed.
+pub(crate) fn imported_sounds_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|error| format!("Failed to resolve app config dir: {error}"))?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|error| format!("Failed to create config directory: {error}"))?;
+    Ok(config_dir.join(IMPORTED_SOUNDS_FILE))
+}
+
+fn read_imported(manifest: &Path) -> Vec<String> {
+    fs::read_to_string(manifest)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_imported(manifest: &Path, names: &[String]) -> Result<(), String> {
+    let content = serde_json::to_string(names).map_err(|error| error.to_string())?;
+    let tmp_path = manifest.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
+    fs::write(&tmp_path, content)
+        .and_then(|()| fs::rename(&tmp_path, manifest))
+        .map_err(|error| format!("Failed to record imported sound: {error}"))
+}
+
+/// Where a sound of that name would be, in lookup order.
+fn sound_files<'a>(dir: &'a Path, name: &'a str) -> impl Iterator<Item = PathBuf> + 'a {
+    SOUND_EXTENSIONS
+        .iter()
+        .map(move |extension| dir.join(format!("{name}.{extension}")))
+}
+
+/// Real format by magic bytes: an AIFF/AIFC `FORM` container or a CAF file.
+fn is_supported_sound(bytes: &[u8]) -> bool {
+    let aiff =
+        bytes.len() >= 12 && &bytes[..4] == b"FORM" && matches!(&bytes[8..12], b"AIFF" | b"AIFC");
+    aiff || bytes.starts_with(b"caff")
+}
+
+/// Copies an `.aiff`/`.caf` file into `~/Library/Sounds` and returns the name
+/// to use as a notification sound. The file name (without extension) becomes
+/// the sound name. A sound imported earlier under that name is replaced; a
+/// file this app did not put there is refused rather than overwritten.
+pub(crate) fn import_sound(manifest: &Path, path: &str) -> Result<String, String> {
+    let source = crate::settings_bundle::resolve_user_path(path)?;
+    let extension = source
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+        .filter(|extension| SOUND_EXTENSIONS.contains(&extension.as_str()))
+        .ok_or_else(|| "Sound must be an .aiff or .caf file".to_string())?;
+    let name = source
+        .file_stem()
+        .map(|stem| normalize_sound(&stem.to_string_lossy()))
+        .transpose()?
+        .ok_or_else(|| "Sound file has no name".to_string())?;
+    if name == NOTIFICATION_SOUND_DEFAULT || name == NOTIFICATION_SOUND_NONE {
+        return Err(format!("{name:?} is reserved; rename the file"));
+    }
+    if system_sound_path(&name).is_some() {
+        return Err(format!("{name} is a system sound; rename the file"));
+    }
+
+    let size = fs::metadata(&source)
+        .map_err(|error| format!("Failed to read sound: {error}"))?
+        .len();
+    if size > MAX_CUSTOM_SOUND_BYTES {
+        return Err(format!(
+            "Sound is larger than {} MB",
+            MAX_CUSTOM_SOUND_BYTES / (1024 * 1024)
+        ));
+    }
+    let bytes = fs::read(&source).map_err(|error| format!("Failed to read sound: {error}"))?;
+    if !is_supported_sound(&bytes) {
+        return Err("The file is not an AIFF or CAF sound".to_string());
+    }
+    let duration = sound_duration_secs(&source)?;
+    if duration >= MAX_CUSTOM_SOUND_SECS {
+        return Err(format!(
+            "Sound is {duration:.1}s long; notification sounds must be shorter than {MAX_CUSTOM_SOUND_SECS:.0}s"
+        ));
+    }
+
+    let dir = user_sounds_dir()?;
+    let mut imported = read_imported(manifest);
+    let owned = imported.contains(&name);
+    if !owned && sound_files(&dir, &name).any(|file| file.exists()) {
+        return Err(format!(
+            "~/Library/Sounds already has a sound named {name}; rename the file"
+        ));
+    }
+    fs::create_dir_all(&dir)
+        .map_err(|error| format!("Failed to create {}: {error}", dir.display()))?;
+    if owned {
+        // One file per name, so a replaced sound does not leave the other format behind.
+        for file in sound_files(&dir, &name) {
+            let _ = fs::remove_file(file);
+        }
+    }
+    let target = dir.join(format!("{name}.{extension}"));
+    let tmp_path = target.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
+    fs::write(&tmp_path, &bytes)
+        .and_then(|()| fs::rename(&tmp_path, &target))
+        .map_err(|error| format!("Failed to copy sound: {error}"))?;
+    if !owned {
+        imported.push(name.clone());
+        write_imported(manifest, &imported)?;
+    }
+    debug_log(&format!("sounds: imported {name} ({duration:.1}s)"));
+    Ok(name)
+}
+
+/// Sounds imported by this app that are still in `~/Library/Sounds`, sorted
+/// by name.
+pub(crate) fn list_custom_sounds(manifest: &Path) -> Result<Vec<String>, String> {
+    let dir = user_sounds_dir()?;
+    let mut names: Vec<String> = read_imported(manifest)
+        .into_iter()
+        .filter(|name| sound_files(&dir, name).any(|file| file.exists()))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names.dedup();
+    Ok(names)
+}
+
+fn system_sound_path(name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(SYSTEM_SOUNDS_DIR).join(format!("{name}.aiff"));
+    path.exists().then_some(path)
+}
+
+/// The file a sound value plays, looked up like Notification Center does:
+/// custom sounds first, then system ones. `None` for `none`.
+fn resolve_sound_path(sound: &str) -> Result<Option<PathBuf>, String> {
+    let sound = normalize_sound(sound)?;
+    if sound == NOTIFICATION_SOUND_NONE {
+        return Ok(None);
+    }
+    if sound == NOTIFICATION_SOUND_DEFAULT {
+        return Ok(Some(PathBuf::from(QUIET_HOURS_SOUND_PATH)));
+    }
+    let dir = user_sounds_dir()?;
+    let custom = sound_files(&dir, &sound).find(|path| path.exists());
+    custom
+        .or_else(|| system_sound_path(&sound))
+        .map(Some)
+        .ok_or_else(|| format!("No sound named {sound}"))
+}
+
+/// Plays a sound value once, for the settings UI. `none` plays nothing.
+pub(crate) fn preview_sound(sound: &str) -> Result<(), String> {
+    let Some(path) = resolve_sound_path(sound)? else {
+        return Ok(());
+    };
+    play(&path)
+}
+
+/// Starts `afplay` and reaps it on a helper thread, so previews and the
+/// quiet-hours sound do not leave zombie processes behind.
+#[cfg(target_os = "macos")]
+pub(crate) fn play(path: &Path) -> Result<(), String> {
+    let mut child = crate::work::background_command("/usr/bin/afplay")
+        .arg(path)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|error| format!("Failed to play sound: {error}"))?;
+    std::thread::spawn(move || {
+        if let Err(error) = child.wait() {
+            debug_log(&format!("sounds: failed to wait for afplay: {error}"));
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn play(_path: &Path) -> Result<(), String> {
+    Err("Sound preview needs macOS".to_string())
+}
+
+/// Length reported by `afinfo`, which reads every format Core Audio plays.
+#[cfg(target_os = "macos")]
+fn sound_duration_secs(path: &Path) -> Result<f64, String> {
+    let output = crate::work::background_command("/usr/bin/afinfo")
+        .arg(path)
+        .output()
+        .map_err(|error| format!("Failed to run afinfo: {error}"))?;
+    if !output.status.success() {
+        return Err("The sound could not be read".to_string());
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("estimated duration:"))
+        .and_then(|rest| {
+            rest.trim()
+                .trim_end_matches("sec")
+                .trim()
+                .parse::<f64>()
+                .ok()
+        })
+        .ok_or_else(|| "Could not tell how long the sound is".to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn sound_duration_secs(_path: &Path) -> Result<f64, String> {
+    Err("Custom sounds need macOS".to_string())
+}
@@ -1,21 +1,39 @@
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
 use futures_util::{SinkExt, StreamExt};
 use tauri::{AppHandle, Manager};
-use tokio::sync::watch;
+use tokio::{net::TcpStream, sync::watch};
 use tokio_tungstenite::{
-    connect_async,
+    client_async_tls_with_config,
     tungstenite::{client::IntoClientRequest, http::HeaderValue, Message},
+    MaybeTlsStream, WebSocketStream,
 };
 
 use crate::{
+    background_streams::BackgroundOwner,
+    connection_history::{self, ConnectionEventKind},
     debug_log,
     diagnostics::{
         mark_stream_activity, publish_runtime_snapshot, snapshot_runtime, RuntimeDiagnostics,
     },
-    messages, redact_ws_url,
-    settings::{build_stream_ws_url, load_token, normalize_base_url, read_settings},
-    truncate_message, unix_now_secs, AppState, STREAM_CONNECT_TIMEOUT_SECS,
+    messages,
+    op_trace::{self, TraceOp},
+    redact_ws_url,
+    settings::{
+        build_stream_ws_url, load_token, read_settings, save_non_secret_settings,
+        to_settings_response, ConnectionMode, ReconnectBackoff, SettingsResponse, StoredSettings,
+    },
+    truncate_message, unix_now_secs, AppState, StreamSlot, CONNECTION_STATE_AUTH_FAILED,
+    CONNECTION_STATE_POLLING, DEFAULT_BACKOFF_RESET_AFTER_SECS, HEARTBEAT_INTERVAL_SECS,
+    MAX_BACKOFF_RESET_AFTER_SECS, STREAM_AUTO_FALLBACK_FAILURES, STREAM_CLOSE_ACK_TIMEOUT_MS,
     STREAM_LIVENESS_CHECK_INTERVAL_SECS, STREAM_LIVENESS_IDLE_SECS,
-    STREAM_LIVENESS_PING_GRACE_SECS, STREAM_SYNC_INTERVAL_SECS,
+    STREAM_LIVENESS_PING_GRACE_SECS, STREAM_POLL_INTERVAL_SECS, STREAM_POLL_WEBSOCKET_RETRY_SECS,
+    STREAM_RTT_PING_INTERVAL_SECS, STREAM_SYNC_INTERVAL_SECS,
 };
 
 pub(crate) fn start_stream(app: AppHandle, token: Option<String>) -> Result<(), String> {
@@ -26,22 +44,39 @@ pub(crate) fn stop_stream(app: AppHandle) -> Result<(), String> {
     stop_stream_internal(&app)
 }
 
+/// Key of the active server's slot in `RuntimeState::streams`.
+pub(crate) fn stream_key(settings: &StoredSettings) -> String {
+    settings.active_profile.clone().unwrap_or_default()
+}
+
 pub(crate) fn get_runtime_diagnostics(app: AppHandle) -> Result<RuntimeDiagnostics, String> {
     snapshot_runtime(&app)
 }
 
+/// Restarts the stream when it should run but is not up, or is stuck (see
+/// `RuntimeState::stall_reason`). A healthy or connecting stream is left alone.
 pub(crate) fn recover_stream(app: AppHandle) -> Result<(), String> {
     let state = app.state::<AppState>();
-    let (should_run, connection_state) = state
+    let (should_run, connection_state, stalled) = state
         .runtime
         .lock()
-        .map(|runtime| (runtime.should_run, runtime.connection_state.clone()))
-        .unwrap_or((false, "Disconnected".to_string()));
+        .map(|runtime| {
+            (
+                runtime.should_run,
+                runtime.connection_state.clone(),
+                runtime.stall_reason(unix_now_secs()).is_some(),
+            )
+        })
+        .unwrap_or((false, "Disconnected".to_string(), false));
 
     if !should_run {
         return Ok(());
     }
-    if connection_state == "Connected" || connection_state == "Connecting" {
+    if !stalled
+        && (connection_state == "Connected"
+            || connection_state == "Connecting"
+            || connection_state == CONNECTION_STATE_POLLING)
+    {
         return Ok(());
     }
 
@@ -54,9 +89,75 @@ pub(crate) fn restart_stream(app: AppHandle) -> Result<(), String> {
     start_stream_internal(app, None)
 }
 
+/// Switches how the stream authenticates for the active server and reconnects a
+/// running stream with it.
+pub(crate) fn set_stream_token_in_query(
+    app: &AppHandle,
+    enabled: bool,
+) -> Result<SettingsResponse, String> {
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.stream_token_in_query = enabled;
+        if let Some(profile) = settings
+            .active_profile
+            .clone()
+            .and_then(|name| settings.profiles.get_mut(&name))
+        {
+            profile.stream_token_in_query = enabled;
+        }
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!("stream token in query set to {enabled}"));
+    let running = app
+        .state::<AppState>()
+        .runtime
+        .lock()
+        .map(|runtime| runtime.should_run)
+        .unwrap_or(false);
+    if running {
+        restart_stream(app.clone())?;
+    }
+    Ok(to_settings_response(settings))
+}
+
+/// Saves the mode and reconnects a running stream with it.
+pub(crate) fn set_connection_mode(
+    app: &AppHandle,
+    mode: ConnectionMode,
+) -> Result<SettingsResponse, String> {
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.connection_mode = mode;
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!("connection mode set to {mode:?}"));
+    let running = app
+        .state::<AppState>()
+        .runtime
+        .lock()
+        .map(|runtime| runtime.should_run)
+        .unwrap_or(false);
+    if running {
+        restart_stream(app.clone())?;
+    }
+    Ok(to_settings_response(settings))
+}
+
 fn start_stream_internal(app: AppHandle, token_override: Option<String>) -> Result<(), String> {
     let settings = read_settings(&app)?;
-    let base_url = normalize_base_url(&settings.base_url)?;
+    let base_url = crate::endpoints::active_base_url(&app, &settings)?;
     let token = match token_override {
         Some(value) if !value.trim().is_empty() => value.trim().to_string(),
         _ => load_token()?
@@ -71,14 +172,26 @@ fn start_stream_internal(app: AppHandle, token_override: Option<String>) -> Resu
             .lock()
             .map_err(|_| "Runtime lock poisoned".to_string())?;
 
-        if runtime.stop_tx.is_some() {
+        if runtime.has_foreground_stream() {
             return Ok(());
         }
 
+        let key = stream_key(&settings);
+        // The profile may have been streaming in the background until now.
+        if let Some(slot) = runtime.streams.remove(&key) {
+            let _ = slot.stop_tx.send(true);
+        }
         let (tx, rx) = watch::channel(false);
-        runtime.stop_tx = Some(tx);
         runtime.stream_epoch = runtime.stream_epoch.wrapping_add(1);
         let task_epoch = runtime.stream_epoch;
+        runtime.streams.insert(
+            key.clone(),
+            StreamSlot {
+                stop_tx: tx,
+                epoch: task_epoch,
+                background: None,
+            },
+        );
         runtime.should_run = true;
         runtime.last_error = None;
         runtime.backoff_seconds = 0;
@@ -90,6 +203,7 @@ fn start_stream_internal(app: AppHandle, token_override: Option<String>) -> Resu
         let app_for_task = app.clone();
         debug_log("spawning stream task");
         tauri::async_runtime::spawn(async move {
+            let prefetch_mode = messages::begin_initial_sync_if_empty(&app_for_task);
             let app_for_prefetch = app_for_task.clone();
             let base_url_for_prefetch = base_url.clone();
             let token_for_prefetch = token.clone();
@@ -102,13 +216,14 @@ fn start_stream_internal(app: AppHandle, token_override: Option<String>) -> Resu
                     &app_for_prefetch,
                     &base_url_for_prefetch,
                     &token_for_prefetch,
+                    prefetch_mode,
                 )
                 .await
                 {
                     debug_log(&format!("failed to fetch recent messages: {error}"));
                 }
             });
-            run_stream_loop(app_for_task, base_url, token, rx, task_epoch).await;
+            run_stream_loop(app_for_task, key, base_url, token, rx, task_epoch).await;
         });
     }
 
@@ -122,12 +237,11 @@ fn stop_stream_internal(app: &AppHandle) -> Result<(), String> {
         .lock()
         .map_err(|_| "Runtime lock poisoned".to_string())?;
 
-    if let Some(stop_tx) = runtime.stop_tx.take() {
-        let _ = stop_tx.send(true);
-    }
+    runtime.stop_foreground_stream();
     runtime.should_run = false;
     runtime.backoff_seconds = 0;
     drop(runtime);
+    crate::operations::cancel_group(app, crate::OPERATION_GROUP_SYNC);
 
     update_connection_state(app, "Disconnected");
     publish_runtime_snapshot(app);
@@ -136,27 +250,75 @@ fn stop_stream_internal(app: &AppHandle) -> Result<(), String> {
 
 async fn run_stream_loop(
     app: AppHandle,
+    key: String,
     base_url: String,
     token: String,
     mut stop_rx: watch::Receiver<bool>,
     task_epoch: u64,
 ) {
-    let mut backoff_secs: u64 = 1;
-    debug_log("stream task started");
+    let mut backoff_secs = reconnect_backoff(&app).base_secs;
+    let mode = read_settings(&app)
+        .map(|settings| settings.connection_mode)
+        .unwrap_or_default();
+    let mut websocket_failures: u32 = 0;
+    // The first session follows the initial fetch in `start_stream_internal`;
+    // later ones catch up on what the dropped socket missed.
+    let mut reconnecting = false;
+    debug_log(&format!("stream task started mode={mode:?}"));
 
     loop {
         if *stop_rx.borrow() {
             break;
         }
 
+        let polling = match mode {
+            ConnectionMode::Websocket => false,
+            ConnectionMode::Polling => true,
+            ConnectionMode::Auto => websocket_failures >= STREAM_AUTO_FALLBACK_FAILURES,
+        };
         update_connection_state(&app, "Connecting");
-        debug_log("attempting stream connection");
-        match stream_once(&app, &base_url, &token, &mut stop_rx).await {
+        connection_history::record(
+            &app,
+            ConnectionEventKind::Connecting,
+            Some(if polling { "polling" } else { "websocket" }.to_string()),
+        );
+        let attempt_started_at = unix_now_secs();
+        let result = if polling {
+            debug_log("attempting message polling");
+            let retry_websocket_after =
+                (mode == ConnectionMode::Auto).then_some(STREAM_POLL_WEBSOCKET_RETRY_SECS);
+            poll_session(&app, &base_url, &token, &mut stop_rx, retry_websocket_after).await
+        } else {
+            debug_log("attempting stream connection");
+            let target = active_target(&app, &base_url, &token);
+            let result = stream_once(
+                &app,
+                &mut StreamOwner::Active,
+                &target,
+                &mut stop_rx,
+                reconnecting,
+            )
+            .await
+            .map_err(|error| error.message);
+            reconnecting = true;
+            result
+        };
+        match result {
             Ok(()) => {
                 if *stop_rx.borrow() {
                     break;
                 }
+                if polling {
+                    debug_log("polling window over; trying the websocket again");
+                    websocket_failures = 0;
+                    continue;
+                }
                 debug_log("stream session ended without error");
+                connection_history::record(
+                    &app,
+                    ConnectionEventKind::Disconnected,
+                    Some("Stream ended".to_string()),
+                );
                 update_connection_state(&app, "Disconnected");
                 publish_runtime_snapshot(&app);
             }
@@ -166,6 +328,44 @@ async fn run_stream_loop(
                 }
 
                 debug_log(&format!("stream loop error: {err}"));
+                crate::tls::announce_untrusted_certificates(&app);
+                let policy = reconnect_backoff(&app);
+                let connected_at = connected_since(&app, attempt_started_at);
+                if !polling {
+                    websocket_failures = if connected_at.is_some() {
+                        0
+                    } else {
+                        websocket_failures.saturating_add(1)
+                    };
+                    if mode == ConnectionMode::Auto
+                        && websocket_failures == STREAM_AUTO_FALLBACK_FAILURES
+                    {
+                        debug_log(&format!(
+                            "websocket failed {websocket_failures} times; falling back to polling"
+                        ));
+                        backoff_secs = policy.base_secs;
+                    }
+                }
+                if let Some(connected_at) = connected_at {
+                    let stable_for = unix_now_secs().saturating_sub(connected_at);
+                    if stable_for >= backoff_reset_after_secs(&app) {
+                        backoff_secs = policy.base_secs;
+                    } else {
+                        debug_log(&format!(
+                            "connection dropped after {stable_for}s; keeping backoff at {backoff_secs}s"
+                        ));
+                    }
+                }
+                connection_history::record(
+                    &app,
+                    ConnectionEventKind::Disconnected,
+                    Some(err.clone()),
+                );
+                connection_history::record(
+                    &app,
+                    ConnectionEventKind::Backoff,
+                    Some(format!("{backoff_secs}s")),
+                );
                 update_connection_state(&app, "Backoff");
                 let _ = crate::contract::publish_stream_error(&app, truncate_message(&err, 200));
                 if let Some(state) = app.try_state::<AppState>() {
@@ -182,12 +382,20 @@ async fn run_stream_loop(
                     .map(|d| (d.subsec_millis() % 500) as u64)
                     .unwrap_or(0);
 
-                tokio::time::sleep(
+                let backoff = tokio::time::sleep(
                     std::time::Duration::from_secs(backoff_secs)
                         + std::time::Duration::from_millis(jitter_ms),
-                )
-                .await;
-                backoff_secs = std::cmp::min(backoff_secs.saturating_mul(2), 30);
+                );
+                let state = app.state::<AppState>();
+                tokio::select! {
+                    _ = backoff => {
+                        backoff_secs = policy.next(backoff_secs);
+                    }
+                    _ = state.network_wake.notified() => {
+                        debug_log("backoff cut short; reconnecting now");
+                        backoff_secs = reconnect_backoff(&app).base_secs;
+                    }
+                }
             }
         }
     }
@@ -195,61 +403,513 @@ async fn run_stream_loop(
     let state = app.state::<AppState>();
     let mut should_emit_disconnected = false;
     if let Ok(mut runtime) = state.runtime.lock() {
-        if runtime.stream_epoch == task_epoch {
-            runtime.stop_tx = None;
+        if runtime
+            .streams
+            .get(&key)
+            .is_some_and(|slot| slot.epoch == task_epoch)
+        {
+            runtime.streams.remove(&key);
             runtime.should_run = false;
             runtime.backoff_seconds = 0;
-            should_emit_disconnected = true;
+            // Keep AuthFailed visible until new credentials restart the stream.
+            should_emit_disconnected = runtime.connection_state != CONNECTION_STATE_AUTH_FAILED;
         }
     }
     if should_emit_disconnected {
+        connection_history::record(&app, ConnectionEventKind::Stopped, None);
         update_connection_state(&app, "Disconnected");
     }
     publish_runtime_snapshot(&app);
 }
 
-async fn stream_once(
+/// Saves the policy; a stream waiting out a backoff retries at once under it.
+pub(crate) fn set_reconnect_backoff(
     app: &AppHandle,
-    base_url: &str,
-    token: &str,
-    stop_rx: &mut watch::Receiver<bool>,
-) -> Result<(), String> {
-    let ws_url = build_stream_ws_url(base_url)?;
+    backoff: ReconnectBackoff,
+) -> Result<SettingsResponse, String> {
+    let backoff = backoff.validate()?;
+    let settings = {
+        let state = app.state::<AppState>();
+        let _settings_guard = state
+            .settings_lock
+            .lock()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let mut settings = read_settings(app)?;
+        settings.reconnect_backoff = backoff;
+        save_non_secret_settings(app, &settings)?;
+        settings
+    };
+    debug_log(&format!("reconnect backoff set to {backoff:?}"));
+    reconnect_now(app);
+    Ok(to_settings_response(settings))
+}
+
+/// Ends a backoff wait and resets the delay to the policy's base. Unlike
+/// `restart_stream` it leaves a working connection alone.
+pub(crate) fn reconnect_now(app: &AppHandle) {
+    if reconnectable_state(app).as_deref() == Some("Backoff") {
+        app.state::<AppState>().network_wake.notify_waiters();
+    }
+}
+
+/// The default route changed or came back. A backoff sleep is cut short; a
+/// live or connecting session is restarted, since its socket may still be
+/// bound to the old route and would only fail after the liveness timeout.
+pub(crate) fn network_changed(app: &AppHandle) {
+    connection_history::record(app, ConnectionEventKind::NetworkChanged, None);
+    crate::background_streams::restart(app);
+    let Some(connection_state) = reconnectable_state(app) else {
+        return;
+    };
+    if connection_state == "Backoff" {
+        app.state::<AppState>().network_wake.notify_waiters();
+        return;
+    }
+    debug_log(&format!(
+        "network changed while {connection_state}; restarting stream"
+    ));
+    if let Err(error) = restart_stream(app.clone()) {
+        debug_log(&format!(
+            "failed to restart stream after network change: {error}"
+        ));
+    }
+}
+
+/// The Mac woke from sleep. Sockets from before sleep are usually dead without
+/// knowing it, so any running session restarts; the restart's initial fetch
+/// also brings in messages pushed while the lid was closed.
+pub(crate) fn system_woke(app: &AppHandle) {
+    connection_history::record(app, ConnectionEventKind::Woke, None);
+    crate::background_streams::restart(app);
+    let Some(connection_state) = reconnectable_state(app) else {
+        return;
+    };
+    debug_log(&format!("woke while {connection_state}; restarting stream"));
+    if let Err(error) = restart_stream(app.clone()) {
+        debug_log(&format!("failed to restart stream after wake: {error}"));
+    }
+}
+
+/// Connection state of a stream that should run and may reconnect on its own;
+/// `None` when stopped or waiting for new credentials.
+fn reconnectable_state(app: &AppHandle) -> Option<String> {
+    let (should_run, connection_state) = app
+        .state::<AppState>()
+        .runtime
+        .lock()
+        .map(|runtime| (runtime.should_run, runtime.connection_state.clone()))
+        .unwrap_or((false, String::new()));
+    (should_run && connection_state != CONNECTION_STATE_AUTH_FAILED).then_some(connection_state)
+}
+
+/// Returns when the current attempt connected, if it got that far.
+fn connected_since(app: &AppHandle, attempt_started_at: u64) -> Option<u64> {
+    let state = app.try_state::<AppState>()?;
+    let runtime = state.runtime.lock().ok()?;
+    runtime
+        .last_connected_at
+        .filter(|connected_at| *connected_at >= attempt_started_at)
+}
+
+/// Stored policy, or the default when the settings file holds one out of range.
+pub(crate) fn reconnect_backoff(app: &AppHandle) -> ReconnectBackoff {
+    read_settings(app)
+        .ok()
+        .and_then(|settings| settings.reconnect_backoff.validate().ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn backoff_reset_after_secs(app: &AppHandle) -> u64 {
+    read_settings(app)
+        .map(|settings| settings.backoff_reset_after_secs)
+        .unwrap_or(DEFAULT_BACKOFF_RESET_AFTER_SECS)
+        .min(MAX_BACKOFF_RESET_AFTER_SECS)
+}
+
+/// Where a stream connects: the server, its token and how to reach its host.
+#[derive(Clone)]
+pub(crate) struct StreamTarget {
+    pub(crate) base_url: String,
+    pub(crate) token: String,
+    pub(crate) host_pins: BTreeMap<String, IpAddr>,
+    /// Interface zone of a link-local IPv6 server address.
+    pub(crate) zone: Option<String>,
+    /// Put the token in the URL query instead of the `X-Gotify-Key` header.
+    pub(crate) token_in_query: bool,
+}
+
+/// The active server's target, with its DNS overrides and IPv6 zone.
+pub(crate) fn active_target(app: &AppHandle, base_url: &str, token: &str) -> StreamTarget {
+    StreamTarget {
+        base_url: base_url.to_string(),
+        token: token.to_string(),
+        host_pins: crate::settings::configured_host_pins(app),
+        zone: crate::endpoints::active_ipv6_zone(app),
+        token_in_query: read_settings(app)
+            .map(|settings| settings.stream_token_in_query)
+            .unwrap_or(false),
+    }
+}
+
+/// Whose stream a session runs. The active server reports through
+/// `RuntimeState` and the shared message cache; a background profile through
+/// the status in its `StreamSlot` and its own cache file.
+pub(crate) enum StreamOwner {
+    Active,
+    Background(BackgroundOwner),
+}
+
+impl StreamOwner {
+    pub(crate) fn is_active(&self) -> bool {
+        matches!(self, Self::Active)
+    }
+
+    fn record(&self, app: &AppHandle, kind: ConnectionEventKind, detail: Option<String>) {
+        match self {
+            Self::Active => connection_history::record(app, kind, detail),
+            Self::Background(owner) => owner.record(app, kind, detail),
+        }
+    }
+
+    fn mark_activity(&self, app: &AppHandle, now: u64, source: &str) {
+        if self.is_active() {
+            mark_stream_activity(app, now, source);
+        }
+    }
+
+    async fn connected(
+        &mut self,
+        app: &AppHandle,
+        target: &StreamTarget,
+        remote_addr: String,
+        handshake_ms: u64,
+        now: u64,
+    ) {
+        let Self::Background(owner) = self else {
+            if let Some(state) = app.try_state::<AppState>() {
+                if let Ok(mut runtime) = state.runtime.lock() {
+                    runtime.handshake_latency.record(handshake_ms);
+                    runtime.stream_remote_addr = Some(remote_addr);
+                    runtime.last_connected_at = Some(now);
+                    runtime.last_stream_event_at = Some(now);
+                    runtime.last_error = None;
+                    runtime.backoff_seconds = 0;
+                }
+            }
+            update_connection_state(app, "Connected");
+            publish_runtime_snapshot(app);
+            crate::server_info::refresh_in_background(app);
+            return;
+        };
+        owner.connected(app, target, now).await;
+    }
+
+    /// Fetches what arrived while the socket was down, without holding up the
+    /// new session.
+    fn spawn_catch_up(&self, app: &AppHandle, target: &StreamTarget) {
+        let app = app.clone();
+        let target = target.clone();
+        match self {
+            Self::Active => {
+                tauri::async_runtime::spawn(async move {
+                    if let Err(error) = messages::catch_up_after_reconnect(&app, &target).await {
+                        debug_log(&format!("reconnect catch-up failed: {error}"));
+                    }
+                });
+            }
+            Self::Background(owner) => {
+                let owner = owner.clone();
+                tauri::async_runtime::spawn(async move {
+                    owner.catch_up(&app, &target).await;
+                });
+            }
+        }
+    }
+
+    async fn text_frame(&mut self, app: &AppHandle, target: &StreamTarget, text: &str) {
+        let Some(wire_message) = messages::parse_stream_message_wire(text) else {
+            debug_log(&format!(
+                "ws text parse miss: {}",
+                truncate_message(text, 140)
+            ));
+            return;
+        };
+        let Self::Background(owner) = self else {
+            crate::diagnostics::record_server_message_date(app, &wire_message.date);
+            if !messages::has_app_meta(app, wire_message.appid) {
+                if let Err(error) =
+                    messages::fetch_applications(app, &target.base_url, &target.token).await
+                {
+                    debug_log(&format!(
+                        "failed to refresh applications for app_id={}: {error}",
+                        wire_message.appid
+                    ));
+                }
+            }
+            let msg = messages::convert_wire_message(app, wire_message);
+            let _ = messages::cache_and_emit_message(app, msg, true);
+            return;
+        };
+        owner.deliver(app, wire_message);
+    }
+}
+
+pub(crate) type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub(crate) struct OpenedStream {
+    pub(crate) ws_stream: WsStream,
+    pub(crate) remote_addr: String,
+    /// TCP connect through websocket upgrade.
+    pub(crate) handshake_ms: u64,
+    /// Hold for as long as `ws_stream` is in use.
+    pub(crate) socket: OpenSocket,
+}
+
+static OPEN_SOCKETS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a websocket as open until dropped, so quitting can wait for every
+/// stream to finish its close handshake.
+pub(crate) struct OpenSocket(());
+
+impl OpenSocket {
+    fn new() -> Self {
+        OPEN_SOCKETS.fetch_add(1, Ordering::SeqCst);
+        Self(())
+    }
+}
+
+impl Drop for OpenSocket {
+    fn drop(&mut self) {
+        OPEN_SOCKETS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub(crate) fn open_sockets() -> usize {
+    OPEN_SOCKETS.load(Ordering::SeqCst)
+}
+
+/// Sends a Close frame and waits briefly for the server's, so the server ends
+/// the session instead of holding it half open until its own timeout.
+pub(crate) async fn close_websocket(ws_stream: &mut WsStream) {
+    if ws_stream.close(None).await.is_err() {
+        return;
+    }
+    let drained = tokio::time::timeout(Duration::from_millis(STREAM_CLOSE_ACK_TIMEOUT_MS), async {
+        while let Some(Ok(_)) = ws_stream.next().await {}
+    })
+    .await;
+    if drained.is_err() {
+        debug_log("ws close: no close ack from server");
+    }
+}
+
+/// `status` is set when the server answered the upgrade with an HTTP error,
+/// so callers can tell a rejected token from a network failure.
+pub(crate) struct StreamConnectError {
+    pub(crate) message: String,
+    pub(crate) status: Option<u16>,
+}
+
+impl From<String> for StreamConnectError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            status: None,
+        }
+    }
+}
+
+/// Opens the websocket for `target` through the configured proxy, CA and
+/// custom headers. Used by the active server's stream and by background streams.
+pub(crate) async fn open_websocket(
+    app: &AppHandle,
+    target: &StreamTarget,
+) -> Result<OpenedStream, StreamConnectError> {
+    let mut ws_url = build_stream_ws_url(&target.base_url)?;
+    if target.token_in_query {
+        let mut with_token =
+            reqwest::Url::parse(&ws_url).map_err(|error| format!("Invalid stream URL: {error}"))?;
+        with_token
+            .query_pairs_mut()
+            .append_pair("token", target.token.trim());
+        ws_url = with_token.to_string();
+    }
+    // Only the redacted form may be logged; the URL can carry the token.
     debug_log(&format!("ws connect {}", redact_ws_url(&ws_url)));
     let mut ws_request = ws_url
         .as_str()
         .into_client_request()
         .map_err(|error| format!("Failed to build websocket request: {error}"))?;
-    let token_header = HeaderValue::from_str(token.trim())
-        .map_err(|error| format!("Invalid token for websocket header: {error}"))?;
+    if !target.token_in_query {
+        let token_header = HeaderValue::from_str(target.token.trim())
+            .map_err(|error| format!("Invalid token for websocket header: {error}"))?;
+        ws_request
+            .headers_mut()
+            .insert("X-Gotify-Key", token_header);
+    }
     ws_request
         .headers_mut()
-        .insert("X-Gotify-Key", token_header);
-    let (mut ws_stream, _) = tokio::time::timeout(
-        std::time::Duration::from_secs(STREAM_CONNECT_TIMEOUT_SECS),
-        connect_async(ws_request),
+        .extend(crate::settings::configured_custom_headers(app));
+    let connector = crate::tls::ws_connector(
+        crate::tls::configured_ca_cert_path(app).as_deref(),
+        &crate::tls::configured_trusted_certificates(app),
+    )?;
+    let parsed_url =
+        reqwest::Url::parse(&ws_url).map_err(|error| format!("Invalid stream URL: {error}"))?;
+    let host = parsed_url
+        .host_str()
+        .ok_or_else(|| "Stream URL has no host".to_string())?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = parsed_url
+        .port_or_known_default()
+        .ok_or_else(|| "Stream URL has no port".to_string())?;
+    let ip_family_preference = read_settings(app)
+        .map(|settings| settings.ip_family_preference)
+        .unwrap_or_default();
+    let pin = target.host_pins.get(&host.to_ascii_lowercase()).copied();
+    let dial_host = match &target.zone {
+        Some(zone) => format!("{host}%{zone}"),
+        None => host.clone(),
+    };
+    op_trace::emit(
+        app,
+        TraceOp::StreamConnect,
+        &host,
+        "start",
+        &format!(
+            "port={port} family={ip_family_preference:?} pinned={}",
+            pin.is_some()
+        ),
+        None,
+    );
+    let proxy = crate::proxy::configured_proxy(app).filter(|proxy| !proxy.bypasses(&host));
+    let connect = async {
+        let (tcp_stream, remote_addr) = match &proxy {
+            Some(proxy) => {
+                let (stream, proxy_addr) = proxy.connect(&host, port, ip_family_preference).await?;
+                (stream, format!("{proxy_addr} (proxy {})", proxy.display()))
+            }
+            None => {
+                let (stream, addr) =
+                    crate::dial::connect_tcp(&dial_host, port, ip_family_preference, pin).await?;
+                (stream, addr.to_string())
+            }
+        };
+        debug_log(&format!("ws tcp connected to {remote_addr}"));
+        op_trace::emit(
+            app,
+            TraceOp::StreamConnect,
+            &host,
+            "tcp",
+            &format!("connected to {remote_addr}"),
+            None,
+        );
+        client_async_tls_with_config(ws_request, tcp_stream, None, connector)
+            .await
+            .map(|(ws_stream, response)| (ws_stream, response, remote_addr))
+            .map_err(|error| match &error {
+                tokio_tungstenite::tungstenite::Error::Http(response) => StreamConnectError {
+                    message: error.to_string(),
+                    status: Some(response.status().as_u16()),
+                },
+                _ => StreamConnectError::from(error.to_string()),
+            })
+    };
+    let connect_timeout_secs =
+        crate::settings::configured_request_timeouts(app).stream_connect_secs;
+    let connect_started = std::time::Instant::now();
+    let connected = tokio::time::timeout(
+        std::time::Duration::from_secs(connect_timeout_secs),
+        connect,
     )
     .await
     .map_err(|_| {
-        format!(
-            "Stream connection timed out after {} seconds",
-            STREAM_CONNECT_TIMEOUT_SECS
-        )
-    })?
-    .map_err(|error| format!("Stream connection failed: {error}"))?;
-
-    debug_log("ws connected");
-    let now = unix_now_secs();
-    if let Some(state) = app.try_state::<AppState>() {
-        if let Ok(mut runtime) = state.runtime.lock() {
-            runtime.last_connected_at = Some(now);
-            runtime.last_stream_event_at = Some(now);
-            runtime.last_error = None;
-            runtime.backoff_seconds = 0;
+        StreamConnectError::from(format!(
+            "Stream connection timed out after {connect_timeout_secs} seconds"
+        ))
+    })
+    .and_then(|result| {
+        result.map_err(|error| StreamConnectError {
+            message: format!("Stream connection failed: {}", error.message),
+            status: error.status,
+        })
+    });
+    let (ws_stream, response, remote_addr) = match connected {
+        Ok(connected) => connected,
+        Err(error) => {
+            op_trace::emit(
+                app,
+                TraceOp::StreamConnect,
+                &host,
+                "error",
+                &error.message,
+                None,
+            );
+            return Err(error);
         }
+    };
+    op_trace::emit(
+        app,
+        TraceOp::StreamConnect,
+        &host,
+        "connected",
+        "websocket upgrade complete",
+        Some(response.status().as_u16()),
+    );
+
+    Ok(OpenedStream {
+        ws_stream,
+        remote_addr,
+        handshake_ms: elapsed_ms(connect_started),
+        socket: OpenSocket::new(),
+    })
+}
+
+/// One websocket session for `owner`, until the socket fails or the stream is
+/// stopped. A reconnect (`catch_up`) first fetches what the old socket missed.
+pub(crate) async fn stream_once(
+    app: &AppHandle,
+    owner: &mut StreamOwner,
+    target: &StreamTarget,
+    stop_rx: &mut watch::Receiver<bool>,
+    catch_up: bool,
+) -> Result<(), StreamConnectError> {
+    let opened = match open_websocket(app, target).await {
+        Ok(opened) => opened,
+        Err(error) => {
+            if let Some(status) = error
+                .status
+                .filter(|status| owner.is_active() && crate::auth::is_auth_failure(*status))
+            {
+                crate::auth::report_auth_failure(app, "stream", status);
+            }
+            return Err(error);
+        }
+    };
+    let OpenedStream {
+        mut ws_stream,
+        remote_addr,
+        handshake_ms,
+        socket: _socket,
+    } = opened;
+    debug_log(&format!("ws connected in {handshake_ms}ms"));
+    owner.record(
+        app,
+        ConnectionEventKind::Connected,
+        Some(format!("{remote_addr} in {handshake_ms}ms")),
+    );
+    let now = unix_now_secs();
+    owner
+        .connected(app, target, remote_addr, handshake_ms, now)
+        .await;
+    if catch_up {
+        owner.spawn_catch_up(app, target);
     }
-    update_connection_state(app, "Connected");
-    publish_runtime_snapshot(app);
+    // Periodic sync, heartbeats, RTT pings and fault injection belong to the
+    // active server only.
+    let active = owner.is_active();
     let mut sync_interval =
         tokio::time::interval(std::time::Duration::from_secs(STREAM_SYNC_INTERVAL_SECS));
     sync_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
@@ -259,14 +919,30 @@ async fn stream_once(
     ));
     liveness_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
     liveness_interval.tick().await;
+    let mut heartbeat_interval =
+        tokio::time::interval(std::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+    heartbeat_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut rtt_interval = tokio::time::interval(std::time::Duration::from_secs(
+        STREAM_RTT_PING_INTERVAL_SECS,
+    ));
+    rtt_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
     let mut last_activity_at = now;
     let mut pending_ping_since: Option<u64> = None;
+    // Sequence number carried in the RTT ping payload, and when it was sent.
+    let mut rtt_ping_seq: u64 = 0;
+    let mut pending_rtt_ping: Option<(u64, std::time::Instant)> = None;
+    let fault_drop = crate::faults::stream_drop_timer();
+    tokio::pin!(fault_drop);
 
     loop {
         tokio::select! {
+            _ = &mut fault_drop, if active => {
+                let _ = ws_stream.close(None).await;
+                return Err(StreamConnectError::from("Stream dropped by fault injection".to_string()));
+            }
             _ = stop_rx.changed() => {
                 if *stop_rx.borrow() {
-                    let _ = ws_stream.close(None).await;
+                    close_websocket(&mut ws_stream).await;
                     return Ok(());
                 }
             }
@@ -276,64 +952,82 @@ async fn stream_once(
                         let event_now = unix_now_secs();
                         last_activity_at = event_now;
                         pending_ping_since = None;
-                        mark_stream_activity(app, event_now, "ws-text");
+                        owner.mark_activity(app, event_now, "ws-text");
                         debug_log(&format!("ws text frame bytes={}", text.len()));
-                        if let Some(wire_message) = messages::parse_stream_message_wire(text.as_ref()) {
-                            if !messages::has_app_meta(app, wire_message.appid) {
-                                if let Err(error) = messages::fetch_applications(app, base_url, token).await {
-                                    debug_log(&format!(
-                                        "failed to refresh applications for app_id={}: {error}",
-                                        wire_message.appid
-                                    ));
-                                }
-                            }
-                            let msg = messages::convert_wire_message(app, wire_message);
-                            let _ = messages::cache_and_emit_message(app, msg, true);
-                        } else {
-                            debug_log(&format!("ws text parse miss: {}", truncate_message(text.as_ref(), 140)));
-                        }
+                        owner.text_frame(app, target, text.as_ref()).await;
                     }
                     Some(Ok(Message::Ping(payload))) => {
                         let event_now = unix_now_secs();
                         last_activity_at = event_now;
                         pending_ping_since = None;
-                        mark_stream_activity(app, event_now, "ws-ping");
+                        owner.mark_activity(app, event_now, "ws-ping");
                         ws_stream.send(Message::Pong(payload)).await
-                            .map_err(|error| format!("Failed to send pong: {error}"))?;
+                            .map_err(|error| StreamConnectError::from(format!("Failed to send pong: {error}")))?;
                     }
-                    Some(Ok(Message::Pong(_))) => {
+                    Some(Ok(Message::Pong(payload))) => {
                         let event_now = unix_now_secs();
                         last_activity_at = event_now;
                         pending_ping_since = None;
-                        mark_stream_activity(app, event_now, "ws-pong");
+                        if let Some((seq, sent_at)) = pending_rtt_ping {
+                            if payload.as_ref() == seq.to_be_bytes() {
+                                pending_rtt_ping = None;
+                                let rtt_ms = elapsed_ms(sent_at);
+                                if let Ok(mut runtime) = app.state::<AppState>().runtime.lock() {
+                                    runtime.ping_rtt.record(rtt_ms);
+                                }
+                            }
+                        }
+                        owner.mark_activity(app, event_now, "ws-pong");
                     }
                     Some(Ok(Message::Close(_))) => {
-                        return Err("Stream closed by server".to_string());
+                        return Err(StreamConnectError::from("Stream closed by server".to_string()));
                     }
                     Some(Ok(_)) => {
                         let event_now = unix_now_secs();
                         last_activity_at = event_now;
                         pending_ping_since = None;
-                        mark_stream_activity(app, event_now, "ws-other");
+                        owner.mark_activity(app, event_now, "ws-other");
+                    }
+                    Some(Err(error)) => {
+                        return Err(StreamConnectError::from(format!("Stream read error: {error}")));
+                    }
+                    None => {
+                        return Err(StreamConnectError::from("Stream ended unexpectedly".to_string()));
                     }
-                    Some(Err(error)) => return Err(format!("Stream read error: {error}")),
-                    None => return Err("Stream ended unexpectedly".to_string()),
                 }
             }
-            _ = sync_interval.tick() => {
+            _ = sync_interval.tick(), if active => {
                 let app_for_sync = app.clone();
-                let base_for_sync = base_url.to_string();
-                let token_for_sync = token.to_string();
+                let base_for_sync = target.base_url.clone();
+                let token_for_sync = target.token.clone();
                 tauri::async_runtime::spawn(async move {
-                    if let Err(error) = messages::fetch_recent_messages(&app_for_sync, &base_for_sync, &token_for_sync).await {
+                    if let Err(error) = messages::fetch_recent_messages(&app_for_sync, &base_for_sync, &token_for_sync, messages::SyncMode::Periodic).await {
                         debug_log(&format!("periodic sync failed: {error}"));
                     }
                 });
             }
+            _ = rtt_interval.tick(), if active => {
+                // An unanswered ping is left to the liveness check; the next
+                // one simply replaces it.
+                rtt_ping_seq = rtt_ping_seq.wrapping_add(1);
+                ws_stream
+                    .send(Message::Ping(rtt_ping_seq.to_be_bytes().to_vec().into()))
+                    .await
+                    .map_err(|error| StreamConnectError::from(format!("Failed to send ping: {error}")))?;
+                pending_rtt_ping = Some((rtt_ping_seq, std::time::Instant::now()));
+            }
+            _ = heartbeat_interval.tick(), if active => {
+                let app_for_heartbeat = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    crate::heartbeat::send_heartbeat(&app_for_heartbeat).await;
+                });
+            }
             _ = liveness_interval.tick() => {
                 let event_now = unix_now_secs();
                 if event_now.saturating_sub(last_activity_at) < STREAM_LIVENESS_IDLE_SECS {
-                    publish_runtime_snapshot(app);
+                    if active {
+                        publish_runtime_snapshot(app);
+                    }
                     continue;
                 }
                 match pending_ping_since {
@@ -342,25 +1036,101 @@ async fn stream_once(
                         ws_stream
                             .send(Message::Ping(Vec::<u8>::new().into()))
                             .await
-                            .map_err(|error| format!("Failed to send liveness ping: {error}"))?;
+                            .map_err(|error| StreamConnectError::from(format!("Failed to send liveness ping: {error}")))?;
                         pending_ping_since = Some(event_now);
                     }
                     Some(started) => {
                         if event_now.saturating_sub(started) >= STREAM_LIVENESS_PING_GRACE_SECS {
-                            return Err(format!(
+                            owner.record(
+                                app,
+                                ConnectionEventKind::LivenessTimeout,
+                                Some(format!("no pong after {}s", event_now.saturating_sub(started))),
+                            );
+                            return Err(StreamConnectError::from(format!(
                                 "Stream liveness timeout after {}s idle",
                                 event_now.saturating_sub(last_activity_at)
-                            ));
+                            )));
                         }
                     }
                 }
+                if active {
+                    publish_runtime_snapshot(app);
+                }
+            }
+        }
+    }
+}
+
+fn elapsed_ms(since: std::time::Instant) -> u64 {
+    u64::try_from(since.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Polling stand-in for `stream_once`. Reports `Polling` once the first poll
+/// succeeds; with `retry_websocket_after` it ends cleanly after that many
+/// seconds so auto mode can try the websocket again.
+async fn poll_session(
+    app: &AppHandle,
+    base_url: &str,
+    token: &str,
+    stop_rx: &mut watch::Receiver<bool>,
+    retry_websocket_after: Option<u64>,
+) -> Result<(), String> {
+    let started_at = unix_now_secs();
+    let mut reported = false;
+    let mut poll_interval =
+        tokio::time::interval(std::time::Duration::from_secs(STREAM_POLL_INTERVAL_SECS));
+    poll_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut heartbeat_interval =
+        tokio::time::interval(std::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+    heartbeat_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => {
+                if *stop_rx.borrow() {
+                    return Ok(());
+                }
+            }
+            _ = poll_interval.tick() => {
+                let delivered = messages::poll_new_messages(app, base_url, token).await?;
+                let now = unix_now_secs();
+                if !reported {
+                    reported = true;
+                    debug_log("polling for messages");
+                    if let Ok(mut runtime) = app.state::<AppState>().runtime.lock() {
+                        runtime.last_connected_at = Some(now);
+                        runtime.last_error = None;
+                        runtime.backoff_seconds = 0;
+                        runtime.stream_remote_addr = None;
+                    }
+                    connection_history::record(app, ConnectionEventKind::Polling, None);
+                    update_connection_state(app, CONNECTION_STATE_POLLING);
+                    crate::server_info::refresh_in_background(app);
+                }
+                mark_stream_activity(app, now, "poll");
+                if delivered > 0 {
+                    debug_log(&format!("poll delivered {delivered} message(s)"));
+                }
+                // The full sync picks up deletions and edits the poll skips.
+                if let Err(error) = messages::fetch_recent_messages(app, base_url, token, messages::SyncMode::Periodic).await {
+                    debug_log(&format!("periodic sync failed: {error}"));
+                }
                 publish_runtime_snapshot(app);
+                if retry_websocket_after.is_some_and(|secs| now.saturating_sub(started_at) >= secs) {
+                    return Ok(());
+                }
+            }
+            _ = heartbeat_interval.tick() => {
+                let app_for_heartbeat = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    crate::heartbeat::send_heartbeat(&app_for_heartbeat).await;
+                });
             }
         }
     }
 }
 
-fn update_connection_state(app: &AppHandle, status: &str) {
+pub(crate) fn update_connection_state(app: &AppHandle, status: &str) {
     if let Some(state) = app.try_state::<AppState>() {
         if let Ok(mut runtime) = state.runtime.lock() {
             runtime.connection_state = status.to_string();
@@ -368,7 +1138,33 @@ fn update_connection_state(app: &AppHandle, status: &str) {
     }
 
     let _ = crate::contract::publish_connection_update(app, status.to_string());
+    refresh_tray_status(app);
+}
+
+/// Tray icon for the active server, shown as degraded while it is healthy but
+/// a background stream is not.
+pub(crate) fn refresh_tray_status(app: &AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let Ok(status) = state.runtime.lock().map(|runtime| {
+        let background_down = runtime
+            .background_streams()
+            .iter()
+            .any(|stream| stream.connection_state != "Connected");
+        let foreground_up = matches!(
+            runtime.connection_state.as_str(),
+            "Connected" | CONNECTION_STATE_POLLING
+        );
+        if foreground_up && background_down {
+            "Backoff".to_string()
+        } else {
+            runtime.connection_state.clone()
+        }
+    }) else {
+        return;
+    };
     if let Some(tray) = app.tray_by_id("main-tray") {
-        let _ = tray.set_icon(crate::ui_shell::tray_icon_for_status(status));
+        let _ = tray.set_icon(crate::ui_shell::tray_icon_for_status(&status));
     }
 }
@@ -0,0 +1,293 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use rustls::{
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        WebPkiServerVerifier,
+    },
+    pki_types::{pem::PemObject, CertificateDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime};
+use tokio_tungstenite::Connector;
+
+use crate::{
+    debug_log,
+    settings::{load_settings, read_settings, save_non_secret_settings, SettingsResponse},
+    AppState,
+};
+
+/// Reads every certificate from a PEM file (a single CA or a bundle).
+pub(crate) fn load_ca_certificates(path: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let pem =
+        fs::read(path).map_err(|error| format!("Failed to read CA certificate {path}: {error}"))?;
+    let certs = CertificateDer::pem_slice_iter(&pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| format!("Failed to parse CA certificate {path}: {error}"))?;
+    if certs.is_empty() {
+        return Err(format!("No PEM certificates found in {path}"));
+    }
+    Ok(certs)
+}
+
+pub(crate) fn configured_ca_cert_path<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    read_settings(app)
+        .ok()
+        .and_then(|settings| settings.ca_cert_path)
+}
+
+pub(crate) fn configured_trusted_certificates<R: Runtime>(
+    app: &AppHandle<R>,
+) -> BTreeMap<String, String> {
+    read_settings(app)
+        .map(|settings| settings.trusted_certificates)
+        .unwrap_or_default()
+}
+
+/// Client builder sharing the stream's TLS verifier (bundled roots, the configured
+/// CA, and certificates the user trusted on first use).
+pub(crate) fn http_client_builder(
+    ca_cert_path: Option<&str>,
+    trusted_certificates: &BTreeMap<String, String>,
+) -> Result<reqwest::ClientBuilder, String> {
+    let config = client_config(ca_cert_path, trusted_certificates)?;
+    Ok(reqwest::Client::builder().use_preconfigured_tls((*config).clone()))
+}
+
+/// Websocket TLS connector using the same verifier as the REST client.
+pub(crate) fn ws_connector(
+    ca_cert_path: Option<&str>,
+    trusted_certificates: &BTreeMap<String, String>,
+) -> Result<Option<Connector>, String> {
+    Ok(Some(Connector::Rustls(client_config(
+        ca_cert_path,
+        trusted_certificates,
+    )?)))
+}
+
+fn client_config(
+    ca_cert_path: Option<&str>,
+    trusted_certificates: &BTreeMap<String, String>,
+) -> Result<Arc<ClientConfig>, String> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Some(path) = ca_cert_path {
+        for cert in load_ca_certificates(path)? {
+            roots
+                .add(cert)
+                .map_err(|error| format!("Invalid CA certificate in {path}: {error}"))?;
+        }
+    }
+    let webpki = WebPkiServerVerifier::builder(Arc::new(roots))
+        .build()
+        .map_err(|error| format!("Failed to build certificate verifier: {error}"))?;
+    let verifier = TofuVerifier {
+        webpki,
+        trusted: trusted_certificates.clone(),
+    };
+    let config = ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Normal webpki validation first; if that rejects the chain, the leaf is accepted
+/// only when its SHA-256 fingerprint is the one the user trusted for this host.
+/// Rejected leaves are remembered so the UI can ask whether to trust them.
+#[derive(Debug)]
+struct TofuVerifier {
+    webpki: Arc<WebPkiServerVerifier>,
+    trusted: BTreeMap<String, String>,
+}
+
+impl ServerCertVerifier for TofuVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let error = match self.webpki.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        ) {
+            Ok(verified) => return Ok(verified),
+            Err(error @ rustls::Error::InvalidCertificate(_)) => error,
+            Err(error) => return Err(error),
+        };
+        let host = server_name.to_str().to_ascii_lowercase();
+        let fingerprint = certificate_fingerprint(end_entity);
+        if self.trusted.get(&host) == Some(&fingerprint) {
+            return Ok(ServerCertVerified::assertion());
+        }
+        debug_log(&format!(
+            "tls: untrusted certificate for {host} ({error}), sha256={fingerprint}"
+        ));
+        if let Ok(mut presented) = presented_certificates().lock() {
+            let changed = presented
+                .get(&host)
+                .is_none_or(|entry| entry.fingerprint_sha256 != fingerprint);
+            if changed {
+                presented.insert(
+                    host.clone(),
+                    PresentedCertificate {
+                        fingerprint_sha256: fingerprint,
+                        reason: error.to_string(),
+                        announced: false,
+                    },
+                );
+            }
+        }
+        Err(error)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.webpki.supported_verify_schemes()
+    }
+}
+
+/// Uppercase, colon-separated SHA-256 of the DER certificate (as browsers show it).
+fn certificate_fingerprint(cert: &CertificateDer<'_>) -> String {
+    Sha256::digest(cert.as_ref())
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+struct PresentedCertificate {
+    fingerprint_sha256: String,
+    reason: String,
+    announced: bool,
+}
+
+static PRESENTED_CERTIFICATES: OnceLock<Mutex<HashMap<String, PresentedCertificate>>> =
+    OnceLock::new();
+
+fn presented_certificates() -> &'static Mutex<HashMap<String, PresentedCertificate>> {
+    PRESENTED_CERTIFICATES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CertificateTrustRequest {
+    pub(crate) host: String,
+    pub(crate) fingerprint_sha256: String,
+    pub(crate) reason: String,
+}
+
+/// Emits `certificate-trust-requested` once per newly rejected certificate.
+pub(crate) fn announce_untrusted_certificates(app: &AppHandle) {
+    let requests: Vec<CertificateTrustRequest> = {
+        let Ok(mut presented) = presented_certificates().lock() else {
+            return;
+        };
+        presented
+            .iter_mut()
+            .filter(|(_, entry)| !entry.announced)
+            .map(|(host, entry)| {
+                entry.announced = true;
+                CertificateTrustRequest {
+                    host: host.clone(),
+                    fingerprint_sha256: entry.fingerprint_sha256.clone(),
+                    reason: entry.reason.clone(),
+                }
+            })
+            .collect()
+    };
+    for request in requests {
+        crate::contract::emit_event(app, "main", "certificate-trust-requested", request);
+    }
+}
+
+/// Persists a fingerprint the server actually presented, then reconnects with it.
+pub(crate) fn trust_certificate(
+    app: &AppHandle,
+    host: &str,
+    fingerprint_sha256: &str,
+) -> Result<SettingsResponse, String> {
+    let host = host.trim().to_ascii_lowercase();
+    let fingerprint = fingerprint_sha256.trim().to_ascii_uppercase();
+    let presented = presented_certificates()
+        .lock()
+        .map_err(|_| "Certificate lock poisoned".to_string())?
+        .get(&host)
+        .map(|entry| entry.fingerprint_sha256.clone());
+    if presented.as_deref() != Some(fingerprint.as_str()) {
+        return Err(format!(
+            "{host} has not presented a certificate with fingerprint {fingerprint}"
+        ));
+    }
+    update_trusted_certificates(app, |trusted| {
+        trusted.insert(host.clone(), fingerprint.clone());
+    })?;
+    if let Ok(mut presented) = presented_certificates().lock() {
+        presented.remove(&host);
+    }
+    debug_log(&format!(
+        "tls: trusted certificate for {host} sha256={fingerprint}"
+    ));
+    restart_stream_after_trust_change(app);
+    load_settings(app)
+}
+
+pub(crate) fn untrust_certificate(app: &AppHandle, host: &str) -> Result<SettingsResponse, String> {
+    let host = host.trim().to_ascii_lowercase();
+    update_trusted_certificates(app, |trusted| {
+        trusted.remove(&host);
+    })?;
+    debug_log(&format!("tls: removed trusted certificate for {host}"));
+    restart_stream_after_trust_change(app);
+    load_settings(app)
+}
+
+fn update_trusted_certificates(
+    app: &AppHandle,
+    update: impl FnOnce(&mut BTreeMap<String, String>),
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _settings_guard = state
+        .settings_lock
+        .lock()
+        .map_err(|_| "Settings lock poisoned".to_string())?;
+    let mut settings = read_settings(app)?;
+    update(&mut settings.trusted_certificates);
+    save_non_secret_settings(app, &settings)
+}
+
+fn restart_stream_after_trust_change(app: &AppHandle) {
+    if let Err(error) = crate::stream::restart_stream(app.clone()) {
+        debug_log(&format!(
+            "failed to restart stream after certificate trust change: {error}"
+        ));
+    }
+}
@@ -0,0 +1,287 @@
+use std::{collections::HashSet, fs, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    contract::{self, DomainSnapshot},
+    debug_log,
+    op_trace::{self, TraceOp},
+    settings::{load_token, read_settings},
+    unix_now_secs, AppState, CachedMessage, TRASH_LABEL, TRASH_PURGE_INTERVAL_SECS,
+};
+
+/// A message the user deleted. It stays on the server (so it can be restored)
+/// until the retention period ends, then it is deleted there for real.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TrashedMessage {
+    pub(crate) message: CachedMessage,
+    pub(crate) trashed_at: u64,
+}
+
+/// Trash file that belongs to the active message cache (`messages.json` ->
+/// `trash.json`, `messages-<hash>.json` -> `trash-<hash>.json`).
+fn trash_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let messages_path = crate::messages_file(app)?;
+    let name = messages_path
+        .file_name()
+        .map(|name| name.to_string_lossy().replacen("messages", "trash", 1))
+        .ok_or_else(|| "Message cache path has no file name".to_string())?;
+    Ok(messages_path.with_file_name(name))
+}
+
+pub(crate) fn load_trash_from_disk(app: &AppHandle) -> Vec<TrashedMessage> {
+    let Ok(path) = trash_file(app) else {
+        return Vec::new();
+    };
+    if !path.exists() {
+        return Vec::new();
+    }
+    match crate::at_rest::read_to_string(TRASH_LABEL, &path).and_then(|content| {
+        serde_json::from_str::<Vec<TrashedMessage>>(&content).map_err(|error| error.to_string())
+    }) {
+        Ok(mut trash) => {
+            for entry in trash.iter_mut() {
+                entry.message.migrate_extras();
+            }
+            trash
+        }
+        Err(error) => {
+            debug_log(&format!(
+                "trash: failed to load {}: {error}",
+                path.display()
+            ));
+            Vec::new()
+        }
+    }
+}
+
+/// Swaps in the trash of the active profile; used at startup and on profile switch.
+pub(crate) fn replace_trash(app: &AppHandle, trash: Vec<TrashedMessage>) {
+    if let Ok(mut current) = app.state::<AppState>().trash.lock() {
+        *current = trash.clone();
+    }
+    contract::publish_trash_update(app, trash);
+}
+
+fn persist_trash(app: &AppHandle, trash: Vec<TrashedMessage>) {
+    let Ok(path) = trash_file(app) else {
+        return;
+    };
+    crate::work::run_in_background("persist-trash", move || {
+        let result = serde_json::to_string(&trash)
+            .map_err(|error| error.to_string())
+            .and_then(|content| crate::at_rest::seal(TRASH_LABEL, content.as_bytes()))
+            .and_then(|sealed| {
+                let tmp_path = path.with_extension(format!("tmp-{}", crate::unique_time_suffix()));
+                fs::write(&tmp_path, sealed).map_err(|error| error.to_string())?;
+                crate::restrict_file_permissions(&tmp_path);
+                fs::rename(&tmp_path, &path).map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            debug_log(&format!(
+                "trash: failed to write {}: {error}",
+                path.display()
+            ));
+        }
+    });
+}
+
+/// Applies `change` to the trash, then persists and publishes the result.
+fn update_trash(
+    app: &AppHandle,
+    change: impl FnOnce(&mut Vec<TrashedMessage>) -> Result<(), String>,
+) -> Result<DomainSnapshot<Vec<TrashedMessage>>, String> {
+    let snapshot = {
+        let state = app.state::<AppState>();
+        let mut trash = state
+            .trash
+            .lock()
+            .map_err(|_| "Trash lock poisoned".to_string())?;
+        change(&mut trash)?;
+        trash.clone()
+    };
+    persist_trash(app, snapshot.clone());
+    Ok(contract::publish_trash_update(app, snapshot))
+}
+
+pub(crate) fn list(app: &AppHandle) -> Result<Vec<TrashedMessage>, String> {
+    app.state::<AppState>()
+        .trash
+        .lock()
+        .map(|trash| trash.clone())
+        .map_err(|_| "Trash lock poisoned".to_string())
+}
+
+/// Ids syncs must not bring back into the cache while they sit in the trash.
+pub(crate) fn trashed_ids(app: &AppHandle) -> HashSet<i64> {
+    app.try_state::<AppState>()
+        .and_then(|state| {
+            state
+                .trash
+                .lock()
+                .ok()
+                .map(|trash| trash.iter().map(|entry| entry.message.id).collect())
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn retention_days(app: &AppHandle) -> u64 {
+    read_settings(app)
+        .map(|settings| settings.trash_retention_days)
+        .unwrap_or(0)
+}
+
+/// Moves a cached message to the trash. Returns `false` when the message is not
+/// cached, in which case the caller deletes it on the server directly.
+pub(crate) fn move_to_trash(app: &AppHandle, message_id: i64) -> Result<bool, String> {
+    let Some(message) = app
+        .state::<AppState>()
+        .messages
+        .lock()
+        .map_err(|_| "Message cache lock poisoned".to_string())?
+        .iter()
+        .find(|message| message.id == message_id)
+        .cloned()
+    else {
+        return Ok(false);
+    };
+    update_trash(app, |trash| {
+        trash.retain(|entry| entry.message.id != message_id);
+        trash.insert(
+            0,
+            TrashedMessage {
+                message,
+                trashed_at: unix_now_secs(),
+            },
+        );
+        Ok(())
+    })?;
+    crate::messages::remove_message_from_cache(app, message_id)?;
+    op_trace::emit(
+        app,
+        TraceOp::Delete,
+        message_id,
+        "trash",
+        "moved to trash",
+        None,
+    );
+    Ok(true)
+}
+
+pub(crate) fn restore(
+    app: &AppHandle,
+    message_id: i64,
+) -> Result<DomainSnapshot<Vec<TrashedMessage>>, String> {
+    let mut restored = None;
+    let snapshot = update_trash(app, |trash| {
+        let position = trash
+            .iter()
+            .position(|entry| entry.message.id == message_id)
+            .ok_or_else(|| format!("Message {message_id} is not in the trash"))?;
+        restored = Some(trash.remove(position).message);
+        Ok(())
+    })?;
+    if let Some(message) = restored {
+        crate::messages::restore_message(app, message)?;
+    }
+    debug_log(&format!("trash: restored id={message_id}"));
+    Ok(snapshot)
+}
+
+/// Deletes every trashed message on the server now. Entries whose delete fails
+/// stay in the trash and are retried by the next purge.
+pub(crate) async fn empty(app: &AppHandle) -> Result<DomainSnapshot<Vec<TrashedMessage>>, String> {
+    purge_where(app, |_| true).await
+}
+
+async fn purge_expired(app: &AppHandle) -> Result<(), String> {
+    let retention_secs = retention_days(app).saturating_mul(24 * 60 * 60);
+    let now = unix_now_secs();
+    // Retention 0 means the trash is off; anything left from before still expires.
+    purge_where(app, |entry| {
+        entry.trashed_at.saturating_add(retention_secs) <= now
+    })
+    .await
+    .map(|_| ())
+}
+
+async fn purge_where(
+    app: &AppHandle,
+    predicate: impl Fn(&TrashedMessage) -> bool,
+) -> Result<DomainSnapshot<Vec<TrashedMessage>>, String> {
+    let due: Vec<i64> = list(app)?
+        .iter()
+        .filter(|entry| predicate(entry))
+        .map(|entry| entry.message.id)
+        .collect();
+    if due.is_empty() {
+        return Ok(contract::snapshot_at_revision(
+            contract::current_revision(app, crate::RevisionKey::Trash),
+            list(app)?,
+        ));
+    }
+
+    let settings = read_settings(app)?;
+    let base_url = crate::endpoints::active_base_url(app, &settings)?;
+    let token =
+        load_token()?.ok_or_else(|| "No token found. Save token in settings first.".to_string())?;
+    let client = crate::settings::server_client_builder(app)?
+        .build()
+        .map_err(|error| format!("Failed to build HTTP client: {error}"))?;
+
+    let mut purged = Vec::new();
+    let mut last_error = None;
+    for message_id in due {
+        let request = client
+            .delete(format!("{base_url}/message/{message_id}"))
+            .header("X-Gotify-Key", &token);
+        let result = crate::retry::send(request)
+            .await
+            .map_err(|error| error.to_string())
+            .map(|response| response.status().as_u16());
+        match result {
+            // 404: already gone, e.g. deleted from another client.
+            Ok(status) if (200..300).contains(&status) || status == 404 => {
+                op_trace::emit(
+                    app,
+                    TraceOp::Delete,
+                    message_id,
+                    "purge",
+                    "deleted from trash on server",
+                    Some(status),
+                );
+                purged.push(message_id);
+            }
+            Ok(status) => last_error = Some(format!("HTTP {status}")),
+            Err(error) => last_error = Some(error),
+        }
+    }
+    debug_log(&format!(
+        "trash: purged {} message(s){}",
+        purged.len(),
+        last_error
+            .as_ref()
+            .map(|error| format!(", last error: {error}"))
+            .unwrap_or_default()
+    ));
+    let snapshot = update_trash(app, |trash| {
+        trash.retain(|entry| !purged.contains(&entry.message.id));
+        Ok(())
+    })?;
+    match last_error {
+        Some(error) if purged.is_empty() => Err(format!("Failed to empty trash: {error}")),
+        _ => Ok(snapshot),
+    }
+}
+
+pub(crate) fn watch_trash_purge(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(TRASH_PURGE_INTERVAL_SECS)).await;
+            if let Err(error) = purge_expired(&app).await {
+                debug_log(&format!("trash: scheduled purge failed: {error}"));
+            }
+        }
+    });
+}
@@ -137,7 +137,9 @@ pub(crate) fn handle_window_event<R: Runtime>(window: &tauri::Window<R>, event:
 
 pub(crate) fn tray_icon_for_status(status: &str) -> Option<Image<'static>> {
     let bytes = match status {
-        "Connected" => include_bytes!("../icons/tray-connected.png").as_slice(),
+        "Connected" | crate::CONNECTION_STATE_POLLING => {
+            include_bytes!("../icons/tray-connected.png").as_slice()
+        }
         "Connecting" => include_bytes!("../icons/tray-connecting.png").as_slice(),
         "Backoff" => include_bytes!("../icons/tray-backoff.png").as_slice(),
         _ => include_bytes!("../icons/tray-disconnected.png").as_slice(),
@@ -0,0 +1,334 @@
+use std::{fs, path::Path, sync::OnceLock};
+
+use block2::RcBlock;
+use objc2::{define_class, msg_send, rc::Retained, runtime::Bool, AnyThread, DefinedClass};
+use objc2_foundation::{
+    ns_string, NSArray, NSDictionary, NSError, NSNumber, NSObject, NSObjectProtocol, NSSet,
+    NSString, NSURL,
+};
+use objc2_user_notifications::{
+    UNAuthorizationOptions, UNMutableNotificationContent, UNNotification, UNNotificationAction,
+    UNNotificationActionOptions, UNNotificationAttachment, UNNotificationCategory,
+    UNNotificationCategoryOptions, UNNotificationDefaultActionIdentifier,
+    UNNotificationPresentationOptions, UNNotificationRequest, UNNotificationResponse,
+    UNNotificationSound, UNUserNotificationCenter, UNUserNotificationCenterDelegate,
+};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    contexts::{NOTIFICATION_SOUND_DEFAULT, NOTIFICATION_SOUND_NONE},
+    debug_log,
+    notifications::NotificationContent,
+    AppState, ACK_ACTION_LABEL, NOTIFICATION_MARK_READ_LABEL, NOTIFICATION_SNOOZE_LABEL,
+    NOTIFICATION_SNOOZE_MINUTES,
+};
+
+const MESSAGE_CATEGORY: &str = "gotify-message";
+const MESSAGE_ACK_CATEGORY: &str = "gotify-message-ack";
+const ACTION_MARK_READ: &str = "mark-read";
+const ACTION_SNOOZE: &str = "snooze";
+const ACTION_ACKNOWLEDGE: &str = "acknowledge";
+
+define_class!(
+    #[unsafe(super(NSObject))]
+    #[name = "GotifyNotificationDelegate"]
+    #[ivars = AppHandle]
+    struct NotificationDelegate;
+
+    unsafe impl NSObjectProtocol for NotificationDelegate {}
+
+    unsafe impl UNUserNotificationCenterDelegate for NotificationDelegate {
+        #[unsafe(method(userNotificationCenter:willPresentNotification:withCompletionHandler:))]
+        fn will_present(
+            &self,
+            _center: &UNUserNotificationCenter,
+            _notification: &UNNotification,
+            completion_handler: &block2::DynBlock<dyn Fn(UNNotificationPresentationOptions)>,
+        ) {
+            // Banners show while the app is frontmost too, like they did before.
+            completion_handler.call((UNNotificationPresentationOptions::Banner
+                | UNNotificationPresentationOptions::List
+                | UNNotificationPresentationOptions::Sound,));
+        }
+
+        #[unsafe(method(userNotificationCenter:didReceiveNotificationResponse:withCompletionHandler:))]
+        fn did_receive_response(
+            &self,
+            _center: &UNUserNotificationCenter,
+            response: &UNNotificationResponse,
+            completion_handler: &block2::DynBlock<dyn Fn()>,
+        ) {
+            let action = response.actionIdentifier().to_string();
+            match message_id_of(&response.notification()) {
+                Some(message_id) => handle_response(self.ivars(), message_id, &action),
+                None => debug_log(&format!("un notify response {action} without message id")),
+            }
+            completion_handler.call(());
+        }
+    }
+);
+
+impl NotificationDelegate {
+    fn new(app: AppHandle) -> Retained<Self> {
+        let this = Self::alloc().set_ivars(app);
+        unsafe { msg_send![super(this), init] }
+    }
+}
+
+/// The center keeps a weak reference to its delegate.
+static DELEGATE: OnceLock<Retained<NotificationDelegate>> = OnceLock::new();
+
+/// `UNUserNotificationCenter` needs a bundled app; `tauri dev` runs a bare
+/// binary, where asking for the center throws, so that keeps the old path.
+pub(crate) fn is_available() -> bool {
+    static BUNDLED: OnceLock<bool> = OnceLock::new();
+    *BUNDLED.get_or_init(|| {
+        std::env::current_exe()
+            .is_ok_and(|path| path.to_string_lossy().contains(".app/Contents/MacOS/"))
+    })
+}
+
+/// Installs the delegate, registers the action categories and asks for
+/// permission once. Does nothing when the app is not bundled.
+pub(crate) fn init(app: &AppHandle) {
+    if !is_available() || DELEGATE.get().is_some() {
+        return;
+    }
+    let center = UNUserNotificationCenter::currentNotificationCenter();
+    let delegate = DELEGATE.get_or_init(|| NotificationDelegate::new(app.clone()));
+    center.setDelegate(Some(objc2::runtime::ProtocolObject::from_ref(&**delegate)));
+    center.setNotificationCategories(&categories());
+
+    let completion =
+        RcBlock::new(
+            |granted: Bool, error: *mut NSError| match unsafe { error.as_ref() } {
+                Some(error) => debug_log(&format!(
+                    "un notify authorization failed: {}",
+                    error.localizedDescription()
+                )),
+                None => debug_log(&format!(
+                    "un notify authorization granted={}",
+                    granted.as_bool()
+                )),
+            },
+        );
+    center.requestAuthorizationWithOptions_completionHandler(
+        UNAuthorizationOptions::Alert
+            | UNAuthorizationOptions::Sound
+            | UNAuthorizationOptions::Badge,
+        &completion,
+    );
+}
+
+fn action(identifier: &str, title: &str) -> Retained<UNNotificationAction> {
+    UNNotificationAction::actionWithIdentifier_title_options(
+        &NSString::from_str(identifier),
+        &NSString::from_str(title),
+        UNNotificationActionOptions::empty(),
+    )
+}
+
+fn category(
+    identifier: &str,
+    actions: &[Retained<UNNotificationAction>],
+) -> Retained<UNNotificationCategory> {
+    UNNotificationCategory::categoryWithIdentifier_actions_intentIdentifiers_options(
+        &NSString::from_str(identifier),
+        &NSArray::from_retained_slice(actions),
+        &NSArray::new(),
+        UNNotificationCategoryOptions::empty(),
+    )
+}
+
+fn categories() -> Retained<NSSet<UNNotificationCategory>> {
+    let mark_read = action(ACTION_MARK_READ, NOTIFICATION_MARK_READ_LABEL);
+    let snooze = action(ACTION_SNOOZE, NOTIFICATION_SNOOZE_LABEL);
+    let acknowledge = action(ACTION_ACKNOWLEDGE, ACK_ACTION_LABEL);
+    NSSet::from_retained_slice(&[
+        category(MESSAGE_CATEGORY, &[mark_read.clone(), snooze.clone()]),
+        category(MESSAGE_ACK_CATEGORY, &[acknowledge, mark_read, snooze]),
+    ])
+}
+
+/// One notification per message: posting the same message again replaces its
+/// banner, and [`remove`] takes it out of Notification Center.
+fn request_identifier(message_id: i64) -> Retained<NSString> {
+    NSString::from_str(&format!("gotify-message-{message_id}"))
+}
+
+/// `userInfo` key holding the message id, so a response finds its message.
+fn message_id_key() -> &'static NSString {
+    ns_string!("messageId")
+}
+
+fn message_id_of(notification: &UNNotification) -> Option<i64> {
+    notification
+        .request()
+        .content()
+        .userInfo()
+        .objectForKey(message_id_key())
+        .and_then(|value| value.downcast::<NSNumber>().ok())
+        .map(|number| number.as_i64())
+}
+
+/// Posts a message notification. `actions` adds Mark Read and Snooze, and
+/// `ack_action` Acknowledge in front of them; both answer without opening the
+/// app and without keeping a thread waiting for the response.
+pub(crate) fn deliver(
+    message_id: i64,
+    app_id: i64,
+    content: &NotificationContent,
+    actions: bool,
+    ack_action: bool,
+) {
+    let notification = UNMutableNotificationContent::new();
+    notification.setTitle(&NSString::from_str(&content.title));
+    if !content.subtitle.is_empty() {
+        notification.setSubtitle(&NSString::from_str(&content.subtitle));
+    }
+    notification.setBody(&NSString::from_str(&content.body));
+    match content.sound.as_str() {
+        NOTIFICATION_SOUND_NONE => {}
+        NOTIFICATION_SOUND_DEFAULT => {
+            notification.setSound(Some(&*UNNotificationSound::defaultSound()));
+        }
+        name => {
+            notification.setSound(Some(&*UNNotificationSound::soundNamed(
+                &NSString::from_str(name),
+            )));
+        }
+    }
+    if ack_action {
+        notification.setCategoryIdentifier(&NSString::from_str(MESSAGE_ACK_CATEGORY));
+    } else if actions {
+        notification.setCategoryIdentifier(&NSString::from_str(MESSAGE_CATEGORY));
+    }
+    // Messages of one application stack together in Notification Center.
+    notification.setThreadIdentifier(&NSString::from_str(&format!("gotify-app-{app_id}")));
+    let user_info =
+        NSDictionary::from_retained_objects(&[message_id_key()], &[NSNumber::new_i64(message_id)]);
+    unsafe {
+        notification.setUserInfo(&Retained::cast_unchecked::<NSDictionary>(user_info));
+    }
+    if let Some(attachment) = content
+        .image_path
+        .as_deref()
+        .and_then(|path| image_attachment(message_id, Path::new(path)))
+    {
+        notification.setAttachments(&NSArray::from_retained_slice(&[attachment]));
+    }
+
+    let request = UNNotificationRequest::requestWithIdentifier_content_trigger(
+        &request_identifier(message_id),
+        &notification,
+        None,
+    );
+    let completion = RcBlock::new(move |error: *mut NSError| {
+        if let Some(error) = unsafe { error.as_ref() } {
+            debug_log(&format!(
+                "un notify id={message_id} failed: {}",
+                error.localizedDescription()
+            ));
+        }
+    });
+    UNUserNotificationCenter::currentNotificationCenter()
+        .addNotificationRequest_withCompletionHandler(&request, Some(&*completion));
+}
+
+/// Notification Center moves an attachment into its own store, so it gets a
+/// copy rather than the cached image.
+fn image_attachment(message_id: i64, path: &Path) -> Option<Retained<UNNotificationAttachment>> {
+    let extension = path.extension()?.to_string_lossy().into_owned();
+    let copy = std::env::temp_dir().join(format!(
+        "gotify-attachment-{message_id}-{}.{extension}",
+        crate::unique_time_suffix()
+    ));
+    if let Err(error) = fs::copy(path, &copy) {
+        debug_log(&format!(
+            "un notify id={message_id} attachment copy failed: {error}"
+        ));
+        return None;
+    }
+    let url = NSURL::from_file_path(&copy)?;
+    match unsafe {
+        UNNotificationAttachment::attachmentWithIdentifier_URL_options_error(
+            ns_string!("image"),
+            &url,
+            None,
+        )
+    } {
+        Ok(attachment) => Some(attachment),
+        Err(error) => {
+            let _ = fs::remove_file(&copy);
+            debug_log(&format!(
+                "un notify id={message_id} attachment failed: {}",
+                error.localizedDescription()
+            ));
+            None
+        }
+    }
+}
+
+/// Takes delivered and still pending notifications of these messages out of
+/// Notification Center.
+pub(crate) fn remove(message_ids: &[i64]) {
+    if !is_available() || message_ids.is_empty() {
+        return;
+    }
+    let identifiers: Vec<Retained<NSString>> = message_ids
+        .iter()
+        .map(|message_id| request_identifier(*message_id))
+        .collect();
+    let identifiers = NSArray::from_retained_slice(&identifiers);
+    let center = UNUserNotificationCenter::currentNotificationCenter();
+    center.removeDeliveredNotificationsWithIdentifiers(&identifiers);
+    center.removePendingNotificationRequestsWithIdentifiers(&identifiers);
+}
+
+fn handle_response(app: &AppHandle, message_id: i64, action: &str) {
+    debug_log(&format!(
+        "un notify response id={message_id} action={action}"
+    ));
+    match action {
+        ACTION_ACKNOWLEDGE => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(error) = crate::ack::acknowledge(&app, message_id).await {
+                    debug_log(&format!("acknowledge id={message_id} failed: {error}"));
+                }
+            });
+        }
+        ACTION_MARK_READ => {
+            if let Err(error) = crate::messages::mark_messages_read(app, Some(vec![message_id])) {
+                debug_log(&format!("mark read id={message_id} failed: {error}"));
+            }
+        }
+        ACTION_SNOOZE => {
+            if let Err(error) =
+                crate::snooze::snooze_message(app, message_id, NOTIFICATION_SNOOZE_MINUTES)
+            {
+                debug_log(&format!("snooze id={message_id} failed: {error}"));
+            }
+        }
+        _ if action == unsafe { UNNotificationDefaultActionIdentifier }.to_string() => {
+            let message = app
+                .state::<AppState>()
+                .messages
+                .lock()
+                .ok()
+                .and_then(|messages| {
+                    messages
+                        .iter()
+                        .find(|message| message.id == message_id)
+                        .cloned()
+                });
+            match message {
+                Some(message) => crate::notifications::open_notified_message(app, &message),
+                // Background profiles and messages deleted since delivery.
+                None => crate::ui_shell::show_main_window(app),
+            }
+        }
+        // Dismissed banners need no work.
+        _ => {}
+    }
+}
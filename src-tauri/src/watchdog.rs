@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    connection_history::{self, ConnectionEventKind},
+    debug_log,
+    diagnostics::publish_runtime_snapshot,
+    unix_now_secs, AppState, STREAM_WATCHDOG_INTERVAL_SECS,
+};
+
+/// Checks the foreground stream every `STREAM_WATCHDOG_INTERVAL_SECS` and runs
+/// `recover_stream` when it is stuck. A problem has to show on two checks in a
+/// row, so a restart that is between stopping and starting is not mistaken for
+/// a missing task.
+pub(crate) fn watch_stream(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut suspected = false;
+        loop {
+            tokio::time::sleep(Duration::from_secs(STREAM_WATCHDOG_INTERVAL_SECS)).await;
+            let now = unix_now_secs();
+            let reason = app
+                .state::<AppState>()
+                .runtime
+                .lock()
+                .ok()
+                .and_then(|runtime| runtime.stall_reason(now));
+            let Some(reason) = reason else {
+                suspected = false;
+                continue;
+            };
+            if !suspected {
+                suspected = true;
+                debug_log(&format!(
+                    "watchdog: stream looks stuck ({reason}); rechecking"
+                ));
+                continue;
+            }
+            suspected = false;
+            debug_log(&format!("watchdog: recovering stream ({reason})"));
+            if let Ok(mut runtime) = app.state::<AppState>().runtime.lock() {
+                runtime.watchdog_recoveries = runtime.watchdog_recoveries.saturating_add(1);
+                runtime.last_watchdog_recovery = Some(reason.clone());
+                runtime.last_watchdog_recovery_at = Some(now);
+            }
+            connection_history::record(&app, ConnectionEventKind::WatchdogRecovery, Some(reason));
+            if let Err(error) = crate::stream::recover_stream(app.clone()) {
+                debug_log(&format!("watchdog: recovery failed: {error}"));
+            }
+            publish_runtime_snapshot(&app);
+        }
+    });
+}
@@ -0,0 +1,99 @@
+#[cfg(target_os = "macos")]
+use std::process::Command;
+use std::{
+    sync::{mpsc, OnceLock},
+    thread,
+    time::Instant,
+};
+
+use crate::debug_log;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+static BACKGROUND_QUEUE: OnceLock<Option<mpsc::Sender<(&'static str, Job)>>> = OnceLock::new();
+
+/// Serial worker for deferrable work (cache persistence, icon conversion, statistics).
+/// Its thread runs at background QoS, so on Apple Silicon it is scheduled on the
+/// efficiency cores and on Intel it yields to the UI and foreground apps.
+fn background_queue() -> Option<&'static mpsc::Sender<(&'static str, Job)>> {
+    BACKGROUND_QUEUE
+        .get_or_init(|| {
+            let (sender, receiver) = mpsc::channel::<(&'static str, Job)>();
+            let spawned = thread::Builder::new()
+                .name("gotify-background".to_string())
+                .spawn(move || {
+                    lower_current_thread_priority();
+                    for (label, job) in receiver {
+                        let started_at = Instant::now();
+                        job();
+                        let elapsed_ms = started_at.elapsed().as_millis();
+                        if elapsed_ms >= 250 {
+                            debug_log(&format!("background job {label} took {elapsed_ms}ms"));
+                        }
+                    }
+                });
+            match spawned {
+                Ok(_) => Some(sender),
+                Err(error) => {
+                    debug_log(&format!("failed to start background worker: {error}"));
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// Queues `job` on the background worker; runs it inline if the worker is gone.
+pub(crate) fn run_in_background(label: &'static str, job: impl FnOnce() + Send + 'static) {
+    let job: Job = Box::new(job);
+    let Some(queue) = background_queue() else {
+        job();
+        return;
+    };
+    if let Err(mpsc::SendError((_, job))) = queue.send((label, job)) {
+        debug_log(&format!(
+            "background worker unavailable, running {label} inline"
+        ));
+        job();
+    }
+}
+
+/// Runs `job` on the background worker and waits for its result without blocking
+/// the async runtime.
+pub(crate) async fn run_in_background_for<T: Send + 'static>(
+    label: &'static str,
+    job: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, String> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    run_in_background(label, move || {
+        let _ = sender.send(job());
+    });
+    receiver
+        .await
+        .map_err(|_| format!("Background job {label} did not complete"))
+}
+
+/// Command for helper tools (`sips`, `iconutil`) that should not compete with the
+/// foreground; the child process is started under the Darwin background policy.
+#[cfg(target_os = "macos")]
+pub(crate) fn background_command(program: &str) -> Command {
+    let mut command = Command::new("/usr/sbin/taskpolicy");
+    command.arg("-b").arg(program);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn lower_current_thread_priority() {
+    const QOS_CLASS_BACKGROUND: u32 = 0x09;
+    extern "C" {
+        fn pthread_set_qos_class_self_np(qos_class: u32, relative_priority: i32) -> i32;
+    }
+    // SAFETY: only changes the scheduling class of the calling thread.
+    let result = unsafe { pthread_set_qos_class_self_np(QOS_CLASS_BACKGROUND, 0) };
+    if result != 0 {
+        debug_log(&format!("failed to set background QoS: {result}"));
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn lower_current_thread_priority() {}
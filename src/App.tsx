@@ -6,23 +6,65 @@ import gotifyLogo from "./assets/gotify-logo.png";
 import { DiagnosticsPanel } from "./components/DiagnosticsPanel";
 import { MessageFeed } from "./components/MessageFeed";
 import { SettingsForm } from "./components/SettingsForm";
+import { AccountPanel } from "./components/AccountPanel";
+import { ApplicationsPanel } from "./components/ApplicationsPanel";
+import { PluginsPanel } from "./components/PluginsPanel";
+import type { PluginDetails } from "./components/PluginsPanel";
+import { SendMessagePanel } from "./components/SendMessagePanel";
+import type { SendMessageInput } from "./components/SendMessagePanel";
+import { TrashPanel } from "./components/TrashPanel";
+import { ArchivePanel } from "./components/ArchivePanel";
 import type {
+  AndroidImportSummary,
+  ArchivedMessage,
+  ArchivedMessagePage,
+  MessageImportSummary,
+  OlderMessagesPage,
   AppUpdate,
   AppGroup,
+  AuthRequiredEvent,
   BootstrapState,
+  CertificateTrustRequest,
+  CleanupSuggestedEvent,
+  ClockSkewWarning,
+  ConnectionMode,
   ConnectionState,
+  ReconnectBackoff,
+  RequestTimeouts,
+  RetryPolicy,
+  MessageRetention,
   DomainSnapshot,
+  EndpointChangedEvent,
   DrawerTab,
+  AckSettings,
+  AndroidActionRule,
+  FaultInjection,
+  GotifyApplication,
+  GotifyPlugin,
+  CurrentUser,
+  MemoryStats,
+  ServerInfo,
+  InboxZeroEvent,
+  RemotelyDeletedEvent,
+  NotificationsWithdrawn,
+  MessageSentEvent,
+  OperationProgressEvent,
+  OpTraceEvent,
+  ProfileSummary,
   GotifyMessage,
   MessageRemovedData,
+  MessagesDeltaData,
   PauseStateData,
   PriorityThreshold,
   PauseMode,
   RuntimeDiagnostics,
   SelectionHistoryState,
+  SettingsReloaded,
   SettingsResponse,
+  SoundBand,
   StreamErrorData,
   ThemePreference,
+  TrashedMessage,
   UiMessage,
   UrlPreview,
 } from "./types";
@@ -40,6 +82,15 @@ import {
 
 const THEME_STORAGE_KEY = "gotify-theme-preference";
 const PAUSE_FOREVER_SENTINEL = 0;
+const OP_TRACE_LIMIT = 200;
+const SETTINGS_EXPORT_DEFAULT_PATH = "~/Desktop/gotify-desktop-settings.gdexport";
+const MESSAGES_EXPORT_DEFAULT_PATH = "~/Desktop/gotify-messages.json";
+const PREVIEW_OPERATION_GROUP = "previews";
+const OPERATION_CANCELLED = "Cancelled";
+const SEARCH_DEBOUNCE_MS = 150;
+const SEARCH_RESULT_LIMIT = 1000;
+/** Event payload schema this build understands; must match `EVENT_SCHEMA_VERSION` in consts.rs. */
+const EVENT_SCHEMA_VERSION = 1;
 const THEME_BADGE_SENTINEL = "__THEME_BADGE__";
 const DEFAULT_PRIORITY_THRESHOLDS: PriorityThreshold[] = [
   { value: 0, color: THEME_BADGE_SENTINEL },
@@ -52,6 +103,7 @@ type RevisionTracker = {
   connection: number;
   runtime: number;
   stream_error: number;
+  trash: number;
 };
 
 function loadThemePreference(): ThemePreference {
@@ -125,17 +177,84 @@ export function App() {
   const [launchAtLogin, setLaunchAtLogin] = useState(false);
   const [startMinimizedToTray, setStartMinimizedToTray] = useState(false);
   const [showPriorityInNotifications, setShowPriorityInNotifications] = useState(true);
+  const [privacyMode, setPrivacyMode] = useState(false);
+  const [redactionPatterns, setRedactionPatterns] = useState("");
+  const [trashRetentionDays, setTrashRetentionDays] = useState(7);
+  const [trash, setTrash] = useState<TrashedMessage[]>([]);
+  const [archiveRemoteDeletions, setArchiveRemoteDeletions] = useState(false);
+  const [archivedMessages, setArchivedMessages] = useState<ArchivedMessage[]>([]);
+  const [archiveTotal, setArchiveTotal] = useState(0);
+  const [archiveNextOffset, setArchiveNextOffset] = useState<number | null>(null);
+  const [loadingArchive, setLoadingArchive] = useState(false);
+  const [selectionMode, setSelectionMode] = useState(false);
+  const [selectedIds, setSelectedIds] = useState<Record<number, boolean>>({});
+  const [operation, setOperation] = useState<OperationProgressEvent | null>(null);
+  const [previewPass, setPreviewPass] = useState(0);
+  const [applications, setApplications] = useState<GotifyApplication[] | null>(null);
+  const [applicationsBusy, setApplicationsBusy] = useState(false);
+  const [currentUser, setCurrentUser] = useState<CurrentUser | null>(null);
+  const [plugins, setPlugins] = useState<GotifyPlugin[] | null>(null);
+  const [memoryStats, setMemoryStats] = useState<MemoryStats | null>(null);
+  const [serverInfo, setServerInfo] = useState<ServerInfo | null>(null);
+  const [opTraceEnabled, setOpTraceEnabled] = useState(false);
+  const [opTraceEvents, setOpTraceEvents] = useState<OpTraceEvent[]>([]);
+  const [cleanupSuggestion, setCleanupSuggestion] = useState<CleanupSuggestedEvent | null>(null);
+  const [profiles, setProfiles] = useState<ProfileSummary[]>([]);
+  const [activeProfile, setActiveProfile] = useState<string | null>(null);
+  const [appTokenNames, setAppTokenNames] = useState<string[]>([]);
+  const [lanBaseUrl, setLanBaseUrl] = useState("");
+  const [settingsPassphrase, setSettingsPassphrase] = useState("");
+  const [hostPins, setHostPins] = useState("");
+  const [healthCheckIntervalSecs, setHealthCheckIntervalSecs] = useState(60);
+  const [connectionMode, setConnectionMode] = useState<ConnectionMode>("websocket");
+  const [streamTokenInQuery, setStreamTokenInQuery] = useState(false);
+  const [reconnectBackoff, setReconnectBackoff] = useState<ReconnectBackoff>({
+    base_secs: 1,
+    multiplier: 2,
+    max_secs: 30,
+  });
+  const [requestTimeouts, setRequestTimeouts] = useState<RequestTimeouts>({
+    request_secs: 15,
+    stream_connect_secs: 10,
+  });
+  const [requestRetry, setRequestRetry] = useState<RetryPolicy>({ attempts: 3, base_delay_ms: 500 });
+  const [messageRetention, setMessageRetention] = useState<MessageRetention>({ days: 0, delete_on_server: false });
+  const [notificationSound, setNotificationSound] = useState("default");
+  const [soundBands, setSoundBands] = useState<SoundBand[]>([]);
+  const [maxSoundBands, setMaxSoundBands] = useState(0);
+  const [customSounds, setCustomSounds] = useState<string[]>([]);
+  const [contextNames, setContextNames] = useState<string[]>([]);
+  const [activeContext, setActiveContext] = useState<string | null>(null);
+  const [mutedAppIds, setMutedAppIds] = useState<number[]>([]);
+  const [androidActionRules, setAndroidActionRules] = useState<AndroidActionRule[]>([]);
+  const [acknowledgement, setAcknowledgement] = useState<AckSettings>({
+    enabled: false,
+    app_token_name: "",
+    message_template: "",
+    min_priority: 0,
+  });
+  const [faultInjection, setFaultInjection] = useState<FaultInjection | null>(null);
   const [quietStart, setQuietStart] = useState("");
   const [quietEnd, setQuietEnd] = useState("");
   const [hasStoredToken, setHasStoredToken] = useState(false);
+  const [tokenRevealed, setTokenRevealed] = useState(false);
   const [pauseUntil, setPauseUntil] = useState<number | null>(null);
   const [pauseMode, setPauseMode] = useState<PauseMode | null>(null);
   const [clockSec, setClockSec] = useState<number>(() => Math.floor(Date.now() / 1000));
   const [isQuickWindow, setIsQuickWindow] = useState(false);
 
   const [messages, setMessages] = useState<UiMessage[]>([]);
+  // Pages fetched past the cache for scrolling back; never written to the cache.
+  const [olderMessages, setOlderMessages] = useState<UiMessage[]>([]);
+  const [hasOlderMessages, setHasOlderMessages] = useState(true);
+  // The server's `next_before_id` from the last page; null before the first one.
+  const [olderMessagesCursor, setOlderMessagesCursor] = useState<number | null>(null);
+  const [loadingOlderMessages, setLoadingOlderMessages] = useState(false);
   const [selectedMessageId, setSelectedMessageId] = useState<number | null>(null);
   const [selectedApp, setSelectedApp] = useState<string>("all");
+  const [searchQuery, setSearchQuery] = useState("");
+  // Ids matched by `search_messages`; null while the search box is empty.
+  const [searchHitIds, setSearchHitIds] = useState<Set<number> | null>(null);
 
   const [drawerTab, setDrawerTab] = useState<DrawerTab | null>(null);
 
@@ -162,6 +281,7 @@ export function App() {
     connection: 0,
     runtime: 0,
     stream_error: 0,
+    trash: 0,
   });
   const updateChannelRef = useRef<Channel<AppUpdate> | null>(null);
   const cacheLimitRef = useRef(activeCacheLimit);
@@ -193,6 +313,36 @@ export function App() {
     setLaunchAtLogin(settings.launch_at_login ?? false);
     setStartMinimizedToTray(settings.start_minimized_to_tray ?? false);
     setShowPriorityInNotifications(settings.show_priority_in_notifications ?? true);
+    setPrivacyMode(settings.privacy_mode ?? false);
+    setRedactionPatterns((settings.redaction_patterns ?? []).join("\n"));
+    setTrashRetentionDays(settings.trash_retention_days ?? 7);
+    setOpTraceEnabled(settings.op_trace_enabled ?? false);
+    setProfiles(settings.profiles ?? []);
+    setAppTokenNames(settings.app_token_names ?? []);
+    setLanBaseUrl(settings.lan_base_url ?? "");
+    setHostPins(
+      Object.entries(settings.host_pins ?? {})
+        .map(([host, address]) => `${host}=${address}`)
+        .join("\n"),
+    );
+    setHealthCheckIntervalSecs(settings.health_check_interval_secs ?? 60);
+    setConnectionMode(settings.connection_mode ?? "websocket");
+    setStreamTokenInQuery(settings.stream_token_in_query ?? false);
+    if (settings.reconnect_backoff) setReconnectBackoff(settings.reconnect_backoff);
+    if (settings.request_timeouts) setRequestTimeouts(settings.request_timeouts);
+    if (settings.request_retry) setRequestRetry(settings.request_retry);
+    if (settings.message_retention) setMessageRetention(settings.message_retention);
+    setArchiveRemoteDeletions(settings.archive_remote_deletions ?? false);
+    setNotificationSound(settings.notification_sound ?? "default");
+    setSoundBands(settings.notification_sound_bands ?? []);
+    setMaxSoundBands(settings.max_notification_sound_bands ?? 0);
+    setContextNames(Object.keys(settings.contexts ?? {}));
+    setActiveContext(settings.active_context ?? null);
+    setMutedAppIds(settings.muted_app_ids ?? []);
+    setAndroidActionRules(settings.android_action_rules ?? []);
+    if (settings.acknowledgement) setAcknowledgement(settings.acknowledgement);
+    setFaultInjection(settings.fault_injection_available ? settings.fault_injection : null);
+    setActiveProfile(settings.active_profile ?? null);
     setQuietStart(settings.quiet_hours_start == null ? "" : String(settings.quiet_hours_start));
     setQuietEnd(settings.quiet_hours_end == null ? "" : String(settings.quiet_hours_end));
     applyPauseState(settings.pause_until ?? null, settings.pause_mode ?? null);
@@ -228,6 +378,21 @@ export function App() {
     if (snapshot.revision <= revisionsRef.current.messages) return false;
     revisionsRef.current.messages = snapshot.revision;
     setMessages((current) => current.filter((item) => item.id !== snapshot.data.message_id));
+    setOlderMessages((current) => current.filter((item) => item.id !== snapshot.data.message_id));
+    return true;
+  };
+
+  const applyMessagesDeltaSnapshot = (snapshot: DomainSnapshot<MessagesDeltaData>) => {
+    if (snapshot.revision <= revisionsRef.current.messages) return false;
+    revisionsRef.current.messages = snapshot.revision;
+    const { upserted, removed } = snapshot.data;
+    setMessages((current) => {
+      // Untouched messages keep their objects and rendered HTML; only the delta is re-rendered to HTML.
+      const changedIds = new Set<number>([...removed, ...upserted.map((message) => message.id)]);
+      return [...current.filter((item) => !changedIds.has(item.id)), ...upserted.map(toUiMessage)]
+        .sort(compareMessagesNewestFirst)
+        .slice(0, cacheLimitRef.current);
+    });
     return true;
   };
 
@@ -235,7 +400,7 @@ export function App() {
     if (snapshot.revision <= revisionsRef.current.connection) return false;
     revisionsRef.current.connection = snapshot.revision;
     setConnectionState(snapshot.data.state);
-    if (snapshot.data.state === "Connected") {
+    if (snapshot.data.state === "Connected" || snapshot.data.state === "Polling") {
       setStreamErrorMessage(null);
     }
     return true;
@@ -246,7 +411,7 @@ export function App() {
     revisionsRef.current.runtime = snapshot.revision;
     setDiagnostics(snapshot.data);
     setConnectionState(snapshot.data.connection_state);
-    if (snapshot.data.connection_state === "Connected") {
+    if (snapshot.data.connection_state === "Connected" || snapshot.data.connection_state === "Polling") {
       setStreamErrorMessage(null);
     }
     return true;
@@ -259,6 +424,13 @@ export function App() {
     return true;
   };
 
+  const applyTrashSnapshot = (snapshot: DomainSnapshot<TrashedMessage[]>) => {
+    if (snapshot.revision <= revisionsRef.current.trash) return false;
+    revisionsRef.current.trash = snapshot.revision;
+    setTrash(snapshot.data);
+    return true;
+  };
+
   const applyBootstrap = (bootstrap: BootstrapState) => {
     applySettingsSnapshot(bootstrap.settings);
     applyPauseSnapshot(bootstrap.pause);
@@ -284,6 +456,9 @@ export function App() {
       case "messages.remove":
         applyMessageRemoveSnapshot(update.payload);
         return;
+      case "messages.delta":
+        applyMessagesDeltaSnapshot(update.payload);
+        return;
       case "connection.updated":
         applyConnectionSnapshot(update.payload);
         return;
@@ -293,6 +468,9 @@ export function App() {
       case "stream.error":
         applyStreamErrorSnapshot(update.payload);
         return;
+      case "trash.updated":
+        applyTrashSnapshot(update.payload);
+        return;
       default:
         return;
     }
@@ -302,6 +480,11 @@ export function App() {
     cacheLimitRef.current = Math.max(1, activeCacheLimit);
   }, [activeCacheLimit]);
 
+  // A revealed token is masked again once the field is cleared (after a save or profile switch).
+  useEffect(() => {
+    if (token === "") setTokenRevealed(false);
+  }, [token]);
+
   useEffect(() => {
     try {
       const label = getCurrentWebviewWindow().label;
@@ -466,6 +649,18 @@ export function App() {
     let destroyed = false;
     let unlistenNotification: (() => void) | undefined;
     let unlistenNotificationClicked: (() => void) | undefined;
+    let unlistenClockSkew: (() => void) | undefined;
+    let unlistenSettingsReloaded: (() => void) | undefined;
+    let unlistenCertificateTrust: (() => void) | undefined;
+    let unlistenInboxZero: (() => void) | undefined;
+    let unlistenRemotelyDeleted: (() => void) | undefined;
+    let unlistenNotificationWithdrawn: (() => void) | undefined;
+    let unlistenAuthRequired: (() => void) | undefined;
+    let unlistenCleanupSuggested: (() => void) | undefined;
+    let unlistenOpTrace: (() => void) | undefined;
+    let unlistenOperationProgress: (() => void) | undefined;
+    let unlistenMessageSent: (() => void) | undefined;
+    let unlistenEndpointChanged: (() => void) | undefined;
 
     listen<GotifyMessage>("notification-message", (event) => {
       applySelection(String(event.payload.app_id || "all"), event.payload.id, false);
@@ -483,12 +678,175 @@ export function App() {
       unlistenNotificationClicked = fn;
     });
 
+    listen<ClockSkewWarning>("clock-skew-warning", (event) => {
+      const direction = event.payload.skew_seconds > 0 ? "ahead of" : "behind";
+      setFeedback({
+        kind: "error",
+        message: `Local clock is ${Math.abs(event.payload.skew_seconds)}s ${direction} the Gotify server. Quiet hours and pause timers may be inaccurate.`,
+      });
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenClockSkew = fn;
+    });
+
+    listen<SettingsReloaded>("settings-reloaded", (event) => {
+      setFeedback({
+        kind: "ok",
+        message: `Settings reloaded from disk (${event.payload.changed_fields.join(", ")}).`,
+      });
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenSettingsReloaded = fn;
+    });
+
+    listen<CertificateTrustRequest>("certificate-trust-requested", (event) => {
+      const { host, fingerprint_sha256: fingerprint, reason } = event.payload;
+      const accepted = window.confirm(
+        `The certificate for ${host} could not be verified (${reason}).\n\n` +
+          `SHA-256: ${fingerprint}\n\nTrust this exact certificate?`,
+      );
+      if (!accepted) {
+        setFeedback({ kind: "error", message: `Certificate for ${host} was not trusted.` });
+        return;
+      }
+      void invoke<DomainSnapshot<SettingsResponse>>("trust_certificate", { host, fingerprintSha256: fingerprint })
+        .then((snapshot) => {
+          applySettingsSnapshot(snapshot);
+          setFeedback({ kind: "ok", message: `Trusted certificate for ${host}.` });
+        })
+        .catch((error) => {
+          setFeedback({ kind: "error", message: `Failed to trust certificate: ${String(error)}` });
+        });
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenCertificateTrust = fn;
+    });
+
+    listen<AuthRequiredEvent>("auth-required", (event) => {
+      setFeedback({
+        kind: "error",
+        message: `The server rejected the client token (HTTP ${event.payload.status}). Enter a new token to reconnect.`,
+      });
+      setDrawerTab("settings");
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenAuthRequired = fn;
+    });
+
+    listen<OperationProgressEvent>("operation-progress", (event) => {
+      const progress = event.payload;
+      if (!progress.finished) {
+        setOperation(progress);
+        return;
+      }
+      setOperation(null);
+      const verb = progress.kind === "export" ? "Exported" : "Deleted";
+      const succeeded = progress.done - progress.failed;
+      if (progress.error || progress.failed > 0) {
+        setFeedback({
+          kind: "error",
+          message: `${verb} ${succeeded} of ${progress.total}; ${progress.error ?? `${progress.failed} failed`}`,
+        });
+      } else {
+        setFeedback({
+          kind: "ok",
+          message: progress.cancelled
+            ? `Cancelled after ${succeeded} of ${progress.total}.`
+            : `${verb} ${succeeded} message(s).`,
+        });
+      }
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenOperationProgress = fn;
+    });
+
+    listen<MessageSentEvent>("message-sent", (event) => {
+      const sent = event.payload;
+      setFeedback({
+        kind: "ok",
+        message: `Sent "${sent.title || "message"}"${sent.token_name ? ` as ${sent.token_name}` : ""}.`,
+      });
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenMessageSent = fn;
+    });
+
+    listen<EndpointChangedEvent>("endpoint-changed", (event) => {
+      const changed = event.payload;
+      setFeedback({
+        kind: "ok",
+        message: `Switched to ${changed.lan ? "the LAN URL" : "the server URL"} (${changed.latency_ms} ms).`,
+      });
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenEndpointChanged = fn;
+    });
+
+    listen<InboxZeroEvent>("inbox-zero", () => {
+      setFeedback({ kind: "ok", message: "Inbox zero. You're all caught up." });
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenInboxZero = fn;
+    });
+
+    listen<RemotelyDeletedEvent>("message-remotely-deleted", (event) => {
+      const { message_ids: ids, archived } = event.payload;
+      setFeedback({
+        kind: "ok",
+        message: `${ids.length} message(s) were deleted on the server${archived ? " and kept in the archive" : ""}.`,
+      });
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenRemotelyDeleted = fn;
+    });
+
+    listen<NotificationsWithdrawn>("notification-withdrawn", (event) => {
+      const withdrawn = new Set(event.payload.message_ids);
+      setSelectedMessageId((current) => (current != null && withdrawn.has(current) ? null : current));
+      setOlderMessages((current) => current.filter((message) => !withdrawn.has(message.id)));
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenNotificationWithdrawn = fn;
+    });
+
+    listen<CleanupSuggestedEvent>("cleanup-suggested", (event) => {
+      setCleanupSuggestion(event.payload);
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenCleanupSuggested = fn;
+    });
+
+    listen<OpTraceEvent>("op-trace", (event) => {
+      setOpTraceEvents((current) => [event.payload, ...current].slice(0, OP_TRACE_LIMIT));
+    }).then((fn) => {
+      if (destroyed) { fn(); return; }
+      unlistenOpTrace = fn;
+    });
+
     const initialize = async () => {
       try {
         const bootstrap = await invoke<BootstrapState>("bootstrap_state");
         if (destroyed) return;
         applyBootstrap(bootstrap);
 
+        applyTrashSnapshot(await invoke<DomainSnapshot<TrashedMessage[]>>("get_trash"));
+        void invoke<string[]>("list_custom_sounds")
+          .then((sounds) => {
+            if (!destroyed) setCustomSounds(sounds);
+          })
+          .catch(() => {});
+
+        const schemaVersion = await invoke<number>("get_event_schema_version");
+        if (schemaVersion !== EVENT_SCHEMA_VERSION) {
+          console.warn(
+            `Backend event schema ${schemaVersion} differs from frontend schema ${EVENT_SCHEMA_VERSION}`,
+          );
+          setFeedback({
+            kind: "error",
+            message: "This window is out of date with the app backend. Restart the app to reload it.",
+          });
+        }
+
         const channel = new Channel<AppUpdate>((update) => {
           if (destroyed) return;
           handleAppUpdate(update);
@@ -511,6 +869,18 @@ export function App() {
       destroyed = true;
       if (unlistenNotification) unlistenNotification();
       if (unlistenNotificationClicked) unlistenNotificationClicked();
+      if (unlistenClockSkew) unlistenClockSkew();
+      if (unlistenSettingsReloaded) unlistenSettingsReloaded();
+      if (unlistenCertificateTrust) unlistenCertificateTrust();
+      if (unlistenInboxZero) unlistenInboxZero();
+      if (unlistenRemotelyDeleted) unlistenRemotelyDeleted();
+      if (unlistenNotificationWithdrawn) unlistenNotificationWithdrawn();
+      if (unlistenAuthRequired) unlistenAuthRequired();
+      if (unlistenCleanupSuggested) unlistenCleanupSuggested();
+      if (unlistenOpTrace) unlistenOpTrace();
+      if (unlistenOperationProgress) unlistenOperationProgress();
+      if (unlistenMessageSent) unlistenMessageSent();
+      if (unlistenEndpointChanged) unlistenEndpointChanged();
       updateChannelRef.current = null;
       void invoke("unsubscribe_app_updates").catch(() => {});
     };
@@ -528,11 +898,20 @@ export function App() {
       if (Object.prototype.hasOwnProperty.call(urlPreviews, url)) {
         continue;
       }
-      void invoke<UrlPreview>("fetch_url_preview", { url })
+      void invoke<UrlPreview>("fetch_url_preview", { url, group: PREVIEW_OPERATION_GROUP })
         .then((preview) => {
           setUrlPreviews((current) => ({ ...current, [url]: preview }));
         })
-        .catch(() => {
+        .catch((error) => {
+          if (String(error) === OPERATION_CANCELLED) {
+            // Fetched again on the next pass instead of being remembered as failed.
+            setUrlPreviews((current) => {
+              const next = { ...current };
+              delete next[url];
+              return next;
+            });
+            return;
+          }
           setUrlPreviews((current) => ({ ...current, [url]: null }));
         });
     }
@@ -540,7 +919,22 @@ export function App() {
   // inside the loop prevents duplicate fetches, so adding urlPreviews would
   // cause an O(n²) cascade where every resolved preview triggers a re-scan.
   // eslint-disable-next-line react-hooks/exhaustive-deps
-  }, [messages]);
+  }, [messages, previewPass]);
+
+  useEffect(() => {
+    // Hidden windows do not need previews; stop the fetches and resume on return.
+    const onVisibility = () => {
+      if (document.hidden) {
+        void invoke<number>("cancel_operations", { group: PREVIEW_OPERATION_GROUP }).catch(() => {});
+      } else {
+        setPreviewPass((current) => current + 1);
+      }
+    };
+    document.addEventListener("visibilitychange", onVisibility);
+    return () => {
+      document.removeEventListener("visibilitychange", onVisibility);
+    };
+  }, []);
 
   useEffect(() => {
     window.localStorage.setItem(THEME_STORAGE_KEY, activeThemePreference);
@@ -636,6 +1030,16 @@ export function App() {
       setFeedback({ kind: "error", message: "Quiet end hour must be a number between 0 and 23" });
       return;
     }
+    const parsedHostPins: Record<string, string> = {};
+    for (const line of hostPins.split("\n").map((entry) => entry.trim())) {
+      if (line.length === 0) continue;
+      const separator = line.indexOf("=");
+      if (separator <= 0) {
+        setFeedback({ kind: "error", message: `DNS override "${line}" must look like host=address` });
+        return;
+      }
+      parsedHostPins[line.slice(0, separator).trim()] = line.slice(separator + 1).trim();
+    }
 
     setIsSaving(true);
 
@@ -654,6 +1058,14 @@ export function App() {
         launchAtLogin,
         startMinimizedToTray,
         showPriorityInNotifications,
+        opTraceEnabled,
+        privacyMode,
+        redactionPatterns: redactionPatterns
+          .split("\n")
+          .map((pattern) => pattern.trim())
+          .filter((pattern) => pattern.length > 0),
+        trashRetentionDays,
+        hostPins: parsedHostPins,
         quietHoursStart,
         quietHoursEnd,
       });
@@ -692,6 +1104,566 @@ export function App() {
     }
   };
 
+  const runProfileCommand = async (
+    command: "save_profile" | "switch_profile" | "delete_profile",
+    name: string,
+    okMessage: string,
+  ) => {
+    setFeedback(null);
+    try {
+      const snapshot = await invoke<DomainSnapshot<SettingsResponse>>(command, { name });
+      applySettingsSnapshot(snapshot);
+      setToken("");
+      setFeedback({ kind: "ok", message: okMessage });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onSaveProfile = () => {
+    const name = window.prompt("Save the current server as:", activeProfile ?? "");
+    if (name === null || name.trim() === "") return;
+    void runProfileCommand("save_profile", name, `Saved server "${name.trim()}".`);
+  };
+
+  const onSwitchProfile = (name: string) => {
+    void invoke<number>("cancel_operations", { group: PREVIEW_OPERATION_GROUP }).catch(() => {});
+    void runProfileCommand("switch_profile", name, `Switched to "${name}". Reconnecting...`);
+  };
+
+  const onEditProfileBadge = async (profile: ProfileSummary) => {
+    const badge = window.prompt(
+      `Emoji or short text shown before notifications from "${profile.name}" (empty to remove):`,
+      profile.badge ?? "",
+    );
+    if (badge === null) return;
+    const color = window.prompt("Accent color as #rrggbb (empty for none):", profile.color ?? "");
+    if (color === null) return;
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_profile_badge", {
+          name: profile.name,
+          badge: badge.trim() || null,
+          color: color.trim() || null,
+        }),
+      );
+      setFeedback({ kind: "ok", message: `Updated badge for "${profile.name}".` });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onToggleProfileBackgroundStream = async (profile: ProfileSummary) => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_profile_background_stream", {
+          name: profile.name,
+          enabled: !profile.background_stream,
+        }),
+      );
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onDeleteProfile = (name: string) => {
+    if (!window.confirm(`Remove saved server "${name}" and its cached messages?`)) return;
+    void runProfileCommand("delete_profile", name, `Removed "${name}".`);
+  };
+
+  const runAppTokenCommand = async (
+    command: "save_app_token" | "delete_app_token",
+    args: Record<string, string>,
+    okMessage: string,
+  ) => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(await invoke<DomainSnapshot<SettingsResponse>>(command, args));
+      setFeedback({ kind: "ok", message: okMessage });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onAddAppToken = () => {
+    const name = window.prompt("Name for this application token (e.g. the Gotify app it belongs to):");
+    if (name === null || name.trim() === "") return;
+    const token = window.prompt(`Application token for "${name.trim()}" (Gotify - Apps):`);
+    if (token === null || token.trim() === "") return;
+    void runAppTokenCommand("save_app_token", { name, token }, `Saved application token "${name.trim()}".`);
+  };
+
+  const onDeleteAppToken = (name: string) => {
+    if (!window.confirm(`Remove application token "${name}"?`)) return;
+    void runAppTokenCommand("delete_app_token", { name }, `Removed application token "${name}".`);
+  };
+
+  const onApplyLanBaseUrl = async () => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_lan_base_url", { url: lanBaseUrl.trim() || null }),
+      );
+      setFeedback({ kind: "ok", message: lanBaseUrl.trim() ? "LAN URL saved." : "LAN URL removed." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const runNotificationSettingsCommand = async (
+    command:
+      | "save_context"
+      | "switch_context"
+      | "delete_context"
+      | "set_notification_sound"
+      | "set_notification_sound_bands"
+      | "set_app_muted"
+      | "set_android_action_rules",
+    args: Record<string, unknown>,
+    okMessage: string,
+  ) => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(await invoke<DomainSnapshot<SettingsResponse>>(command, args));
+      setFeedback({ kind: "ok", message: okMessage });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onSaveContext = () => {
+    const name = window.prompt("Save the current notification settings as context:", activeContext ?? "Work");
+    if (name === null || name.trim() === "") return;
+    void runNotificationSettingsCommand("save_context", { name }, `Saved context "${name.trim()}".`);
+  };
+
+  const onSwitchContext = (name: string) => {
+    void runNotificationSettingsCommand("switch_context", { name }, `Switched to "${name}".`);
+  };
+
+  const onDeleteContext = (name: string) => {
+    if (!window.confirm(`Remove context "${name}"? Current settings stay as they are.`)) return;
+    void runNotificationSettingsCommand("delete_context", { name }, `Removed "${name}".`);
+  };
+
+  const onToggleAppMute = (application: GotifyApplication) => {
+    const muted = !mutedAppIds.includes(application.id);
+    void runNotificationSettingsCommand(
+      "set_app_muted",
+      { appId: application.id, muted },
+      `${muted ? "Muted" : "Unmuted"} "${application.name}".`,
+    );
+  };
+
+  const onChangeConnectionMode = async (mode: ConnectionMode) => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(await invoke<DomainSnapshot<SettingsResponse>>("set_connection_mode", { mode }));
+      setFeedback({ kind: "ok", message: "Connection mode saved." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onChangeStreamTokenInQuery = async (enabled: boolean) => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_stream_token_in_query", { enabled }),
+      );
+      setFeedback({ kind: "ok", message: "Stream authentication saved." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onApplyReconnectBackoff = async () => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_reconnect_backoff", { backoff: reconnectBackoff }),
+      );
+      setFeedback({ kind: "ok", message: "Reconnect delays saved." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onApplyRequestTimeouts = async () => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_request_timeouts", { timeouts: requestTimeouts }),
+      );
+      setFeedback({ kind: "ok", message: "Timeouts saved." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onApplyRequestRetry = async () => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_request_retry", { policy: requestRetry }),
+      );
+      setFeedback({ kind: "ok", message: "Retry settings saved." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onApplyMessageRetention = async () => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_message_retention", { retention: messageRetention }),
+      );
+      setFeedback({ kind: "ok", message: "Message retention saved." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onApplyHealthCheckInterval = async () => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_health_check_interval", {
+          secs: healthCheckIntervalSecs,
+        }),
+      );
+      setFeedback({
+        kind: "ok",
+        message: healthCheckIntervalSecs > 0 ? "Health check interval saved." : "Health checks off.",
+      });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onApplyAcknowledgement = async () => {
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_acknowledgement", { config: acknowledgement }),
+      );
+      setFeedback({ kind: "ok", message: acknowledgement.enabled ? "Acknowledge action on." : "Acknowledge action off." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onAcknowledgeMessage = async (messageId: number) => {
+    setFeedback(null);
+    try {
+      await invoke<MessageSentEvent>("acknowledge_message", { messageId });
+      setFeedback({ kind: "ok", message: "Acknowledgement sent." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onApplyFaultInjection = async () => {
+    if (!faultInjection) return;
+    setFeedback(null);
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_fault_injection", { config: faultInjection }),
+      );
+      setFeedback({ kind: "ok", message: faultInjection.enabled ? "Fault injection applied." : "Fault injection off." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const runApplicationCommand = async (action: () => Promise<unknown>, okMessage: string | null) => {
+    setApplicationsBusy(true);
+    setFeedback(null);
+    try {
+      await action();
+      setApplications(await invoke<GotifyApplication[]>("list_applications"));
+      if (okMessage) setFeedback({ kind: "ok", message: okMessage });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    } finally {
+      setApplicationsBusy(false);
+    }
+  };
+
+  const onReloadApplications = () => {
+    void runApplicationCommand(async () => {}, null);
+    void invoke<CurrentUser>("get_current_user")
+      .then(setCurrentUser)
+      .catch(() => setCurrentUser(null));
+  };
+
+  const runPluginCommand = async (action: () => Promise<unknown>, okMessage: string | null) => {
+    setApplicationsBusy(true);
+    setFeedback(null);
+    try {
+      await action();
+      setPlugins(await invoke<GotifyPlugin[]>("list_plugins"));
+      if (okMessage) setFeedback({ kind: "ok", message: okMessage });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    } finally {
+      setApplicationsBusy(false);
+    }
+  };
+
+  const onTogglePlugin = (plugin: GotifyPlugin) => {
+    const command = plugin.enabled ? "disable_plugin" : "enable_plugin";
+    void runPluginCommand(
+      () => invoke(command, { pluginId: plugin.id }),
+      `${plugin.enabled ? "Disabled" : "Enabled"} plugin "${plugin.name}".`,
+    );
+  };
+
+  const onLoadPluginDetails = async (plugin: GotifyPlugin): Promise<PluginDetails | null> => {
+    try {
+      const [display, config] = await Promise.all([
+        plugin.capabilities.includes("displayer")
+          ? invoke<string>("get_plugin_display", { pluginId: plugin.id })
+          : Promise.resolve(null),
+        plugin.capabilities.includes("configurer")
+          ? invoke<string>("get_plugin_config", { pluginId: plugin.id })
+          : Promise.resolve(null),
+      ]);
+      return { display, config };
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+      return null;
+    }
+  };
+
+  const onSavePluginConfig = async (plugin: GotifyPlugin, config: string) => {
+    setFeedback(null);
+    try {
+      await invoke("update_plugin_config", { pluginId: plugin.id, config });
+      setFeedback({ kind: "ok", message: `Saved config of "${plugin.name}".` });
+      return true;
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+      return false;
+    }
+  };
+
+  const onChangePassword = async (password: string) => {
+    if (!window.confirm("Change the password of this Gotify account?")) return false;
+    setFeedback(null);
+    try {
+      await invoke("change_password", { password });
+      setFeedback({ kind: "ok", message: "Password changed." });
+      return true;
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+      return false;
+    }
+  };
+
+  const onCreateApplication = () => {
+    const name = window.prompt("Name of the new application:");
+    if (name === null || name.trim() === "") return;
+    const saveToken = window.confirm(`Save the token of "${name.trim()}" for sending messages?`);
+    void runApplicationCommand(
+      () => invoke<GotifyApplication>("create_application", { name, saveToken }),
+      `Created application "${name.trim()}".`,
+    );
+  };
+
+  const onRenameApplication = (application: GotifyApplication) => {
+    const name = window.prompt("New name:", application.name);
+    if (name === null || name.trim() === "" || name.trim() === application.name) return;
+    void runApplicationCommand(
+      () =>
+        invoke<GotifyApplication>("update_application", {
+          appId: application.id,
+          name,
+          description: application.description,
+          defaultPriority: application.default_priority,
+        }),
+      `Renamed to "${name.trim()}".`,
+    );
+  };
+
+  const onUploadApplicationImage = (application: GotifyApplication) => {
+    const filePath = window.prompt(`Path to a PNG, JPEG or GIF image for "${application.name}":`);
+    if (filePath === null || filePath.trim() === "") return;
+    void runApplicationCommand(
+      () => invoke<GotifyApplication>("upload_application_image", { appId: application.id, filePath }),
+      `Updated the image of "${application.name}".`,
+    );
+  };
+
+  const onDeleteApplication = (application: GotifyApplication) => {
+    if (!window.confirm(`Delete application "${application.name}" and all of its messages on the server?`)) return;
+    void runApplicationCommand(
+      () => invoke("delete_application", { appId: application.id }),
+      `Deleted application "${application.name}".`,
+    );
+  };
+
+  const onSendMessage = async (input: SendMessageInput) => {
+    setFeedback(null);
+    try {
+      // Success feedback comes from the "message-sent" event.
+      await invoke<MessageSentEvent>("send_message", {
+        appToken: input.appToken,
+        title: input.title,
+        message: input.message,
+        priority: input.priority,
+        extras: input.markdown ? { "client::display": { contentType: "text/markdown" } } : null,
+      });
+      return true;
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+      return false;
+    }
+  };
+
+  const onRevealToken = async () => {
+    setFeedback(null);
+    try {
+      setToken(await invoke<string>("reveal_token"));
+      setTokenRevealed(true);
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onExportSettings = async () => {
+    const path = window.prompt("Export settings to file:", SETTINGS_EXPORT_DEFAULT_PATH);
+    if (path === null || path.trim() === "") return;
+    setFeedback(null);
+    try {
+      await invoke("export_settings", { path, passphrase: settingsPassphrase });
+      setSettingsPassphrase("");
+      setFeedback({ kind: "ok", message: `Settings exported to ${path.trim()}.` });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onImportSettings = async () => {
+    const path = window.prompt("Import settings from file:", SETTINGS_EXPORT_DEFAULT_PATH);
+    if (path === null || path.trim() === "") return;
+    if (!window.confirm("Replace all current settings and saved servers with the imported ones?")) return;
+    setFeedback(null);
+    try {
+      const snapshot = await invoke<DomainSnapshot<SettingsResponse>>("import_settings", {
+        path,
+        passphrase: settingsPassphrase,
+      });
+      setSettingsPassphrase("");
+      applySettingsSnapshot(snapshot);
+      setToken("");
+      setFeedback({ kind: "ok", message: "Settings imported. Reconnecting..." });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onImportAndroidBackup = async () => {
+    const path = window.prompt("Import Android client messages from file:", "~/Downloads/gotify-messages.json");
+    if (path === null || path.trim() === "") return;
+    const serverUrl = window.prompt("Server the Android client was connected to:", baseUrl);
+    if (serverUrl === null || serverUrl.trim() === "") return;
+    setFeedback(null);
+    try {
+      const summary = await invoke<AndroidImportSummary>("import_android_backup", { path, serverUrl });
+      setFeedback({
+        kind: "ok",
+        message: `Imported ${summary.imported} message(s); ${summary.duplicates} already known${
+          summary.skipped > 0 ? `, ${summary.skipped} unreadable entries skipped` : ""
+        }.`,
+      });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onImportMessages = async () => {
+    const path = window.prompt("Import exported messages from file:", MESSAGES_EXPORT_DEFAULT_PATH);
+    if (path === null || path.trim() === "") return;
+    setFeedback(null);
+    try {
+      const summary = await invoke<MessageImportSummary>("import_messages", { path });
+      setFeedback({
+        kind: "ok",
+        message: `Imported ${summary.imported} message(s); ${summary.duplicates} already known${
+          summary.conflicts > 0 ? `, ${summary.conflicts} kept from the cache (different message, same id)` : ""
+        }${summary.skipped > 0 ? `, ${summary.skipped} unreadable entries skipped` : ""}.`,
+      });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onImportSound = async () => {
+    const path = window.prompt("Import an .aiff or .caf sound (shorter than 30 seconds) from file:");
+    if (path === null || path.trim() === "") return;
+    setFeedback(null);
+    try {
+      const name = await invoke<string>("import_sound", { path });
+      setCustomSounds(await invoke<string[]>("list_custom_sounds"));
+      setFeedback({ kind: "ok", message: `Sound "${name}" imported; use it by name below.` });
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onPreviewSound = (sound: string) => {
+    void invoke("preview_sound", { sound }).catch((error) => {
+      setFeedback({ kind: "error", message: String(error) });
+    });
+  };
+
+  const loadArchivePage = async (offset: number) => {
+    setLoadingArchive(true);
+    try {
+      const page = await invoke<ArchivedMessagePage>("load_archived_messages", { offset });
+      setArchivedMessages((current) => (offset === 0 ? page.messages : [...current, ...page.messages]));
+      setArchiveTotal(page.total);
+      setArchiveNextOffset(page.next_offset);
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    } finally {
+      setLoadingArchive(false);
+    }
+  };
+
+  const onToggleArchive = async (enabled: boolean) => {
+    try {
+      applySettingsSnapshot(
+        await invoke<DomainSnapshot<SettingsResponse>>("set_archive_remote_deletions", { enabled }),
+      );
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onRestoreFromTrash = async (messageId: number) => {
+    try {
+      applyTrashSnapshot(
+        await invoke<DomainSnapshot<TrashedMessage[]>>("restore_from_trash", { messageId }),
+      );
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
+  const onEmptyTrash = async () => {
+    if (!window.confirm(`Permanently delete ${trash.length} message(s) from the server?`)) return;
+    try {
+      applyTrashSnapshot(await invoke<DomainSnapshot<TrashedMessage[]>>("empty_trash"));
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+    }
+  };
+
   const onResetPriorityThresholds = () => {
     setPriorityThresholds([...DEFAULT_PRIORITY_THRESHOLDS]);
   };
@@ -741,6 +1713,7 @@ export function App() {
     if (selectedMessageId === messageId) setSelectedMessageId(null);
     window.setTimeout(() => {
       setMessages((current) => current.filter((m) => m.id !== messageId));
+      setOlderMessages((current) => current.filter((m) => m.id !== messageId));
       setDeletingMessageIds((current) => { const next = { ...current }; delete next[messageId]; return next; });
     }, ANIM_MS);
 
@@ -761,9 +1734,124 @@ export function App() {
     }
   };
 
+  const onMarkRead = (messageIds: number[] | null) => {
+    void invoke<DomainSnapshot<GotifyMessage[]>>("mark_messages_read", { messageIds })
+      .then((snapshot) => {
+        applyMessagesReplaceSnapshot(snapshot);
+      })
+      .catch((error) => {
+        setFeedback({ kind: "error", message: String(error) });
+      });
+  };
+
+  const onTogglePinned = (message: { id: number; pinned: boolean }) => {
+    void invoke<DomainSnapshot<GotifyMessage[]>>(message.pinned ? "unpin_message" : "pin_message", {
+      messageId: message.id,
+    })
+      .then((snapshot) => {
+        applyMessagesReplaceSnapshot(snapshot);
+      })
+      .catch((error) => {
+        setFeedback({ kind: "error", message: String(error) });
+      });
+  };
+
+  const onSnooze = (messageId: number, minutes: number) => {
+    void invoke<DomainSnapshot<GotifyMessage[]>>("snooze_message", { messageId, minutes })
+      .then((snapshot) => {
+        applyMessagesReplaceSnapshot(snapshot);
+      })
+      .catch((error) => {
+        setFeedback({ kind: "error", message: String(error) });
+      });
+  };
+
+  const onEditLabels = (message: { id: number; labels?: string[] }) => {
+    const input = window.prompt("Labels, separated by commas:", (message.labels ?? []).join(", "));
+    if (input === null) return;
+    const labels = input.split(",").map((label) => label.trim()).filter(Boolean);
+    void invoke<DomainSnapshot<GotifyMessage[]>>("set_message_labels", { messageId: message.id, labels })
+      .then((snapshot) => {
+        applyMessagesReplaceSnapshot(snapshot);
+      })
+      .catch((error) => {
+        setFeedback({ kind: "error", message: String(error) });
+      });
+  };
+
+  const onDismiss = (messageId: number) => {
+    // The cache change arrives as `messages.replace`.
+    void invoke<boolean>("dismiss_message", { messageId }).catch((error) => {
+      setFeedback({ kind: "error", message: String(error) });
+    });
+  };
+
+  const onAcceptCleanup = async () => {
+    const suggestion = cleanupSuggestion;
+    if (!suggestion) return;
+    setCleanupSuggestion(null);
+    await startOperation("delete_messages", { messageIds: suggestion.candidate_ids });
+  };
+
+  const startOperation = async (command: string, args: Record<string, unknown>) => {
+    try {
+      // Progress, including the first 0/total event, arrives on "operation-progress".
+      await invoke<number>(command, args);
+      return true;
+    } catch (error) {
+      setFeedback({ kind: "error", message: String(error) });
+      return false;
+    }
+  };
+
+  const onToggleSelected = (messageId: number) => {
+    setSelectedIds((current) => {
+      const next = { ...current };
+      if (next[messageId]) delete next[messageId];
+      else next[messageId] = true;
+      return next;
+    });
+  };
+
+  const onToggleSelectionMode = () => {
+    setSelectionMode((current) => !current);
+    setSelectedIds({});
+  };
+
+  const onDeleteSelected = async () => {
+    const messageIds = Object.keys(selectedIds).map(Number);
+    if (messageIds.length === 0) return;
+    if (await startOperation("delete_messages", { messageIds })) {
+      setSelectedIds({});
+      setSelectionMode(false);
+    }
+  };
+
+  const onDeleteAppMessages = async () => {
+    const appId = Number(selectedApp);
+    if (!Number.isFinite(appId) || appId <= 0) return;
+    if (!window.confirm(`Delete all messages of ${selectedAppName}?`)) return;
+    await startOperation("delete_app_messages", { appId });
+  };
+
+  const onExportMessages = async () => {
+    const path = window.prompt("Export cached messages to file:", MESSAGES_EXPORT_DEFAULT_PATH);
+    if (!path) return;
+    const appId = selectedApp === "all" ? null : Number(selectedApp);
+    await startOperation("export_messages", { path, appId });
+  };
+
+  const onCancelOperation = () => {
+    if (!operation) return;
+    void invoke<boolean>("cancel_operation", { opId: operation.op_id });
+  };
+
   const sortedMessages = useMemo(() => {
     const renderStart = performance.now();
-    const sorted = [...messages].sort(compareMessagesNewestFirst);
+    const cachedIds = new Set(messages.map((message) => message.id));
+    const sorted = [...messages, ...olderMessages.filter((message) => !cachedIds.has(message.id))].sort(
+      compareMessagesNewestFirst,
+    );
     const elapsedMs = Math.round((performance.now() - renderStart) * 100) / 100;
     debugUi("list sort/render prep", {
       count: sorted.length,
@@ -771,7 +1859,33 @@ export function App() {
       at: Date.now(),
     });
     return sorted;
-  }, [messages]);
+  }, [messages, olderMessages]);
+
+  useEffect(() => {
+    setOlderMessages([]);
+    setHasOlderMessages(true);
+    setOlderMessagesCursor(null);
+  }, [activeProfile]);
+
+  const onLoadOlderMessages = async () => {
+    if (loadingOlderMessages || !hasOlderMessages) return;
+    // The first page starts below the oldest cached message.
+    const beforeId =
+      olderMessagesCursor ?? (messages.length > 0 ? Math.min(...messages.map((message) => message.id)) : null);
+    if (beforeId === null) return;
+    setLoadingOlderMessages(true);
+    try {
+      const page = await invoke<OlderMessagesPage>("load_older_messages", { beforeId });
+      setOlderMessages((current) => [...current, ...toUiMessagesSnapshot(page.messages)]);
+      setOlderMessagesCursor(page.next_before_id);
+      setHasOlderMessages(page.next_before_id !== null);
+    } catch (error) {
+      setHasOlderMessages(false);
+      setFeedback({ kind: "error", message: String(error) });
+    } finally {
+      setLoadingOlderMessages(false);
+    }
+  };
 
   const appGroups = useMemo<AppGroup[]>(() => {
     const groups = new Map<string, AppGroup>();
@@ -793,11 +1907,39 @@ export function App() {
     return Array.from(groups.values());
   }, [sortedMessages]);
 
+  useEffect(() => {
+    const query = searchQuery.trim();
+    if (isQuickWindow || !query) {
+      setSearchHitIds(null);
+      return;
+    }
+    let cancelled = false;
+    // Re-runs when the cache changes so new arrivals show up in the results.
+    const timer = window.setTimeout(() => {
+      void invoke<UiMessage[]>("search_messages", {
+        query,
+        appId: selectedApp === "all" ? null : Number(selectedApp),
+        limit: SEARCH_RESULT_LIMIT,
+      })
+        .then((hits) => {
+          if (!cancelled) setSearchHitIds(new Set(hits.map((message) => message.id)));
+        })
+        .catch((error) => {
+          if (!cancelled) setFeedback({ kind: "error", message: `Search failed: ${String(error)}` });
+        });
+    }, SEARCH_DEBOUNCE_MS);
+    return () => {
+      cancelled = true;
+      window.clearTimeout(timer);
+    };
+  }, [isQuickWindow, messages, searchQuery, selectedApp]);
+
   const filteredMessages = useMemo(() => {
     if (isQuickWindow) return sortedMessages;
+    if (searchHitIds) return sortedMessages.filter((message) => searchHitIds.has(message.id));
     if (selectedApp === "all") return sortedMessages;
     return sortedMessages.filter((message) => String(message.app_id || 0) === selectedApp);
-  }, [isQuickWindow, sortedMessages, selectedApp]);
+  }, [isQuickWindow, searchHitIds, sortedMessages, selectedApp]);
   const isWindowed = filteredMessages.length > WINDOWING_THRESHOLD;
 
   useEffect(() => {
@@ -987,6 +2129,30 @@ export function App() {
 
         {feedback ? <div className={feedback.kind === "ok" ? "feedback ok" : "feedback error"}>{feedback.message}</div> : null}
         {!feedback && streamErrorMessage ? <div className="feedback error">{streamErrorMessage}</div> : null}
+        {operation ? (
+          <div className="feedback ok operation-progress">
+            <span>
+              {operation.kind === "export" ? "Exporting" : "Deleting"} {operation.done}/{operation.total}
+              {operation.failed > 0 ? ` (${operation.failed} failed)` : ""}
+            </span>
+            <progress max={Math.max(operation.total, 1)} value={operation.done} />
+            <button type="button" className="utility-button" onClick={onCancelOperation} disabled={operation.cancelled}>
+              Cancel
+            </button>
+          </div>
+        ) : null}
+        {cleanupSuggestion && !isQuickWindow ? (
+          <div className="feedback ok">
+            {cleanupSuggestion.candidate_count} read message(s) are older than {cleanupSuggestion.older_than_days} days.
+            Delete them from the server?{" "}
+            <button type="button" className="utility-button" onClick={() => void onAcceptCleanup()}>
+              Delete
+            </button>{" "}
+            <button type="button" className="utility-button" onClick={() => setCleanupSuggestion(null)}>
+              Not Now
+            </button>
+          </div>
+        ) : null}
 
         <MessageFeed
           isQuickWindow={isQuickWindow}
@@ -1008,6 +2174,30 @@ export function App() {
           setSelectedMessageId={setSelectedMessageId}
           setWindowRange={setWindowRange}
           onDeleteMessage={onDeleteMessage}
+          onMarkRead={onMarkRead}
+          onTogglePinned={onTogglePinned}
+          onSnooze={onSnooze}
+          onEditLabels={onEditLabels}
+          onDismiss={onDismiss}
+          selectionMode={selectionMode}
+          selectedIds={selectedIds}
+          operationRunning={operation !== null}
+          onToggleSelected={onToggleSelected}
+          onToggleSelectionMode={onToggleSelectionMode}
+          onDeleteSelected={() => void onDeleteSelected()}
+          onDeleteAppMessages={() => void onDeleteAppMessages()}
+          onExportMessages={() => void onExportMessages()}
+          acknowledgeMinPriority={
+            acknowledgement.enabled && appTokenNames.includes(acknowledgement.app_token_name)
+              ? acknowledgement.min_priority
+              : null
+          }
+          onAcknowledge={(messageId) => void onAcknowledgeMessage(messageId)}
+          hasOlderMessages={hasOlderMessages}
+          loadingOlderMessages={loadingOlderMessages}
+          onLoadOlderMessages={() => void onLoadOlderMessages()}
+          searchQuery={searchQuery}
+          onSearchQueryChange={setSearchQuery}
         />
         {!isQuickWindow ? (
           <>
@@ -1047,7 +2237,19 @@ export function App() {
             ) : null}
             <aside className={drawerTab ? "drawer-panel open" : "drawer-panel"} aria-hidden={drawerTab == null}>
           <div className="drawer-head">
-            <h2>{drawerTab === "diagnostics" ? "Diagnostics" : "Settings"}</h2>
+            <h2>
+              {drawerTab === "diagnostics"
+                ? "Diagnostics"
+                : drawerTab === "trash"
+                  ? "Trash"
+                  : drawerTab === "archive"
+                    ? "Archive"
+                    : drawerTab === "send"
+                      ? "Send Message"
+                      : drawerTab === "apps"
+                        ? "Applications"
+                        : "Settings"}
+            </h2>
             <button
               type="button"
               className="utility-button icon-button"
@@ -1070,10 +2272,53 @@ export function App() {
               <button
                 type="button"
                 className={drawerTab === "diagnostics" ? "drawer-tab active" : "drawer-tab"}
-                onClick={() => setDrawerTab("diagnostics")}
+                onClick={() => {
+                  setDrawerTab("diagnostics");
+                  void invoke<MemoryStats>("get_memory_stats")
+                    .then(setMemoryStats)
+                    .catch(() => {});
+                  void invoke<ServerInfo>("get_server_info")
+                    .then(setServerInfo)
+                    .catch(() => setServerInfo(null));
+                }}
               >
                 Diagnostics
               </button>
+              <button
+                type="button"
+                className={drawerTab === "trash" ? "drawer-tab active" : "drawer-tab"}
+                onClick={() => setDrawerTab("trash")}
+              >
+                Trash{trash.length > 0 ? ` (${trash.length})` : ""}
+              </button>
+              <button
+                type="button"
+                className={drawerTab === "archive" ? "drawer-tab active" : "drawer-tab"}
+                onClick={() => {
+                  setDrawerTab("archive");
+                  void loadArchivePage(0);
+                }}
+              >
+                Archive
+              </button>
+              <button
+                type="button"
+                className={drawerTab === "send" ? "drawer-tab active" : "drawer-tab"}
+                onClick={() => setDrawerTab("send")}
+              >
+                Send
+              </button>
+              <button
+                type="button"
+                className={drawerTab === "apps" ? "drawer-tab active" : "drawer-tab"}
+                onClick={() => {
+                  setDrawerTab("apps");
+                  if (applications === null) onReloadApplications();
+                  if (plugins === null) void runPluginCommand(async () => {}, null);
+                }}
+              >
+                Apps
+              </button>
             </div>
             <div className="drawer-tab-actions">
               {drawerTab === "settings" ? (
@@ -1095,6 +2340,8 @@ export function App() {
                 baseUrl={baseUrl}
                 token={token}
                 hasStoredToken={hasStoredToken}
+                tokenRevealed={tokenRevealed}
+                onRevealToken={() => void onRevealToken()}
                 minPriority={minPriority}
                 priorityThresholds={priorityThresholds}
                 quietStart={quietStart}
@@ -1103,6 +2350,82 @@ export function App() {
                 launchAtLogin={launchAtLogin}
                 startMinimizedToTray={startMinimizedToTray}
                 showPriorityInNotifications={showPriorityInNotifications}
+                privacyMode={privacyMode}
+                redactionPatterns={redactionPatterns}
+                trashRetentionDays={trashRetentionDays}
+                opTraceEnabled={opTraceEnabled}
+                profiles={profiles}
+                activeProfile={activeProfile}
+                appTokenNames={appTokenNames}
+                lanBaseUrl={lanBaseUrl}
+                hostPins={hostPins}
+                setHostPins={setHostPins}
+                healthCheckIntervalSecs={healthCheckIntervalSecs}
+                setHealthCheckIntervalSecs={setHealthCheckIntervalSecs}
+                onApplyHealthCheckInterval={() => void onApplyHealthCheckInterval()}
+                connectionMode={connectionMode}
+                onChangeConnectionMode={(mode) => void onChangeConnectionMode(mode)}
+                streamTokenInQuery={streamTokenInQuery}
+                onChangeStreamTokenInQuery={(enabled) => void onChangeStreamTokenInQuery(enabled)}
+                reconnectBackoff={reconnectBackoff}
+                setReconnectBackoff={setReconnectBackoff}
+                onApplyReconnectBackoff={() => void onApplyReconnectBackoff()}
+                requestTimeouts={requestTimeouts}
+                setRequestTimeouts={setRequestTimeouts}
+                onApplyRequestTimeouts={() => void onApplyRequestTimeouts()}
+                requestRetry={requestRetry}
+                setRequestRetry={setRequestRetry}
+                onApplyRequestRetry={() => void onApplyRequestRetry()}
+                messageRetention={messageRetention}
+                setMessageRetention={setMessageRetention}
+                onApplyMessageRetention={() => void onApplyMessageRetention()}
+                notificationSound={notificationSound}
+                setNotificationSound={setNotificationSound}
+                onApplyNotificationSound={() =>
+                  void runNotificationSettingsCommand(
+                    "set_notification_sound",
+                    { sound: notificationSound },
+                    "Notification sound saved.",
+                  )
+                }
+                customSounds={customSounds}
+                onImportSound={() => void onImportSound()}
+                onPreviewSound={onPreviewSound}
+                soundBands={soundBands}
+                maxSoundBands={maxSoundBands}
+                setSoundBands={setSoundBands}
+                onApplySoundBands={() =>
+                  void runNotificationSettingsCommand(
+                    "set_notification_sound_bands",
+                    { bands: soundBands },
+                    "Sound bands saved.",
+                  )
+                }
+                contextNames={contextNames}
+                activeContext={activeContext}
+                onSaveContext={onSaveContext}
+                onSwitchContext={onSwitchContext}
+                onDeleteContext={onDeleteContext}
+                setLanBaseUrl={setLanBaseUrl}
+                onApplyLanBaseUrl={() => void onApplyLanBaseUrl()}
+                acknowledgement={acknowledgement}
+                setAcknowledgement={setAcknowledgement}
+                onApplyAcknowledgement={() => void onApplyAcknowledgement()}
+                androidActionRules={androidActionRules}
+                setAndroidActionRules={setAndroidActionRules}
+                onApplyAndroidActionRules={() =>
+                  void runNotificationSettingsCommand(
+                    "set_android_action_rules",
+                    { rules: androidActionRules },
+                    "Intent rules saved.",
+                  )
+                }
+                appChoices={appGroups
+                  .filter((group) => group.key !== "0")
+                  .map((group) => ({ id: Number(group.key), name: group.name }))}
+                faultInjection={faultInjection}
+                setFaultInjection={setFaultInjection}
+                onApplyFaultInjection={() => void onApplyFaultInjection()}
                 themePreference={themePreference}
                 isLoading={isLoading}
                 isSaving={isSaving}
@@ -1111,6 +2434,19 @@ export function App() {
                 feedback={feedback}
                 onSave={onSave}
                 onTest={onTest}
+                onSaveProfile={onSaveProfile}
+                settingsPassphrase={settingsPassphrase}
+                setSettingsPassphrase={setSettingsPassphrase}
+                onExportSettings={() => void onExportSettings()}
+                onImportSettings={() => void onImportSettings()}
+                onImportAndroidBackup={() => void onImportAndroidBackup()}
+                onImportMessages={() => void onImportMessages()}
+                onSwitchProfile={onSwitchProfile}
+                onDeleteProfile={onDeleteProfile}
+                onEditProfileBadge={(profile) => void onEditProfileBadge(profile)}
+                onToggleProfileBackgroundStream={(profile) => void onToggleProfileBackgroundStream(profile)}
+                onAddAppToken={onAddAppToken}
+                onDeleteAppToken={onDeleteAppToken}
                 setBaseUrl={setBaseUrl}
                 setToken={setToken}
                 setMinPriority={setMinPriority}
@@ -1122,17 +2458,77 @@ export function App() {
                 setLaunchAtLogin={setLaunchAtLogin}
                 setStartMinimizedToTray={setStartMinimizedToTray}
                 setShowPriorityInNotifications={setShowPriorityInNotifications}
+                setPrivacyMode={setPrivacyMode}
+                setRedactionPatterns={setRedactionPatterns}
+                setTrashRetentionDays={setTrashRetentionDays}
+                setOpTraceEnabled={setOpTraceEnabled}
                 setThemePreference={setThemePreference}
               />
             ) : null}
 
+            {drawerTab === "apps" ? (
+              <>
+                <AccountPanel user={currentUser} busy={applicationsBusy} onChangePassword={onChangePassword} />
+                <ApplicationsPanel
+                  applications={applications}
+                  busy={applicationsBusy}
+                  onReload={onReloadApplications}
+                  onCreate={onCreateApplication}
+                  onRename={onRenameApplication}
+                  onUploadImage={onUploadApplicationImage}
+                  mutedAppIds={mutedAppIds}
+                  onToggleMute={onToggleAppMute}
+                  onDelete={onDeleteApplication}
+                />
+                <PluginsPanel
+                  plugins={plugins}
+                  busy={applicationsBusy}
+                  onReload={() => void runPluginCommand(async () => {}, null)}
+                  onToggle={onTogglePlugin}
+                  onLoadDetails={onLoadPluginDetails}
+                  onSaveConfig={onSavePluginConfig}
+                />
+              </>
+            ) : null}
+
+            {drawerTab === "send" ? (
+              <SendMessagePanel appTokenNames={appTokenNames} onSend={onSendMessage} />
+            ) : null}
+
+            {drawerTab === "trash" ? (
+              <TrashPanel
+                trash={trash}
+                retentionDays={trashRetentionDays}
+                onRestore={(messageId) => void onRestoreFromTrash(messageId)}
+                onEmpty={() => void onEmptyTrash()}
+              />
+            ) : null}
+
+            {drawerTab === "archive" ? (
+              <ArchivePanel
+                enabled={archiveRemoteDeletions}
+                archived={archivedMessages}
+                total={archiveTotal}
+                hasMore={archiveNextOffset !== null}
+                loading={loadingArchive}
+                onToggle={(enabled) => void onToggleArchive(enabled)}
+                onLoadMore={() => {
+                  if (archiveNextOffset !== null) void loadArchivePage(archiveNextOffset);
+                }}
+              />
+            ) : null}
+
             {drawerTab === "diagnostics" ? (
               <DiagnosticsPanel
                 baseUrl={baseUrl}
                 connectionState={connectionState}
                 diagnostics={diagnostics}
                 messageCount={messages.length}
+                memoryStats={memoryStats}
+                serverInfo={serverInfo}
                 streamIdleSeconds={streamIdleSeconds}
+                opTraceEvents={opTraceEvents}
+                onClearOpTrace={() => setOpTraceEvents([])}
                 onForceReconnect={() => {
                   void invoke<DomainSnapshot<RuntimeDiagnostics>>("restart_stream")
                     .then((snapshot) => {
@@ -1140,6 +2536,13 @@ export function App() {
                     })
                     .catch(() => {});
                 }}
+                onReconnectNow={() => {
+                  void invoke<DomainSnapshot<RuntimeDiagnostics>>("reconnect_now")
+                    .then((snapshot) => {
+                      applyRuntimeSnapshot(snapshot);
+                    })
+                    .catch(() => {});
+                }}
               />
             ) : null}
           </div>
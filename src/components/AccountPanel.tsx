@@ -0,0 +1,69 @@
+import { FormEvent, useState } from "react";
+import type { CurrentUser } from "../types";
+
+type AccountPanelProps = {
+  user: CurrentUser | null;
+  busy: boolean;
+  onChangePassword: (password: string) => Promise<boolean>;
+};
+
+export function AccountPanel({ user, busy, onChangePassword }: AccountPanelProps) {
+  const [password, setPassword] = useState("");
+  const [confirmation, setConfirmation] = useState("");
+  const [isSaving, setIsSaving] = useState(false);
+
+  const mismatch = confirmation !== "" && password !== confirmation;
+
+  const onSubmit = async (event: FormEvent<HTMLFormElement>) => {
+    event.preventDefault();
+    if (password === "" || password !== confirmation) return;
+    setIsSaving(true);
+    const changed = await onChangePassword(password);
+    setIsSaving(false);
+    if (changed) {
+      setPassword("");
+      setConfirmation("");
+    }
+  };
+
+  return (
+    <form className="settings-card" onSubmit={(event) => void onSubmit(event)} style={{ marginBottom: 12 }}>
+      <div className="settings-field">
+        <span className="settings-label">Account</span>
+        <span className="settings-hint">
+          {user ? `Signed in as ${user.name}${user.admin ? " (admin)" : ""}` : "Loading account..."}
+        </span>
+      </div>
+      <label className="settings-field">
+        <span className="settings-label">New password</span>
+        <input
+          type="password"
+          autoComplete="new-password"
+          value={password}
+          onChange={(event) => setPassword(event.target.value)}
+          disabled={busy || isSaving}
+        />
+      </label>
+      <label className="settings-field">
+        <span className="settings-label">Repeat password</span>
+        {mismatch ? <span className="settings-hint">Passwords do not match</span> : null}
+        <input
+          type="password"
+          autoComplete="new-password"
+          value={confirmation}
+          onChange={(event) => setConfirmation(event.target.value)}
+          disabled={busy || isSaving}
+        />
+      </label>
+      <div className="settings-inline-actions">
+        <button
+          type="submit"
+          className="secondary-button"
+          disabled={busy || isSaving || password === "" || password !== confirmation}
+        >
+          {isSaving ? "Changing..." : "Change Password"}
+        </button>
+      </div>
+    </form>
+  );
+}
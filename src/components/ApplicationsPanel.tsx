@@ -0,0 +1,92 @@
+import type { GotifyApplication } from "../types";
+
+type ApplicationsPanelProps = {
+  applications: GotifyApplication[] | null;
+  busy: boolean;
+  onReload: () => void;
+  onCreate: () => void;
+  onRename: (application: GotifyApplication) => void;
+  onUploadImage: (application: GotifyApplication) => void;
+  mutedAppIds: number[];
+  onToggleMute: (application: GotifyApplication) => void;
+  onDelete: (application: GotifyApplication) => void;
+};
+
+export function ApplicationsPanel({
+  applications,
+  busy,
+  onReload,
+  onCreate,
+  onRename,
+  onUploadImage,
+  mutedAppIds,
+  onToggleMute,
+  onDelete,
+}: ApplicationsPanelProps) {
+  return (
+    <>
+      <div className="actions">
+        <button type="button" className="utility-button" onClick={onCreate} disabled={busy}>
+          New Application
+        </button>
+        <button type="button" className="utility-button" onClick={onReload} disabled={busy}>
+          Reload
+        </button>
+      </div>
+      {applications === null ? (
+        <p className="settings-hint" style={{ marginTop: 12 }}>
+          Loading applications...
+        </p>
+      ) : applications.length === 0 ? (
+        <p className="settings-hint" style={{ marginTop: 12 }}>
+          No applications on this server.
+        </p>
+      ) : (
+        <div className="diagnostics" style={{ marginTop: 12 }}>
+          {applications.map((application) => (
+            <div key={application.id} className="settings-inline-actions">
+              <span>
+                <strong>{application.name}</strong>
+                {application.description ? ` — ${application.description}` : ""}
+                {` (P${application.default_priority})`}
+                {mutedAppIds.includes(application.id) ? " · muted" : ""}
+              </span>
+              <button
+                type="button"
+                className="utility-button"
+                onClick={() => onRename(application)}
+                disabled={busy || application.internal}
+              >
+                Rename
+              </button>
+              <button
+                type="button"
+                className="utility-button"
+                onClick={() => onUploadImage(application)}
+                disabled={busy || application.internal}
+              >
+                Image
+              </button>
+              <button
+                type="button"
+                className="utility-button"
+                onClick={() => onToggleMute(application)}
+                disabled={busy}
+              >
+                {mutedAppIds.includes(application.id) ? "Unmute" : "Mute"}
+              </button>
+              <button
+                type="button"
+                className="utility-button"
+                onClick={() => onDelete(application)}
+                disabled={busy || application.internal}
+              >
+                Delete
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+    </>
+  );
+}
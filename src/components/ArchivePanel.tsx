@@ -0,0 +1,47 @@
+import type { ArchivedMessage } from "../types";
+
+type ArchivePanelProps = {
+  enabled: boolean;
+  archived: ArchivedMessage[];
+  total: number;
+  hasMore: boolean;
+  loading: boolean;
+  onToggle: (enabled: boolean) => void;
+  onLoadMore: () => void;
+};
+
+export function ArchivePanel({ enabled, archived, total, hasMore, loading, onToggle, onLoadMore }: ArchivePanelProps) {
+  return (
+    <>
+      <label className="settings-toggle">
+        <span className="settings-label">Keep messages deleted on the server</span>
+        <input type="checkbox" checked={enabled} onChange={(event) => onToggle(event.target.checked)} />
+      </label>
+      {archived.length === 0 ? (
+        <p className="settings-hint" style={{ marginTop: 12 }}>
+          {enabled
+            ? "Messages that disappear from the server during a sync are kept here."
+            : "The archive is off; messages deleted on the server are dropped from the cache."}
+        </p>
+      ) : (
+        <div className="diagnostics" style={{ marginTop: 12 }}>
+          <p className="settings-hint">{total} archived</p>
+          {archived.map((entry) => (
+            <div key={entry.message.id} className="settings-inline-actions">
+              <span>
+                {entry.message.app}: <strong>{entry.message.title || "(no title)"}</strong>
+                {" — archived "}
+                {new Date(entry.archived_at * 1000).toLocaleDateString()}
+              </span>
+            </div>
+          ))}
+          {hasMore ? (
+            <button type="button" className="utility-button" onClick={onLoadMore} disabled={loading}>
+              {loading ? "Loading..." : "Load more"}
+            </button>
+          ) : null}
+        </div>
+      )}
+    </>
+  );
+}
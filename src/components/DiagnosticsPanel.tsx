@@ -1,28 +1,95 @@
-import type { ConnectionState, RuntimeDiagnostics } from "../types";
+import type {
+  ConnectionState,
+  MemoryStats,
+  OpTraceEvent,
+  RollingLatency,
+  RuntimeDiagnostics,
+  ServerInfo,
+} from "../types";
 
 type DiagnosticsPanelProps = {
   baseUrl: string;
   connectionState: ConnectionState;
   diagnostics: RuntimeDiagnostics | null;
   messageCount: number;
+  memoryStats: MemoryStats | null;
+  serverInfo: ServerInfo | null;
   streamIdleSeconds: number;
+  opTraceEvents: OpTraceEvent[];
+  onClearOpTrace: () => void;
   onForceReconnect: () => void;
+  onReconnectNow: () => void;
 };
 
+function formatLatency(latency: RollingLatency): string {
+  return `${latency.last_ms} ms (min ${latency.min_ms} / avg ${latency.avg_ms} / max ${latency.max_ms}, ${latency.count} samples)`;
+}
+
+function formatBytes(bytes: number): string {
+  if (bytes >= 1024 * 1024) return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+  if (bytes >= 1024) return `${Math.round(bytes / 1024)} KB`;
+  return `${bytes} B`;
+}
+
 export function DiagnosticsPanel({
   baseUrl,
   connectionState,
   diagnostics,
   messageCount,
+  memoryStats,
+  serverInfo,
   streamIdleSeconds,
+  opTraceEvents,
+  onClearOpTrace,
   onForceReconnect,
+  onReconnectNow,
 }: DiagnosticsPanelProps) {
   return (
     <>
       <div className="diagnostics">
         <div><span>Server:</span> <strong>{baseUrl || "—"}</strong></div>
+        {diagnostics?.active_endpoint ? (
+          <div><span>Using:</span> <strong>{diagnostics.active_endpoint}</strong></div>
+        ) : null}
+        {diagnostics?.server_health ? (
+          <div>
+            <span>Server health:</span>{" "}
+            <strong>
+              {diagnostics.server_health}
+              {diagnostics.server_health !== "unreachable" ? `, database ${diagnostics.database_health ?? "?"}` : ""}
+              {diagnostics.last_health_check_at
+                ? ` (${new Date(diagnostics.last_health_check_at * 1000).toLocaleTimeString()})`
+                : ""}
+            </strong>
+          </div>
+        ) : null}
+        {serverInfo ? (
+          <div>
+            <span>Server version:</span>{" "}
+            <strong>
+              Gotify {serverInfo.version || "unknown"}
+              {serverInfo.commit ? ` (${serverInfo.commit.slice(0, 7)})` : ""}
+            </strong>
+          </div>
+        ) : null}
         <div><span>Connection:</span> <strong>{diagnostics?.connection_state ?? connectionState}</strong></div>
         <div><span>Cached messages:</span> <strong>{messageCount}</strong></div>
+        {diagnostics ? (
+          <div>
+            <span>Cache encryption:</span>{" "}
+            <strong>{diagnostics.encrypted_at_rest ? "On (Keychain key)" : "Off (Keychain key unavailable)"}</strong>
+          </div>
+        ) : null}
+        {memoryStats ? (
+          <div>
+            <span>Cache memory:</span>{" "}
+            <strong>
+              {formatBytes(memoryStats.message_cache_bytes + memoryStats.app_meta_bytes)} (icons{" "}
+              {formatBytes(memoryStats.message_icon_bytes + memoryStats.app_meta_icon_bytes)}
+              {memoryStats.disk_backed_icons > 0 ? `, ${memoryStats.disk_backed_icons} on disk` : ""})
+            </strong>
+          </div>
+        ) : null}
         <div>
           <span>Last connected:</span>{" "}
           <strong>
@@ -40,7 +107,27 @@ export function DiagnosticsPanel({
           </strong>
         </div>
         <div><span>Stream idle:</span> <strong>{streamIdleSeconds}s</strong></div>
+        {diagnostics?.stream_remote_addr ? (
+          <div><span>Stream address:</span> <strong>{diagnostics.stream_remote_addr}</strong></div>
+        ) : null}
+        {diagnostics?.handshake_latency ? (
+          <div><span>Connect time:</span> <strong>{formatLatency(diagnostics.handshake_latency)}</strong></div>
+        ) : null}
+        {diagnostics?.ping_rtt ? (
+          <div><span>Ping round trip:</span> <strong>{formatLatency(diagnostics.ping_rtt)}</strong></div>
+        ) : null}
         <div><span>Reconnect attempts:</span> <strong>{diagnostics?.reconnect_attempts ?? 0}</strong></div>
+        {diagnostics && diagnostics.watchdog_recoveries > 0 ? (
+          <div>
+            <span>Watchdog recoveries:</span>{" "}
+            <strong>
+              {diagnostics.watchdog_recoveries}
+              {diagnostics.last_watchdog_recovery_at
+                ? `, last ${new Date(diagnostics.last_watchdog_recovery_at * 1000).toLocaleString()} (${diagnostics.last_watchdog_recovery ?? "?"})`
+                : ""}
+            </strong>
+          </div>
+        ) : null}
         {(diagnostics?.backoff_seconds ?? 0) > 0 ? (
           <div><span>Backoff:</span> <strong>{diagnostics!.backoff_seconds}s</strong></div>
         ) : null}
@@ -52,18 +139,72 @@ export function DiagnosticsPanel({
               : "Never"}
           </strong>
         </div>
+        {diagnostics?.clock_skew_seconds != null ? (
+          <div><span>Clock skew vs server:</span> <strong>{diagnostics.clock_skew_seconds}s</strong></div>
+        ) : null}
         {diagnostics?.last_error ? (
           <div>
             <span>Last error:</span>{" "}
             <strong style={{ color: "var(--pill-disconnected-color)" }}>{diagnostics.last_error}</strong>
           </div>
         ) : null}
+        {diagnostics?.background_streams.map((stream) => (
+          <div key={stream.profile}>
+            <span>{stream.profile} (background):</span>{" "}
+            <strong>
+              {stream.connection_state}
+              {stream.reconnect_attempts > 0 ? `, ${stream.reconnect_attempts} reconnects` : ""}
+              {stream.last_error ? ` \u2014 ${stream.last_error}` : ""}
+            </strong>
+          </div>
+        ))}
       </div>
       <div className="actions" style={{ marginTop: 12 }}>
+        {(diagnostics?.connection_state ?? connectionState) === "Backoff" ? (
+          <button type="button" className="utility-button" onClick={onReconnectNow}>
+            Reconnect Now
+          </button>
+        ) : null}
         <button type="button" className="utility-button" onClick={onForceReconnect}>
           Force Reconnect
         </button>
+        {opTraceEvents.length > 0 ? (
+          <button type="button" className="utility-button" onClick={onClearOpTrace}>
+            Clear Trace
+          </button>
+        ) : null}
       </div>
+      {diagnostics && diagnostics.recent_connection_events.length > 0 ? (
+        <div className="diagnostics" style={{ marginTop: 12 }}>
+          {diagnostics.recent_connection_events.map((event, index) => (
+            <div key={`${event.at_ms}-${index}`}>
+              <span>
+                {new Date(event.at_ms).toLocaleTimeString()}
+                {event.profile ? ` ${event.profile}` : ""}:
+              </span>{" "}
+              <strong>
+                {event.kind.replace(/_/g, " ")}
+                {event.detail ? ` \u2014 ${event.detail}` : ""}
+              </strong>
+            </div>
+          ))}
+        </div>
+      ) : null}
+      {opTraceEvents.length > 0 ? (
+        <div className="diagnostics" style={{ marginTop: 12 }}>
+          {opTraceEvents.map((event, index) => (
+            <div key={`${event.at_ms}-${index}`}>
+              <span>
+                {new Date(event.at_ms).toLocaleTimeString()} {event.op} {event.subject}:
+              </span>{" "}
+              <strong>
+                {event.phase}
+                {event.status != null ? ` (${event.status})` : ""} — {event.detail}
+              </strong>
+            </div>
+          ))}
+        </div>
+      ) : null}
     </>
   );
 }
@@ -4,6 +4,13 @@ import type { AppGroup, PriorityThreshold, UiMessage, UrlPreview } from "../type
 import { initials } from "../utils/selection";
 import { computeWindowRange } from "../utils/windowing";
 
+const SNOOZE_CHOICES = [
+  { minutes: 15, label: "15 minutes" },
+  { minutes: 60, label: "1 hour" },
+  { minutes: 4 * 60, label: "4 hours" },
+  { minutes: 24 * 60, label: "1 day" },
+];
+
 type MessageFeedProps = {
   isQuickWindow: boolean;
   selectedApp: string;
@@ -24,6 +31,28 @@ type MessageFeedProps = {
   setSelectedMessageId: (id: number | null) => void;
   setWindowRange: (range: { start: number; end: number }) => void;
   onDeleteMessage: (messageId: number) => Promise<void>;
+  onMarkRead: (messageIds: number[] | null) => void;
+  onTogglePinned: (message: { id: number; pinned: boolean }) => void;
+  onSnooze: (messageId: number, minutes: number) => void;
+  onEditLabels: (message: { id: number; labels?: string[] }) => void;
+  onDismiss: (messageId: number) => void;
+  selectionMode: boolean;
+  selectedIds: Record<number, boolean>;
+  operationRunning: boolean;
+  onToggleSelected: (messageId: number) => void;
+  onToggleSelectionMode: () => void;
+  onDeleteSelected: () => void;
+  onDeleteAppMessages: () => void;
+  onExportMessages: () => void;
+  /** Null while the acknowledge action is off. */
+  acknowledgeMinPriority: number | null;
+  onAcknowledge: (messageId: number) => void;
+  /** False once the server has no older messages than the list shows. */
+  hasOlderMessages: boolean;
+  loadingOlderMessages: boolean;
+  onLoadOlderMessages: () => void;
+  searchQuery: string;
+  onSearchQueryChange: (query: string) => void;
 };
 
 export function MessageFeed({
@@ -46,9 +75,33 @@ export function MessageFeed({
   setSelectedMessageId,
   setWindowRange,
   onDeleteMessage,
+  onMarkRead,
+  onTogglePinned,
+  onSnooze,
+  onEditLabels,
+  onDismiss,
+  selectionMode,
+  selectedIds,
+  operationRunning,
+  onToggleSelected,
+  onToggleSelectionMode,
+  onDeleteSelected,
+  onDeleteAppMessages,
+  onExportMessages,
+  acknowledgeMinPriority,
+  onAcknowledge,
+  hasOlderMessages,
+  loadingOlderMessages,
+  onLoadOlderMessages,
+  searchQuery,
+  onSearchQueryChange,
 }: MessageFeedProps) {
   const themeBadgeColor = getThemeBadgeColor();
+  // Search covers the cache only, so older server pages are not offered meanwhile.
+  const searching = searchQuery.trim() !== "";
   const [showScrollTopButton, setShowScrollTopButton] = useState(false);
+  const hasUnread = filteredMessages.some((message) => !message.read);
+  const selectedCount = Object.keys(selectedIds).length;
 
   useEffect(() => {
     const list = messageListRef.current;
@@ -62,6 +115,10 @@ export function MessageFeed({
 
   const handleListScroll = (target: HTMLUListElement) => {
     setShowScrollTopButton(target.scrollTop > 180);
+    const nearBottom = target.scrollHeight - target.scrollTop - target.clientHeight < 400;
+    if (nearBottom && hasOlderMessages && !loadingOlderMessages && !isQuickWindow && !searching) {
+      onLoadOlderMessages();
+    }
     if (!isWindowed) return;
     setWindowRange(
       computeWindowRange(filteredMessages.length, target.scrollTop, target.clientHeight, estimatedRowHeightRef.current)
@@ -124,11 +181,59 @@ export function MessageFeed({
             <div>
               <h2>{selectedAppName}</h2>
             </div>
+            <div className="feed-header-actions">
+              <input
+                type="search"
+                className="feed-search"
+                placeholder="Search"
+                aria-label="Search messages"
+                value={searchQuery}
+                onChange={(event) => onSearchQueryChange(event.target.value)}
+              />
+              {selectionMode ? (
+                <button
+                  type="button"
+                  className="danger-button subtle"
+                  onClick={onDeleteSelected}
+                  disabled={selectedCount === 0 || operationRunning}
+                >
+                  Delete {selectedCount}
+                </button>
+              ) : null}
+              {!selectionMode && selectedApp !== "all" ? (
+                <button
+                  type="button"
+                  className="utility-button"
+                  onClick={onDeleteAppMessages}
+                  disabled={filteredMessages.length === 0 || operationRunning}
+                >
+                  Delete All
+                </button>
+              ) : null}
+              {!selectionMode ? (
+                <button
+                  type="button"
+                  className="utility-button"
+                  onClick={onExportMessages}
+                  disabled={filteredMessages.length === 0 || operationRunning}
+                >
+                  Export
+                </button>
+              ) : null}
+              <button type="button" className="utility-button" onClick={onToggleSelectionMode}>
+                {selectionMode ? "Done" : "Select"}
+              </button>
+              {hasUnread && !selectionMode ? (
+                <button type="button" className="utility-button" onClick={() => onMarkRead(null)}>
+                  Mark All Read
+                </button>
+              ) : null}
+            </div>
           </div>
         ) : null}
 
         {filteredMessages.length === 0 ? (
-          <p className="help">No messages cached yet.</p>
+          <p className="help">{searching ? "No messages match your search." : "No messages cached yet."}</p>
         ) : (
           <ul
             ref={messageListRef}
@@ -148,8 +253,20 @@ export function MessageFeed({
                 <li
                   key={message.id}
                   data-message-id={message.id}
-                  className={message.id === activeMessage?.id ? "message-item selected" : "message-item"}
+                  className={[
+                    "message-item",
+                    message.id === activeMessage?.id ? "selected" : "",
+                    message.snoozed_until ? "snoozed" : message.read ? "" : "unread",
+                    message.pinned ? "pinned" : "",
+                  ]
+                    .filter(Boolean)
+                    .join(" ")}
                   onClick={() => {
+                    if (selectionMode) {
+                      onToggleSelected(message.id);
+                      return;
+                    }
+                    if (!message.read) onMarkRead([message.id]);
                     if (isQuickWindow) {
                       setSelectedMessageId(message.id);
                       return;
@@ -163,6 +280,15 @@ export function MessageFeed({
                 >
                   <div className="message-row-top">
                     <div className="message-title-wrap">
+                      {selectionMode ? (
+                        <input
+                          type="checkbox"
+                          aria-label="Select message"
+                          checked={Boolean(selectedIds[message.id])}
+                          onChange={() => onToggleSelected(message.id)}
+                          onClick={(event) => event.stopPropagation()}
+                        />
+                      ) : null}
                       {isQuickWindow ? (
                         <span className="quick-message-icon-wrap">
                           {message.app_icon ? (
@@ -201,6 +327,11 @@ export function MessageFeed({
                     >
                       P{message.priority}
                     </span>
+                    {message.labels?.map((label) => (
+                      <span key={label} className="label-pill">
+                        {label}
+                      </span>
+                    ))}
                   </div>
                   <div
                     className="markdown-body list-message-body"
@@ -219,6 +350,75 @@ export function MessageFeed({
                     </a>
                   ) : null}
                   <div className="message-row-actions">
+                    {acknowledgeMinPriority !== null && message.priority >= acknowledgeMinPriority ? (
+                      <button
+                        type="button"
+                        className="utility-button"
+                        title="Let others know this was picked up"
+                        onClick={(event) => {
+                          event.stopPropagation();
+                          onAcknowledge(message.id);
+                        }}
+                      >
+                        Ack
+                      </button>
+                    ) : null}
+                    <button
+                      type="button"
+                      className={message.pinned ? "utility-button active" : "utility-button"}
+                      aria-pressed={message.pinned}
+                      title={message.pinned ? "Unpin message" : "Pin message so it is never pruned"}
+                      onClick={(event) => {
+                        event.stopPropagation();
+                        onTogglePinned(message);
+                      }}
+                    >
+                      {message.pinned ? "Unpin" : "Pin"}
+                    </button>
+                    <button
+                      type="button"
+                      className="utility-button"
+                      title="Set labels for this message"
+                      onClick={(event) => {
+                        event.stopPropagation();
+                        onEditLabels(message);
+                      }}
+                    >
+                      Labels
+                    </button>
+                    <select
+                      className="snooze-select"
+                      aria-label="Snooze message"
+                      title={
+                        message.snoozed_until
+                          ? `Snoozed until ${new Date(message.snoozed_until * 1000).toLocaleString()}`
+                          : "Hide from unread and notify again later"
+                      }
+                      value=""
+                      onClick={(event) => event.stopPropagation()}
+                      onChange={(event) => {
+                        const minutes = Number(event.target.value);
+                        if (minutes > 0) onSnooze(message.id, minutes);
+                      }}
+                    >
+                      <option value="">{message.snoozed_until ? "Snoozed" : "Snooze"}</option>
+                      {SNOOZE_CHOICES.map((choice) => (
+                        <option key={choice.minutes} value={choice.minutes}>
+                          {choice.label}
+                        </option>
+                      ))}
+                    </select>
+                    <button
+                      type="button"
+                      className="utility-button"
+                      title="Hide on this Mac; other clients keep it"
+                      onClick={(event) => {
+                        event.stopPropagation();
+                        onDismiss(message.id);
+                      }}
+                    >
+                      Hide
+                    </button>
                     <button
                       type="button"
                       className="danger-button subtle icon-button"
@@ -238,6 +438,18 @@ export function MessageFeed({
             {isWindowed && bottomSpacerPx > 0 ? (
               <li aria-hidden="true" className="message-spacer" style={{ height: `${bottomSpacerPx}px` }} />
             ) : null}
+            {!isQuickWindow && hasOlderMessages && !searching ? (
+              <li className="message-spacer">
+                <button
+                  type="button"
+                  className="utility-button"
+                  onClick={onLoadOlderMessages}
+                  disabled={loadingOlderMessages}
+                >
+                  {loadingOlderMessages ? "Loading..." : "Load older messages"}
+                </button>
+              </li>
+            ) : null}
           </ul>
         )}
         {showScrollTopButton ? (
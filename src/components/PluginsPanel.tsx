@@ -0,0 +1,108 @@
+import { useState } from "react";
+import type { GotifyPlugin } from "../types";
+
+export type PluginDetails = {
+  display: string | null;
+  config: string | null;
+};
+
+type PluginsPanelProps = {
+  plugins: GotifyPlugin[] | null;
+  busy: boolean;
+  onReload: () => void;
+  onToggle: (plugin: GotifyPlugin) => void;
+  onLoadDetails: (plugin: GotifyPlugin) => Promise<PluginDetails | null>;
+  onSaveConfig: (plugin: GotifyPlugin, config: string) => Promise<boolean>;
+};
+
+export function PluginsPanel({ plugins, busy, onReload, onToggle, onLoadDetails, onSaveConfig }: PluginsPanelProps) {
+  const [openId, setOpenId] = useState<number | null>(null);
+  const [details, setDetails] = useState<PluginDetails | null>(null);
+  const [config, setConfig] = useState("");
+
+  const onOpen = async (plugin: GotifyPlugin) => {
+    if (openId === plugin.id) {
+      setOpenId(null);
+      return;
+    }
+    setOpenId(plugin.id);
+    setDetails(null);
+    const loaded = await onLoadDetails(plugin);
+    setDetails(loaded);
+    setConfig(loaded?.config ?? "");
+  };
+
+  const onSave = async (plugin: GotifyPlugin) => {
+    if (await onSaveConfig(plugin, config)) {
+      setDetails((current) => (current ? { ...current, config } : current));
+    }
+  };
+
+  return (
+    <>
+      <div className="actions" style={{ marginTop: 16 }}>
+        <strong>Plugins</strong>
+        <button type="button" className="utility-button" onClick={onReload} disabled={busy}>
+          Reload Plugins
+        </button>
+      </div>
+      {plugins === null ? null : plugins.length === 0 ? (
+        <p className="settings-hint" style={{ marginTop: 12 }}>
+          No plugins installed on this server.
+        </p>
+      ) : (
+        <div className="diagnostics" style={{ marginTop: 12 }}>
+          {plugins.map((plugin) => (
+            <div key={plugin.id}>
+              <div className="settings-inline-actions">
+                <span>
+                  <strong>{plugin.name}</strong>
+                  {plugin.author ? ` by ${plugin.author}` : ""}
+                  {plugin.enabled ? "" : " (disabled)"}
+                </span>
+                <button type="button" className="utility-button" onClick={() => onToggle(plugin)} disabled={busy}>
+                  {plugin.enabled ? "Disable" : "Enable"}
+                </button>
+                {plugin.capabilities.includes("displayer") || plugin.capabilities.includes("configurer") ? (
+                  <button type="button" className="utility-button" onClick={() => void onOpen(plugin)} disabled={busy}>
+                    {openId === plugin.id ? "Hide" : "Details"}
+                  </button>
+                ) : null}
+              </div>
+              {openId === plugin.id ? (
+                details === null ? (
+                  <p className="settings-hint">Loading...</p>
+                ) : (
+                  <div className="settings-field">
+                    {details.display ? <pre style={{ whiteSpace: "pre-wrap" }}>{details.display}</pre> : null}
+                    {details.config !== null ? (
+                      <>
+                        <textarea
+                          rows={8}
+                          value={config}
+                          onChange={(event) => setConfig(event.target.value)}
+                          disabled={busy}
+                          spellCheck={false}
+                        />
+                        <div className="settings-inline-actions">
+                          <button
+                            type="button"
+                            className="secondary-button"
+                            onClick={() => void onSave(plugin)}
+                            disabled={busy || config === details.config}
+                          >
+                            Save Config
+                          </button>
+                        </div>
+                      </>
+                    ) : null}
+                  </div>
+                )
+              ) : null}
+            </div>
+          ))}
+        </div>
+      )}
+    </>
+  );
+}
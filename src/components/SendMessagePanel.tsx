@@ -0,0 +1,100 @@
+import { FormEvent, useState } from "react";
+
+export type SendMessageInput = {
+  appToken: string;
+  title: string;
+  message: string;
+  priority: number;
+  markdown: boolean;
+};
+
+type SendMessagePanelProps = {
+  appTokenNames: string[];
+  onSend: (input: SendMessageInput) => Promise<boolean>;
+};
+
+export function SendMessagePanel({ appTokenNames, onSend }: SendMessagePanelProps) {
+  const [appToken, setAppToken] = useState(appTokenNames[0] ?? "");
+  const [title, setTitle] = useState("");
+  const [message, setMessage] = useState("");
+  const [priority, setPriority] = useState(5);
+  const [markdown, setMarkdown] = useState(false);
+  const [isSending, setIsSending] = useState(false);
+
+  if (appTokenNames.length === 0) {
+    return (
+      <p className="settings-hint">
+        Add an application token under Settings - Connection to send messages.
+      </p>
+    );
+  }
+
+  const selectedToken = appTokenNames.includes(appToken) ? appToken : appTokenNames[0];
+
+  const onSubmit = async (event: FormEvent<HTMLFormElement>) => {
+    event.preventDefault();
+    setIsSending(true);
+    const sent = await onSend({ appToken: selectedToken, title, message, priority, markdown });
+    setIsSending(false);
+    if (sent) {
+      setTitle("");
+      setMessage("");
+    }
+  };
+
+  return (
+    <form className="settings-form" onSubmit={(event) => void onSubmit(event)}>
+      <div className="settings-card">
+        <label className="settings-field">
+          <span className="settings-label">Send as</span>
+          <select value={selectedToken} onChange={(event) => setAppToken(event.target.value)} disabled={isSending}>
+            {appTokenNames.map((name) => (
+              <option key={name} value={name}>
+                {name}
+              </option>
+            ))}
+          </select>
+        </label>
+        <label className="settings-field">
+          <span className="settings-label">Title</span>
+          <input type="text" value={title} onChange={(event) => setTitle(event.target.value)} disabled={isSending} />
+        </label>
+        <label className="settings-field">
+          <span className="settings-label">Message</span>
+          <textarea
+            rows={5}
+            value={message}
+            onChange={(event) => setMessage(event.target.value)}
+            required
+            disabled={isSending}
+          />
+        </label>
+        <label className="settings-field">
+          <span className="settings-label">Priority</span>
+          <input
+            type="number"
+            min={0}
+            max={10}
+            value={priority}
+            onChange={(event) => setPriority(Number(event.target.value || 0))}
+            disabled={isSending}
+          />
+        </label>
+        <label className="settings-toggle">
+          <span className="settings-label">Render as Markdown</span>
+          <input
+            type="checkbox"
+            checked={markdown}
+            onChange={(event) => setMarkdown(event.target.checked)}
+            disabled={isSending}
+          />
+        </label>
+        <div className="settings-inline-actions">
+          <button type="submit" className="secondary-button" disabled={isSending || message.trim() === ""}>
+            {isSending ? "Sending..." : "Send"}
+          </button>
+        </div>
+      </div>
+    </form>
+  );
+}
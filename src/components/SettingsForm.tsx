@@ -1,10 +1,54 @@
 import { FormEvent } from "react";
-import type { PriorityThreshold, ThemePreference } from "../types";
+import type {
+  AckSettings,
+  AndroidAction,
+  AndroidActionRule,
+  ConnectionMode,
+  FaultInjection,
+  PriorityThreshold,
+  ProfileSummary,
+  ReconnectBackoff,
+  RequestTimeouts,
+  RetryPolicy,
+  MessageRetention,
+  SoundBand,
+  ThemePreference,
+} from "../types";
+
+const NOTIFICATION_SOUNDS = [
+  "default",
+  "none",
+  "Basso",
+  "Blow",
+  "Bottle",
+  "Frog",
+  "Funk",
+  "Glass",
+  "Hero",
+  "Morse",
+  "Ping",
+  "Pop",
+  "Purr",
+  "Sosumi",
+  "Submarine",
+  "Tink",
+];
+
+const ANDROID_ACTION_LABELS: Record<AndroidAction, string> = {
+  open_url: "Open URL",
+  star: "Star",
+  mark_read: "Mark read",
+  archive: "Archive",
+  silence: "No notification",
+  ignore: "Do nothing",
+};
 
 type SettingsFormProps = {
   baseUrl: string;
   token: string;
   hasStoredToken: boolean;
+  tokenRevealed: boolean;
+  onRevealToken: () => void;
   minPriority: number;
   priorityThresholds: PriorityThreshold[];
   quietStart: string;
@@ -13,6 +57,62 @@ type SettingsFormProps = {
   launchAtLogin: boolean;
   startMinimizedToTray: boolean;
   showPriorityInNotifications: boolean;
+  privacyMode: boolean;
+  redactionPatterns: string;
+  trashRetentionDays: number;
+  opTraceEnabled: boolean;
+  profiles: ProfileSummary[];
+  activeProfile: string | null;
+  appTokenNames: string[];
+  lanBaseUrl: string;
+  hostPins: string;
+  setHostPins: (value: string) => void;
+  healthCheckIntervalSecs: number;
+  setHealthCheckIntervalSecs: (value: number) => void;
+  onApplyHealthCheckInterval: () => void;
+  connectionMode: ConnectionMode;
+  onChangeConnectionMode: (mode: ConnectionMode) => void;
+  streamTokenInQuery: boolean;
+  onChangeStreamTokenInQuery: (enabled: boolean) => void;
+  reconnectBackoff: ReconnectBackoff;
+  setReconnectBackoff: (value: ReconnectBackoff) => void;
+  onApplyReconnectBackoff: () => void;
+  requestTimeouts: RequestTimeouts;
+  setRequestTimeouts: (value: RequestTimeouts) => void;
+  onApplyRequestTimeouts: () => void;
+  requestRetry: RetryPolicy;
+  setRequestRetry: (value: RetryPolicy) => void;
+  onApplyRequestRetry: () => void;
+  messageRetention: MessageRetention;
+  setMessageRetention: (value: MessageRetention) => void;
+  onApplyMessageRetention: () => void;
+  notificationSound: string;
+  setNotificationSound: (value: string) => void;
+  onApplyNotificationSound: () => void;
+  customSounds: string[];
+  onImportSound: () => void;
+  onPreviewSound: (sound: string) => void;
+  soundBands: SoundBand[];
+  maxSoundBands: number;
+  setSoundBands: (value: SoundBand[]) => void;
+  onApplySoundBands: () => void;
+  contextNames: string[];
+  activeContext: string | null;
+  onSaveContext: () => void;
+  onSwitchContext: (name: string) => void;
+  onDeleteContext: (name: string) => void;
+  setLanBaseUrl: (value: string) => void;
+  onApplyLanBaseUrl: () => void;
+  acknowledgement: AckSettings;
+  setAcknowledgement: (value: AckSettings) => void;
+  onApplyAcknowledgement: () => void;
+  androidActionRules: AndroidActionRule[];
+  setAndroidActionRules: (value: AndroidActionRule[]) => void;
+  onApplyAndroidActionRules: () => void;
+  appChoices: { id: number; name: string }[];
+  faultInjection: FaultInjection | null;
+  setFaultInjection: (value: FaultInjection) => void;
+  onApplyFaultInjection: () => void;
   themePreference: ThemePreference;
   isLoading: boolean;
   isSaving: boolean;
@@ -21,6 +121,19 @@ type SettingsFormProps = {
   feedback: { kind: "ok" | "error"; message: string } | null;
   onSave: (event: FormEvent<HTMLFormElement>) => void;
   onTest: () => void;
+  onSaveProfile: () => void;
+  onSwitchProfile: (name: string) => void;
+  onDeleteProfile: (name: string) => void;
+  onEditProfileBadge: (profile: ProfileSummary) => void;
+  onToggleProfileBackgroundStream: (profile: ProfileSummary) => void;
+  onAddAppToken: () => void;
+  onDeleteAppToken: (name: string) => void;
+  settingsPassphrase: string;
+  setSettingsPassphrase: (value: string) => void;
+  onExportSettings: () => void;
+  onImportSettings: () => void;
+  onImportAndroidBackup: () => void;
+  onImportMessages: () => void;
   setBaseUrl: (value: string) => void;
   setToken: (value: string) => void;
   setMinPriority: (value: number) => void;
@@ -32,6 +145,10 @@ type SettingsFormProps = {
   setLaunchAtLogin: (value: boolean) => void;
   setStartMinimizedToTray: (value: boolean) => void;
   setShowPriorityInNotifications: (value: boolean) => void;
+  setPrivacyMode: (value: boolean) => void;
+  setRedactionPatterns: (value: string) => void;
+  setTrashRetentionDays: (value: number) => void;
+  setOpTraceEnabled: (value: boolean) => void;
   setThemePreference: (value: ThemePreference) => void;
 };
 
@@ -40,6 +157,8 @@ export function SettingsForm(props: SettingsFormProps) {
     baseUrl,
     token,
     hasStoredToken,
+    tokenRevealed,
+    onRevealToken,
     minPriority,
     priorityThresholds,
     quietStart,
@@ -48,6 +167,62 @@ export function SettingsForm(props: SettingsFormProps) {
     launchAtLogin,
     startMinimizedToTray,
     showPriorityInNotifications,
+    privacyMode,
+    redactionPatterns,
+    trashRetentionDays,
+    opTraceEnabled,
+    profiles,
+    activeProfile,
+    appTokenNames,
+    lanBaseUrl,
+    hostPins,
+    setHostPins,
+    healthCheckIntervalSecs,
+    setHealthCheckIntervalSecs,
+    onApplyHealthCheckInterval,
+    connectionMode,
+    onChangeConnectionMode,
+    streamTokenInQuery,
+    onChangeStreamTokenInQuery,
+    reconnectBackoff,
+    setReconnectBackoff,
+    onApplyReconnectBackoff,
+    requestTimeouts,
+    setRequestTimeouts,
+    onApplyRequestTimeouts,
+    requestRetry,
+    setRequestRetry,
+    onApplyRequestRetry,
+    messageRetention,
+    setMessageRetention,
+    onApplyMessageRetention,
+    notificationSound,
+    setNotificationSound,
+    onApplyNotificationSound,
+    customSounds,
+    onImportSound,
+    onPreviewSound,
+    soundBands,
+    maxSoundBands,
+    setSoundBands,
+    onApplySoundBands,
+    contextNames,
+    activeContext,
+    onSaveContext,
+    onSwitchContext,
+    onDeleteContext,
+    setLanBaseUrl,
+    onApplyLanBaseUrl,
+    acknowledgement,
+    setAcknowledgement,
+    onApplyAcknowledgement,
+    androidActionRules,
+    setAndroidActionRules,
+    onApplyAndroidActionRules,
+    appChoices,
+    faultInjection,
+    setFaultInjection,
+    onApplyFaultInjection,
     themePreference,
     isLoading,
     isSaving,
@@ -56,6 +231,19 @@ export function SettingsForm(props: SettingsFormProps) {
     feedback,
     onSave,
     onTest,
+    onSaveProfile,
+    onSwitchProfile,
+    onDeleteProfile,
+    onEditProfileBadge,
+    onToggleProfileBackgroundStream,
+    onAddAppToken,
+    onDeleteAppToken,
+    settingsPassphrase,
+    setSettingsPassphrase,
+    onExportSettings,
+    onImportSettings,
+    onImportAndroidBackup,
+    onImportMessages,
     setBaseUrl,
     setToken,
     setMinPriority,
@@ -67,6 +255,10 @@ export function SettingsForm(props: SettingsFormProps) {
     setLaunchAtLogin,
     setStartMinimizedToTray,
     setShowPriorityInNotifications,
+    setPrivacyMode,
+    setRedactionPatterns,
+    setTrashRetentionDays,
+    setOpTraceEnabled,
     setThemePreference,
   } = props;
   const disabled = isLoading || isSaving || isTesting;
@@ -93,11 +285,202 @@ export function SettingsForm(props: SettingsFormProps) {
               disabled={disabled}
             />
           </label>
+          <div className="settings-field">
+            <span className="settings-label">LAN URL</span>
+            <span className="settings-hint">
+              Optional second address of the same server. Whichever is reachable and faster is used.
+            </span>
+            <div className="settings-inline-actions">
+              <input
+                type="url"
+                value={lanBaseUrl}
+                onChange={(event) => setLanBaseUrl(event.target.value)}
+                placeholder="http://gotify.local"
+                disabled={disabled}
+              />
+              <button type="button" className="secondary-button" onClick={onApplyLanBaseUrl} disabled={disabled}>
+                Apply
+              </button>
+            </div>
+          </div>
+          <label className="settings-field">
+            <span className="settings-label">DNS overrides</span>
+            <span className="settings-hint">
+              One host=address per line, like curl's --resolve. Saved with this server profile.
+            </span>
+            <textarea
+              rows={2}
+              spellCheck={false}
+              placeholder="gotify.example.com=192.168.1.10"
+              value={hostPins}
+              onChange={(event) => setHostPins(event.target.value)}
+              disabled={disabled}
+            />
+          </label>
+          <label className="settings-toggle">
+            <span className="settings-label">Send token in stream URL</span>
+            <span className="settings-hint">
+              For reverse proxies that drop the X-Gotify-Key header on websocket upgrade. Saved with this server profile.
+            </span>
+            <input
+              type="checkbox"
+              checked={streamTokenInQuery}
+              onChange={(event) => onChangeStreamTokenInQuery(event.target.checked)}
+              disabled={disabled}
+            />
+          </label>
+          <label className="settings-field">
+            <span className="settings-label">Live updates</span>
+            <span className="settings-hint">
+              Polling checks for new messages every 10 seconds on networks that block websockets. Auto polls only while
+              the websocket keeps failing.
+            </span>
+            <select
+              value={connectionMode}
+              onChange={(event) => onChangeConnectionMode(event.target.value as ConnectionMode)}
+              disabled={disabled}
+            >
+              <option value="websocket">Websocket</option>
+              <option value="auto">Auto</option>
+              <option value="polling">Polling</option>
+            </select>
+          </label>
+          <div className="settings-field">
+            <span className="settings-label">Reconnect delay (s)</span>
+            <span className="settings-hint">
+              Wait after a failed connection, multiplied after each further failure up to the maximum.
+            </span>
+            <div className="settings-inline-actions">
+              <input
+                type="number"
+                min={1}
+                max={3600}
+                value={reconnectBackoff.base_secs}
+                onChange={(event) =>
+                  setReconnectBackoff({ ...reconnectBackoff, base_secs: Number(event.target.value || 1) })
+                }
+                title="Initial delay (s)"
+                disabled={disabled}
+              />
+              <input
+                type="number"
+                min={1}
+                max={10}
+                step={0.5}
+                value={reconnectBackoff.multiplier}
+                onChange={(event) =>
+                  setReconnectBackoff({ ...reconnectBackoff, multiplier: Number(event.target.value || 1) })
+                }
+                title="Multiplier"
+                disabled={disabled}
+              />
+              <input
+                type="number"
+                min={1}
+                max={3600}
+                value={reconnectBackoff.max_secs}
+                onChange={(event) =>
+                  setReconnectBackoff({ ...reconnectBackoff, max_secs: Number(event.target.value || 1) })
+                }
+                title="Maximum delay (s)"
+                disabled={disabled}
+              />
+              <button type="button" className="secondary-button" onClick={onApplyReconnectBackoff} disabled={disabled}>
+                Apply
+              </button>
+            </div>
+          </div>
+          <div className="settings-field">
+            <span className="settings-label">Timeouts (s)</span>
+            <span className="settings-hint">How long a server request, and opening the live connection, may take.</span>
+            <div className="settings-inline-actions">
+              <input
+                type="number"
+                min={1}
+                max={300}
+                value={requestTimeouts.request_secs}
+                onChange={(event) =>
+                  setRequestTimeouts({ ...requestTimeouts, request_secs: Number(event.target.value || 1) })
+                }
+                title="Request timeout (s)"
+                disabled={disabled}
+              />
+              <input
+                type="number"
+                min={1}
+                max={300}
+                value={requestTimeouts.stream_connect_secs}
+                onChange={(event) =>
+                  setRequestTimeouts({ ...requestTimeouts, stream_connect_secs: Number(event.target.value || 1) })
+                }
+                title="Stream connect timeout (s)"
+                disabled={disabled}
+              />
+              <button type="button" className="secondary-button" onClick={onApplyRequestTimeouts} disabled={disabled}>
+                Apply
+              </button>
+            </div>
+          </div>
+          <div className="settings-field">
+            <span className="settings-label">Request attempts</span>
+            <span className="settings-hint">
+              Server requests that fail with a network error or a server error are tried again, waiting the delay (ms)
+              and doubling it each time.
+            </span>
+            <div className="settings-inline-actions">
+              <input
+                type="number"
+                min={1}
+                max={10}
+                value={requestRetry.attempts}
+                onChange={(event) => setRequestRetry({ ...requestRetry, attempts: Number(event.target.value || 1) })}
+                title="Attempts"
+                disabled={disabled}
+              />
+              <input
+                type="number"
+                min={1}
+                max={10000}
+                step={100}
+                value={requestRetry.base_delay_ms}
+                onChange={(event) =>
+                  setRequestRetry({ ...requestRetry, base_delay_ms: Number(event.target.value || 1) })
+                }
+                title="First retry delay (ms)"
+                disabled={disabled}
+              />
+              <button type="button" className="secondary-button" onClick={onApplyRequestRetry} disabled={disabled}>
+                Apply
+              </button>
+            </div>
+          </div>
+          <div className="settings-field">
+            <span className="settings-label">Health check every (s)</span>
+            <span className="settings-hint">Polls the server's /health so a failing database shows in the tray. 0 turns it off.</span>
+            <div className="settings-inline-actions">
+              <input
+                type="number"
+                min={0}
+                max={3600}
+                value={healthCheckIntervalSecs}
+                onChange={(event) => setHealthCheckIntervalSecs(Number(event.target.value || 0))}
+                disabled={disabled}
+              />
+              <button
+                type="button"
+                className="secondary-button"
+                onClick={onApplyHealthCheckInterval}
+                disabled={disabled}
+              >
+                Apply
+              </button>
+            </div>
+          </div>
           <label className="settings-field">
             <span className="settings-label">Client token</span>
             <span className="settings-hint">Found in Gotify - Clients</span>
             <input
-              type="password"
+              type={tokenRevealed ? "text" : "password"}
               value={token}
               onChange={(event) => setToken(event.target.value)}
               placeholder={hasStoredToken ? "Leave blank to keep existing" : "Enter Gotify client token"}
@@ -122,6 +505,84 @@ export function SettingsForm(props: SettingsFormProps) {
               >
                 {isTesting ? "Testing..." : "Test Connection"}
               </button>
+              <button type="button" className="secondary-button" onClick={onSaveProfile} disabled={disabled}>
+                Save as Profile
+              </button>
+              {hasStoredToken && !tokenRevealed && (
+                <button type="button" className="secondary-button" onClick={onRevealToken} disabled={disabled}>
+                  Reveal Token
+                </button>
+              )}
+            </div>
+          </div>
+          {profiles.length > 0 ? (
+            <div className="settings-field">
+              <span className="settings-label">Saved servers</span>
+              <span className="settings-hint">Also available from the tray menu</span>
+              {profiles.map((profile) => (
+                <div key={profile.name} className="settings-inline-actions">
+                  <span style={profile.color ? { borderLeft: `4px solid ${profile.color}`, paddingLeft: 6 } : undefined}>
+                    {profile.name === activeProfile ? "\u2713 " : ""}
+                    {profile.badge ? `${profile.badge} ` : ""}
+                    {profile.name} ({profile.base_url})
+                  </span>
+                  <button
+                    type="button"
+                    className="utility-button"
+                    onClick={() => onEditProfileBadge(profile)}
+                    disabled={disabled}
+                  >
+                    Badge
+                  </button>
+                  <label className="settings-hint">
+                    <input
+                      type="checkbox"
+                      checked={profile.background_stream}
+                      onChange={() => onToggleProfileBackgroundStream(profile)}
+                      disabled={disabled}
+                    />{" "}
+                    Stay connected
+                  </label>
+                  <button
+                    type="button"
+                    className="utility-button"
+                    onClick={() => onSwitchProfile(profile.name)}
+                    disabled={disabled || profile.name === activeProfile}
+                  >
+                    Switch
+                  </button>
+                  <button
+                    type="button"
+                    className="utility-button"
+                    onClick={() => onDeleteProfile(profile.name)}
+                    disabled={disabled || profile.name === activeProfile}
+                  >
+                    Remove
+                  </button>
+                </div>
+              ))}
+            </div>
+          ) : null}
+          <div className="settings-field">
+            <span className="settings-label">Application tokens</span>
+            <span className="settings-hint">Used to send messages; found in Gotify - Apps. Stored per server.</span>
+            {appTokenNames.map((name) => (
+              <div key={name} className="settings-inline-actions">
+                <span>{name}</span>
+                <button
+                  type="button"
+                  className="utility-button"
+                  onClick={() => onDeleteAppToken(name)}
+                  disabled={disabled}
+                >
+                  Remove
+                </button>
+              </div>
+            ))}
+            <div className="settings-inline-actions">
+              <button type="button" className="secondary-button" onClick={onAddAppToken} disabled={disabled}>
+                Add Application Token
+              </button>
             </div>
           </div>
         </div>
@@ -130,6 +591,141 @@ export function SettingsForm(props: SettingsFormProps) {
       <div className="settings-group">
         <p className="settings-group-title">Notifications</p>
         <div className="settings-card">
+          <div className="settings-field">
+            <span className="settings-label">Context</span>
+            <span className="settings-hint">
+              Saved sets of minimum priority, quiet hours, muted apps and sound. Also in the tray menu.
+            </span>
+            {contextNames.map((name) => (
+              <div key={name} className="settings-inline-actions">
+                <span>
+                  {name === activeContext ? "\u2713 " : ""}
+                  {name}
+                </span>
+                <button
+                  type="button"
+                  className="utility-button"
+                  onClick={() => onSwitchContext(name)}
+                  disabled={disabled}
+                >
+                  Switch
+                </button>
+                <button
+                  type="button"
+                  className="utility-button"
+                  onClick={() => onDeleteContext(name)}
+                  disabled={disabled}
+                >
+                  Remove
+                </button>
+              </div>
+            ))}
+            <div className="settings-inline-actions">
+              <button type="button" className="secondary-button" onClick={onSaveContext} disabled={disabled}>
+                Save Current as Context
+              </button>
+            </div>
+          </div>
+          <div className="settings-field">
+            <span className="settings-label">Sound</span>
+            <span className="settings-hint">
+              "default", "none", a system sound such as Ping, Glass or Submarine, or an imported .aiff/.caf sound
+            </span>
+            <div className="settings-inline-actions">
+              <input
+                type="text"
+                list="notification-sounds"
+                value={notificationSound}
+                onChange={(event) => setNotificationSound(event.target.value)}
+                disabled={disabled}
+              />
+              <datalist id="notification-sounds">
+                {[...NOTIFICATION_SOUNDS, ...customSounds].map((sound) => (
+                  <option key={sound} value={sound} />
+                ))}
+              </datalist>
+              <button
+                type="button"
+                className="utility-button"
+                onClick={() => onPreviewSound(notificationSound)}
+                disabled={disabled}
+              >
+                Preview
+              </button>
+              <button type="button" className="utility-button" onClick={onImportSound} disabled={disabled}>
+                Import Sound
+              </button>
+              <button
+                type="button"
+                className="secondary-button"
+                onClick={onApplyNotificationSound}
+                disabled={disabled}
+              >
+                Apply
+              </button>
+            </div>
+          </div>
+          <div className="settings-field">
+            <span className="settings-label">Sound by priority</span>
+            <span className="settings-hint">
+              Each band applies from its priority up to the next band. Priorities below the lowest band use the sound
+              above.
+            </span>
+            {soundBands.map((band, index) => {
+              const updateBand = (next: Partial<SoundBand>) =>
+                setSoundBands(soundBands.map((current, position) => (position === index ? { ...current, ...next } : current)));
+              return (
+                <div key={index} className="settings-inline-actions">
+                  <input
+                    type="number"
+                    min={0}
+                    max={10}
+                    aria-label="From priority"
+                    value={band.min_priority}
+                    onChange={(event) => updateBand({ min_priority: Number(event.target.value || 0) })}
+                    disabled={disabled}
+                  />
+                  <input
+                    type="text"
+                    list="notification-sounds"
+                    aria-label="Sound"
+                    value={band.sound}
+                    onChange={(event) => updateBand({ sound: event.target.value })}
+                    disabled={disabled}
+                  />
+                  <button
+                    type="button"
+                    className="utility-button"
+                    onClick={() => onPreviewSound(band.sound)}
+                    disabled={disabled}
+                  >
+                    Preview
+                  </button>
+                  <button
+                    type="button"
+                    className="utility-button"
+                    onClick={() => setSoundBands(soundBands.filter((_, position) => position !== index))}
+                    disabled={disabled}
+                  >
+                    Remove
+                  </button>
+                </div>
+              );
+            })}
+            <div className="settings-inline-actions">
+              <button
+                type="button"
+                className="utility-button"
+                onClick={() => setSoundBands([...soundBands, { min_priority: 0, sound: "default" }])}
+                disabled={disabled || soundBands.length >= maxSoundBands}
+              >
+                Add Band
+              </button>
+              <button type="button" className="secondary-button" onClick={onApplySoundBands} disabled={disabled}>
+                Apply
+              </button>
+            </div>
+          </div>
           <label className="settings-field">
             <span className="settings-label">Minimum priority</span>
             <span className="settings-hint">Only notify for messages at this priority or above (0-10)</span>
@@ -151,6 +747,165 @@ export function SettingsForm(props: SettingsFormProps) {
               disabled={disabled}
             />
           </label>
+          <label className="settings-toggle">
+            <span className="settings-label">Privacy mode</span>
+            <span className="settings-hint">Notifications show only the sender, not the title or body</span>
+            <input
+              type="checkbox"
+              checked={privacyMode}
+              onChange={(event) => setPrivacyMode(event.target.checked)}
+              disabled={disabled}
+            />
+          </label>
+          <div className="settings-field">
+            <span className="settings-label">Acknowledge action</span>
+            <span className="settings-hint">
+              Adds an Acknowledge button to notifications that posts a message with an application token, so others
+              on the server see the alert was picked up. Placeholders: {"{title}"}, {"{app}"}, {"{id}"}, {"{priority}"}.
+            </span>
+            <label className="settings-inline-actions">
+              <span>Enabled</span>
+              <input
+                type="checkbox"
+                checked={acknowledgement.enabled}
+                onChange={(event) => setAcknowledgement({ ...acknowledgement, enabled: event.target.checked })}
+                disabled={disabled || appTokenNames.length === 0}
+              />
+            </label>
+            <label className="settings-inline-actions">
+              <span>Send with</span>
+              <select
+                value={acknowledgement.app_token_name}
+                onChange={(event) => setAcknowledgement({ ...acknowledgement, app_token_name: event.target.value })}
+                disabled={disabled}
+              >
+                <option value="">Choose an application token</option>
+                {appTokenNames.map((name) => (
+                  <option key={name} value={name}>
+                    {name}
+                  </option>
+                ))}
+              </select>
+            </label>
+            <label className="settings-inline-actions">
+              <span>Message</span>
+              <input
+                type="text"
+                maxLength={500}
+                value={acknowledgement.message_template}
+                onChange={(event) => setAcknowledgement({ ...acknowledgement, message_template: event.target.value })}
+                disabled={disabled}
+              />
+            </label>
+            <label className="settings-inline-actions">
+              <span>Minimum priority</span>
+              <input
+                type="number"
+                min={0}
+                max={10}
+                value={acknowledgement.min_priority}
+                onChange={(event) =>
+                  setAcknowledgement({ ...acknowledgement, min_priority: Number(event.target.value || 0) })
+                }
+                disabled={disabled}
+              />
+            </label>
+            <div className="settings-inline-actions">
+              <button type="button" className="secondary-button" onClick={onApplyAcknowledgement} disabled={disabled}>
+                Apply
+              </button>
+            </div>
+          </div>
+          <div className="settings-field">
+            <span className="settings-label">Android intents</span>
+            <span className="settings-hint">
+              What to do when a new message carries an <code>android::action</code> onReceive intent. The first rule
+              whose URL prefix and app match wins; an empty prefix matches any URL.
+            </span>
+            {androidActionRules.map((rule, index) => {
+              const updateRule = (next: Partial<AndroidActionRule>) =>
+                setAndroidActionRules(
+                  androidActionRules.map((current, position) => (position === index ? { ...current, ...next } : current)),
+                );
+              return (
+                <div key={index} className="settings-inline-actions">
+                  <input
+                    type="text"
+                    placeholder="https://"
+                    maxLength={500}
+                    value={rule.url_prefix}
+                    onChange={(event) => updateRule({ url_prefix: event.target.value })}
+                    disabled={disabled}
+                  />
+                  <select
+                    value={rule.app_id == null ? "" : String(rule.app_id)}
+                    onChange={(event) =>
+                      updateRule({ app_id: event.target.value === "" ? null : Number(event.target.value) })
+                    }
+                    disabled={disabled}
+                  >
+                    <option value="">Any app</option>
+                    {rule.app_id != null && !appChoices.some((choice) => choice.id === rule.app_id) ? (
+                      <option value={String(rule.app_id)}>App #{rule.app_id}</option>
+                    ) : null}
+                    {appChoices.map((choice) => (
+                      <option key={choice.id} value={String(choice.id)}>
+                        {choice.name}
+                      </option>
+                    ))}
+                  </select>
+                  <select
+                    value={rule.action}
+                    onChange={(event) => updateRule({ action: event.target.value as AndroidAction })}
+                    disabled={disabled}
+                  >
+                    {(Object.keys(ANDROID_ACTION_LABELS) as AndroidAction[]).map((action) => (
+                      <option key={action} value={action}>
+                        {ANDROID_ACTION_LABELS[action]}
+                      </option>
+                    ))}
+                  </select>
+                  <button
+                    type="button"
+                    className="utility-button"
+                    onClick={() => setAndroidActionRules(androidActionRules.filter((_, position) => position !== index))}
+                    disabled={disabled}
+                  >
+                    Remove
+                  </button>
+                </div>
+              );
+            })}
+            <div className="settings-inline-actions">
+              <button
+                type="button"
+                className="utility-button"
+                onClick={() =>
+                  setAndroidActionRules([...androidActionRules, { url_prefix: "", app_id: null, action: "open_url" }])
+                }
+                disabled={disabled || androidActionRules.length >= 32}
+              >
+                Add Rule
+              </button>
+              <button type="button" className="secondary-button" onClick={onApplyAndroidActionRules} disabled={disabled}>
+                Apply
+              </button>
+            </div>
+          </div>
+          <label className="settings-field">
+            <span className="settings-label">Redaction patterns</span>
+            <span className="settings-hint">
+              One regular expression per line; matches are masked before messages are shown or cached
+            </span>
+            <textarea
+              rows={3}
+              spellCheck={false}
+              placeholder={"token=\\S+"}
+              value={redactionPatterns}
+              onChange={(event) => setRedactionPatterns(event.target.value)}
+              disabled={disabled}
+            />
+          </label>
           <div className="settings-field">
             <span className="settings-label">Priority colors</span>
             <span className="settings-hint">Thresholds apply when priority is greater than or equal to each value</span>
@@ -228,7 +983,7 @@ export function SettingsForm(props: SettingsFormProps) {
           </div>
           <div className="settings-field">
             <span className="settings-label">Quiet hours</span>
-            <span className="settings-hint">Suppress notifications between these hours (24-hour clock)</span>
+            <span className="settings-hint">Hold notifications between these hours and deliver them afterwards (24-hour clock)</span>
             <div className="settings-two-col">
               <label>
                 <span className="settings-sublabel">From</span>
@@ -264,16 +1019,59 @@ export function SettingsForm(props: SettingsFormProps) {
         <div className="settings-card">
           <label className="settings-field">
             <span className="settings-label">Message cache size</span>
-            <span className="settings-hint">Maximum messages stored locally (1-2000)</span>
+            <span className="settings-hint">Maximum messages stored locally (1-10000)</span>
             <input
               type="number"
               min={1}
-              max={2000}
+              max={10000}
               value={cacheLimit}
               onChange={(event) => setCacheLimit(Number(event.target.value || 100))}
               disabled={disabled}
             />
           </label>
+          <label className="settings-field">
+            <span className="settings-label">Keep deleted messages</span>
+            <span className="settings-hint">Days in Trash before removal from the server (0-90, 0 deletes immediately)</span>
+            <input
+              type="number"
+              min={0}
+              max={90}
+              value={trashRetentionDays}
+              onChange={(event) => setTrashRetentionDays(Number(event.target.value || 0))}
+              disabled={disabled}
+            />
+          </label>
+          <div className="settings-field">
+            <span className="settings-label">Prune messages older than (days)</span>
+            <span className="settings-hint">
+              Checked hourly. Starred, archived and imported messages are kept. 0 keeps everything up to the cache size.
+            </span>
+            <div className="settings-inline-actions">
+              <input
+                type="number"
+                min={0}
+                max={3650}
+                value={messageRetention.days}
+                onChange={(event) => setMessageRetention({ ...messageRetention, days: Number(event.target.value || 0) })}
+                title="Days"
+                disabled={disabled}
+              />
+              <label className="settings-toggle">
+                <span className="settings-label">Also delete on server</span>
+                <input
+                  type="checkbox"
+                  checked={messageRetention.delete_on_server}
+                  onChange={(event) =>
+                    setMessageRetention({ ...messageRetention, delete_on_server: event.target.checked })
+                  }
+                  disabled={disabled}
+                />
+              </label>
+              <button type="button" className="secondary-button" onClick={onApplyMessageRetention} disabled={disabled}>
+                Apply
+              </button>
+            </div>
+          </div>
           <label className="settings-toggle">
             <span className="settings-label">Launch at login</span>
             <input
@@ -292,6 +1090,115 @@ export function SettingsForm(props: SettingsFormProps) {
               disabled={disabled}
             />
           </label>
+          <label className="settings-toggle">
+            <span className="settings-label">Trace operations in Diagnostics</span>
+            <input
+              type="checkbox"
+              checked={opTraceEnabled}
+              onChange={(event) => setOpTraceEnabled(event.target.checked)}
+              disabled={disabled}
+            />
+          </label>
+          {faultInjection ? (
+            <div className="settings-field">
+              <span className="settings-label">Fault injection</span>
+              <span className="settings-hint">Debug builds only. Simulates a bad network for the stream and Gotify API calls.</span>
+              <label className="settings-inline-actions">
+                <span>Enabled</span>
+                <input
+                  type="checkbox"
+                  checked={faultInjection.enabled}
+                  onChange={(event) => setFaultInjection({ ...faultInjection, enabled: event.target.checked })}
+                  disabled={disabled}
+                />
+              </label>
+              <label className="settings-inline-actions">
+                <span>Drop stream after (s, 0 = never)</span>
+                <input
+                  type="number"
+                  min={0}
+                  value={faultInjection.drop_stream_after_secs}
+                  onChange={(event) =>
+                    setFaultInjection({ ...faultInjection, drop_stream_after_secs: Number(event.target.value || 0) })
+                  }
+                  disabled={disabled}
+                />
+              </label>
+              <label className="settings-inline-actions">
+                <span>HTTP delay (ms)</span>
+                <input
+                  type="number"
+                  min={0}
+                  max={60000}
+                  value={faultInjection.http_delay_ms}
+                  onChange={(event) =>
+                    setFaultInjection({ ...faultInjection, http_delay_ms: Number(event.target.value || 0) })
+                  }
+                  disabled={disabled}
+                />
+              </label>
+              <label className="settings-inline-actions">
+                <span>HTTP 500 rate (%)</span>
+                <input
+                  type="number"
+                  min={0}
+                  max={100}
+                  value={faultInjection.http_error_percent}
+                  onChange={(event) =>
+                    setFaultInjection({ ...faultInjection, http_error_percent: Number(event.target.value || 0) })
+                  }
+                  disabled={disabled}
+                />
+              </label>
+              <div className="settings-inline-actions">
+                <button type="button" className="secondary-button" onClick={onApplyFaultInjection} disabled={disabled}>
+                  Apply
+                </button>
+              </div>
+            </div>
+          ) : null}
+          <div className="settings-field">
+            <span className="settings-label">Move to another Mac</span>
+            <span className="settings-hint">Encrypted file with servers, tokens and notification rules</span>
+            <div className="settings-inline-actions">
+              <input
+                type="password"
+                value={settingsPassphrase}
+                onChange={(event) => setSettingsPassphrase(event.target.value)}
+                placeholder="Passphrase (at least 8 characters)"
+                autoComplete="new-password"
+                disabled={disabled}
+              />
+              <button type="button" className="secondary-button" onClick={onExportSettings} disabled={disabled}>
+                Export Settings
+              </button>
+              <button type="button" className="secondary-button" onClick={onImportSettings} disabled={disabled}>
+                Import Settings
+              </button>
+            </div>
+          </div>
+          <div className="settings-field">
+            <span className="settings-label">Android history</span>
+            <span className="settings-hint">
+              Merge messages from the Gotify Android client (JSON export or its database) into the active server's history
+            </span>
+            <div className="settings-inline-actions">
+              <button type="button" className="secondary-button" onClick={onImportAndroidBackup} disabled={disabled}>
+                Import Android Backup
+              </button>
+            </div>
+          </div>
+          <div className="settings-field">
+            <span className="settings-label">Message archive</span>
+            <span className="settings-hint">
+              Merge a file written by Export back into the active server's history; it is kept after the server prunes it
+            </span>
+            <div className="settings-inline-actions">
+              <button type="button" className="secondary-button" onClick={onImportMessages} disabled={disabled}>
+                Import Messages
+              </button>
+            </div>
+          </div>
         </div>
       </div>
 
@@ -0,0 +1,42 @@
+import type { TrashedMessage } from "../types";
+
+type TrashPanelProps = {
+  trash: TrashedMessage[];
+  retentionDays: number;
+  onRestore: (messageId: number) => void;
+  onEmpty: () => void;
+};
+
+export function TrashPanel({ trash, retentionDays, onRestore, onEmpty }: TrashPanelProps) {
+  return (
+    <>
+      <div className="actions">
+        <button type="button" className="utility-button" onClick={onEmpty} disabled={trash.length === 0}>
+          Empty Trash
+        </button>
+      </div>
+      {trash.length === 0 ? (
+        <p className="settings-hint" style={{ marginTop: 12 }}>
+          {retentionDays > 0
+            ? `Deleted messages stay here for ${retentionDays} day${retentionDays === 1 ? "" : "s"} before they are removed from the server.`
+            : "Trash is off; deleted messages are removed from the server immediately."}
+        </p>
+      ) : (
+        <div className="diagnostics" style={{ marginTop: 12 }}>
+          {trash.map((entry) => (
+            <div key={entry.message.id} className="settings-inline-actions">
+              <span>
+                {entry.message.app}: <strong>{entry.message.title || "(no title)"}</strong>
+                {" — purged "}
+                {new Date((entry.trashed_at + retentionDays * 86400) * 1000).toLocaleDateString()}
+              </span>
+              <button type="button" className="utility-button" onClick={() => onRestore(entry.message.id)}>
+                Restore
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+    </>
+  );
+}
@@ -1,4 +1,32 @@
-export type ConnectionState = "Connected" | "Disconnected" | "Connecting" | "Backoff";
+export type ConnectionState = "Connected" | "Polling" | "Disconnected" | "Connecting" | "Backoff" | "AuthFailed";
+
+/** `auto` uses the websocket and polls while it keeps failing. */
+export type ConnectionMode = "websocket" | "polling" | "auto";
+
+/** Reconnect delays: `base_secs`, multiplied per further failure, capped at `max_secs`. */
+export type ReconnectBackoff = {
+  base_secs: number;
+  multiplier: number;
+  max_secs: number;
+};
+
+/** Seconds allowed per REST request and for opening the websocket. */
+export type RequestTimeouts = {
+  request_secs: number;
+  stream_connect_secs: number;
+};
+
+/** REST retries on network errors and 5xx: `attempts` includes the first try. */
+export type RetryPolicy = {
+  attempts: number;
+  base_delay_ms: number;
+};
+
+/** Age limit for cached messages; `days` 0 is off. Starred, archived and imported messages are kept. */
+export type MessageRetention = {
+  days: number;
+  delete_on_server: boolean;
+};
 
 export type DomainSnapshot<T> = {
   revision: number;
@@ -6,6 +34,31 @@ export type DomainSnapshot<T> = {
   data: T;
 };
 
+export type FaultInjection = {
+  enabled: boolean;
+  drop_stream_after_secs: number;
+  http_delay_ms: number;
+  http_error_percent: number;
+};
+
+export type AckSettings = {
+  enabled: boolean;
+  app_token_name: string;
+  message_template: string;
+  min_priority: number;
+};
+
+export type AndroidAction = "open_url" | "star" | "mark_read" | "archive" | "silence" | "ignore";
+
+/** Maps an `android::action.onReceive.intentUrl` to a desktop action; first match wins. */
+export type AndroidActionRule = {
+  /** Empty matches every intent URL. */
+  url_prefix: string;
+  /** Null matches every application. */
+  app_id: number | null;
+  action: AndroidAction;
+};
+
 export type SettingsResponse = {
   base_url: string;
   has_token: boolean;
@@ -21,8 +74,99 @@ export type SettingsResponse = {
   pause_mode: string | null;
   quiet_hours_start: number | null;
   quiet_hours_end: number | null;
+  heartbeat_url: string | null;
+  ca_cert_path: string | null;
+  quiet_hours_bands: QuietHoursBand[];
+  backoff_reset_after_secs: number;
+  custom_header_names: string[];
+  pause_schedules: PauseSchedule[];
+  ip_family_preference: IpFamilyPreference;
+  host_pins: Record<string, string>;
+  /** Stream authenticates with `?token=` instead of the `X-Gotify-Key` header. */
+  stream_token_in_query: boolean;
+  trusted_certificates: Record<string, string>;
+  op_trace_enabled: boolean;
+  proxy_url: string | null;
+  proxy_username: string | null;
+  has_proxy_password: boolean;
+  proxy_bypass: string[];
+  profiles: ProfileSummary[];
+  active_profile: string | null;
+  privacy_mode: boolean;
+  redaction_patterns: string[];
+  trash_retention_days: number;
+  app_token_names: string[];
+  lan_base_url: string | null;
+  health_check_interval_secs: number;
+  acknowledgement: AckSettings;
+  fault_injection: FaultInjection;
+  fault_injection_available: boolean;
+  muted_app_ids: number[];
+  /** "default", "none" or a macOS system sound name. */
+  notification_sound: string;
+  /** Per-priority sounds; priorities below the lowest band use `notification_sound`. */
+  notification_sound_bands: SoundBand[];
+  /** Most sound bands the backend accepts. */
+  max_notification_sound_bands: number;
+  contexts: Record<string, NotificationContext>;
+  active_context: string | null;
+  android_action_rules: AndroidActionRule[];
+  connection_mode: ConnectionMode;
+  reconnect_backoff: ReconnectBackoff;
+  request_timeouts: RequestTimeouts;
+  request_retry: RetryPolicy;
+  message_retention: MessageRetention;
+  archive_remote_deletions: boolean;
+};
+
+/** Sound for messages at or above `min_priority`, up to the next band. */
+export type SoundBand = {
+  min_priority: number;
+  sound: string;
+};
+
+/** Saved bundle of notification settings, applied with `switch_context`. */
+export type NotificationContext = {
+  min_priority: number;
+  quiet_hours_start: number | null;
+  quiet_hours_end: number | null;
+  quiet_hours_bands: QuietHoursBand[];
+  muted_app_ids: number[];
+  notification_sound: string;
+  notification_sound_bands: SoundBand[];
+};
+
+export type ProfileSummary = {
+  name: string;
+  base_url: string;
+  has_token: boolean;
+  /** Emoji or short text shown in front of this server's notifications. */
+  badge: string | null;
+  /** `#rrggbb` accent, or null. */
+  color: string | null;
+  /** Stay connected while another profile is active. */
+  background_stream: boolean;
+};
+
+export type TraceOp = "delete" | "sync" | "icon_fetch" | "preview" | "stream_connect";
+
+export type OpTraceEvent = {
+  at_ms: number;
+  op: TraceOp;
+  subject: string;
+  phase: string;
+  detail: string;
+  status: number | null;
 };
 
+export type CertificateTrustRequest = {
+  host: string;
+  fingerprint_sha256: string;
+  reason: string;
+};
+
+export type IpFamilyPreference = "auto" | "ipv4" | "ipv6";
+
 export type PauseStateData = {
   pause_until: number | null;
   pause_mode: string | null;
@@ -39,6 +183,155 @@ export type GotifyMessage = {
   app: string;
   app_icon: string | null;
   date: string;
+  language: string | null;
+  starred: boolean;
+  archived: boolean;
+  /** Pinned from the inbox; never evicted or pruned while pinned. */
+  pinned: boolean;
+  /** Unix seconds the snooze ends; it comes back unread then. Absent when not snoozed. */
+  snoozed_until?: number;
+  /** Local triage labels set with `set_message_labels`; absent when there are none. */
+  labels?: string[];
+  read: boolean;
+  /** Unix seconds it was marked read in this app; absent while unread or when it arrived read. */
+  read_at?: number;
+  /** Merged from an Android client backup; kept even after the server purges it. */
+  imported: boolean;
+  /** The sender set `client::display.contentType` to `text/markdown`; other bodies are plain text. */
+  markdown: boolean;
+  /** Gotify `extras` keyed by namespace (e.g. `client::display`); absent when the message has none. */
+  extras?: Record<string, unknown>;
+  /** Server fields the client does not model yet; absent when there are none. */
+  extra?: Record<string, unknown>;
+};
+
+/** Page from `load_older_messages`; not part of the cache. */
+export type OlderMessagesPage = {
+  messages: GotifyMessage[];
+  next_before_id: number | null;
+};
+
+/** Filters for `query_messages`; omitted fields do not filter. Dates are RFC 3339. */
+export type MessageQuery = {
+  app_ids?: number[];
+  min_priority?: number | null;
+  max_priority?: number | null;
+  since?: string | null;
+  until?: string | null;
+  read?: boolean | null;
+  /** Messages carrying any of these labels (case-insensitive). */
+  labels?: string[];
+  offset?: number;
+  limit?: number | null;
+};
+
+export type LabelUsage = {
+  label: string;
+  count: number;
+};
+
+export type MessagePage = {
+  messages: GotifyMessage[];
+  total: number;
+  next_offset: number | null;
+};
+
+export type AndroidImportSummary = {
+  imported: number;
+  duplicates: number;
+  skipped: number;
+};
+
+export type MessageImportSummary = {
+  imported: number;
+  duplicates: number;
+  /** Same id as a cached message but a different date; the cached one is kept. */
+  conflicts: number;
+  skipped: number;
+};
+
+export type AuthRequiredEvent = {
+  at_ms: number;
+  source: "stream" | "sync";
+  status: number;
+  base_url: string;
+};
+
+export type OperationProgressEvent = {
+  op_id: number;
+  kind: "delete" | "delete-app" | "export";
+  done: number;
+  total: number;
+  failed: number;
+  finished: boolean;
+  cancelled: boolean;
+  error: string | null;
+};
+
+/** Gotify user behind the client token (`GET /current/user`). */
+export type CurrentUser = {
+  id: number;
+  name: string;
+  admin: boolean;
+};
+
+/** Server plugin from `GET /plugin`; the plugin token is not exposed. */
+export type GotifyPlugin = {
+  id: number;
+  name: string;
+  module_path: string;
+  enabled: boolean;
+  author: string | null;
+  website: string | null;
+  license: string | null;
+  /** "webhooker" | "displayer" | "configurer" | "messenger" | "storager" */
+  capabilities: string[];
+};
+
+export type GotifyApplication = {
+  id: number;
+  name: string;
+  description: string;
+  internal: boolean;
+  image: string;
+  default_priority: number;
+  last_used: string | null;
+};
+
+export type MessageSentEvent = {
+  at_ms: number;
+  id: number;
+  app_id: number;
+  title: string;
+  priority: number;
+  token_name: string | null;
+};
+
+export type UnreadCountChangedEvent = {
+  at_ms: number;
+  unread_count: number;
+};
+
+/** One per sync that found cached messages gone from the server. */
+export type RemotelyDeletedEvent = {
+  at_ms: number;
+  message_ids: number[];
+  archived: boolean;
+};
+
+export type InboxZeroEvent = {
+  at_ms: number;
+  cached_messages: number;
+};
+
+export type CleanupSuggestedEvent = {
+  at_ms: number;
+  candidate_count: number;
+  candidate_ids: number[];
+  oldest_candidate_date: string | null;
+  older_than_days: number;
+  cached_messages: number;
+  cache_limit: number;
 };
 
 export type UiMessage = GotifyMessage & {
@@ -48,6 +341,15 @@ export type UiMessage = GotifyMessage & {
   formatted_time: string;
 };
 
+/** Rolling window of the last 50 samples of one stream measurement. */
+export type RollingLatency = {
+  count: number;
+  last_ms: number;
+  min_ms: number;
+  avg_ms: number;
+  max_ms: number;
+};
+
 export type RuntimeDiagnostics = {
   connection_state: ConnectionState;
   should_run: boolean;
@@ -59,6 +361,74 @@ export type RuntimeDiagnostics = {
   last_error: string | null;
   backoff_seconds: number;
   reconnect_attempts: number;
+  clock_skew_seconds: number | null;
+  last_heartbeat_at: number | null;
+  stream_remote_addr: string | null;
+  /** LAN/WAN pick; null while the server URL is in use. */
+  active_endpoint: string | null;
+  /** `GET /health`: "green" | "orange" | "red", or "unreachable". */
+  server_health: string | null;
+  database_health: string | null;
+  last_health_check_at: number | null;
+  /** TCP connect through websocket upgrade. */
+  handshake_latency: RollingLatency | null;
+  /** Round trip of the stream's own pings, sent every minute. */
+  ping_rtt: RollingLatency | null;
+  background_streams: BackgroundStreamStatus[];
+  /** Restarts of a stream that was up on paper but silent, or had no task. */
+  watchdog_recoveries: number;
+  last_watchdog_recovery: string | null;
+  last_watchdog_recovery_at: number | null;
+  /** Newest connection events, oldest first; `get_connection_history` returns up to 200. */
+  recent_connection_events: ConnectionEvent[];
+  /** False when the Keychain key is unavailable and the cache is written in plaintext. */
+  encrypted_at_rest: boolean;
+};
+
+export type ConnectionEventKind =
+  | "connecting"
+  | "connected"
+  | "polling"
+  | "disconnected"
+  | "backoff"
+  | "liveness_timeout"
+  | "auth_failed"
+  | "network_changed"
+  | "woke"
+  | "watchdog_recovery"
+  | "stopped";
+
+export type ConnectionEvent = {
+  at_ms: number;
+  kind: ConnectionEventKind;
+  detail: string | null;
+  /** Set for background streams; null for the active server. */
+  profile: string | null;
+};
+
+/** Stream kept open to a saved server that is not the active one. */
+export type BackgroundStreamStatus = {
+  profile: string;
+  connection_state: ConnectionState;
+  last_connected_at: number | null;
+  last_message_at: number | null;
+  last_error: string | null;
+  reconnect_attempts: number;
+};
+
+export type EndpointChangedEvent = {
+  at_ms: number;
+  base_url: string;
+  previous: string;
+  lan: boolean;
+  latency_ms: number;
+};
+
+export type ClockSkewWarning = {
+  skew_seconds: number;
+  threshold_seconds: number;
+  source: string;
+  detected_at: number;
 };
 
 export type ConnectionStateData = {
@@ -69,6 +439,20 @@ export type MessageRemovedData = {
   message_id: number;
 };
 
+/** Sync result: new or edited messages and ids that left the cache. */
+export type MessagesDeltaData = {
+  upserted: GotifyMessage[];
+  removed: number[];
+};
+
+export type SettingsReloaded = {
+  changed_fields: string[];
+};
+
+export type NotificationsWithdrawn = {
+  message_ids: number[];
+};
+
 export type StreamErrorData = {
   message: string;
 };
@@ -81,15 +465,49 @@ export type BootstrapState = {
   runtime: DomainSnapshot<RuntimeDiagnostics>;
 };
 
-export type AppUpdate =
+export type PendingNotificationReason = "quiet_hours";
+
+export type PendingNotification = {
+  entry_id: number;
+  message_id: number;
+  title: string;
+  app: string;
+  priority: number;
+  reason: PendingNotificationReason;
+  queued_at: number;
+  deliver_at: number | null;
+};
+
+export type TrashedMessage = {
+  message: GotifyMessage;
+  trashed_at: number;
+};
+
+/** A message the server deleted, kept locally; unrelated to the `archived` flag. */
+export type ArchivedMessage = {
+  message: GotifyMessage;
+  archived_at: number;
+};
+
+export type ArchivedMessagePage = {
+  messages: ArchivedMessage[];
+  total: number;
+  next_offset: number | null;
+};
+
+export type AppUpdate = { schema: number } & (
   | { type: "settings.updated"; payload: DomainSnapshot<SettingsResponse> }
   | { type: "pause.updated"; payload: DomainSnapshot<PauseStateData> }
   | { type: "messages.replace"; payload: DomainSnapshot<GotifyMessage[]> }
   | { type: "messages.upsert"; payload: DomainSnapshot<GotifyMessage> }
   | { type: "messages.remove"; payload: DomainSnapshot<MessageRemovedData> }
+  | { type: "messages.delta"; payload: DomainSnapshot<MessagesDeltaData> }
   | { type: "connection.updated"; payload: DomainSnapshot<ConnectionStateData> }
   | { type: "runtime.updated"; payload: DomainSnapshot<RuntimeDiagnostics> }
-  | { type: "stream.error"; payload: DomainSnapshot<StreamErrorData> };
+  | { type: "stream.error"; payload: DomainSnapshot<StreamErrorData> }
+  | { type: "notifications.pending"; payload: DomainSnapshot<PendingNotification[]> }
+  | { type: "trash.updated"; payload: DomainSnapshot<TrashedMessage[]> }
+);
 
 export type AppGroup = {
   key: string;
@@ -106,14 +524,62 @@ export type UrlPreview = {
   image: string | null;
 };
 
+export type LatencySummary = {
+  count: number;
+  min_ms: number;
+  p50_ms: number;
+  p90_ms: number;
+  p99_ms: number;
+  max_ms: number;
+};
+
+export type DeliveryMetrics = {
+  sample_count: number;
+  server_to_receive: LatencySummary | null;
+  receive_to_notify: LatencySummary | null;
+  server_to_notify: LatencySummary | null;
+};
+
+export type ServerInfo = {
+  base_url: string;
+  version: string;
+  commit: string;
+  build_date: string;
+  /** Version-gated API features, e.g. "message_paging", "health", "plugins". */
+  features: string[];
+  fetched_at: number;
+};
+
+export type MemoryStats = {
+  cached_messages: number;
+  message_cache_bytes: number;
+  message_icon_bytes: number;
+  app_meta_entries: number;
+  app_meta_bytes: number;
+  app_meta_icon_bytes: number;
+  disk_backed_icons: number;
+  icon_memory_cap_bytes: number;
+};
+
 export type ThemePreference = "system" | "light" | "dark" | "dracula";
-export type DrawerTab = "settings" | "diagnostics";
-export type PauseMode = "15m" | "1h" | "custom" | "forever";
+export type DrawerTab = "settings" | "diagnostics" | "trash" | "archive" | "send" | "apps";
+export type PauseMode = "15m" | "1h" | "custom" | "forever" | "scheduled";
 export type PriorityColorMode = "gradient" | "thresholds";
 export type PriorityThreshold = {
   value: number;
   color: string;
 };
+export type PauseSchedule = {
+  /** 0 = Monday ... 6 = Sunday */
+  days: number[];
+  start_minute: number;
+  end_minute: number;
+};
+export type QuietHoursDelivery = "suppress" | "silent" | "sound_only";
+export type QuietHoursBand = {
+  min_priority: number;
+  delivery: QuietHoursDelivery;
+};
 export type PriorityGradient = {
   min: number;
   max: number;
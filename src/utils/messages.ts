@@ -3,6 +3,8 @@ import { marked } from "marked";
 import type { GotifyMessage, UiMessage } from "../types";
 
 export function toUiMessage(message: GotifyMessage): UiMessage {
+  // Every body goes through marked, as before `client::display` was honored:
+  // senders that never set it still rely on markdown rendering in the inbox.
   const html = marked.parse(message.message || "", { gfm: true, breaks: true, async: false });
   const urls = extractPlainUrls(message.message || "");
   const parsedTs = Date.parse(message.date || "");
@@ -44,6 +46,7 @@ function isSameRawMessage(current: UiMessage, next: GotifyMessage): boolean {
     current.app_id === next.app_id &&
     current.title === next.title &&
     current.message === next.message &&
+    current.markdown === next.markdown &&
     current.priority === next.priority &&
     current.app === next.app &&
     current.app_icon === next.app_icon &&
@@ -5,7 +5,7 @@ export function normalizeSelectionMessageId(value: unknown): number | null {
 }
 
 export function normalizePauseMode(value: unknown): PauseMode | null {
-  if (value === "15m" || value === "1h" || value === "custom" || value === "forever") {
+  if (value === "15m" || value === "1h" || value === "custom" || value === "forever" || value === "scheduled") {
     return value;
   }
   return null;